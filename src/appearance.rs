@@ -0,0 +1,78 @@
+use crate::{effects::Effect, tools::Color, Theme};
+
+/// Alternative look of a map, e.g. its dark mode: another tile source, background and effects.
+///
+/// Render a map in its own look and an appearance at once with
+/// [StaticMap::render_pair][crate::StaticMap::render_pair].
+/// Settings left unset are taken from the map.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{effects::Invert, tools::Color, Appearance};
+///
+/// // Dark tiles from a provider offering a dark style.
+/// let dark = Appearance::new()
+///     .url_template("https://tiles.example.com/dark/{z}/{x}/{y}.png")
+///     .background(Color::new(true, 20, 20, 25, 255));
+///
+/// // Light tiles turned dark, leaving the tools as they are.
+/// let inverted = Appearance::new().tile_effect(Invert::new());
+/// ```
+#[derive(Default)]
+pub struct Appearance {
+    pub(crate) url_template: Option<String>,
+    pub(crate) overlay_templates: Option<Vec<String>>,
+    pub(crate) background: Option<Color>,
+    pub(crate) tile_effects: Vec<Box<dyn Effect>>,
+    pub(crate) effects: Vec<Box<dyn Effect>>,
+}
+
+impl Appearance {
+    /// Create a new appearance, looking like the map until changed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// URL template of the tile source, replacing that of the map.
+    pub fn url_template<I: Into<String>>(mut self, url_template: I) -> Self {
+        self.url_template = Some(url_template.into());
+        self
+    }
+
+    /// Add a tile layer drawn over the tile source.
+    /// Once added, the overlay layers of the map are left out.
+    pub fn overlay_layer<I: Into<String>>(mut self, url_template: I) -> Self {
+        self.overlay_templates
+            .get_or_insert_with(Vec::new)
+            .push(url_template.into());
+        self
+    }
+
+    /// Color drawn beneath the tiles, replacing that of the map.
+    /// Use [Color][Color] to generate a color instance.
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Apply the map-wide styles of a theme, i.e. its background color.
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        if let Some(ref color) = theme.background {
+            self.background = Some(color.clone());
+        }
+        self
+    }
+
+    /// Add an effect applied to the tiles before the tools are drawn,
+    /// e.g. [Invert][crate::effects::Invert] to turn light tiles dark.
+    pub fn tile_effect(mut self, effect: impl Effect + 'static) -> Self {
+        self.tile_effects.push(Box::new(effect));
+        self
+    }
+
+    /// Add an effect applied to the rendered map, after the effects of the map.
+    pub fn effect(mut self, effect: impl Effect + 'static) -> Self {
+        self.effects.push(Box::new(effect));
+        self
+    }
+}
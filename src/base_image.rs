@@ -0,0 +1,148 @@
+use crate::{bounds::Bounds, decode::decode_image, digest::Digest, Error, Result};
+use std::hash::Hasher;
+use tiny_skia::{FilterQuality, Pixmap, PixmapMut, PixmapPaint, Transform};
+
+/// A single georeferenced image used as the base layer instead of tiles,
+/// e.g. a floor plan, a drone orthophoto or a fantasy map.
+/// Use [BaseImageBuilder][BaseImageBuilder] as an entrypoint.
+///
+/// The image is cropped and scaled to the map, and is assumed to be in the projection
+/// of the map, i.e. Web Mercator unless a custom tile grid is used.
+#[derive(Debug, Clone)]
+pub struct BaseImage {
+    image: Pixmap,
+    north_lat_coordinate: f64,
+    south_lat_coordinate: f64,
+    east_lon_coordinate: f64,
+    west_lon_coordinate: f64,
+}
+
+/// Builder for [BaseImage][BaseImage].
+///
+/// ## Example
+/// ```rust
+/// use staticmap::BaseImageBuilder;
+///
+/// let base_image = BaseImageBuilder::new()
+///     .path("examples/results/empty_map.png")
+///     .unwrap()
+///     .north_lat_coordinate(52.6)
+///     .south_lat_coordinate(52.4)
+///     .east_lon_coordinate(13.6)
+///     .west_lon_coordinate(13.2)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BaseImageBuilder {
+    image: Option<Pixmap>,
+    north_lat_coordinate: Option<f64>,
+    south_lat_coordinate: Option<f64>,
+    east_lon_coordinate: Option<f64>,
+    west_lon_coordinate: Option<f64>,
+}
+
+impl BaseImageBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// Path to an image file.
+    /// PNG images are always supported, JPEG and WebP with the `image` feature.
+    pub fn path<P: AsRef<std::path::Path>>(self, path: P) -> Result<Self> {
+        self.data(std::fs::read(path)?)
+    }
+
+    /// **Required**.
+    /// Load an image from bytes.
+    /// PNG images are always supported, JPEG and WebP with the `image` feature.
+    pub fn data<D: AsRef<[u8]>>(mut self, data: D) -> Result<Self> {
+        self.image = Some(decode_image(data.as_ref())?);
+        Ok(self)
+    }
+
+    /// **Required**.
+    /// The latitude coordinate of the top edge of the image.
+    pub fn north_lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.north_lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The latitude coordinate of the bottom edge of the image.
+    pub fn south_lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.south_lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The longitude coordinate of the right edge of the image.
+    pub fn east_lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.east_lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The longitude coordinate of the left edge of the image.
+    pub fn west_lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.west_lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// Build the base image, consuming the builder.
+    /// Returns an error if the builder is missing required fields.
+    pub fn build(self) -> Result<BaseImage> {
+        Ok(BaseImage {
+            image: self
+                .image
+                .ok_or(Error::BuildError("Base image not supplied."))?,
+            north_lat_coordinate: self
+                .north_lat_coordinate
+                .ok_or(Error::BuildError("North latitude coordinate not supplied."))?,
+            south_lat_coordinate: self
+                .south_lat_coordinate
+                .ok_or(Error::BuildError("South latitude coordinate not supplied."))?,
+            east_lon_coordinate: self
+                .east_lon_coordinate
+                .ok_or(Error::BuildError("East longitude coordinate not supplied."))?,
+            west_lon_coordinate: self
+                .west_lon_coordinate
+                .ok_or(Error::BuildError("West longitude coordinate not supplied."))?,
+        })
+    }
+}
+
+impl BaseImage {
+    pub(crate) fn draw(&self, mut pixmap: PixmapMut, bounds: &Bounds) {
+        let (left, top) = bounds.lon_lat_to_px(self.west_lon_coordinate, self.north_lat_coordinate);
+        let (right, bottom) =
+            bounds.lon_lat_to_px(self.east_lon_coordinate, self.south_lat_coordinate);
+
+        let scale_x = (right - left) as f32 / self.image.width() as f32;
+        let scale_y = (bottom - top) as f32 / self.image.height() as f32;
+
+        pixmap.draw_pixmap(
+            0,
+            0,
+            self.image.as_ref(),
+            &PixmapPaint {
+                quality: FilterQuality::Bilinear,
+                ..Default::default()
+            },
+            Transform::from_scale(scale_x, scale_y).post_translate(left as f32, top as f32),
+            None,
+        );
+    }
+}
+
+impl Digest for BaseImage {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.image.digest(state);
+        self.north_lat_coordinate.digest(state);
+        self.south_lat_coordinate.digest(state);
+        self.east_lon_coordinate.digest(state);
+        self.west_lon_coordinate.digest(state);
+    }
+}
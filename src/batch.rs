@@ -0,0 +1,278 @@
+use crate::{Error, Result, StaticMap, StaticMapBuilder};
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+/// Extension of job files in the queue directory.
+const JOB_EXTENSION: &str = "json";
+
+/// Queue of render jobs kept on disk, for rendering thousands of maps in batches,
+/// e.g. one thumbnail per listing or activity.
+/// Use [RenderQueueBuilder][RenderQueueBuilder] as an entrypoint.
+///
+/// Each job is a [scene][StaticMap::to_scene] stored as a file in the queue directory, named
+/// after the id of the job. [run][RenderQueue::run] renders the pending jobs with bounded
+/// parallelism, writes each map to the output directory and removes its job, so an
+/// interrupted batch resumes where it stopped. Failed jobs stay in the queue.
+///
+/// All maps are built from one base [StaticMapBuilder][StaticMapBuilder], sharing its
+/// tile fetcher and image cache, so a caching tile fetcher serves tiles shared by many maps.
+///
+/// Requires the `batch` feature.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{
+///     testing::MockTileFetcher, tools::CircleBuilder, RenderQueueBuilder, StaticMapBuilder,
+/// };
+///
+/// let directory = std::env::temp_dir().join("staticmap-queue-example");
+/// let base = StaticMapBuilder::new()
+///     .width(200)
+///     .height(150)
+///     .zoom(12)
+///     .tile_fetcher(MockTileFetcher::new());
+///
+/// let queue = RenderQueueBuilder::new()
+///     .queue_dir(directory.join("jobs"))
+///     .output_dir(directory.join("thumbnails"))
+///     .file_name("listing-{id}.png")
+///     .base(base.clone())
+///     .parallelism(4)
+///     .build()
+///     .unwrap();
+///
+/// for (id, lat, lon) in [("1", 59.91, 10.75), ("2", 60.39, 5.32)] {
+///     let mut map = base.clone().lat_center(lat).lon_center(lon).build().unwrap();
+///     map.add_tool(CircleBuilder::new().lat_coordinate(lat).lon_coordinate(lon).build().unwrap());
+///     queue.submit_map(id, &map).unwrap();
+/// }
+///
+/// let report = queue.run().unwrap();
+/// assert_eq!(report.rendered.len(), 2);
+/// assert!(directory.join("thumbnails/listing-1.png").exists());
+/// # std::fs::remove_dir_all(directory).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RenderQueue {
+    queue_dir: PathBuf,
+    output_dir: PathBuf,
+    file_name: String,
+    base: StaticMapBuilder,
+    parallelism: usize,
+}
+
+/// Builder for [RenderQueue][RenderQueue].
+#[derive(Debug, Clone)]
+pub struct RenderQueueBuilder {
+    queue_dir: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+    file_name: String,
+    base: StaticMapBuilder,
+    parallelism: usize,
+}
+
+impl Default for RenderQueueBuilder {
+    fn default() -> Self {
+        Self {
+            queue_dir: None,
+            output_dir: None,
+            file_name: "{id}.png".to_string(),
+            base: StaticMapBuilder::new(),
+            parallelism: std::thread::available_parallelism()
+                .map(usize::from)
+                .unwrap_or(4),
+        }
+    }
+}
+
+impl RenderQueueBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// Directory holding the pending jobs, created if missing.
+    pub fn queue_dir<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.queue_dir = Some(path.into());
+        self
+    }
+
+    /// **Required**.
+    /// Directory the rendered maps are written to, created if missing.
+    pub fn output_dir<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_dir = Some(path.into());
+        self
+    }
+
+    /// Name of the rendered files, with `{id}` replaced by the id of the job.
+    /// Names ending in `.jpg` or `.jpeg` are encoded as JPEG with quality 85
+    /// if the `image` feature is enabled, and all other names as PNG.
+    /// Default is `{id}.png`.
+    pub fn file_name<I: Into<String>>(mut self, template: I) -> Self {
+        self.file_name = template.into();
+        self
+    }
+
+    /// Builder the maps are built from, providing everything scenes do not contain,
+    /// such as the tile fetcher, see [StaticMapBuilder::build_scene][StaticMapBuilder::build_scene].
+    /// Default is [StaticMapBuilder::new][StaticMapBuilder::new].
+    pub fn base(mut self, base: StaticMapBuilder) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Greatest number of maps rendered at once.
+    /// Default is the number of available CPUs.
+    pub fn parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Build the queue, consuming the builder, and create its directories.
+    /// Returns an error if a directory is not supplied or can not be created.
+    pub fn build(self) -> Result<RenderQueue> {
+        let queue_dir = self
+            .queue_dir
+            .ok_or(Error::BuildError("Queue directory not supplied."))?;
+        let output_dir = self
+            .output_dir
+            .ok_or(Error::BuildError("Output directory not supplied."))?;
+
+        fs::create_dir_all(&queue_dir)?;
+        fs::create_dir_all(&output_dir)?;
+
+        Ok(RenderQueue {
+            queue_dir,
+            output_dir,
+            file_name: self.file_name,
+            base: self.base,
+            parallelism: self.parallelism,
+        })
+    }
+}
+
+/// Outcome of [RenderQueue::run][RenderQueue::run].
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Ids of the jobs rendered and removed from the queue.
+    pub rendered: Vec<String>,
+
+    /// Ids of the jobs which failed, left in the queue, with their errors.
+    pub failed: Vec<(String, Error)>,
+}
+
+impl RenderQueue {
+    /// Add a job rendering a [scene][StaticMap::to_scene], replacing a pending job
+    /// with the same id. Ids may contain letters, digits, `-`, `_` and `.`.
+    pub fn submit(&self, id: &str, scene: &str) -> Result<()> {
+        let valid = !id.is_empty()
+            && !id.starts_with('.')
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(Error::IoError(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid job id \"{}\"", id),
+            )));
+        }
+
+        write_atomically(&self.job_path(id), scene.as_bytes())
+    }
+
+    /// Add a job rendering a map, stored as its scene, see [submit][RenderQueue::submit].
+    /// Maps with tools which can not be part of a scene are rejected.
+    pub fn submit_map(&self, id: &str, map: &StaticMap) -> Result<()> {
+        self.submit(id, &map.to_scene()?)
+    }
+
+    /// Ids of the pending jobs, in the order they are rendered.
+    pub fn pending(&self) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(&self.queue_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|x| x.to_str()) != Some(JOB_EXTENSION) {
+                continue;
+            }
+            if let Some(id) = path.file_stem().and_then(|x| x.to_str()) {
+                ids.push(id.to_string());
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Path of the rendered map of a job.
+    pub fn output_path(&self, id: &str) -> PathBuf {
+        self.output_dir.join(self.file_name.replace("{id}", id))
+    }
+
+    /// Render the jobs pending when called.
+    /// Errors of single jobs are reported in the [BatchReport][BatchReport],
+    /// only errors reading the queue are returned.
+    pub fn run(&self) -> Result<BatchReport> {
+        let ids = self.pending()?;
+        let next = AtomicUsize::new(0);
+        let report = Mutex::new(BatchReport::default());
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.parallelism.min(ids.len()) {
+                scope.spawn(|| {
+                    while let Some(id) = ids.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let result = self.render(id);
+                        let mut report = report.lock().unwrap_or_else(|x| x.into_inner());
+                        match result {
+                            Ok(()) => report.rendered.push(id.clone()),
+                            Err(error) => report.failed.push((id.clone(), error)),
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(report.into_inner().unwrap_or_else(|x| x.into_inner()))
+    }
+
+    /// Render a job, write its output and remove it from the queue.
+    fn render(&self, id: &str) -> Result<()> {
+        let job = self.job_path(id);
+        let scene = fs::read_to_string(&job)?;
+        let mut map = self.base.clone().build_scene(&scene)?;
+
+        let output = self.output_path(id);
+        let extension = output
+            .extension()
+            .and_then(|x| x.to_str())
+            .map(str::to_ascii_lowercase);
+        let image = match extension.as_deref() {
+            #[cfg(feature = "image")]
+            Some("jpg" | "jpeg") => map.encode_jpeg(85)?,
+            _ => map.encode_png()?,
+        };
+
+        write_atomically(&output, &image)?;
+        fs::remove_file(job)?;
+        Ok(())
+    }
+
+    fn job_path(&self, id: &str) -> PathBuf {
+        self.queue_dir.join(format!("{}.{}", id, JOB_EXTENSION))
+    }
+}
+
+/// Write a file through a temporary file, so readers never see it half written.
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
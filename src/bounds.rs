@@ -1,8 +1,71 @@
-use crate::{lat_to_y, lon_to_x, tools::Tool};
+use crate::{
+    coord::BoundingBox,
+    digest::Digest,
+    grid::TileGrid,
+    json::Json,
+    lat_to_y, lon_to_x_continuous, m_to_px,
+    scene::Scene,
+    tools::{Alignment, Tool},
+    x_to_lon, y_to_lat,
+};
+use std::{
+    f64::consts::PI,
+    hash::{Hash, Hasher},
+};
+
+/// Zoom from which coordinates are converted to pixels relative to the center of the map.
+/// Global tile coordinates grow as 2^zoom, and converting each coordinate to them rounds away
+/// the low bits of its offset from the center, so lines wobble at extreme zoom.
+const RELATIVE_ZOOM: u8 = 19;
+
+/// How pixel coordinates of tools are rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PixelSnapping {
+    /// Round coordinates to whole pixels, so features do not shift between renders
+    /// of nearby extents.
+    #[default]
+    Round,
+    /// Round coordinates to whole pixels, and move strokes of odd width to pixel centers,
+    /// so 1px lines and outlines cover a single row of pixels instead of two gray rows.
+    Crisp,
+    /// Keep sub-pixel coordinates, for smooth curves and positions.
+    Subpixel,
+}
+
+/// How the features are fitted to the map when its zoom is not set,
+/// if the shape of their extent differs from that of the map.
+/// Where the features are placed in the space left beside them, or which side of them
+/// is kept when cropped, is set with
+/// [StaticMapBuilder::fit_alignment][crate::StaticMapBuilder::fit_alignment].
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{testing::MockTileFetcher, tools::Alignment, Fit, StaticMapBuilder};
+///
+/// // A wide route kept at the top, leaving room for an elevation profile below it.
+/// let map = StaticMapBuilder::new()
+///     .width(600)
+///     .height(400)
+///     .padding((20, 20))
+///     .fit(Fit::Letterbox)
+///     .fit_alignment(Alignment::Top)
+///     .tile_fetcher(MockTileFetcher::new())
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Fit {
+    /// Show all features, leaving space beside them along one axis.
+    #[default]
+    Letterbox,
+    /// Zoom in until the features fit the map along one axis only, filling the map
+    /// and cropping them along the other axis.
+    Crop,
+}
 
 /// Helper struct for converting to pixels,
 /// and to pass information about map bounds to implementors of [Tool][Tool].
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Bounds {
     /// Height of the map in pixels.
     pub height: u32,
@@ -31,25 +94,315 @@ pub struct Bounds {
     /// Tile size in pixels.
     pub tile_size: u32,
 
-    /// Map zoom.
+    /// Zoom of the tile grid, including any zoom offset.
     pub zoom: u8,
+
+    /// Offset applied to the map zoom to get the zoom of the tile grid.
+    pub zoom_offset: i8,
+
+    /// Custom tile grid, if the map does not use Web Mercator tiles.
+    pub grid: Option<TileGrid>,
+
+    /// How pixel coordinates are rounded.
+    pub pixel_snapping: PixelSnapping,
 }
 
 impl Bounds {
     /// Helper function for converting an x coordinate to pixel.
+    /// At high zoom, prefer [lon_lat_to_px][Bounds::lon_lat_to_px], which keeps the precision
+    /// of coordinates close to the center of the map.
     pub fn x_to_px(&self, x: f64) -> f64 {
         let px = (x - self.x_center) * f64::from(self.tile_size) + f64::from(self.width) / 2.;
-        px.round()
+        self.snap(px)
     }
 
     /// Helper function for converting a y coordinate to pixel.
     pub fn y_to_px(&self, y: f64) -> f64 {
         let px = (y - self.y_center) * f64::from(self.tile_size) + f64::from(self.height) / 2.;
-        px.round()
+        self.snap(px)
+    }
+
+    /// Helper function for converting a longitude and latitude coordinate to pixels,
+    /// using the custom tile grid if there is one.
+    ///
+    /// Longitudes beyond -180.0 and 180.0 continue past the antimeridian at every zoom
+    /// instead of wrapping around like in [lon_to_x][crate::lon_to_x],
+    /// so lines crossing it stay continuous.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::StaticMapBuilder;
+    ///
+    /// for zoom in [18, 19] {
+    ///     let mut map = StaticMapBuilder::new()
+    ///         .width(200)
+    ///         .height(200)
+    ///         .zoom(zoom)
+    ///         .lat_center(0.)
+    ///         .lon_center(179.9999)
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     // Just east of the center, across the antimeridian.
+    ///     let (x, y) = map.bounds().lon_lat_to_px(180.0001, 0.);
+    ///     assert!(x > 100. && x < 200., "{} at zoom {}", x, zoom);
+    ///     assert_eq!(y, 100.);
+    /// }
+    /// ```
+    pub fn lon_lat_to_px(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let (x, y) = match self.grid {
+            Some(ref grid) => grid.lon_lat_to_tile(lon, lat, self.zoom, self.tile_size),
+            None if self.zoom >= RELATIVE_ZOOM => return self.relative_px(lon, lat),
+            None => (
+                lon_to_x_continuous(lon, self.zoom),
+                lat_to_y(lat, self.zoom),
+            ),
+        };
+
+        (self.x_to_px(x), self.y_to_px(y))
+    }
+
+    /// Helper function for converting pixels on the map to a longitude and latitude
+    /// coordinate, the inverse of [lon_lat_to_px][Bounds::lon_lat_to_px].
+    pub fn px_to_lon_lat(&self, x: f64, y: f64) -> (f64, f64) {
+        let tile_size = f64::from(self.tile_size);
+        let x = self.x_center + (x - f64::from(self.width) / 2.) / tile_size;
+        let y = self.y_center + (y - f64::from(self.height) / 2.) / tile_size;
+
+        match self.grid {
+            Some(ref grid) => grid.tile_to_lon_lat(x, y, self.zoom, self.tile_size),
+            None => (x_to_lon(x, self.zoom), y_to_lat(y, self.zoom)),
+        }
+    }
+
+    /// Web Mercator pixels of a coordinate from its offset to the center of the map,
+    /// computed from the differences of the longitudes and latitudes, which are exact
+    /// for nearby coordinates.
+    fn relative_px(&self, lon: f64, mut lat: f64) -> (f64, f64) {
+        // Longitudes continue past the antimeridian like in lon_to_x_continuous,
+        // and latitudes are wrapped like in lat_to_y.
+        if !(-90_f64..90_f64).contains(&lat) {
+            lat = (lat + 90_f64) % 180_f64 - 90_f64;
+        }
+
+        let (lon_center, lat_center) = self.center();
+        let scale = 2_f64.powi(self.zoom.into()) * f64::from(self.tile_size);
+        let dx = (lon - lon_center) / 360. * scale;
+
+        // Difference of the Mercator ordinates atanh(sin(lat)), using
+        // atanh(a) - atanh(b) = atanh((a - b) / (1 - ab)) and the sum-to-product of the sines.
+        let (phi, phi_center) = (lat.to_radians(), lat_center.to_radians());
+        let sines = 2. * ((phi + phi_center) / 2.).cos() * ((phi - phi_center) / 2.).sin();
+        let dy = -(sines / (1. - phi.sin() * phi_center.sin())).atanh() / (2. * PI) * scale;
+
+        (
+            self.snap(dx + f64::from(self.width) / 2.),
+            self.snap(dy + f64::from(self.height) / 2.),
+        )
+    }
+
+    fn snap(&self, px: f64) -> f64 {
+        match self.pixel_snapping {
+            PixelSnapping::Round | PixelSnapping::Crisp => px.round(),
+            PixelSnapping::Subpixel => px,
+        }
+    }
+
+    /// Move a point of a stroke with the given width to the center of its pixel
+    /// if snapping is [PixelSnapping::Crisp][PixelSnapping::Crisp] and the width is odd,
+    /// e.g. for the points of lines and outlines in custom tools.
+    pub fn snap_stroke(&self, (x, y): (f64, f64), width: f32) -> (f64, f64) {
+        match self.pixel_snapping {
+            PixelSnapping::Crisp if width.round() as i64 % 2 == 1 => {
+                (x.floor() + 0.5, y.floor() + 0.5)
+            }
+            _ => (x, y),
+        }
+    }
+
+    /// Helper function for converting a distance in meters at the given latitude to pixels.
+    pub fn m_to_px(&self, meters: f64, lat: f64) -> f64 {
+        match self.grid {
+            Some(ref grid) => meters / grid.resolution(self.zoom),
+            None => m_to_px(meters, lat, self.zoom) * f64::from(self.tile_size) / 256.,
+        }
+    }
+
+    /// Copy of the bounds where everything is drawn moved by the given number of pixels.
+    pub(crate) fn translate(&self, dx: f64, dy: f64) -> Bounds {
+        let tile_size = f64::from(self.tile_size);
+        Bounds {
+            x_center: self.x_center - dx / tile_size,
+            y_center: self.y_center - dy / tile_size,
+            grid: self.grid.clone(),
+            ..*self
+        }
+    }
+
+    /// Copy of the bounds showing the same area with everything the given times larger,
+    /// e.g. for high resolution renders. The tile size is rounded to whole pixels.
+    pub(crate) fn scale(&self, factor: f64) -> Bounds {
+        let scale = |px: u32| (f64::from(px) * factor).round().max(1.) as u32;
+        Bounds {
+            width: scale(self.width),
+            height: scale(self.height),
+            tile_size: scale(self.tile_size),
+            grid: self.grid.clone(),
+            ..*self
+        }
+    }
+
+    /// Geographic center of the map, as (longitude, latitude).
+    pub fn center(&self) -> (f64, f64) {
+        match self.grid {
+            Some(ref grid) => {
+                grid.tile_to_lon_lat(self.x_center, self.y_center, self.zoom, self.tile_size)
+            }
+            None => (
+                x_to_lon(self.x_center, self.zoom),
+                y_to_lat(self.y_center, self.zoom),
+            ),
+        }
+    }
+
+    /// Rectangle covering the whole map, in pixels.
+    pub fn pixel_rect(&self) -> PixelRect {
+        PixelRect::new(0., 0., self.width.into(), self.height.into())
+    }
+
+    /// Geographic box visible on the map, see [BoundingBox][BoundingBox].
+    pub fn bbox(&self) -> BoundingBox {
+        let (west, south, east, north) = self.extent();
+        BoundingBox {
+            south,
+            west,
+            north,
+            east,
+        }
+    }
+
+    /// Geographic extent of the map, as (lon_min, lat_min, lon_max, lat_max).
+    pub fn extent(&self) -> (f64, f64, f64, f64) {
+        let x_m = 0.5 * f64::from(self.width) / f64::from(self.tile_size);
+        let y_m = 0.5 * f64::from(self.height) / f64::from(self.tile_size);
+
+        match self.grid {
+            Some(ref grid) => {
+                let corners = [
+                    (self.x_center - x_m, self.y_center - y_m),
+                    (self.x_center + x_m, self.y_center - y_m),
+                    (self.x_center - x_m, self.y_center + y_m),
+                    (self.x_center + x_m, self.y_center + y_m),
+                ];
+                corners
+                    .iter()
+                    .map(|(x, y)| grid.tile_to_lon_lat(*x, *y, self.zoom, self.tile_size))
+                    .fold(
+                        (f64::NAN, f64::NAN, f64::NAN, f64::NAN),
+                        |extent, (lon, lat)| {
+                            (
+                                extent.0.min(lon),
+                                extent.1.min(lat),
+                                extent.2.max(lon),
+                                extent.3.max(lat),
+                            )
+                        },
+                    )
+            }
+            None => (
+                x_to_lon(self.x_center - x_m, self.zoom),
+                y_to_lat(self.y_center + y_m, self.zoom),
+                x_to_lon(self.x_center + x_m, self.zoom),
+                y_to_lat(self.y_center - y_m, self.zoom),
+            ),
+        }
+    }
+}
+
+/// Axis aligned rectangle on the map, in pixels.
+/// Returned by [Tool::pixel_extent][Tool::pixel_extent].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelRect {
+    /// Left edge.
+    pub x_min: f64,
+
+    /// Top edge.
+    pub y_min: f64,
+
+    /// Right edge.
+    pub x_max: f64,
+
+    /// Bottom edge.
+    pub y_max: f64,
+}
+
+impl PixelRect {
+    /// Creates a new [PixelRect][PixelRect] spanning two corners, in any order.
+    pub fn new(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
+        PixelRect {
+            x_min: x0.min(x1),
+            y_min: y0.min(y1),
+            x_max: x0.max(x1),
+            y_max: y0.max(y1),
+        }
+    }
+
+    /// Width of the rectangle.
+    pub fn width(&self) -> f64 {
+        self.x_max - self.x_min
+    }
+
+    /// Height of the rectangle.
+    pub fn height(&self) -> f64 {
+        self.y_max - self.y_min
+    }
+
+    /// The rectangle grown by `margin` pixels on each side.
+    pub fn outset(&self, margin: f64) -> Self {
+        PixelRect {
+            x_min: self.x_min - margin,
+            y_min: self.y_min - margin,
+            x_max: self.x_max + margin,
+            y_max: self.y_max + margin,
+        }
+    }
+
+    /// Whether the rectangles overlap, including rectangles sharing only an edge.
+    pub fn intersects(&self, other: &PixelRect) -> bool {
+        self.x_min <= other.x_max
+            && other.x_min <= self.x_max
+            && self.y_min <= other.y_max
+            && other.y_min <= self.y_max
+    }
+
+    /// Smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &PixelRect) -> PixelRect {
+        PixelRect {
+            x_min: self.x_min.min(other.x_min),
+            y_min: self.y_min.min(other.y_min),
+            x_max: self.x_max.max(other.x_max),
+            y_max: self.y_max.max(other.y_max),
+        }
+    }
+
+    /// The part of the rectangle inside the other one,
+    /// or None if they do not overlap.
+    pub fn intersection(&self, other: &PixelRect) -> Option<PixelRect> {
+        self.intersects(other).then(|| PixelRect {
+            x_min: self.x_min.max(other.x_min),
+            y_min: self.y_min.max(other.y_min),
+            x_max: self.x_max.min(other.x_max),
+            y_max: self.y_max.min(other.y_max),
+        })
+    }
+
+    /// Whether the point is inside the rectangle, including its edges.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        (self.x_min..=self.x_max).contains(&x) && (self.y_min..=self.y_max).contains(&y)
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 /// Builder for [Bounds][Bounds].
 pub struct BoundsBuilder {
     lon_min: f64,
@@ -57,12 +410,18 @@ pub struct BoundsBuilder {
     lon_max: f64,
     lat_max: f64,
     zoom: Option<u8>,
+    zoom_offset: i8,
     height: u32,
     width: u32,
     padding: (u32, u32),
     tile_size: u32,
     lat_center: Option<f64>,
     lon_center: Option<f64>,
+    grid: Option<TileGrid>,
+    zoom_range: Option<(u8, u8)>,
+    pixel_snapping: PixelSnapping,
+    fit: Fit,
+    fit_alignment: Alignment,
 }
 
 impl BoundsBuilder {
@@ -75,6 +434,11 @@ impl BoundsBuilder {
         self
     }
 
+    pub fn zoom_offset(mut self, offset: i8) -> Self {
+        self.zoom_offset = offset;
+        self
+    }
+
     pub fn tile_size(mut self, size: u32) -> Self {
         self.tile_size = size;
         self
@@ -100,13 +464,77 @@ impl BoundsBuilder {
         self
     }
 
+    pub fn grid(mut self, grid: Option<TileGrid>) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    pub fn zoom_range(mut self, range: Option<(u8, u8)>) -> Self {
+        self.zoom_range = range;
+        self
+    }
+
     pub fn padding(mut self, padding: (u32, u32)) -> Self {
         self.padding = padding;
         self
     }
 
+    pub fn pixel_snapping(mut self, snapping: PixelSnapping) -> Self {
+        self.pixel_snapping = snapping;
+        self
+    }
+
+    pub fn fit(mut self, fit: Fit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    pub fn fit_alignment(mut self, alignment: Alignment) -> Self {
+        self.fit_alignment = alignment;
+        self
+    }
+
+    /// Members of a scene with the configured bounds, or None with a custom tile grid.
+    pub fn scene(&self) -> Option<Vec<(&'static str, Json)>> {
+        if self.grid.is_some() {
+            return None;
+        }
+
+        Some(vec![
+            ("width", self.width.into()),
+            ("height", self.height.into()),
+            ("padding", self.padding.into()),
+            ("zoom", self.zoom.into()),
+            ("zoom_offset", self.zoom_offset.into()),
+            ("lat_center", self.lat_center.into()),
+            ("lon_center", self.lon_center.into()),
+            ("tile_size", self.tile_size.into()),
+            ("pixel_snapping", self.pixel_snapping.to_json()),
+            ("fit", self.fit.to_json()),
+            ("fit_alignment", self.fit_alignment.to_json()),
+        ])
+    }
+
+    /// Feed the configured bounds into `state`, see [Digest][Digest].
+    pub fn digest(&self, mut state: &mut dyn Hasher) {
+        self.zoom.digest(state);
+        self.zoom_offset.digest(state);
+        self.height.digest(state);
+        self.width.digest(state);
+        self.padding.digest(state);
+        self.tile_size.digest(state);
+        self.lat_center.digest(state);
+        self.lon_center.digest(state);
+        self.grid.digest(state);
+        self.zoom_range.digest(state);
+        self.pixel_snapping.hash(&mut state);
+        self.fit.hash(&mut state);
+        self.fit_alignment.hash(&mut state);
+    }
+
     pub fn build(&mut self, tools: &[Box<dyn Tool>]) -> Bounds {
         let zoom = if let Some(z) = self.zoom {
+            let z = self.tile_zoom(z);
             self.determine_extent(z, tools);
             z
         } else {
@@ -114,14 +542,8 @@ impl BoundsBuilder {
         };
 
         let (x_center, y_center) = match self.lon_center.zip(self.lat_center) {
-            Some((lon, lat)) => (lon_to_x(lon, zoom), lat_to_y(lat, zoom)),
-            _ => {
-                let x_min = lon_to_x(self.lon_min, zoom);
-                let x_max = lon_to_x(self.lon_max, zoom);
-                let y_min = lat_to_y(self.lat_max, zoom);
-                let y_max = lat_to_y(self.lat_min, zoom);
-                ((x_min + x_max) / 2., (y_min + y_max) / 2.)
-            }
+            Some((lon, lat)) => self.to_tile(lon, lat, zoom),
+            _ => self.aligned_center(zoom),
         };
 
         let x_m = 0.5 * f64::from(self.width) / f64::from(self.tile_size);
@@ -146,17 +568,77 @@ impl BoundsBuilder {
             y_max,
             tile_size: self.tile_size,
             zoom,
+            zoom_offset: self.zoom_offset,
+            grid: self.grid.clone(),
+            pixel_snapping: self.pixel_snapping,
+        }
+    }
+
+    /// Longitude and latitude to x and y in tiles, using the custom tile grid if there is one.
+    #[inline]
+    fn to_tile(&self, lon: f64, lat: f64, zoom: u8) -> (f64, f64) {
+        match self.grid {
+            Some(ref grid) => grid.lon_lat_to_tile(lon, lat, zoom, self.tile_size),
+            None => (lon_to_x_continuous(lon, zoom), lat_to_y(lat, zoom)),
         }
     }
 
+    /// Extent of the map features in tiles, as (x_min, y_min, x_max, y_max).
+    fn tile_extent(&self, zoom: u8) -> (f64, f64, f64, f64) {
+        let corners = [
+            self.to_tile(self.lon_min, self.lat_min, zoom),
+            self.to_tile(self.lon_min, self.lat_max, zoom),
+            self.to_tile(self.lon_max, self.lat_min, zoom),
+            self.to_tile(self.lon_max, self.lat_max, zoom),
+        ];
+
+        corners.iter().fold(
+            (f64::NAN, f64::NAN, f64::NAN, f64::NAN),
+            |extent, (x, y)| {
+                (
+                    extent.0.min(*x),
+                    extent.1.min(*y),
+                    extent.2.max(*x),
+                    extent.3.max(*y),
+                )
+            },
+        )
+    }
+
+    /// Center in tiles placing the extent of the map features at the fit alignment
+    /// within the padding, moving it into the space left beside it,
+    /// or keeping that side of it when it is cropped.
+    fn aligned_center(&self, zoom: u8) -> (f64, f64) {
+        let (x_min, y_min, x_max, y_max) = self.tile_extent(zoom);
+        let (x_fraction, y_fraction) = self.fit_alignment.fractions();
+        let tile_size = f64::from(self.tile_size);
+
+        let axis = |min: f64, max: f64, size: u32, padding: u32, fraction: f64| {
+            let free = (f64::from(size) - 2. * f64::from(padding)) / tile_size - (max - min);
+            (min + max) / 2. + (0.5 - fraction) * free
+        };
+        (
+            axis(x_min, x_max, self.width, self.padding.0, x_fraction),
+            axis(y_min, y_max, self.height, self.padding.1, y_fraction),
+        )
+    }
+
+    /// Zoom of the tile grid for the given map zoom.
+    #[inline]
+    fn tile_zoom(&self, zoom: u8) -> u8 {
+        (i16::from(zoom) + i16::from(self.zoom_offset)).clamp(0, i16::from(u8::MAX)) as u8
+    }
+
     #[inline]
     fn determine_height(&self, zoom: u8) -> f64 {
-        (lat_to_y(self.lat_min, zoom) - lat_to_y(self.lat_max, zoom)) * f64::from(self.tile_size)
+        let (_, y_min, _, y_max) = self.tile_extent(zoom);
+        (y_max - y_min) * f64::from(self.tile_size)
     }
 
     #[inline]
     fn determine_width(&self, zoom: u8) -> f64 {
-        (lon_to_x(self.lon_max, zoom) - lon_to_x(self.lon_min, zoom)) * f64::from(self.tile_size)
+        let (x_min, _, x_max, _) = self.tile_extent(zoom);
+        (x_max - x_min) * f64::from(self.tile_size)
     }
 
     #[inline]
@@ -186,15 +668,31 @@ impl BoundsBuilder {
     }
 
     fn calculate_zoom(&mut self, tools: &[Box<dyn Tool>]) -> u8 {
-        let mut zoom = 1;
-        for z in (0..=17).rev() {
-            self.determine_extent(z, tools);
+        let max_zoom = self.grid.as_ref().map(TileGrid::max_zoom).unwrap_or(17);
+        let (min_tile_zoom, max_tile_zoom) = self.zoom_range.unwrap_or((0, u8::MAX));
 
-            if self.determine_width(z) > (self.width - self.padding.0 * 2).into() {
+        let mut zoom = self
+            .tile_zoom(1.min(max_zoom))
+            .clamp(min_tile_zoom, max_tile_zoom);
+        for z in (0..=max_zoom).rev() {
+            let z = self.tile_zoom(z);
+
+            // Never pick a zoom the tile source does not serve.
+            if !(min_tile_zoom..=max_tile_zoom).contains(&z) {
                 continue;
             }
 
-            if self.determine_height(z) > (self.height - self.padding.1 * 2).into() {
+            self.determine_extent(z, tools);
+
+            // Padding of half the size or more leaves no room, rather than overflowing.
+            let room = |size: u32, padding: u32| size.saturating_sub(padding.saturating_mul(2));
+            let wide = self.determine_width(z) > room(self.width, self.padding.0).into();
+            let tall = self.determine_height(z) > room(self.height, self.padding.1).into();
+            let overflows = match self.fit {
+                Fit::Letterbox => wide || tall,
+                Fit::Crop => wide && tall,
+            };
+            if overflows {
                 continue;
             }
 
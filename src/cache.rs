@@ -0,0 +1,75 @@
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Number of temporary files written by this process, giving each write a unique name.
+static TEMPORARY_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// Trait implemented by types which cache rendered maps, encoded as PNG,
+/// keyed by [StaticMap::render_digest][crate::StaticMap::render_digest].
+///
+/// Implement this trait to store images elsewhere, e.g. in memory or an object store.
+/// Use [DiskImageCache][DiskImageCache] to store images in a directory.
+pub trait ImageCache: Send + Sync {
+    /// Get the cached image with the given digest, if any.
+    fn get(&self, digest: u64) -> Option<Vec<u8>>;
+    /// Store the image with the given digest.
+    /// Failures should be ignored, since the image can always be rendered again.
+    fn put(&self, digest: u64, png: &[u8]);
+}
+
+/// Image cache storing each image as a PNG file in a directory.
+///
+/// Files are never removed, so clean up the directory periodically
+/// if the number of distinct maps is unbounded.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{DiskImageCache, StaticMapBuilder};
+///
+/// let map = StaticMapBuilder::new()
+///     .image_cache(DiskImageCache::new(std::env::temp_dir().join("staticmap")))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiskImageCache {
+    directory: PathBuf,
+}
+
+impl DiskImageCache {
+    /// Creates a new [DiskImageCache][DiskImageCache] in the given directory,
+    /// which is created when the first image is stored.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        DiskImageCache {
+            directory: directory.into(),
+        }
+    }
+
+    fn path(&self, digest: u64) -> PathBuf {
+        self.directory.join(format!("{:016x}.png", digest))
+    }
+}
+
+impl ImageCache for DiskImageCache {
+    fn get(&self, digest: u64) -> Option<Vec<u8>> {
+        std::fs::read(self.path(digest)).ok()
+    }
+
+    fn put(&self, digest: u64, png: &[u8]) {
+        // Write to a temporary file first, so concurrent readers never see a partial image.
+        // Each write has its own file, also when threads put the same digest at once.
+        let path = self.path(digest);
+        let count = TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed);
+        let temporary = path.with_extension(format!("{}.{}.tmp", std::process::id(), count));
+
+        let written = std::fs::create_dir_all(&self.directory)
+            .and_then(|_| std::fs::write(&temporary, png))
+            .and_then(|_| std::fs::rename(&temporary, &path));
+
+        if written.is_err() {
+            let _ = std::fs::remove_file(&temporary);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Clipping of projected geometry to the map before building paths, keeping paths small
+//! and their coordinates in a range tiny-skia draws accurately, e.g. for continent-scale
+//! lines at city zoom.
+
+use crate::bounds::PixelRect;
+
+type Point = (f64, f64);
+
+/// Parts of a line inside a rectangle, as separate runs of points.
+/// Points where the line crosses the rectangle are added at the edges.
+pub(crate) fn clip_line(points: &[Point], rect: &PixelRect) -> Vec<Vec<Point>> {
+    let mut runs: Vec<Vec<Point>> = Vec::new();
+    let mut open = false;
+
+    for segment in points.windows(2) {
+        match clip_segment(segment[0], segment[1], rect) {
+            Some((start, end, exits)) => {
+                match runs.last_mut() {
+                    Some(run) if open => run.push(end),
+                    _ => runs.push(vec![start, end]),
+                }
+                open = !exits;
+            }
+            None => open = false,
+        }
+    }
+
+    runs
+}
+
+/// Part of a segment inside a rectangle, using the Liang-Barsky algorithm,
+/// and whether the segment leaves the rectangle before its end.
+fn clip_segment(start: Point, end: Point, rect: &PixelRect) -> Option<(Point, Point, bool)> {
+    if ![start.0, start.1, end.0, end.1]
+        .iter()
+        .all(|x| x.is_finite())
+    {
+        return None;
+    }
+
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let (mut t0, mut t1) = (0_f64, 1_f64);
+
+    // Each edge as the direction of the segment towards it, and the distance to it.
+    let edges = [
+        (-dx, start.0 - rect.x_min),
+        (dx, rect.x_max - start.0),
+        (-dy, start.1 - rect.y_min),
+        (dy, rect.y_max - start.1),
+    ];
+    for (p, q) in edges.iter().copied() {
+        if p == 0. {
+            // Parallel to the edge, and outside it.
+            if q < 0. {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0. {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+
+    // Points at the ends of the segment are kept exact, so runs join up.
+    let at = |t: f64| {
+        if t <= 0. {
+            start
+        } else if t >= 1. {
+            end
+        } else {
+            (start.0 + t * dx, start.1 + t * dy)
+        }
+    };
+    Some((at(t0), at(t1), t1 < 1.))
+}
+
+/// Part of a closed ring inside a rectangle, using the Sutherland-Hodgman algorithm.
+/// Parts outside are replaced by the edges of the rectangle, which fills the same area
+/// inside the rectangle.
+pub(crate) fn clip_ring(ring: &[Point], rect: &PixelRect) -> Vec<Point> {
+    // Signed distance of a point inside each edge.
+    let edges: [&dyn Fn(Point) -> f64; 4] = [
+        &|(x, _)| x - rect.x_min,
+        &|(x, _)| rect.x_max - x,
+        &|(_, y)| y - rect.y_min,
+        &|(_, y)| rect.y_max - y,
+    ];
+
+    let mut output = ring.to_vec();
+    for inside in edges.iter() {
+        let input = std::mem::take(&mut output);
+        let mut previous = match input.last() {
+            Some(previous) => *previous,
+            None => break,
+        };
+
+        for current in input {
+            let (a, b) = (inside(previous), inside(current));
+            if (a < 0.) != (b < 0.) {
+                let t = a / (a - b);
+                output.push((
+                    previous.0 + t * (current.0 - previous.0),
+                    previous.1 + t * (current.1 - previous.1),
+                ));
+            }
+            if b >= 0. {
+                output.push(current);
+            }
+            previous = current;
+        }
+    }
+
+    output
+}
@@ -0,0 +1,132 @@
+use crate::{bounds::Bounds, digest::Digest, tools::Tool};
+use std::{collections::HashMap, f64::consts::PI, hash::Hasher};
+use tiny_skia::{Paint, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Strategy for drawing point features at exactly the same coordinate,
+/// e.g. geocoded addresses in the same building, which would otherwise hide each other.
+///
+/// Applies to tools which report a coordinate through [Tool::point][Tool::point],
+/// such as circles and icons. Spread points are not considered when fitting the map
+/// to its features, so use padding to keep them inside the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoincidentPoints {
+    /// Draw the points on top of each other.
+    Overlap,
+    /// Place the points evenly on a circle around the coordinate,
+    /// with the given distance in pixels between neighboring points,
+    /// and connect them to the coordinate with thin lines.
+    Spiderfy(f64),
+    /// Move each point in a random direction, up to the given distance in pixels.
+    /// The offsets are derived from the coordinate, so renders are reproducible.
+    Jitter(f64),
+}
+
+impl Digest for CoincidentPoints {
+    fn digest(&self, state: &mut dyn Hasher) {
+        match *self {
+            CoincidentPoints::Overlap => state.write_u8(0),
+            CoincidentPoints::Spiderfy(distance) => {
+                state.write_u8(1);
+                distance.digest(state);
+            }
+            CoincidentPoints::Jitter(distance) => {
+                state.write_u8(2);
+                distance.digest(state);
+            }
+        }
+    }
+}
+
+impl CoincidentPoints {
+    /// Pixel offset of each tool, in the same order as the tools.
+    pub(crate) fn offsets(&self, tools: &[Box<dyn Tool>]) -> Vec<(f64, f64)> {
+        let mut offsets = vec![(0., 0.); tools.len()];
+        if *self == CoincidentPoints::Overlap {
+            return offsets;
+        }
+
+        let mut groups: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+        for (index, tool) in tools.iter().enumerate() {
+            if let Some((lon, lat)) = tool.point() {
+                groups
+                    .entry((lon.to_bits(), lat.to_bits()))
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        for (key, group) in groups.iter().filter(|(_, group)| group.len() > 1) {
+            let count = group.len() as f64;
+
+            for (i, index) in group.iter().enumerate() {
+                offsets[*index] = match *self {
+                    CoincidentPoints::Overlap => (0., 0.),
+                    CoincidentPoints::Spiderfy(distance) => {
+                        let radius = distance / (2. * (PI / count).sin());
+                        let angle = 2. * PI * i as f64 / count - PI / 2.;
+                        (radius * angle.cos(), radius * angle.sin())
+                    }
+                    CoincidentPoints::Jitter(distance) => {
+                        let seed = (key.0 ^ key.1.rotate_left(32)).wrapping_add(2 * i as u64);
+                        let angle = 2. * PI * unit(seed);
+                        let radius = distance * unit(seed.wrapping_add(1)).sqrt();
+                        (radius * angle.cos(), radius * angle.sin())
+                    }
+                };
+            }
+        }
+
+        offsets
+    }
+
+    /// Draw lines from the shared coordinate to each spiderfied point, `scale` times larger.
+    pub(crate) fn draw_legs(
+        &self,
+        tools: &[Box<dyn Tool>],
+        offsets: &[(f64, f64)],
+        bounds: &Bounds,
+        scale: f32,
+        mut pixmap: PixmapMut,
+    ) {
+        if !matches!(self, CoincidentPoints::Spiderfy(_)) {
+            return;
+        }
+
+        let mut path_builder = PathBuilder::new();
+        for (tool, (dx, dy)) in tools.iter().zip(offsets) {
+            if let Some((lon, lat)) = tool.point().filter(|_| *dx != 0. || *dy != 0.) {
+                let (x, y) = bounds.lon_lat_to_px(lon, lat);
+                path_builder.move_to(x as f32, y as f32);
+                path_builder.line_to((x + dx) as f32, (y + dy) as f32);
+            }
+        }
+
+        if let Some(path) = path_builder.finish() {
+            let mut paint = Paint::default();
+            paint.set_color_rgba8(80, 80, 80, 160);
+            paint.anti_alias = true;
+
+            let stroke = Stroke {
+                width: 1.,
+                ..Default::default()
+            };
+
+            pixmap.stroke_path(
+                &path,
+                &paint,
+                &stroke,
+                Transform::from_scale(scale, scale),
+                None,
+            );
+        }
+    }
+}
+
+/// Pseudo-random number in [0, 1) derived from the seed, using SplitMix64.
+fn unit(seed: u64) -> f64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1_u64 << 53) as f64
+}
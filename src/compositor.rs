@@ -0,0 +1,214 @@
+//! Compositing of tiles onto the map in the order they arrive.
+
+use crate::{
+    decode::{decode_image, decode_image_pooled},
+    fetcher::preview,
+    placement::TilePlacement,
+    pool::PixmapPool,
+    Error, Result,
+};
+use tiny_skia::{FilterQuality, Paint, PixmapMut, PixmapPaint, Transform};
+
+/// Composites tiles onto the map as they are fetched, in any order, while keeping each tile
+/// above the tiles of earlier layers it overlaps. Fetched tiles are decoded, drawn and dropped
+/// as soon as nothing beneath them is missing, so only tiles waiting on others are held.
+pub(crate) struct TileCompositor<'a> {
+    tiles: &'a [TilePlacement],
+    /// Index of the URL of each tile.
+    url_indices: Vec<usize>,
+    /// Earlier tiles overlapping each tile, which must be drawn before it.
+    beneath: Vec<Vec<usize>>,
+    /// Later tiles overlapping each tile, which may be waiting on it.
+    above: Vec<Vec<usize>>,
+    /// Tiles of each URL.
+    url_tiles: Vec<Vec<usize>>,
+    /// Number of tiles of each URL not drawn yet.
+    pending: Vec<usize>,
+    /// Fetched tile of each URL, kept until all tiles of the URL are drawn.
+    fetched: Vec<Option<Vec<u8>>>,
+    drawn: Vec<bool>,
+    paint: PixmapPaint,
+    pool: Option<&'a PixmapPool>,
+}
+
+impl<'a> TileCompositor<'a> {
+    /// Compositor of the tiles of a tile plan, with the URL index of each tile
+    /// out of `url_count` distinct URLs. Tiles are decoded into pixmaps of the pool, if any.
+    pub(crate) fn new(
+        tiles: &'a [TilePlacement],
+        url_indices: Vec<usize>,
+        url_count: usize,
+        paint: PixmapPaint,
+        pool: Option<&'a PixmapPool>,
+    ) -> Self {
+        let rects: Vec<_> = tiles.iter().map(pixel_rect).collect();
+        let beneath: Vec<Vec<usize>> = rects
+            .iter()
+            .enumerate()
+            .map(|(index, rect)| {
+                (0..index)
+                    .filter(|&earlier| overlaps(&rects[earlier], rect))
+                    .collect()
+            })
+            .collect();
+
+        let mut above = vec![Vec::new(); tiles.len()];
+        for (index, below) in beneath.iter().enumerate() {
+            for &earlier in below {
+                above[earlier].push(index);
+            }
+        }
+
+        let mut url_tiles = vec![Vec::new(); url_count];
+        for (index, &url_index) in url_indices.iter().enumerate() {
+            url_tiles[url_index].push(index);
+        }
+        let pending = url_tiles.iter().map(Vec::len).collect();
+
+        Self {
+            tiles,
+            url_indices,
+            beneath,
+            above,
+            url_tiles,
+            pending,
+            fetched: vec![None; url_count],
+            drawn: vec![false; tiles.len()],
+            paint,
+            pool,
+        }
+    }
+
+    /// Draw the tiles of a fetched URL, and any tiles above them which were waiting on them.
+    pub(crate) fn add(
+        &mut self,
+        url_index: usize,
+        tile: Vec<u8>,
+        image: &mut PixmapMut,
+    ) -> Result<()> {
+        self.fetched[url_index] = Some(tile);
+
+        // Only the tiles of the URL, and the tiles above the tiles drawn, can become ready.
+        let mut candidates = self.url_tiles[url_index].clone();
+        while let Some(index) = candidates.pop() {
+            let url_index = self.url_indices[index];
+            let ready = !self.drawn[index]
+                && self.fetched[url_index].is_some()
+                && self.beneath[index].iter().all(|&below| self.drawn[below]);
+            if !ready {
+                continue;
+            }
+
+            if let Some(ref tile) = self.fetched[url_index] {
+                draw_tile(image, &self.tiles[index], tile, self.paint, self.pool)?;
+            }
+            self.drawn[index] = true;
+            candidates.extend_from_slice(&self.above[index]);
+
+            // Tiles are only needed until all their placements are drawn.
+            self.pending[url_index] -= 1;
+            if self.pending[url_index] == 0 {
+                self.fetched[url_index] = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draw the tiles left waiting on tiles that were never fetched, e.g. when out of time.
+    /// Missing tiles of the base layer are drawn as gray squares, missing overlay tiles
+    /// are left out. Returns whether every tile was fetched.
+    pub(crate) fn finish(self, image: &mut PixmapMut) -> Result<bool> {
+        let complete = self.drawn.iter().all(|&drawn| drawn);
+        for (index, tile) in self.tiles.iter().enumerate() {
+            if self.drawn[index] {
+                continue;
+            }
+
+            match self.fetched[self.url_indices[index]] {
+                Some(ref fetched) => draw_tile(image, tile, fetched, self.paint, self.pool)?,
+                None if tile.layer > 0 => (),
+                None => {
+                    let rect = tiny_skia::Rect::from_xywh(
+                        tile.x_px as f32,
+                        tile.y_px as f32,
+                        tile.size as f32,
+                        tile.size as f32,
+                    );
+                    if let Some(rect) = rect {
+                        let mut paint = Paint::default();
+                        paint.set_color_rgba8(224, 224, 224, 255);
+                        image.fill_rect(rect, &paint, Transform::default(), None);
+                    }
+                }
+            }
+        }
+
+        Ok(complete)
+    }
+}
+
+/// Pixels covered by a tile, as (left, top, right, bottom).
+/// Tiles are aligned to whole pixels, as tiles with anti-aliased edges
+/// leave semi-transparent seams between them.
+fn pixel_rect(tile: &TilePlacement) -> (f64, f64, f64, f64) {
+    (
+        tile.x_px.round(),
+        tile.y_px.round(),
+        (tile.x_px + tile.size).round(),
+        (tile.y_px + tile.size).round(),
+    )
+}
+
+/// Whether two pixel rectangles share any pixels, not only an edge.
+fn overlaps(a: &(f64, f64, f64, f64), b: &(f64, f64, f64, f64)) -> bool {
+    a.0 < b.2 && b.0 < a.2 && a.1 < b.3 && b.1 < a.3
+}
+
+fn draw_tile(
+    image: &mut PixmapMut,
+    tile: &TilePlacement,
+    tile_image: &[u8],
+    tile_paint: PixmapPaint,
+    pool: Option<&PixmapPool>,
+) -> Result<()> {
+    let decoded = match pool {
+        Some(pool) => decode_image_pooled(tile_image, pool),
+        None => decode_image(tile_image),
+    };
+    let pixmap = decoded.map_err(|error| Error::InvalidTile {
+        url: tile.url.clone(),
+        reason: format!("{}, starting with \"{}\"", error, preview(tile_image)),
+    })?;
+
+    let (left, top, right, bottom) = pixel_rect(tile);
+    let (width, height) = (right - left, bottom - top);
+
+    // Tiles not matching the configured tile size, e.g. 512px retina tiles,
+    // are rescaled to fit the tile grid.
+    let (scale_x, scale_y) = (
+        width as f32 / pixmap.width() as f32,
+        height as f32 / pixmap.height() as f32,
+    );
+
+    let (paint, transform) = if scale_x == 1. && scale_y == 1. {
+        (
+            PixmapPaint {
+                quality: FilterQuality::Nearest,
+                ..tile_paint
+            },
+            Transform::from_translate(left as f32, top as f32),
+        )
+    } else {
+        (
+            tile_paint,
+            Transform::from_scale(scale_x, scale_y).post_translate(left as f32, top as f32),
+        )
+    };
+
+    image.draw_pixmap(0, 0, pixmap.as_ref(), &paint, transform, None);
+    if let Some(pool) = pool {
+        pool.recycle(pixmap);
+    }
+    Ok(())
+}
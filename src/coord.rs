@@ -0,0 +1,155 @@
+/// Geographic coordinate, as latitude and longitude in degrees.
+///
+/// Builders taking a sequence of coordinates accept anything converting into it,
+/// so the latitudes and longitudes can not get out of sync:
+/// - `(f64, f64)` and `(f32, f32)` tuples, as (latitude, longitude).
+/// - [geo::Coord][geo::Coord] and [geo::Point][geo::Point], with the `geo` feature.
+///   Their x is the longitude and their y the latitude.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{tools::LineBuilder, LatLon};
+///
+/// let line = LineBuilder::new()
+///     .coordinates(vec![(52.5, 13.4), (48.9, 2.3)])
+///     .build()
+///     .unwrap();
+///
+/// // E.g. telemetry stored as integer micro-degrees.
+/// let berlin = LatLon::from_micro_degrees(52_500_000, 13_400_000);
+/// let paris = LatLon::from_micro_degrees(48_900_000, 2_300_000);
+/// let line = LineBuilder::new()
+///     .coordinates([berlin, paris])
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LatLon {
+    /// Latitude in degrees.
+    pub lat: f64,
+
+    /// Longitude in degrees.
+    pub lon: f64,
+}
+
+impl LatLon {
+    /// Creates a new [LatLon][LatLon].
+    pub fn new(lat: f64, lon: f64) -> Self {
+        LatLon { lat, lon }
+    }
+
+    /// Creates a new [LatLon][LatLon] from fixed-point degrees with the given number of
+    /// decimals, e.g. 7 for the 1e-7 degrees of OpenStreetMap and many GPS receivers.
+    pub fn from_fixed_point(lat: i64, lon: i64, decimals: u32) -> Self {
+        let scale = 10_f64.powi(decimals as i32);
+        LatLon {
+            lat: lat as f64 / scale,
+            lon: lon as f64 / scale,
+        }
+    }
+
+    /// Creates a new [LatLon][LatLon] from integer millionths of degrees.
+    pub fn from_micro_degrees(lat: i64, lon: i64) -> Self {
+        Self::from_fixed_point(lat, lon, 6)
+    }
+}
+
+/// Tuple of (latitude, longitude).
+impl From<(f64, f64)> for LatLon {
+    fn from((lat, lon): (f64, f64)) -> Self {
+        LatLon { lat, lon }
+    }
+}
+
+/// Tuple of (latitude, longitude).
+impl From<(f32, f32)> for LatLon {
+    fn from((lat, lon): (f32, f32)) -> Self {
+        LatLon {
+            lat: lat.into(),
+            lon: lon.into(),
+        }
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<geo::Coord<f64>> for LatLon {
+    fn from(coord: geo::Coord<f64>) -> Self {
+        LatLon {
+            lat: coord.y,
+            lon: coord.x,
+        }
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<geo::Point<f64>> for LatLon {
+    fn from(point: geo::Point<f64>) -> Self {
+        point.0.into()
+    }
+}
+
+/// Latitudes and longitudes of a sequence of coordinates.
+pub(crate) fn unzip<I, C>(coordinates: I) -> (Vec<f64>, Vec<f64>)
+where
+    I: IntoIterator<Item = C>,
+    C: Into<LatLon>,
+{
+    coordinates
+        .into_iter()
+        .map(|coordinate| {
+            let LatLon { lat, lon } = coordinate.into();
+            (lat, lon)
+        })
+        .unzip()
+}
+
+/// Geographic bounding box, in degrees.
+///
+/// Get the box visible on a map with [Viewport::bbox][crate::Viewport::bbox]
+/// or [Bounds::bbox][crate::Bounds::bbox], e.g. to query exactly the features
+/// of the rendered area from Overpass or a WFS service.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{LatLon, Viewport};
+///
+/// let bbox = Viewport::new(400, 300, 12, (10.75, 59.91)).bbox(256);
+/// assert!(bbox.contains(LatLon::new(59.91, 10.75)));
+///
+/// // Overpass order: south, west, north, east.
+/// let overpass = format!("[bbox:{},{},{},{}]", bbox.south, bbox.west, bbox.north, bbox.east);
+/// // WFS BBOX parameter in longitude, latitude order.
+/// let wfs = format!("{},{},{},{},CRS:84", bbox.west, bbox.south, bbox.east, bbox.north);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BoundingBox {
+    /// Southern latitude.
+    pub south: f64,
+
+    /// Western longitude.
+    pub west: f64,
+
+    /// Northern latitude.
+    pub north: f64,
+
+    /// Eastern longitude.
+    pub east: f64,
+}
+
+impl BoundingBox {
+    /// Corners of the box, clockwise from the north west corner.
+    pub fn corners(&self) -> [LatLon; 4] {
+        [
+            LatLon::new(self.north, self.west),
+            LatLon::new(self.north, self.east),
+            LatLon::new(self.south, self.east),
+            LatLon::new(self.south, self.west),
+        ]
+    }
+
+    /// Whether the box contains a coordinate, including its edges.
+    pub fn contains(&self, coordinate: LatLon) -> bool {
+        (self.south..=self.north).contains(&coordinate.lat)
+            && (self.west..=self.east).contains(&coordinate.lon)
+    }
+}
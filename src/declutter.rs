@@ -0,0 +1,194 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    tools::Tool,
+};
+use std::hash::Hasher;
+use tiny_skia::{FilterQuality, Pixmap, PixmapMut, PixmapPaint, Transform};
+
+/// Step by which overlapping tools are shrunk until they fit.
+const SHRINK_STEP: f64 = 0.1;
+
+/// Strategy for point features overlapping each other, e.g. many icons in a dense area
+/// at a low zoom, which would otherwise be drawn on top of each other.
+///
+/// Applies to tools with a priority, see [Tool::priority][Tool::priority], such as icons
+/// and markers given one with their builders. Tools are placed greedily at the final zoom,
+/// highest priority first and tools added earlier first among equal priorities,
+/// and tools overlapping a tool placed before them are hidden or shrunk.
+/// Tools without a priority are always drawn and never displace others.
+///
+/// Placement happens after spreading [coincident points][crate::CoincidentPoints],
+/// and does not affect the bounds of the map.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{testing::MockTileFetcher, tools::MarkerBuilder, Declutter, StaticMapBuilder};
+///
+/// let mut map = StaticMapBuilder::new()
+///     .tile_fetcher(MockTileFetcher::new())
+///     .zoom(5)
+///     .declutter(Declutter::Shrink(0.5))
+///     .build()
+///     .unwrap();
+///
+/// // The second marker is shrunk, or hidden if it still overlaps the first.
+/// for (lat, lon, priority) in [(59.91, 10.75, 2.), (59.92, 10.76, 1.)] {
+///     map.add_tool(
+///         MarkerBuilder::new()
+///             .lat_coordinate(lat)
+///             .lon_coordinate(lon)
+///             .priority(priority)
+///             .build()
+///             .unwrap(),
+///     );
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Declutter {
+    /// Draw all tools, overlapping or not.
+    Off,
+    /// Hide tools overlapping tools of higher priority.
+    Hide,
+    /// Shrink tools overlapping tools of higher priority around their coordinate,
+    /// down to the given fraction of their size, e.g. 0.5,
+    /// and hide tools still overlapping at that size.
+    Shrink(f64),
+}
+
+impl Digest for Declutter {
+    fn digest(&self, state: &mut dyn Hasher) {
+        match *self {
+            Declutter::Off => state.write_u8(0),
+            Declutter::Hide => state.write_u8(1),
+            Declutter::Shrink(min_scale) => {
+                state.write_u8(2);
+                min_scale.digest(state);
+            }
+        }
+    }
+}
+
+impl Declutter {
+    /// Scale each tool is drawn at, in the same order as the tools,
+    /// or None for hidden tools. Tools are displaced by the offsets,
+    /// and only the visible ones are placed.
+    pub(crate) fn scales(
+        &self,
+        tools: &[Box<dyn Tool>],
+        offsets: &[(f64, f64)],
+        visible: &[bool],
+        bounds: &Bounds,
+    ) -> Vec<Option<f64>> {
+        let mut scales = vec![Some(1.); tools.len()];
+        let min_scale = match *self {
+            Declutter::Off => return scales,
+            Declutter::Hide => 1.,
+            Declutter::Shrink(min_scale) => min_scale.clamp(0., 1.),
+        };
+
+        let mut candidates: Vec<_> = tools
+            .iter()
+            .zip(offsets)
+            .enumerate()
+            .filter(|&(index, _)| visible[index])
+            .filter_map(|(index, (tool, &(dx, dy)))| {
+                let priority = tool.priority()?;
+                let bounds = bounds.translate(dx, dy);
+                let rect = tool.pixel_extent(&bounds)?;
+                Some((index, priority, rect, anchor(tool, &bounds, &rect)))
+            })
+            .collect();
+        // Stable, so earlier tools win among equal priorities.
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut placed: Vec<PixelRect> = Vec::new();
+        for (index, _, rect, anchor) in candidates {
+            let steps = std::iter::successors(Some(1.), |&scale: &f64| {
+                (scale > min_scale).then(|| (scale - SHRINK_STEP).max(min_scale))
+            });
+            let fit = steps
+                .filter(|&scale| scale > 0.)
+                .map(|scale| (scale, scaled(&rect, anchor, scale)))
+                .find(|(_, rect)| !placed.iter().any(|other| overlaps(rect, other)));
+
+            scales[index] = fit.map(|(scale, rect)| {
+                placed.push(rect);
+                scale
+            });
+        }
+
+        scales
+    }
+}
+
+/// Draw a tool scaled around its anchor, as placed by [Declutter::scales],
+/// onto a pixmap `pixel_scale` times the size of the map.
+pub(crate) fn draw_scaled(
+    tool: &dyn Tool,
+    bounds: &Bounds,
+    scale: f64,
+    pixel_scale: f32,
+    mut pixmap: PixmapMut,
+) {
+    let rect = match tool.pixel_extent(bounds) {
+        Some(rect) => rect,
+        None => return,
+    };
+    let mut layer = match Pixmap::new(pixmap.width(), pixmap.height()) {
+        Some(layer) => layer,
+        None => return,
+    };
+    tool.draw_at_scale(bounds, pixel_scale, layer.as_mut());
+
+    let (x, y) = anchor(tool, bounds, &rect);
+    let (x, y, scale) = (x as f32 * pixel_scale, y as f32 * pixel_scale, scale as f32);
+    pixmap.draw_pixmap(
+        0,
+        0,
+        layer.as_ref(),
+        &PixmapPaint {
+            quality: FilterQuality::Bilinear,
+            ..Default::default()
+        },
+        Transform::from_translate(-x, -y)
+            .post_scale(scale, scale)
+            .post_translate(x, y),
+        None,
+    );
+}
+
+/// Pixel extent of a tool drawn scaled by [draw_scaled], given its unscaled extent.
+pub(crate) fn scaled_extent(
+    tool: &dyn Tool,
+    bounds: &Bounds,
+    rect: &PixelRect,
+    scale: f64,
+) -> PixelRect {
+    scaled(rect, anchor(tool, bounds, rect), scale)
+}
+
+/// Point tools are shrunk around their coordinate, others around their center.
+fn anchor(tool: &dyn Tool, bounds: &Bounds, rect: &PixelRect) -> (f64, f64) {
+    match tool.point() {
+        Some((lon, lat)) => bounds.lon_lat_to_px(lon, lat),
+        None => (
+            (rect.x_min + rect.x_max) / 2.,
+            (rect.y_min + rect.y_max) / 2.,
+        ),
+    }
+}
+
+fn scaled(rect: &PixelRect, (x, y): (f64, f64), scale: f64) -> PixelRect {
+    PixelRect::new(
+        x + (rect.x_min - x) * scale,
+        y + (rect.y_min - y) * scale,
+        x + (rect.x_max - x) * scale,
+        y + (rect.y_max - y) * scale,
+    )
+}
+
+/// Whether the rectangles share any pixels, so tools touching at an edge both fit.
+fn overlaps(a: &PixelRect, b: &PixelRect) -> bool {
+    a.x_min < b.x_max && b.x_min < a.x_max && a.y_min < b.y_max && b.y_min < a.y_max
+}
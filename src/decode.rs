@@ -0,0 +1,83 @@
+use crate::{pool::PixmapPool, Result};
+use tiny_skia::{ColorU8, Pixmap};
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Decode an image from bytes.
+///
+/// PNG images are always supported.
+/// JPEG and WebP images are supported with the `image` feature enabled.
+pub(crate) fn decode_image(data: &[u8]) -> Result<Pixmap> {
+    if data.starts_with(PNG_SIGNATURE) {
+        return Ok(Pixmap::decode_png(data)?);
+    }
+
+    decode_other(data)
+}
+
+/// Decode an image from bytes like [decode_image][decode_image],
+/// allocating the pixmap and the decoding buffer of PNG images from a pool.
+pub(crate) fn decode_image_pooled(data: &[u8], pool: &PixmapPool) -> Result<Pixmap> {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return decode_other(data);
+    }
+
+    let unsupported = |reason: &str| -> crate::Error {
+        png::DecodingError::from(std::io::Error::other(reason.to_string())).into()
+    };
+
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut decoded = pool.buffer(reader.output_buffer_size());
+    let info = reader.next_frame(&mut decoded)?;
+
+    let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::Rgb => 3,
+        png::ColorType::Rgba => 4,
+        png::ColorType::Indexed => return Err(unsupported("indexed PNG is not supported")),
+    };
+    if info.bit_depth != png::BitDepth::Eight {
+        return Err(unsupported("unsupported bit depth"));
+    }
+
+    let mut pixmap = pool
+        .pixmap(info.width, info.height)
+        .ok_or(crate::Error::InvalidSize)?;
+    let rows = decoded.chunks(info.line_size).take(info.height as usize);
+    let pixels = rows.flat_map(|row| row[..info.width as usize * channels].chunks(channels));
+    for (pixel, value) in pixmap.pixels_mut().iter_mut().zip(pixels) {
+        let (r, g, b, a) = match *value {
+            [gray] => (gray, gray, gray, 255),
+            [gray, alpha] => (gray, gray, gray, alpha),
+            [r, g, b] => (r, g, b, 255),
+            [r, g, b, a] => (r, g, b, a),
+            _ => (0, 0, 0, 0),
+        };
+        *pixel = ColorU8::from_rgba(r, g, b, a).premultiply();
+    }
+
+    pool.give(decoded);
+    Ok(pixmap)
+}
+
+#[cfg(feature = "image")]
+fn decode_other(data: &[u8]) -> Result<Pixmap> {
+    let image = image::load_from_memory(data)?.into_rgba8();
+    let (width, height) = image.dimensions();
+
+    let mut pixmap = Pixmap::new(width, height).ok_or(crate::Error::InvalidSize)?;
+    for (pixel, rgba) in pixmap.pixels_mut().iter_mut().zip(image.pixels()) {
+        let [r, g, b, a] = rgba.0;
+        *pixel = ColorU8::from_rgba(r, g, b, a).premultiply();
+    }
+
+    Ok(pixmap)
+}
+
+#[cfg(not(feature = "image"))]
+fn decode_other(data: &[u8]) -> Result<Pixmap> {
+    Ok(Pixmap::decode_png(data)?)
+}
@@ -0,0 +1,184 @@
+use std::{cell::Cell, hash::Hasher};
+use tiny_skia::{FilterQuality, Pixmap, PixmapPaint};
+
+/// FNV-1a hasher, stable across platforms and releases, unlike the standard library hasher.
+/// Integers are hashed as little endian bytes.
+pub(crate) struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+}
+
+thread_local! {
+    /// Whether a value digested on this thread cannot be part of a digest.
+    static UNCACHEABLE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Mark the digest being computed on this thread as unusable, for values which cannot be
+/// told apart by their digest, e.g. colors with shaders other than a solid color.
+pub(crate) fn mark_uncacheable() {
+    UNCACHEABLE.with(|x| x.set(true));
+}
+
+/// Whether a value digested on this thread since the last call was
+/// [marked as uncacheable][mark_uncacheable], clearing the mark.
+pub(crate) fn take_uncacheable() -> bool {
+    UNCACHEABLE.with(Cell::take)
+}
+
+/// Values which are part of the render digest.
+pub(crate) trait Digest {
+    /// Feed the value into the hasher.
+    fn digest(&self, state: &mut dyn Hasher);
+}
+
+impl Digest for f64 {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_u64(self.to_bits());
+    }
+}
+
+impl Digest for f32 {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_u32(self.to_bits());
+    }
+}
+
+impl Digest for u32 {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_u32(*self);
+    }
+}
+
+/// As 64 bits, so digests are the same on 32 and 64 bit platforms.
+impl Digest for usize {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_u64(*self as u64);
+    }
+}
+
+impl Digest for u8 {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_u8(*self);
+    }
+}
+
+impl Digest for i8 {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_i8(*self);
+    }
+}
+
+impl Digest for bool {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_u8(u8::from(*self));
+    }
+}
+
+impl Digest for str {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_usize(self.len());
+        state.write(self.as_bytes());
+    }
+}
+
+impl Digest for String {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.as_str().digest(state);
+    }
+}
+
+impl Digest for Pixmap {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_u32(self.width());
+        state.write_u32(self.height());
+        state.write(self.data());
+    }
+}
+
+impl Digest for PixmapPaint {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.opacity.digest(state);
+        format!("{:?}", self.blend_mode).digest(state);
+        self.quality.digest(state);
+    }
+}
+
+impl Digest for FilterQuality {
+    fn digest(&self, state: &mut dyn Hasher) {
+        format!("{:?}", self).digest(state);
+    }
+}
+
+impl<T: Digest> Digest for Option<T> {
+    fn digest(&self, state: &mut dyn Hasher) {
+        match self {
+            Some(value) => {
+                state.write_u8(1);
+                value.digest(state);
+            }
+            None => state.write_u8(0),
+        }
+    }
+}
+
+impl<T: Digest> Digest for [T] {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_usize(self.len());
+        for value in self {
+            value.digest(state);
+        }
+    }
+}
+
+impl<T: Digest> Digest for Vec<T> {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.as_slice().digest(state);
+    }
+}
+
+impl<A: Digest, B: Digest> Digest for (A, B) {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.0.digest(state);
+        self.1.digest(state);
+    }
+}
+
+impl<A: Digest, B: Digest, C: Digest, D: Digest> Digest for (A, B, C, D) {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.0.digest(state);
+        self.1.digest(state);
+        self.2.digest(state);
+        self.3.digest(state);
+    }
+}
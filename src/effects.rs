@@ -0,0 +1,264 @@
+//! Effects applied to the rendered map, after all tools are drawn.
+//!
+//! Add effects with [StaticMap::add_effect][crate::StaticMap::add_effect].
+//! Closures taking a `&mut PixmapMut` are effects too, for custom post-processing.
+
+use crate::digest::Digest;
+use std::hash::Hasher;
+use tiny_skia::PixmapMut;
+
+/// Trait implemented by types which can post-process the rendered map.
+pub trait Effect {
+    /// Apply the effect to the pixmap, in place.
+    fn apply(&self, pixmap: &mut PixmapMut);
+
+    /// Feed everything affecting the effect into `state`, returning true.
+    /// Used by [StaticMap::render_digest][crate::StaticMap::render_digest].
+    /// Default returns false, meaning the effect can not be part of a digest.
+    fn digest(&self, _state: &mut dyn Hasher) -> bool {
+        false
+    }
+}
+
+impl<F: Fn(&mut PixmapMut)> Effect for F {
+    fn apply(&self, pixmap: &mut PixmapMut) {
+        self(pixmap)
+    }
+}
+
+/// Vignette effect, darkening the map towards its corners.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::effects::Vignette;
+///
+/// let vignette = Vignette::new(0.5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Vignette {
+    strength: f32,
+    radius: f32,
+}
+
+impl Vignette {
+    /// Creates a new [Vignette][Vignette] darkening the corners by `strength`,
+    /// between 0.0 (no effect) and 1.0 (black corners).
+    pub fn new(strength: f32) -> Self {
+        Vignette {
+            strength: strength.clamp(0., 1.),
+            radius: 0.5,
+        }
+    }
+
+    /// Distance from the center where the darkening starts,
+    /// relative to the distance from the center to the corners.
+    /// Default is 0.5.
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius.clamp(0., 1.);
+        self
+    }
+}
+
+impl Effect for Vignette {
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "vignette".digest(state);
+        self.strength.digest(state);
+        self.radius.digest(state);
+        true
+    }
+
+    fn apply(&self, pixmap: &mut PixmapMut) {
+        let (width, height) = (pixmap.width() as usize, pixmap.height() as usize);
+        let (center_x, center_y) = (width as f32 / 2., height as f32 / 2.);
+        let max_distance = center_x.hypot(center_y);
+
+        for (index, pixel) in pixmap.data_mut().chunks_exact_mut(4).enumerate() {
+            let (x, y) = ((index % width) as f32 + 0.5, (index / width) as f32 + 0.5);
+            let distance = (x - center_x).hypot(y - center_y) / max_distance;
+
+            let t = ((distance - self.radius) / (1. - self.radius)).clamp(0., 1.);
+            let factor = 1. - self.strength * t * t * (3. - 2. * t);
+
+            // Premultiplied channels stay valid when scaling the color, but not the alpha.
+            for channel in pixel[..3].iter_mut() {
+                *channel = (f32::from(*channel) * factor).round() as u8;
+            }
+        }
+    }
+}
+
+/// Blur effect, blurring the whole map.
+///
+/// Approximates a gaussian blur with three box blurs.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::effects::Blur;
+///
+/// let blur = Blur::new(2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Blur {
+    radius: usize,
+}
+
+impl Blur {
+    /// Creates a new [Blur][Blur] with the given radius in pixels.
+    pub fn new(radius: u32) -> Self {
+        Blur {
+            radius: radius as usize,
+        }
+    }
+}
+
+impl Effect for Blur {
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "blur".digest(state);
+        state.write_usize(self.radius);
+        true
+    }
+
+    fn apply(&self, pixmap: &mut PixmapMut) {
+        if self.radius == 0 {
+            return;
+        }
+
+        let (width, height) = (pixmap.width() as usize, pixmap.height() as usize);
+        let data = pixmap.data_mut();
+
+        for _ in 0..3 {
+            box_blur(data, width, height, self.radius, true);
+            box_blur(data, width, height, self.radius, false);
+        }
+    }
+}
+
+/// One horizontal or vertical box blur pass over premultiplied RGBA data,
+/// repeating the edge pixels beyond the borders.
+fn box_blur(data: &mut [u8], width: usize, height: usize, radius: usize, horizontal: bool) {
+    let (lines, length) = if horizontal {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    let index = |line: usize, i: usize| {
+        if horizontal {
+            (line * width + i) * 4
+        } else {
+            (i * width + line) * 4
+        }
+    };
+
+    let size = (2 * radius + 1) as u32;
+    let mut line_buffer = vec![0_u8; length * 4];
+
+    for line in 0..lines {
+        for i in 0..length {
+            let offset = index(line, i);
+            line_buffer[i * 4..i * 4 + 4].copy_from_slice(&data[offset..offset + 4]);
+        }
+
+        let pixel = |i: isize| {
+            let i = i.clamp(0, length as isize - 1) as usize * 4;
+            &line_buffer[i..i + 4]
+        };
+
+        let mut sum = [0_u32; 4];
+        for i in -(radius as isize)..=radius as isize {
+            for (total, value) in sum.iter_mut().zip(pixel(i)) {
+                *total += u32::from(*value);
+            }
+        }
+
+        for i in 0..length {
+            let offset = index(line, i);
+            for (channel, total) in sum.iter().enumerate() {
+                data[offset + channel] = ((total + size / 2) / size) as u8;
+            }
+
+            let (incoming, outgoing) = (
+                i as isize + radius as isize + 1,
+                i as isize - radius as isize,
+            );
+            for (channel, total) in sum.iter_mut().enumerate() {
+                *total = *total + u32::from(pixel(incoming)[channel])
+                    - u32::from(pixel(outgoing)[channel]);
+            }
+        }
+    }
+}
+
+/// Invert effect, turning light maps dark, e.g. for a dark variant of a light tile source.
+///
+/// Keeps the hues by default, like the CSS filter `invert(1) hue-rotate(180deg)`,
+/// so water stays blue and parks stay green.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::effects::Invert;
+///
+/// let invert = Invert::new();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Invert {
+    keep_hue: bool,
+}
+
+impl Default for Invert {
+    fn default() -> Self {
+        Invert { keep_hue: true }
+    }
+}
+
+impl Invert {
+    /// Creates a new [Invert][Invert] keeping the hues.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to keep the hues, or invert them along with the lightness.
+    /// Default is true.
+    pub fn keep_hue(mut self, keep_hue: bool) -> Self {
+        self.keep_hue = keep_hue;
+        self
+    }
+}
+
+/// Hue rotation by 180 degrees, as defined for the CSS filter `hue-rotate`.
+const HUE_ROTATE_HALF: [[f32; 3]; 3] = [
+    [-0.574, 1.430, 0.144],
+    [0.426, 0.430, 0.144],
+    [0.426, 1.430, -0.856],
+];
+
+impl Effect for Invert {
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "invert".digest(state);
+        self.keep_hue.digest(state);
+        true
+    }
+
+    fn apply(&self, pixmap: &mut PixmapMut) {
+        for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+            // Inverted premultiplied channels are the alpha minus the channel.
+            let alpha = f32::from(pixel[3]);
+            let inverted = [
+                alpha - f32::from(pixel[0]),
+                alpha - f32::from(pixel[1]),
+                alpha - f32::from(pixel[2]),
+            ];
+
+            for (channel, target) in pixel[..3].iter_mut().enumerate() {
+                let value = match self.keep_hue {
+                    true => HUE_ROTATE_HALF[channel]
+                        .iter()
+                        .zip(inverted)
+                        .map(|(factor, x)| factor * x)
+                        .sum(),
+                    false => inverted[channel],
+                };
+                *target = value.clamp(0., alpha).round() as u8;
+            }
+        }
+    }
+}
@@ -7,6 +7,10 @@ pub enum Error {
     /// Error when decoding PNG from bytes.
     PngDecodingError(png::DecodingError),
 
+    /// Error when decoding a non-PNG image from bytes.
+    #[cfg(feature = "image")]
+    ImageDecodingError(image::ImageError),
+
     /// Request error when fetching tile from a tile server.
     TileError {
         /// Internal error from the HTTP client.
@@ -15,9 +19,64 @@ pub enum Error {
         url: String,
     },
 
+    /// The tile server responded with something other than a valid tile image,
+    /// e.g. an HTML error page.
+    InvalidTile {
+        /// The URL of the rejected tile.
+        url: String,
+        /// Why the tile was rejected.
+        reason: String,
+    },
+
+    /// A web service, e.g. the Overpass API, could not be reached,
+    /// or responded with an error or an unexpected document.
+    ServiceError {
+        /// The URL of the request.
+        url: String,
+        /// Why the request failed.
+        reason: String,
+    },
+
+    /// The font data could not be parsed.
+    InvalidFont,
+
+    /// Error when reading a file.
+    IoError(std::io::Error),
+
     /// Invalid image size.
     InvalidSize,
 
+    /// The zoom of the map is outside the zoom levels served by the tile source.
+    ZoomOutOfRange {
+        /// Zoom of the tile grid.
+        zoom: u8,
+        /// Lowest zoom level of the tile source.
+        min: u8,
+        /// Highest zoom level of the tile source.
+        max: u8,
+    },
+
+    /// The scene could not be parsed, or contains unsupported settings.
+    InvalidScene(String),
+
+    /// The response of a routing engine could not be read as a route.
+    InvalidRoute(String),
+
+    /// The GTFS feed is missing required columns or contains invalid values.
+    InvalidFeed(String),
+
+    /// The GPX document could not be parsed, or contains no tracks.
+    InvalidGpx(String),
+
+    /// The SVG image could not be parsed.
+    InvalidSvg(String),
+
+    /// The GeoJSON document could not be read as features.
+    InvalidGeoJson(String),
+
+    /// The map has not been rendered yet, e.g. when annotating the last render.
+    NotRendered,
+
     /// Missing a field/fields when consuming a builder.
     BuildError(&'static str),
 }
@@ -34,12 +93,28 @@ impl From<png::DecodingError> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for Error {
+    fn from(e: image::ImageError) -> Self {
+        Self::ImageDecodingError(e)
+    }
+}
+
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
             Error::PngEncodingError(ref error) => Some(error),
             Error::PngDecodingError(ref error) => Some(error),
+            #[cfg(feature = "image")]
+            Error::ImageDecodingError(ref error) => Some(error),
             Error::TileError { ref error, .. } => Some(error),
+            Error::IoError(ref error) => Some(error),
             _ => None,
         }
     }
@@ -49,9 +124,25 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
             Error::InvalidSize => write!(f, "Width or height of map is invalid."),
+            Error::InvalidFont => write!(f, "Font data is invalid."),
+            Error::NotRendered => write!(f, "The map has not been rendered yet."),
+            Error::ZoomOutOfRange { zoom, min, max } => write!(
+                f,
+                "Zoom {} is outside the zoom levels {}-{} of the tile source.",
+                zoom, min, max
+            ),
+            Error::IoError(ref error) => write!(f, "{}.", error),
             Error::PngEncodingError(ref error) => write!(f, "{}.", error),
             Error::PngDecodingError(ref error) => write!(f, "{}.", error),
+            #[cfg(feature = "image")]
+            Error::ImageDecodingError(ref error) => write!(f, "{}.", error),
             Error::BuildError(ref error) => write!(f, "{}.", error),
+            Error::InvalidScene(ref reason) => write!(f, "Invalid scene: {}.", reason),
+            Error::InvalidRoute(ref reason) => write!(f, "Invalid route: {}.", reason),
+            Error::InvalidFeed(ref reason) => write!(f, "Invalid GTFS feed: {}.", reason),
+            Error::InvalidGpx(ref reason) => write!(f, "Invalid GPX: {}.", reason),
+            Error::InvalidSvg(ref reason) => write!(f, "Invalid SVG: {}.", reason),
+            Error::InvalidGeoJson(ref reason) => write!(f, "Invalid GeoJSON: {}.", reason),
             Error::TileError { ref error, ref url } => {
                 write!(
                     f,
@@ -59,6 +150,18 @@ impl std::fmt::Display for Error {
                     url, error
                 )
             }
+            Error::InvalidTile {
+                ref url,
+                ref reason,
+            } => {
+                write!(f, "Invalid tile with url {}. {}.", url, reason)
+            }
+            Error::ServiceError {
+                ref url,
+                ref reason,
+            } => {
+                write!(f, "Request to {} failed. {}.", url, reason)
+            }
         }
     }
 }
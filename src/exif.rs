@@ -0,0 +1,77 @@
+//! Minimal EXIF writer for geotagging exported images.
+
+const BYTE: u16 = 1;
+const ASCII: u16 = 2;
+const LONG: u16 = 4;
+const RATIONAL: u16 = 5;
+
+/// Little endian TIFF structure with an image description and GPS tags
+/// for the given coordinate, as embedded in the EXIF segment of JPEG images.
+pub(crate) fn gps_exif(lon: f64, lat: f64, description: &str) -> Vec<u8> {
+    let mut description = description.as_bytes().to_vec();
+    description.push(0);
+
+    // Header, IFD0 with two entries, the description, then the GPS IFD with five entries.
+    let ifd0_offset = 8;
+    let description_offset = ifd0_offset + 2 + 2 * 12 + 4;
+    let gps_offset = (description_offset + description.len() as u32 + 1) & !1;
+    let latitude_offset = gps_offset + 2 + 5 * 12 + 4;
+    let longitude_offset = latitude_offset + 24;
+
+    let mut exif = b"II*\0".to_vec();
+    exif.extend_from_slice(&ifd0_offset.to_le_bytes());
+
+    exif.extend_from_slice(&2_u16.to_le_bytes());
+    entry(
+        &mut exif,
+        0x010E,
+        ASCII,
+        description.len() as u32,
+        description_offset,
+    );
+    entry(&mut exif, 0x8825, LONG, 1, gps_offset);
+    exif.extend_from_slice(&0_u32.to_le_bytes());
+
+    exif.extend_from_slice(&description);
+    exif.resize(gps_offset as usize, 0);
+
+    let latitude_ref = if lat < 0. { b'S' } else { b'N' };
+    let longitude_ref = if lon < 0. { b'W' } else { b'E' };
+
+    exif.extend_from_slice(&5_u16.to_le_bytes());
+    entry(&mut exif, 0x0000, BYTE, 4, u32::from_le_bytes([2, 3, 0, 0]));
+    entry(&mut exif, 0x0001, ASCII, 2, u32::from(latitude_ref));
+    entry(&mut exif, 0x0002, RATIONAL, 3, latitude_offset);
+    entry(&mut exif, 0x0003, ASCII, 2, u32::from(longitude_ref));
+    entry(&mut exif, 0x0004, RATIONAL, 3, longitude_offset);
+    exif.extend_from_slice(&0_u32.to_le_bytes());
+
+    degrees(&mut exif, lat.abs());
+    degrees(&mut exif, lon.abs());
+
+    exif
+}
+
+/// IFD entry, with the value inline if it fits in four bytes and as an offset otherwise.
+fn entry(exif: &mut Vec<u8>, tag: u16, kind: u16, count: u32, value: u32) {
+    exif.extend_from_slice(&tag.to_le_bytes());
+    exif.extend_from_slice(&kind.to_le_bytes());
+    exif.extend_from_slice(&count.to_le_bytes());
+    exif.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Decimal degrees as three rationals: degrees, minutes and seconds.
+fn degrees(exif: &mut Vec<u8>, value: f64) {
+    let degrees = value.trunc();
+    let minutes = ((value - degrees) * 60.).trunc();
+    let seconds = ((value - degrees) * 60. - minutes) * 60.;
+
+    for (numerator, denominator) in [
+        (degrees as u32, 1_u32),
+        (minutes as u32, 1),
+        ((seconds * 10_000.).round() as u32, 10_000),
+    ] {
+        exif.extend_from_slice(&numerator.to_le_bytes());
+        exif.extend_from_slice(&denominator.to_le_bytes());
+    }
+}
@@ -0,0 +1,356 @@
+use crate::{Error, Result};
+use attohttpc::{Method, ProxySettings, RequestBuilder, Response, StatusCode};
+use rayon::prelude::*;
+use std::sync::{Arc, RwLock};
+use url::Url;
+
+type TokenRefresh = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+/// Trait implemented by types which can fetch tiles from a tile server.
+///
+/// Implement this trait to customize how tiles are fetched, e.g. to add caching
+/// or to read tiles from disk. Use [DefaultTileFetcher][DefaultTileFetcher] for plain HTTP.
+pub trait TileFetcher: Send + Sync {
+    /// Fetch the tiles at the given URLs.
+    /// Must return one result per URL, in the same order.
+    fn fetch(&self, urls: &[String]) -> Vec<Result<Vec<u8>>>;
+
+    /// Fetch the tiles at the given URLs, calling `done` with the index of each URL and its
+    /// result as soon as it is fetched, in any order. The map composites each tile as it
+    /// arrives, so a few slow tiles do not hold back the others.
+    /// URLs are ordered from the center of the map outwards, and are best fetched in that order.
+    ///
+    /// Default fetches all tiles with [fetch][TileFetcher::fetch] before reporting them.
+    fn fetch_each(&self, urls: &[String], done: &(dyn Fn(usize, Result<Vec<u8>>) + Sync)) {
+        for (index, tile) in self.fetch(urls).into_iter().enumerate() {
+            done(index, tile);
+        }
+    }
+}
+
+/// Authentication used for tile requests.
+#[derive(Clone)]
+enum Auth {
+    None,
+    Bearer(String),
+    ApiKey {
+        param: String,
+        key: String,
+    },
+    Refresh {
+        refresh: TokenRefresh,
+        token: Arc<RwLock<Option<String>>>,
+    },
+}
+
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Auth::None => write!(f, "None"),
+            Auth::Bearer(_) => write!(f, "Bearer"),
+            Auth::ApiKey { ref param, .. } => write!(f, "ApiKey({})", param),
+            Auth::Refresh { .. } => write!(f, "Refresh"),
+        }
+    }
+}
+
+/// HTTP tile fetcher used by default, fetching tiles in parallel.
+/// Use [DefaultTileFetcherBuilder][DefaultTileFetcherBuilder] to configure it.
+///
+/// Connections are not reused: every tile is fetched over a new connection, as the
+/// underlying HTTP client closes the connection after each response. To keep connections
+/// alive across tiles, implement [TileFetcher][TileFetcher] with a pooling HTTP client.
+///
+/// Proxies are read from the `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY`
+/// environment variables unless configured explicitly.
+/// Only HTTP(S) proxies are supported. SOCKS proxies are not, and are ignored when read
+/// from the environment, so tiles are fetched without a proxy. Use a custom
+/// [TileFetcher][TileFetcher] to fetch tiles through a SOCKS proxy.
+#[derive(Debug, Clone)]
+pub struct DefaultTileFetcher {
+    proxy: ProxySettings,
+    auth: Auth,
+}
+
+impl Default for DefaultTileFetcher {
+    fn default() -> Self {
+        Self {
+            proxy: ProxySettings::from_env(),
+            auth: Auth::None,
+        }
+    }
+}
+
+impl DefaultTileFetcher {
+    fn fetch_one(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self.send(url, false)?;
+
+        let response = match self.auth {
+            Auth::Refresh { .. } if response.status() == StatusCode::UNAUTHORIZED => {
+                self.send(url, true)?
+            }
+            _ => response,
+        };
+
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|x| x.to_str().ok())
+            .map(str::to_string);
+
+        let bytes = response.bytes().map_err(|error| Error::TileError {
+            error,
+            url: url.to_string(),
+        })?;
+
+        if !status.is_success() {
+            return Err(Error::InvalidTile {
+                url: url.to_string(),
+                reason: format!(
+                    "Server responded with status {}: \"{}\"",
+                    status,
+                    preview(&bytes)
+                ),
+            });
+        }
+
+        if let Some(content_type) = content_type.filter(|x| !is_image_content_type(x)) {
+            return Err(Error::InvalidTile {
+                url: url.to_string(),
+                reason: format!(
+                    "Expected an image, got content type {}: \"{}\"",
+                    content_type,
+                    preview(&bytes)
+                ),
+            });
+        }
+
+        Ok(bytes)
+    }
+
+    fn send(&self, url: &str, refresh: bool) -> Result<Response> {
+        RequestBuilder::try_new(Method::GET, url)
+            .map(|request| {
+                let request = request.proxy_settings(self.proxy.clone());
+
+                match self.auth {
+                    Auth::None => request,
+                    Auth::Bearer(ref token) => request.bearer_auth(token.as_str()),
+                    Auth::ApiKey { ref param, ref key } => request.param(param, key),
+                    Auth::Refresh {
+                        refresh: ref callback,
+                        ref token,
+                    } => match current_token(callback, token, refresh) {
+                        Some(token) => request.bearer_auth(token),
+                        None => request,
+                    },
+                }
+            })
+            .and_then(RequestBuilder::send)
+            .map_err(|error| Error::TileError {
+                error,
+                url: url.to_string(),
+            })
+    }
+}
+
+/// Whether a response with the given content type may contain a tile image.
+/// Generic binary responses are accepted, since some servers do not label tiles correctly.
+fn is_image_content_type(content_type: &str) -> bool {
+    let content_type = content_type.trim().to_ascii_lowercase();
+    content_type.starts_with("image/") || content_type.starts_with("application/octet-stream")
+}
+
+/// Printable preview of the first bytes of a response, used in error messages.
+pub(crate) fn preview(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(&bytes[..bytes.len().min(64)])
+        .chars()
+        .map(|x| if x.is_control() { ' ' } else { x })
+        .collect()
+}
+
+/// Returns the cached token, calling the refresh callback if there is none
+/// or if `refresh` is set, e.g. after the server rejected the cached token.
+fn current_token(
+    callback: &TokenRefresh,
+    token: &RwLock<Option<String>>,
+    refresh: bool,
+) -> Option<String> {
+    if !refresh {
+        if let Some(token) = token.read().ok().and_then(|x| x.clone()) {
+            return Some(token);
+        }
+    }
+
+    let fresh = callback();
+    if let Ok(mut token) = token.write() {
+        *token = fresh.clone();
+    }
+    fresh
+}
+
+impl TileFetcher for DefaultTileFetcher {
+    fn fetch(&self, urls: &[String]) -> Vec<Result<Vec<u8>>> {
+        urls.par_iter().map(|url| self.fetch_one(url)).collect()
+    }
+
+    fn fetch_each(&self, urls: &[String], done: &(dyn Fn(usize, Result<Vec<u8>>) + Sync)) {
+        // Bridged from a sequential iterator, so tiles are started in order.
+        urls.iter()
+            .enumerate()
+            .par_bridge()
+            .for_each(|(index, url)| done(index, self.fetch_one(url)));
+    }
+}
+
+/// Builder for [DefaultTileFetcher][DefaultTileFetcher].
+///
+/// ## Example
+/// ```rust
+/// use staticmap::DefaultTileFetcherBuilder;
+///
+/// let fetcher = DefaultTileFetcherBuilder::new()
+///     .proxy("http://proxy.example.com:3128")
+///     .no_proxy("localhost")
+///     .bearer_token("secret")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DefaultTileFetcherBuilder {
+    http_proxy: Option<String>,
+    https_proxy: Option<String>,
+    no_proxy: Vec<String>,
+    proxy_from_env: bool,
+    auth: Auth,
+}
+
+impl Default for DefaultTileFetcherBuilder {
+    fn default() -> Self {
+        Self {
+            http_proxy: None,
+            https_proxy: None,
+            no_proxy: Vec::new(),
+            proxy_from_env: true,
+            auth: Auth::None,
+        }
+    }
+}
+
+impl DefaultTileFetcherBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Proxy URL used for both HTTP and HTTPS tile requests,
+    /// e.g. "http://proxy.example.com:3128".
+    /// Only HTTP(S) proxies are supported, SOCKS proxies are rejected by
+    /// [build][DefaultTileFetcherBuilder::build].
+    pub fn proxy<I: Into<String>>(mut self, url: I) -> Self {
+        let url = url.into();
+        self.http_proxy = Some(url.clone());
+        self.https_proxy = Some(url);
+        self
+    }
+
+    /// Proxy URL used for HTTP tile requests.
+    pub fn http_proxy<I: Into<String>>(mut self, url: I) -> Self {
+        self.http_proxy = Some(url.into());
+        self
+    }
+
+    /// Proxy URL used for HTTPS tile requests.
+    pub fn https_proxy<I: Into<String>>(mut self, url: I) -> Self {
+        self.https_proxy = Some(url.into());
+        self
+    }
+
+    /// Host name suffix which should bypass the explicitly configured proxy.
+    /// Can be called several times.
+    pub fn no_proxy<I: Into<String>>(mut self, host: I) -> Self {
+        self.no_proxy.push(host.into());
+        self
+    }
+
+    /// Whether to read proxy configuration from the environment
+    /// when no proxy is set explicitly.
+    /// Default is true.
+    pub fn proxy_from_env(mut self, proxy_from_env: bool) -> Self {
+        self.proxy_from_env = proxy_from_env;
+        self
+    }
+
+    /// Static bearer token, sent in the `Authorization` header of every tile request.
+    pub fn bearer_token<I: Into<String>>(mut self, token: I) -> Self {
+        self.auth = Auth::Bearer(token.into());
+        self
+    }
+
+    /// API key appended to every tile URL as the query parameter `param`,
+    /// e.g. `api_key("access_token", "secret")`.
+    pub fn api_key<P: Into<String>, K: Into<String>>(mut self, param: P, key: K) -> Self {
+        self.auth = Auth::ApiKey {
+            param: param.into(),
+            key: key.into(),
+        };
+        self
+    }
+
+    /// Callback providing a bearer token, for tile services with expiring tokens.
+    ///
+    /// The token is requested on first use and cached,
+    /// and requested again when the server responds with 401 Unauthorized.
+    pub fn token_refresh<F>(mut self, refresh: F) -> Self
+    where
+        F: Fn() -> Option<String> + Send + Sync + 'static,
+    {
+        self.auth = Auth::Refresh {
+            refresh: Arc::new(refresh),
+            token: Arc::new(RwLock::new(None)),
+        };
+        self
+    }
+
+    /// Build the fetcher, consuming the builder.
+    /// Returns an error if a proxy URL is invalid or uses an unsupported scheme.
+    pub fn build(self) -> Result<DefaultTileFetcher> {
+        let proxy = if self.http_proxy.is_none() && self.https_proxy.is_none() {
+            if self.proxy_from_env {
+                ProxySettings::from_env()
+            } else {
+                ProxySettings::builder().build()
+            }
+        } else {
+            let mut builder = ProxySettings::builder()
+                .http_proxy(self.http_proxy.as_deref().map(parse_proxy).transpose()?)
+                .https_proxy(self.https_proxy.as_deref().map(parse_proxy).transpose()?);
+
+            for host in self.no_proxy.iter() {
+                builder = builder.add_no_proxy_host(host);
+            }
+
+            builder.build()
+        };
+
+        Ok(DefaultTileFetcher {
+            proxy,
+            auth: self.auth,
+        })
+    }
+}
+
+fn parse_proxy(url: &str) -> Result<Url> {
+    let url = Url::parse(url).map_err(|_| Error::BuildError("Invalid proxy URL."))?;
+
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        "socks4" | "socks4a" | "socks5" | "socks5h" => Err(Error::BuildError(
+            "SOCKS proxies are not supported, only HTTP(S) proxies are supported.",
+        )),
+        _ => Err(Error::BuildError(
+            "Unsupported proxy scheme, only HTTP(S) proxies are supported.",
+        )),
+    }
+}
@@ -0,0 +1,96 @@
+//! Geodesic calculations on a spherical earth.
+
+use std::f64::consts::PI;
+
+/// Mean radius of the earth, in meters.
+pub(crate) const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Great-circle distance between two points, in meters.
+pub(crate) fn distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = phi2 - phi1;
+    let d_lambda = (lon2 - lon1).to_radians();
+
+    let a = (d_phi / 2.).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.).sin().powi(2);
+    2. * EARTH_RADIUS * a.sqrt().atan2((1. - a).sqrt())
+}
+
+/// Point at the given distance in meters and bearing in degrees clockwise from north,
+/// as (latitude, longitude).
+pub(crate) fn destination(lat: f64, lon: f64, distance: f64, bearing: f64) -> (f64, f64) {
+    let (phi1, lambda1) = (lat.to_radians(), lon.to_radians());
+    let (delta, theta) = (distance / EARTH_RADIUS, bearing.to_radians());
+
+    let phi2 = (phi1.sin() * delta.cos() + phi1.cos() * delta.sin() * theta.cos()).asin();
+    let lambda2 = lambda1
+        + (theta.sin() * delta.sin() * phi1.cos()).atan2(delta.cos() - phi1.sin() * phi2.sin());
+
+    (phi2.to_degrees(), lambda2.to_degrees())
+}
+
+/// Area of the region between two parallels and two meridians, in square meters.
+pub(crate) fn rect_area(north: f64, south: f64, east: f64, west: f64) -> f64 {
+    let mut d_lambda = (east - west).to_radians();
+    if d_lambda < 0. {
+        d_lambda += 2. * PI;
+    }
+
+    EARTH_RADIUS.powi(2) * (north.to_radians().sin() - south.to_radians().sin()).abs() * d_lambda
+}
+
+/// Initial bearing of the great circle from the first to the second point,
+/// in degrees clockwise from north, between 0.0 and 360.0.
+pub(crate) fn initial_bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lambda = (lon2 - lon1).to_radians();
+
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.)
+}
+
+/// Points along the great circle between two points, as (latitude, longitude),
+/// including both ends and at most `spacing` meters apart.
+///
+/// Longitudes are continuous, e.g. 179.0 followed by 181.0 when crossing the antimeridian,
+/// so the path does not jump across the map.
+pub(crate) fn great_circle(
+    lat1: f64,
+    lon1: f64,
+    lat2: f64,
+    lon2: f64,
+    spacing: f64,
+) -> Vec<(f64, f64)> {
+    let length = distance(lat1, lon1, lat2, lon2);
+    let segments = (length / spacing).ceil().clamp(1., 1024.) as usize;
+    let delta = length / EARTH_RADIUS;
+
+    let (phi1, lambda1) = (lat1.to_radians(), lon1.to_radians());
+    let (phi2, lambda2) = (lat2.to_radians(), lon2.to_radians());
+
+    let mut points = Vec::with_capacity(segments + 1);
+    let mut previous_lon = lon1;
+    for index in 0..=segments {
+        let fraction = index as f64 / segments as f64;
+        let (lat, lon) = if delta.sin().abs() < 1e-12 {
+            (
+                lat1 + (lat2 - lat1) * fraction,
+                lon1 + (lon2 - lon1) * fraction,
+            )
+        } else {
+            let a = ((1. - fraction) * delta).sin() / delta.sin();
+            let b = (fraction * delta).sin() / delta.sin();
+            let x = a * phi1.cos() * lambda1.cos() + b * phi2.cos() * lambda2.cos();
+            let y = a * phi1.cos() * lambda1.sin() + b * phi2.cos() * lambda2.sin();
+            let z = a * phi1.sin() + b * phi2.sin();
+            (z.atan2(x.hypot(y)).to_degrees(), y.atan2(x).to_degrees())
+        };
+
+        // Unwrap the longitude to the one closest to the previous point.
+        let lon = lon + ((previous_lon - lon) / 360.).round() * 360.;
+        previous_lon = lon;
+        points.push((lat, lon));
+    }
+
+    points
+}
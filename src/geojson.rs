@@ -0,0 +1,137 @@
+use crate::{json::quote, tools::Color};
+use std::fmt::{self, Display, Write};
+
+/// GeoJSON Feature of a tool, with style properties following the simplestyle spec,
+/// e.g. "stroke", "stroke-width" and "fill".
+pub(crate) struct Feature {
+    geometry: String,
+    properties: Vec<(&'static str, String)>,
+}
+
+impl Feature {
+    pub(crate) fn point(lon: f64, lat: f64) -> Self {
+        Self::new("Point", position(lon, lat))
+    }
+
+    pub(crate) fn line_string(lon_coordinates: &[f64], lat_coordinates: &[f64]) -> Self {
+        Self::new(
+            "LineString",
+            positions(lon_coordinates.iter().zip(lat_coordinates)),
+        )
+    }
+
+    pub(crate) fn multi_line_string<'a>(
+        lines: impl Iterator<Item = (&'a [f64], &'a [f64])>,
+    ) -> Self {
+        let lines: Vec<String> = lines
+            .map(|(lon_coordinates, lat_coordinates)| {
+                positions(lon_coordinates.iter().zip(lat_coordinates))
+            })
+            .collect();
+        Self::new("MultiLineString", format!("[{}]", lines.join(",")))
+    }
+
+    /// Polygon with a single ring, closed if the first and last positions differ.
+    pub(crate) fn polygon(ring: &[(f64, f64)]) -> Self {
+        Self::new("Polygon", format!("[{}]", closed_ring(ring)))
+    }
+
+    /// Polygons with their rings, outline first, each closed if needed.
+    pub(crate) fn multi_polygon(polygons: &[Vec<Vec<(f64, f64)>>]) -> Self {
+        let polygons: Vec<String> = polygons
+            .iter()
+            .map(|rings| {
+                let rings: Vec<String> = rings.iter().map(|ring| closed_ring(ring)).collect();
+                format!("[{}]", rings.join(","))
+            })
+            .collect();
+        Self::new("MultiPolygon", format!("[{}]", polygons.join(",")))
+    }
+
+    fn new(kind: &str, coordinates: String) -> Self {
+        Self {
+            geometry: format!("{{\"type\":\"{}\",\"coordinates\":{}}}", kind, coordinates),
+            properties: Vec::new(),
+        }
+    }
+
+    pub(crate) fn number(mut self, name: &'static str, value: f64) -> Self {
+        if value.is_finite() {
+            self.properties.push((name, value.to_string()));
+        }
+        self
+    }
+
+    pub(crate) fn string(mut self, name: &'static str, value: &str) -> Self {
+        self.properties.push((name, quote(value)));
+        self
+    }
+
+    /// Color as `prefix` in hex notation, and its alpha as `prefix`-opacity.
+    pub(crate) fn color(self, prefix: &'static str, color: &Color) -> Self {
+        let color = color.solid().to_color_u8();
+        let opacity = match prefix {
+            "stroke" => "stroke-opacity",
+            "fill" => "fill-opacity",
+            _ => "opacity",
+        };
+
+        self.string(
+            prefix,
+            &format!(
+                "#{:02x}{:02x}{:02x}",
+                color.red(),
+                color.green(),
+                color.blue()
+            ),
+        )
+        .number(opacity, f64::from(color.alpha()) / 255.)
+    }
+
+    /// Stroke or fill style of a shape, drawn with a stroke of the given width
+    /// or filled if None.
+    pub(crate) fn style(self, color: &Color, stroke_width: Option<f32>) -> Self {
+        match stroke_width {
+            Some(width) => self
+                .color("stroke", color)
+                .number("stroke-width", width.into())
+                .number("fill-opacity", 0.),
+            None => self.color("fill", color).number("stroke-width", 0.),
+        }
+    }
+}
+
+impl Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{{\"type\":\"Feature\",\"geometry\":{},\"properties\":{{",
+            self.geometry
+        )?;
+        for (index, (name, value)) in self.properties.iter().enumerate() {
+            if index > 0 {
+                f.write_char(',')?;
+            }
+            write!(f, "{}:{}", quote(name), value)?;
+        }
+        f.write_str("}}")
+    }
+}
+
+fn position(lon: f64, lat: f64) -> String {
+    format!("[{},{}]", lon, lat)
+}
+
+fn positions<'a>(coordinates: impl Iterator<Item = (&'a f64, &'a f64)>) -> String {
+    let positions: Vec<String> = coordinates.map(|(lon, lat)| position(*lon, *lat)).collect();
+    format!("[{}]", positions.join(","))
+}
+
+/// Positions of a ring, closed if the first and last positions differ.
+fn closed_ring(ring: &[(f64, f64)]) -> String {
+    let closing = match (ring.first(), ring.last()) {
+        (Some(first), Some(last)) if first != last => Some(first),
+        _ => None,
+    };
+    positions(ring.iter().chain(closing).map(|(lon, lat)| (lon, lat)))
+}
@@ -0,0 +1,315 @@
+use crate::{digest::Digest, Error, Result};
+use std::{hash::Hasher, sync::Arc};
+
+type Transformation = Arc<dyn Fn(f64, f64) -> (f64, f64) + Send + Sync>;
+
+/// Custom tile grid in a projected coordinate reference system,
+/// as used by national mapping agencies.
+/// Use [TileGridBuilder][TileGridBuilder] as an entrypoint,
+/// [TileGrid::swiss_lv95][TileGrid::swiss_lv95] for the Swiss LV95 grid,
+/// or [TileGrid::cartesian][TileGrid::cartesian] for a flat plane without a projection.
+///
+/// Tiles are addressed from the top left origin of the grid,
+/// with `{z}` being the index into the resolutions.
+#[derive(Clone)]
+pub struct TileGrid {
+    forward: Transformation,
+    inverse: Transformation,
+    origin: (f64, f64),
+    resolutions: Vec<f64>,
+    zoom_identifiers: Option<Vec<String>>,
+    extent: Option<(f64, f64, f64, f64)>,
+}
+
+impl std::fmt::Debug for TileGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("TileGrid")
+            .field("origin", &self.origin)
+            .field("resolutions", &self.resolutions)
+            .field("zoom_identifiers", &self.zoom_identifiers)
+            .field("extent", &self.extent)
+            .finish()
+    }
+}
+
+impl TileGrid {
+    /// Swiss LV95 (EPSG:2056) grid used by the swisstopo WMTS.
+    ///
+    /// Uses the approximate swisstopo formulas, accurate to about a meter.
+    pub fn swiss_lv95() -> Self {
+        TileGridBuilder::new()
+            .projection(
+                |lon, lat| {
+                    let phi = (lat * 3600. - 169_028.66) / 10_000.;
+                    let lambda = (lon * 3600. - 26_782.5) / 10_000.;
+
+                    let e = 2_600_072.37 + 211_455.93 * lambda
+                        - 10_938.51 * lambda * phi
+                        - 0.36 * lambda * phi.powi(2)
+                        - 44.54 * lambda.powi(3);
+                    let n = 1_200_147.07
+                        + 308_807.95 * phi
+                        + 3_745.25 * lambda.powi(2)
+                        + 76.63 * phi.powi(2)
+                        - 194.56 * lambda.powi(2) * phi
+                        + 119.79 * phi.powi(3);
+                    (e, n)
+                },
+                |e, n| {
+                    let y = (e - 2_600_000.) / 1_000_000.;
+                    let x = (n - 1_200_000.) / 1_000_000.;
+
+                    let lambda =
+                        2.677_909_4 + 4.728_982 * y + 0.791_484 * y * x + 0.130_6 * y * x.powi(2)
+                            - 0.043_6 * y.powi(3);
+                    let phi = 16.902_389_2 + 3.238_272 * x
+                        - 0.270_978 * y.powi(2)
+                        - 0.002_528 * x.powi(2)
+                        - 0.044_7 * y.powi(2) * x
+                        - 0.014_0 * x.powi(3);
+                    (lambda * 100. / 36., phi * 100. / 36.)
+                },
+            )
+            .origin(2_420_000., 1_350_000.)
+            .resolutions(vec![
+                4000., 3750., 3500., 3250., 3000., 2750., 2500., 2250., 2000., 1750., 1500., 1250.,
+                1000., 750., 650., 500., 250., 100., 50., 20., 10., 5., 2.5, 2., 1.5, 1., 0.5,
+                0.25, 0.1,
+            ])
+            .extent(2_420_000., 1_030_000., 2_900_000., 1_350_000.)
+            .build()
+            .expect("Internal logic error - the LV95 grid is complete")
+    }
+
+    /// Flat Cartesian plane for non-geographic maps, e.g. indoor maps,
+    /// seating charts or game maps.
+    ///
+    /// Longitudes and latitudes passed to tools are used as x and y directly,
+    /// with y pointing up. Zoom 12 maps one unit to one pixel,
+    /// and every zoom level below halves the scale. Distances in meters are plane units.
+    ///
+    /// The plane has no tiles, so use it together with a [BaseImage][crate::BaseImage]
+    /// as background. Use [TileGridBuilder][TileGridBuilder] with an identity projection
+    /// for a plane served as tiles.
+    ///
+    /// The pixel size of circles and icons is only approximated when fitting the map
+    /// to its features. Use padding to keep them inside the image.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{BaseImageBuilder, StaticMapBuilder, TileGrid};
+    ///
+    /// // A floor plan 800 units wide and 600 units high.
+    /// let floor_plan = BaseImageBuilder::new()
+    ///     .path("examples/results/empty_map.png")
+    ///     .unwrap()
+    ///     .north_lat_coordinate(600.)
+    ///     .south_lat_coordinate(0.)
+    ///     .east_lon_coordinate(800.)
+    ///     .west_lon_coordinate(0.)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut map = StaticMapBuilder::new()
+    ///     .width(400)
+    ///     .height(300)
+    ///     .zoom(11)
+    ///     .lon_center(400.)
+    ///     .lat_center(300.)
+    ///     .tile_grid(TileGrid::cartesian())
+    ///     .base_image(floor_plan)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let image = map.render().unwrap();
+    /// ```
+    pub fn cartesian() -> Self {
+        TileGridBuilder::new()
+            .projection(|x, y| (x, y), |x, y| (x, y))
+            .origin(0., 0.)
+            .resolutions((0..=24).map(|z| 2_f64.powi(12 - z)).collect())
+            // An empty extent, so no tiles are fetched.
+            .extent(0., 0., 0., 0.)
+            .build()
+            .expect("Internal logic error - the Cartesian grid is complete")
+    }
+
+    /// Highest zoom level of the grid.
+    pub fn max_zoom(&self) -> u8 {
+        (self.resolutions.len() - 1).min(u8::MAX.into()) as u8
+    }
+
+    /// Resolution at the given zoom, in CRS units per pixel.
+    pub fn resolution(&self, zoom: u8) -> f64 {
+        self.resolutions[usize::from(zoom.min(self.max_zoom()))]
+    }
+
+    /// Value of `{z}` in tile URLs at the given zoom.
+    pub(crate) fn zoom_identifier(&self, zoom: u8) -> String {
+        self.zoom_identifiers
+            .as_ref()
+            .and_then(|x| x.get(usize::from(zoom)))
+            .cloned()
+            .unwrap_or_else(|| zoom.to_string())
+    }
+
+    /// Longitude and latitude to projected coordinates.
+    pub(crate) fn project(&self, lon: f64, lat: f64) -> (f64, f64) {
+        (self.forward)(lon, lat)
+    }
+
+    /// Projected coordinates to longitude and latitude.
+    pub(crate) fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.inverse)(x, y)
+    }
+
+    /// Longitude and latitude to x and y, in tiles from the origin.
+    pub(crate) fn lon_lat_to_tile(
+        &self,
+        lon: f64,
+        lat: f64,
+        zoom: u8,
+        tile_size: u32,
+    ) -> (f64, f64) {
+        let (x, y) = self.project(lon, lat);
+        let tile = self.resolution(zoom) * f64::from(tile_size);
+        ((x - self.origin.0) / tile, (self.origin.1 - y) / tile)
+    }
+
+    /// X and y, in tiles from the origin, to longitude and latitude.
+    pub(crate) fn tile_to_lon_lat(&self, x: f64, y: f64, zoom: u8, tile_size: u32) -> (f64, f64) {
+        let tile = self.resolution(zoom) * f64::from(tile_size);
+        self.unproject(self.origin.0 + x * tile, self.origin.1 - y * tile)
+    }
+
+    /// Whether the tile lies within the extent of the grid.
+    pub(crate) fn contains_tile(&self, x: i32, y: i32, zoom: u8, tile_size: u32) -> bool {
+        if x < 0 || y < 0 {
+            return false;
+        }
+
+        match self.extent {
+            Some((_, min_y, max_x, _)) => {
+                let tile = self.resolution(zoom) * f64::from(tile_size);
+                f64::from(x) * tile < max_x - self.origin.0
+                    && f64::from(y) * tile < self.origin.1 - min_y
+            }
+            None => true,
+        }
+    }
+}
+
+impl Digest for TileGrid {
+    // The projection is not part of the digest, since closures can not be hashed.
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.origin.digest(state);
+        self.resolutions.digest(state);
+        self.zoom_identifiers.digest(state);
+        self.extent.digest(state);
+    }
+}
+
+/// Builder for [TileGrid][TileGrid].
+///
+/// ## Example
+/// ```rust
+/// use staticmap::TileGridBuilder;
+///
+/// // A grid in Web Mercator meters with two zoom levels.
+/// let grid = TileGridBuilder::new()
+///     .projection(
+///         |lon, lat| {
+///             let x = lon.to_radians() * 6_378_137.;
+///             let y = (std::f64::consts::FRAC_PI_4 + lat.to_radians() / 2.).tan().ln() * 6_378_137.;
+///             (x, y)
+///         },
+///         |x, y| {
+///             let lon = (x / 6_378_137.).to_degrees();
+///             let lat = (2. * (y / 6_378_137.).exp().atan() - std::f64::consts::FRAC_PI_2).to_degrees();
+///             (lon, lat)
+///         },
+///     )
+///     .origin(-20_037_508.34, 20_037_508.34)
+///     .resolutions(vec![156_543.03, 78_271.52])
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct TileGridBuilder {
+    forward: Option<Transformation>,
+    inverse: Option<Transformation>,
+    origin: Option<(f64, f64)>,
+    resolutions: Vec<f64>,
+    zoom_identifiers: Option<Vec<String>>,
+    extent: Option<(f64, f64, f64, f64)>,
+}
+
+impl TileGridBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// Transformations from (longitude, latitude) to projected (x, y), and back.
+    pub fn projection<F, I>(mut self, forward: F, inverse: I) -> Self
+    where
+        F: Fn(f64, f64) -> (f64, f64) + Send + Sync + 'static,
+        I: Fn(f64, f64) -> (f64, f64) + Send + Sync + 'static,
+    {
+        self.forward = Some(Arc::new(forward));
+        self.inverse = Some(Arc::new(inverse));
+        self
+    }
+
+    /// **Required**.
+    /// Top left corner of the grid, in projected coordinates.
+    pub fn origin(mut self, x: f64, y: f64) -> Self {
+        self.origin = Some((x, y));
+        self
+    }
+
+    /// **Required**.
+    /// Resolution of each zoom level in projected units per pixel, starting at zoom 0.
+    pub fn resolutions(mut self, resolutions: Vec<f64>) -> Self {
+        self.resolutions = resolutions;
+        self
+    }
+
+    /// Value of `{z}` in tile URLs for each zoom level, e.g. WMTS tile matrix identifiers.
+    /// Default is the zoom level itself.
+    pub fn zoom_identifiers(mut self, identifiers: Vec<String>) -> Self {
+        self.zoom_identifiers = Some(identifiers);
+        self
+    }
+
+    /// Extent of the grid in projected coordinates, as (min_x, min_y, max_x, max_y).
+    /// Tiles outside the extent are not fetched.
+    pub fn extent(mut self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        self.extent = Some((min_x, min_y, max_x, max_y));
+        self
+    }
+
+    /// Build the grid, consuming the builder.
+    /// Returns an error if the builder is missing required fields.
+    pub fn build(self) -> Result<TileGrid> {
+        if self.resolutions.is_empty() {
+            return Err(Error::BuildError("Resolutions not supplied."));
+        }
+
+        Ok(TileGrid {
+            forward: self
+                .forward
+                .ok_or(Error::BuildError("Projection not supplied."))?,
+            inverse: self
+                .inverse
+                .ok_or(Error::BuildError("Projection not supplied."))?,
+            origin: self
+                .origin
+                .ok_or(Error::BuildError("Origin not supplied."))?,
+            resolutions: self.resolutions,
+            zoom_identifiers: self.zoom_identifiers,
+            extent: self.extent,
+        })
+    }
+}
@@ -0,0 +1,448 @@
+use crate::{
+    tools::{CircleBuilder, Color, LineBuilder, Tool},
+    Error, Result,
+};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Points of a shape, as (sequence, latitude, longitude).
+type Shape = Vec<(u32, f64, f64)>;
+
+/// Route of a [GtfsFeed][GtfsFeed], from `routes.txt`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GtfsRoute {
+    /// Id of the route.
+    pub id: String,
+    /// Short name of the route, e.g. "32".
+    pub short_name: String,
+    /// Long name of the route, e.g. "Kjelsås - Ullevål".
+    pub long_name: String,
+    /// Color of the route as RGB, if set in the feed.
+    pub color: Option<(u8, u8, u8)>,
+}
+
+/// Stop of a [GtfsFeed][GtfsFeed], from `stops.txt`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GtfsStop {
+    /// Id of the stop.
+    pub id: String,
+    /// Name of the stop.
+    pub name: String,
+    /// Latitude of the stop.
+    pub lat: f64,
+    /// Longitude of the stop.
+    pub lon: f64,
+}
+
+/// Transit feed in the GTFS format, drawing the shapes and stops of selected routes
+/// in the colors of the routes, e.g. for a static network map.
+///
+/// The feed is read from a directory with the extracted files of the feed.
+/// Routes, trips and stops are read when the feed is opened, shapes and stop times
+/// only for the selected routes when drawn.
+///
+/// Requires the `gtfs` feature.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{GtfsFeed, StaticMapBuilder};
+///
+/// let feed = GtfsFeed::from_dir("gtfs").unwrap().line_width(4.);
+///
+/// let mut map = StaticMapBuilder::new().width(800).height(600).build().unwrap();
+/// for tool in feed.tools(&["1", "2", "3"]).unwrap() {
+///     map.add_tool(tool);
+/// }
+/// map.save_png("network.png").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct GtfsFeed {
+    dir: PathBuf,
+    routes: Vec<GtfsRoute>,
+    stops: HashMap<String, GtfsStop>,
+    /// Trips of each route, as (trip id, shape id).
+    trips: HashMap<String, Vec<(String, Option<String>)>>,
+    line_width: f32,
+    stop_radius: f32,
+    default_color: Color,
+}
+
+impl GtfsFeed {
+    /// Open a feed from a directory containing `routes.txt`, `trips.txt` and `stops.txt`,
+    /// and optionally `shapes.txt` and `stop_times.txt`.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<GtfsFeed> {
+        let dir = dir.as_ref().to_path_buf();
+
+        let routes = read_table(&dir, "routes.txt")?
+            .rows(&["route_id"])?
+            .map(|row| GtfsRoute {
+                id: row[0].to_string(),
+                short_name: row.get("route_short_name").to_string(),
+                long_name: row.get("route_long_name").to_string(),
+                color: parse_color(row.get("route_color")),
+            })
+            .collect();
+
+        let mut stops = HashMap::new();
+        for row in read_table(&dir, "stops.txt")?.rows(&["stop_id", "stop_lat", "stop_lon"])? {
+            // Entrances and other locations without coordinates are left out.
+            if let (Ok(lat), Ok(lon)) = (row[1].parse(), row[2].parse()) {
+                let stop = GtfsStop {
+                    id: row[0].to_string(),
+                    name: row.get("stop_name").to_string(),
+                    lat,
+                    lon,
+                };
+                stops.insert(stop.id.clone(), stop);
+            }
+        }
+
+        let mut trips: HashMap<String, Vec<_>> = HashMap::new();
+        for row in read_table(&dir, "trips.txt")?.rows(&["route_id", "trip_id"])? {
+            let shape_id = Some(row.get("shape_id"))
+                .filter(|x| !x.is_empty())
+                .map(str::to_string);
+            trips
+                .entry(row[0].to_string())
+                .or_default()
+                .push((row[1].to_string(), shape_id));
+        }
+
+        Ok(GtfsFeed {
+            dir,
+            routes,
+            stops,
+            trips,
+            line_width: 3.,
+            stop_radius: 3.,
+            default_color: Color::new(true, 80, 80, 80, 255),
+        })
+    }
+
+    /// Width of the route lines, in pixels.
+    /// Default is 3.0.
+    pub fn line_width(mut self, width: f32) -> Self {
+        self.line_width = width;
+        self
+    }
+
+    /// Radius of the stop markers, in pixels, or 0.0 to leave out the stops.
+    /// Default is 3.0.
+    pub fn stop_radius(mut self, radius: f32) -> Self {
+        self.stop_radius = radius;
+        self
+    }
+
+    /// Color of routes without a color in the feed.
+    /// Default is a gray color.
+    pub fn default_color(mut self, color: Color) -> Self {
+        self.default_color = color;
+        self
+    }
+
+    /// Routes of the feed.
+    pub fn routes(&self) -> &[GtfsRoute] {
+        &self.routes
+    }
+
+    /// Stop by id.
+    pub fn stop(&self, id: &str) -> Option<&GtfsStop> {
+        self.stops.get(id)
+    }
+
+    /// Tools drawing the selected routes, by route id: a line for each distinct shape
+    /// of the trips of a route, in the color of the route, and a white marker for each
+    /// stop served by the routes, outlined in the color of the first route serving it.
+    /// Returns an error if a route id is not in the feed.
+    pub fn tools(&self, route_ids: &[&str]) -> Result<Vec<Box<dyn Tool>>> {
+        let mut colors = Vec::with_capacity(route_ids.len());
+        for id in route_ids {
+            let route = self
+                .routes
+                .iter()
+                .find(|route| route.id == *id)
+                .ok_or_else(|| Error::InvalidFeed(format!("Unknown route \"{}\"", id)))?;
+            colors.push(match route.color {
+                Some((r, g, b)) => Color::new(true, r, g, b, 255),
+                None => self.default_color.clone(),
+            });
+        }
+
+        let trips = |route_id: &str| self.trips.get(route_id).into_iter().flatten();
+
+        // Shapes are drawn once per route, even if shared by many trips.
+        let mut shape_ids = HashSet::new();
+        let mut route_shapes: Vec<Vec<&str>> = vec![Vec::new(); route_ids.len()];
+        for (index, route_id) in route_ids.iter().enumerate() {
+            for (_, shape_id) in trips(route_id) {
+                if let Some(shape_id) = shape_id.as_deref() {
+                    if !route_shapes[index].contains(&shape_id) {
+                        route_shapes[index].push(shape_id);
+                        shape_ids.insert(shape_id);
+                    }
+                }
+            }
+        }
+
+        let mut tools: Vec<Box<dyn Tool>> = Vec::new();
+        let shapes = self.shapes(&shape_ids)?;
+        for (index, shape_ids) in route_shapes.iter().enumerate() {
+            for points in shape_ids.iter().filter_map(|id| shapes.get(*id)) {
+                tools.push(Box::new(
+                    LineBuilder::new()
+                        .lat_coordinates(points.iter().map(|x| x.1))
+                        .lon_coordinates(points.iter().map(|x| x.2))
+                        .color(colors[index].clone())
+                        .width(self.line_width)
+                        .build()?,
+                ));
+            }
+        }
+
+        if self.stop_radius > 0. {
+            let trip_routes: HashMap<&str, usize> = route_ids
+                .iter()
+                .enumerate()
+                .flat_map(|(index, route_id)| {
+                    trips(route_id).map(move |(trip_id, _)| (trip_id.as_str(), index))
+                })
+                .collect();
+
+            for (stop_id, index) in self.served_stops(&trip_routes)? {
+                if let Some(stop) = self.stops.get(&stop_id) {
+                    tools.push(Box::new(
+                        CircleBuilder::new()
+                            .lat_coordinate(stop.lat)
+                            .lon_coordinate(stop.lon)
+                            .radius(self.stop_radius)
+                            .color(Color::new(true, 255, 255, 255, 255))
+                            .casing(colors[index].clone(), 1.5)
+                            .build()?,
+                    ));
+                }
+            }
+        }
+
+        Ok(tools)
+    }
+
+    /// Points of the given shapes from `shapes.txt`, as (sequence, latitude, longitude),
+    /// in the order of the sequence.
+    fn shapes(&self, ids: &HashSet<&str>) -> Result<HashMap<String, Shape>> {
+        let mut shapes: HashMap<String, Shape> = HashMap::new();
+        if ids.is_empty() {
+            return Ok(shapes);
+        }
+
+        let table = read_table(&self.dir, "shapes.txt")?;
+        let rows = table.rows(&[
+            "shape_id",
+            "shape_pt_sequence",
+            "shape_pt_lat",
+            "shape_pt_lon",
+        ])?;
+        for row in rows.filter(|row| ids.contains(&row[0])) {
+            match (row[1].parse(), row[2].parse(), row[3].parse()) {
+                (Ok(sequence), Ok(lat), Ok(lon)) => shapes
+                    .entry(row[0].to_string())
+                    .or_default()
+                    .push((sequence, lat, lon)),
+                _ => {
+                    return Err(Error::InvalidFeed(format!(
+                        "Invalid point in shape \"{}\"",
+                        &row[0]
+                    )))
+                }
+            }
+        }
+
+        for points in shapes.values_mut() {
+            points.sort_by_key(|x| x.0);
+        }
+        Ok(shapes)
+    }
+
+    /// Stops served by the given trips from `stop_times.txt`,
+    /// with the index of the first route serving each, in the order of the file.
+    fn served_stops(&self, trip_routes: &HashMap<&str, usize>) -> Result<Vec<(String, usize)>> {
+        let mut stops: Vec<(String, usize)> = Vec::new();
+        let mut seen = HashSet::new();
+        if trip_routes.is_empty() {
+            return Ok(stops);
+        }
+
+        let table = read_table(&self.dir, "stop_times.txt")?;
+        for row in table.rows(&["trip_id", "stop_id"])? {
+            if let Some(index) = trip_routes.get(&row[0]) {
+                if seen.insert(row[1].to_string()) {
+                    stops.push((row[1].to_string(), *index));
+                }
+            }
+        }
+        Ok(stops)
+    }
+}
+
+/// Parse a GTFS color, six hexadecimal digits without a leading "#".
+fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    let color = color.trim();
+    if color.len() != 6 {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(color.get(index..index + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn read_table(dir: &Path, name: &str) -> Result<Table> {
+    let text = std::fs::read_to_string(dir.join(name))?;
+    Ok(Table {
+        text,
+        name: name.to_string(),
+    })
+}
+
+/// CSV file of a feed.
+struct Table {
+    text: String,
+    name: String,
+}
+
+impl Table {
+    /// Records of the table, after the header, with the `required` columns first.
+    /// Returns an error if a required column is missing.
+    fn rows<'a>(&'a self, required: &[&str]) -> Result<impl Iterator<Item = Row<'a>> + 'a> {
+        // Files may start with a byte order mark.
+        let mut records = Records::new(self.text.trim_start_matches('\u{feff}'));
+        let header: Rc<[String]> = records
+            .next()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|x| x.trim().to_string())
+            .collect();
+
+        let mut columns = Vec::with_capacity(required.len());
+        for name in required {
+            columns.push(header.iter().position(|x| x == name).ok_or_else(|| {
+                Error::InvalidFeed(format!("Missing column \"{}\" in {}", name, self.name))
+            })?);
+        }
+
+        Ok(records
+            .filter(|record| record.iter().any(|x| !x.is_empty()))
+            .map(move |record| Row {
+                header: Rc::clone(&header),
+                required: columns
+                    .iter()
+                    .map(|x| record.get(*x).cloned().unwrap_or_default())
+                    .collect(),
+                record,
+            }))
+    }
+}
+
+/// Record of a table.
+struct Row<'a> {
+    header: Rc<[String]>,
+    required: Vec<Cow<'a, str>>,
+    record: Vec<Cow<'a, str>>,
+}
+
+impl<'a> Row<'a> {
+    /// Value of an optional column, empty if missing.
+    fn get(&self, column: &str) -> &str {
+        self.header
+            .iter()
+            .position(|x| x == column)
+            .and_then(|x| self.record.get(x))
+            .map_or("", |x| x.as_ref())
+    }
+}
+
+impl<'a> std::ops::Index<usize> for Row<'a> {
+    type Output = str;
+
+    /// Value of a required column, in the order they were given.
+    fn index(&self, index: usize) -> &str {
+        &self.required[index]
+    }
+}
+
+/// Records of CSV text, following RFC 4180.
+struct Records<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Records<'a> {
+    fn new(text: &'a str) -> Self {
+        Records { text, position: 0 }
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Vec<Cow<'a, str>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.text.len() {
+            return None;
+        }
+
+        let bytes = self.text.as_bytes();
+        let mut fields = Vec::new();
+        loop {
+            let start = self.position;
+            let field = if bytes.get(start) == Some(&b'"') {
+                // Quoted field, with quotes escaped by doubling them.
+                let mut value = String::new();
+                let mut position = start + 1;
+                loop {
+                    match self.text[position..].find('"') {
+                        Some(end) => {
+                            value.push_str(&self.text[position..position + end]);
+                            position += end + 1;
+                            if bytes.get(position) == Some(&b'"') {
+                                value.push('"');
+                                position += 1;
+                            } else {
+                                break;
+                            }
+                        }
+                        None => {
+                            value.push_str(&self.text[position..]);
+                            position = self.text.len();
+                            break;
+                        }
+                    }
+                }
+                // Skip anything between the closing quote and the delimiter.
+                let end = self.text[position..]
+                    .find([',', '\n'])
+                    .map_or(self.text.len(), |x| position + x);
+                self.position = end;
+                Cow::Owned(value)
+            } else {
+                let end = self.text[start..]
+                    .find([',', '\n'])
+                    .map_or(self.text.len(), |x| start + x);
+                self.position = end;
+                Cow::Borrowed(self.text[start..end].trim_end_matches('\r'))
+            };
+            fields.push(field);
+
+            match bytes.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(_) => {
+                    self.position += 1;
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        Some(fields)
+    }
+}
@@ -0,0 +1,426 @@
+use crate::{Error, Result};
+use std::fmt::{self, Display, Write};
+
+/// Minimal JSON value, used for scenes.
+/// Objects keep the order of their members.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parse a JSON document, returning why it is invalid otherwise,
+    /// for callers to report as their own error.
+    pub(crate) fn parse(text: &str) -> std::result::Result<Json, String> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            position: 0,
+        };
+        let value = parser.value(0)?;
+        parser.whitespace();
+
+        match parser.position == parser.text.len() {
+            true => Ok(value),
+            false => Err(parser.error("Unexpected characters after the JSON value")),
+        }
+    }
+
+    /// Member of an object, None if missing or null.
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match *self {
+            Json::Object(ref members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value)
+                .filter(|value| **value != Json::Null),
+            _ => None,
+        }
+    }
+
+    /// Number member of an object, None if missing.
+    pub(crate) fn number(&self, key: &str) -> Result<Option<f64>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Json::Number(value)) => Ok(Some(*value)),
+            Some(_) => Err(invalid_member(key, "a number")),
+        }
+    }
+
+    /// Boolean member of an object, None if missing.
+    pub(crate) fn boolean(&self, key: &str) -> Result<Option<bool>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Json::Bool(value)) => Ok(Some(*value)),
+            Some(_) => Err(invalid_member(key, "a boolean")),
+        }
+    }
+
+    /// String member of an object, None if missing.
+    pub(crate) fn string(&self, key: &str) -> Result<Option<&str>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Json::String(value)) => Ok(Some(value)),
+            Some(_) => Err(invalid_member(key, "a string")),
+        }
+    }
+
+    /// Array member of an object, None if missing.
+    pub(crate) fn array(&self, key: &str) -> Result<Option<&[Json]>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Json::Array(value)) => Ok(Some(value)),
+            Some(_) => Err(invalid_member(key, "an array")),
+        }
+    }
+
+    /// Array of numbers member of an object, None if missing.
+    pub(crate) fn numbers(&self, key: &str) -> Result<Option<Vec<f64>>> {
+        match self.array(key)? {
+            None => Ok(None),
+            Some(values) => values
+                .iter()
+                .map(|x| match *x {
+                    Json::Number(value) => Ok(value),
+                    _ => Err(invalid_member(key, "an array of numbers")),
+                })
+                .collect::<Result<Vec<f64>>>()
+                .map(Some),
+        }
+    }
+
+    /// Array of two numbers member of an object, None if missing.
+    pub(crate) fn pair(&self, key: &str) -> Result<Option<(f64, f64)>> {
+        match self.numbers(key)?.as_deref() {
+            None => Ok(None),
+            Some(&[a, b]) => Ok(Some((a, b))),
+            Some(_) => Err(invalid_member(key, "an array of two numbers")),
+        }
+    }
+}
+
+/// Error for a member of an unexpected type.
+pub(crate) fn invalid_member(key: &str, expected: &str) -> Error {
+    Error::InvalidScene(format!("\"{}\" must be {}", key, expected))
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        Json::Number(value)
+    }
+}
+
+impl From<f32> for Json {
+    fn from(value: f32) -> Self {
+        Json::Number(value.into())
+    }
+}
+
+impl From<u32> for Json {
+    fn from(value: u32) -> Self {
+        Json::Number(value.into())
+    }
+}
+
+impl From<u8> for Json {
+    fn from(value: u8) -> Self {
+        Json::Number(value.into())
+    }
+}
+
+impl From<i8> for Json {
+    fn from(value: i8) -> Self {
+        Json::Number(value.into())
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(value: Vec<T>) -> Self {
+        Json::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<A: Into<Json>, B: Into<Json>> From<(A, B)> for Json {
+    fn from((a, b): (A, B)) -> Self {
+        Json::Array(vec![a.into(), b.into()])
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            // JSON has no representation of NaN and infinity.
+            Json::Number(value) if !value.is_finite() => f.write_str("null"),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(ref value) => f.write_str(&quote(value)),
+            Json::Array(ref values) => {
+                f.write_char('[')?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_char(']')
+            }
+            Json::Object(ref members) => {
+                f.write_char('{')?;
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}:{}", quote(name), value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+/// JSON string literal, escaping quotes, backslashes and control characters.
+pub(crate) fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Deepest nesting of arrays and objects parsed, so deeply nested documents,
+/// e.g. malicious service responses, do not overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+/// Result of parsing, with the reason and position of the error.
+type ParseResult<T> = std::result::Result<T, String>;
+
+impl<'a> Parser<'a> {
+    fn error(&self, reason: &str) -> String {
+        format!("{} at byte {}", reason, self.position)
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.text.get(self.position) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &str, value: Json) -> ParseResult<Json> {
+        match self.text[self.position..].starts_with(literal.as_bytes()) {
+            true => {
+                self.position += literal.len();
+                Ok(value)
+            }
+            false => Err(self.error("Invalid literal")),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> ParseResult<Json> {
+        self.whitespace();
+
+        match self.text.get(self.position) {
+            Some(b'n') => self.expect("null", Json::Null),
+            Some(b't') => self.expect("true", Json::Bool(true)),
+            Some(b'f') => self.expect("false", Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'[' | b'{') if depth >= MAX_DEPTH => Err(self.error("Nesting too deep")),
+            Some(b'[') => self.array(depth + 1),
+            Some(b'{') => self.object(depth + 1),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("Unexpected character")),
+            None => Err(self.error("Unexpected end of input")),
+        }
+    }
+
+    fn number(&mut self) -> ParseResult<Json> {
+        let start = self.position;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') =
+            self.text.get(self.position)
+        {
+            self.position += 1;
+        }
+
+        std::str::from_utf8(&self.text[start..self.position])
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .map(Json::Number)
+            .ok_or_else(|| self.error("Invalid number"))
+    }
+
+    fn string(&mut self) -> ParseResult<String> {
+        // Skip the opening quote.
+        self.position += 1;
+        let mut bytes = Vec::new();
+
+        loop {
+            let byte = *self
+                .text
+                .get(self.position)
+                .ok_or_else(|| self.error("Unterminated string"))?;
+            self.position += 1;
+
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = *self
+                        .text
+                        .get(self.position)
+                        .ok_or_else(|| self.error("Unterminated string"))?;
+                    self.position += 1;
+
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("Invalid escape")),
+                    };
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte => bytes.push(byte),
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| self.error("Invalid UTF-8 in string"))
+    }
+
+    fn hex4(&mut self) -> ParseResult<u32> {
+        let code = self
+            .text
+            .get(self.position..self.position + 4)
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .and_then(|x| u32::from_str_radix(x, 16).ok())
+            .ok_or_else(|| self.error("Invalid unicode escape"))?;
+        self.position += 4;
+        Ok(code)
+    }
+
+    fn unicode_escape(&mut self) -> ParseResult<char> {
+        let high = self.hex4()?;
+
+        let code = if (0xd800..0xdc00).contains(&high) {
+            // Surrogate pair.
+            if !self.text[self.position..].starts_with(b"\\u") {
+                return Err(self.error("Invalid unicode escape"));
+            }
+            self.position += 2;
+            let low = self.hex4()?;
+            0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("Invalid unicode escape"))
+    }
+
+    fn array(&mut self, depth: usize) -> ParseResult<Json> {
+        self.position += 1;
+        let mut values = Vec::new();
+
+        self.whitespace();
+        if self.text.get(self.position) == Some(&b']') {
+            self.position += 1;
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value(depth)?);
+            self.whitespace();
+
+            match self.text.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Json::Array(values));
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> ParseResult<Json> {
+        self.position += 1;
+        let mut members = Vec::new();
+
+        self.whitespace();
+        if self.text.get(self.position) == Some(&b'}') {
+            self.position += 1;
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.whitespace();
+            if self.text.get(self.position) != Some(&b'"') {
+                return Err(self.error("Expected a member name"));
+            }
+            let name = self.string()?;
+
+            self.whitespace();
+            if self.text.get(self.position) != Some(&b':') {
+                return Err(self.error("Expected ':'"));
+            }
+            self.position += 1;
+
+            members.push((name, self.value(depth)?));
+            self.whitespace();
+
+            match self.text.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+}
@@ -0,0 +1,441 @@
+use crate::{bounds::Bounds, json::Json, tools::Color, BoundingBox, Error, LatLon, Result};
+use std::iter::FromIterator;
+use tiny_skia::{FillRule, Path, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Geometry of a [Feature][Feature], e.g. as read from GeoJSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    /// A single point.
+    Point(LatLon),
+
+    /// A line through the coordinates, in order.
+    LineString(Vec<LatLon>),
+
+    /// An area, as its outer ring followed by its holes.
+    Polygon(Vec<Vec<LatLon>>),
+
+    /// Several geometries, e.g. the parts of a MultiPolygon.
+    Collection(Vec<Geometry>),
+}
+
+impl Geometry {
+    /// Geometry from a GeoJSON geometry object, with positions as [longitude, latitude].
+    /// Multi-geometries are read as collections.
+    pub(crate) fn from_geojson(json: &Json) -> std::result::Result<Self, String> {
+        let kind = match json.string("type") {
+            Ok(Some(kind)) => kind,
+            _ => return Err("Geometry without a type".to_string()),
+        };
+
+        if kind == "GeometryCollection" {
+            return match json.array("geometries") {
+                Ok(Some(geometries)) => geometries
+                    .iter()
+                    .map(Geometry::from_geojson)
+                    .collect::<std::result::Result<_, _>>()
+                    .map(Geometry::Collection),
+                _ => Err("GeometryCollection without geometries".to_string()),
+            };
+        }
+
+        let coordinates = json
+            .get("coordinates")
+            .ok_or_else(|| format!("{} without coordinates", kind))?;
+
+        match kind {
+            "Point" => position(coordinates).map(Geometry::Point),
+            "LineString" => positions(coordinates).map(Geometry::LineString),
+            "Polygon" => rings(coordinates).map(Geometry::Polygon),
+            "MultiPoint" => {
+                parts(coordinates, |x| position(x).map(Geometry::Point)).map(Geometry::Collection)
+            }
+            "MultiLineString" => parts(coordinates, |x| positions(x).map(Geometry::LineString))
+                .map(Geometry::Collection),
+            "MultiPolygon" => {
+                parts(coordinates, |x| rings(x).map(Geometry::Polygon)).map(Geometry::Collection)
+            }
+            kind => Err(format!("Unsupported geometry type {}", kind)),
+        }
+    }
+
+    /// Append the geometry to a path of lines, closing the rings of polygons.
+    fn append(&self, bounds: &Bounds, path: &mut PathBuilder) {
+        let mut line = |coordinates: &[LatLon], close: bool| {
+            for (index, coordinate) in coordinates.iter().enumerate() {
+                let (x, y) = bounds.lon_lat_to_px(coordinate.lon, coordinate.lat);
+                match index {
+                    0 => path.move_to(x as f32, y as f32),
+                    _ => path.line_to(x as f32, y as f32),
+                }
+            }
+            if close && coordinates.len() > 2 {
+                path.close();
+            }
+        };
+
+        match *self {
+            Geometry::Point(_) => (),
+            Geometry::LineString(ref coordinates) => line(coordinates, false),
+            Geometry::Polygon(ref rings) => {
+                for ring in rings {
+                    line(ring, true);
+                }
+            }
+            Geometry::Collection(ref geometries) => {
+                for geometry in geometries {
+                    geometry.append(bounds, path);
+                }
+            }
+        }
+    }
+
+    /// Points of the geometry, including those in collections.
+    fn points(&self) -> Vec<LatLon> {
+        match *self {
+            Geometry::Point(coordinate) => vec![coordinate],
+            Geometry::Collection(ref geometries) => {
+                geometries.iter().flat_map(Geometry::points).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn position(json: &Json) -> std::result::Result<LatLon, String> {
+    match *json {
+        Json::Array(ref values) => match values.as_slice() {
+            [Json::Number(lon), Json::Number(lat), ..] => Ok(LatLon::new(*lat, *lon)),
+            _ => Err("Position without a longitude and latitude".to_string()),
+        },
+        _ => Err("Position is not an array".to_string()),
+    }
+}
+
+fn positions(json: &Json) -> std::result::Result<Vec<LatLon>, String> {
+    parts(json, position)
+}
+
+fn rings(json: &Json) -> std::result::Result<Vec<Vec<LatLon>>, String> {
+    parts(json, positions)
+}
+
+/// Each element of a coordinates array, read with `part`.
+fn parts<T, P>(
+    json: &Json,
+    part: impl Fn(&Json) -> std::result::Result<P, String>,
+) -> std::result::Result<T, String>
+where
+    T: FromIterator<P>,
+{
+    match *json {
+        Json::Array(ref values) => values.iter().map(part).collect(),
+        _ => Err("Coordinates are not an array".to_string()),
+    }
+}
+
+/// Geographic feature drawn by a [FeatureLayer][FeatureLayer]: a geometry with properties,
+/// e.g. a building with its address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature {
+    /// Identifier of the feature, if any.
+    pub id: Option<String>,
+
+    /// Geometry of the feature.
+    pub geometry: Geometry,
+
+    /// Properties of the feature, as (key, value).
+    /// Values other than strings are kept as JSON, e.g. "12.5" or "true".
+    pub properties: Vec<(String, String)>,
+}
+
+impl Feature {
+    /// Value of a property, e.g. "name".
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Features of a GeoJSON FeatureCollection, or of a single Feature.
+    /// Features without a geometry are left out.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{Feature, Geometry, LatLon};
+    ///
+    /// let features = Feature::parse_geojson(
+    ///     r#"{"type": "Feature", "id": 7, "properties": {"name": "Fountain"},
+    ///        "geometry": {"type": "Point", "coordinates": [10.75, 59.91]}}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(features[0].id.as_deref(), Some("7"));
+    /// assert_eq!(features[0].property("name"), Some("Fountain"));
+    /// assert_eq!(features[0].geometry, Geometry::Point(LatLon::new(59.91, 10.75)));
+    /// ```
+    pub fn parse_geojson(text: &str) -> Result<Vec<Feature>> {
+        let json = Json::parse(text).map_err(Error::InvalidGeoJson)?;
+        features_from_geojson(&json).map_err(Error::InvalidGeoJson)
+    }
+
+    /// Feature from a GeoJSON Feature object, None if it has no geometry.
+    fn from_geojson(json: &Json) -> std::result::Result<Option<Self>, String> {
+        let geometry = match json.get("geometry") {
+            Some(geometry) => Geometry::from_geojson(geometry)?,
+            None => return Ok(None),
+        };
+
+        let id = match json.get("id") {
+            Some(Json::String(id)) => Some(id.clone()),
+            Some(id) => Some(id.to_string()),
+            None => None,
+        };
+
+        let properties = match json.get("properties") {
+            Some(Json::Object(members)) => members
+                .iter()
+                .filter(|(_, value)| *value != Json::Null)
+                .map(|(key, value)| match value {
+                    Json::String(value) => (key.clone(), value.clone()),
+                    value => (key.clone(), value.to_string()),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Ok(Some(Feature {
+            id,
+            geometry,
+            properties,
+        }))
+    }
+}
+
+/// Features of a GeoJSON FeatureCollection or Feature, or a reason they could not be read.
+pub(crate) fn features_from_geojson(json: &Json) -> std::result::Result<Vec<Feature>, String> {
+    match json.string("type") {
+        Ok(Some("FeatureCollection")) => match json.array("features") {
+            Ok(Some(features)) => features
+                .iter()
+                .filter_map(|feature| Feature::from_geojson(feature).transpose())
+                .collect(),
+            Ok(None) => Ok(Vec::new()),
+            Err(_) => Err("FeatureCollection with invalid features".to_string()),
+        },
+        Ok(Some("Feature")) => Ok(Feature::from_geojson(json)?.into_iter().collect()),
+        _ => Err("Expected a FeatureCollection or a Feature".to_string()),
+    }
+}
+
+/// Trait implemented by sources of features for a [FeatureLayer][FeatureLayer],
+/// e.g. [WfsSource][crate::WfsSource] or a database.
+///
+/// Sources are asked for the features of the area visible on the map each time it is
+/// rendered, so the data always matches the extent of the map.
+/// Closures taking a [BoundingBox][BoundingBox] are sources too.
+///
+/// Errors of remote services are best reported as
+/// [Error::ServiceError][crate::Error::ServiceError].
+pub trait FeatureSource {
+    /// Features in the given box, in the order they are drawn.
+    /// Features partly or entirely outside the box may be returned too.
+    fn features(&self, bbox: &BoundingBox) -> Result<Vec<Feature>>;
+}
+
+impl<F> FeatureSource for F
+where
+    F: Fn(&BoundingBox) -> Result<Vec<Feature>>,
+{
+    fn features(&self, bbox: &BoundingBox) -> Result<Vec<Feature>> {
+        self(bbox)
+    }
+}
+
+/// How a [FeatureLayer][FeatureLayer] draws a feature.
+/// Points are drawn as circles, lines are stroked, and polygons are filled and outlined.
+#[derive(Debug, Clone)]
+pub struct FeatureStyle {
+    color: Color,
+    width: f32,
+    fill: Option<Color>,
+    radius: f32,
+}
+
+impl Default for FeatureStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::new(true, 220, 40, 40, 255),
+            width: 2.,
+            fill: None,
+            radius: 4.,
+        }
+    }
+}
+
+impl FeatureStyle {
+    /// Create a new style with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Color of lines, outlines and points.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a red color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Width of lines and outlines, in pixels. Zero leaves out the outlines of polygons.
+    /// Default is 2.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Fill color of polygons and points, which are then outlined with the color.
+    /// Default is no fill, polygons are only outlined.
+    pub fn fill(mut self, color: Color) -> Self {
+        self.fill = Some(color);
+        self
+    }
+
+    /// Radius of points, in pixels.
+    /// Default is 4.0.
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    fn draw(&self, geometry: &Geometry, bounds: &Bounds, scale: f32, pixmap: &mut PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let stroke = Stroke {
+            width: self.width,
+            ..Default::default()
+        };
+        let fill = |path: &Path, color: &Color, pixmap: &mut PixmapMut| {
+            pixmap.fill_path(path, color.paint(), FillRule::EvenOdd, transform, None)
+        };
+
+        let mut path = PathBuilder::new();
+        geometry.append(bounds, &mut path);
+        if let Some(path) = path.finish() {
+            if let (Some(color), true) = (&self.fill, has_area(geometry)) {
+                fill(&path, color, pixmap);
+            }
+            if self.width > 0. {
+                pixmap.stroke_path(&path, self.color.paint(), &stroke, transform, None);
+            }
+        }
+
+        for point in geometry.points() {
+            let (x, y) = bounds.lon_lat_to_px(point.lon, point.lat);
+            let circle = match PathBuilder::from_circle(x as f32, y as f32, self.radius) {
+                Some(circle) => circle,
+                None => continue,
+            };
+            match self.fill {
+                Some(ref color) => {
+                    fill(&circle, color, pixmap);
+                    pixmap.stroke_path(&circle, self.color.paint(), &stroke, transform, None);
+                }
+                None => fill(&circle, &self.color, pixmap),
+            }
+        }
+    }
+}
+
+/// Whether the geometry is or contains a polygon.
+fn has_area(geometry: &Geometry) -> bool {
+    match *geometry {
+        Geometry::Polygon(_) => true,
+        Geometry::Collection(ref geometries) => geometries.iter().any(has_area),
+        _ => false,
+    }
+}
+
+type StyleFn = Box<dyn Fn(&Feature) -> Option<FeatureStyle>>;
+
+/// Layer of features fetched from a [FeatureSource][FeatureSource] when the map is rendered,
+/// for the area visible on the map. Add it to a map with
+/// [StaticMap::add_layer][crate::StaticMap::add_layer].
+///
+/// Layers are drawn over the tiles and beneath the tools, and do not affect the extent of
+/// the map, which is set by its tools or its center and zoom.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{
+///     testing::MockTileFetcher, tools::Color, BoundingBox, Error, Feature, FeatureLayer,
+///     FeatureStyle, Geometry, LatLon, StaticMapBuilder,
+/// };
+///
+/// // E.g. a query against a spatial database.
+/// let source = |bbox: &BoundingBox| -> Result<Vec<Feature>, Error> {
+///     Ok(vec![Feature {
+///         id: None,
+///         geometry: Geometry::LineString(vec![
+///             LatLon::new(bbox.south, bbox.west),
+///             LatLon::new(bbox.north, bbox.east),
+///         ]),
+///         properties: vec![("kind".to_string(), "diagonal".to_string())],
+///     }])
+/// };
+///
+/// let layer = FeatureLayer::new(source).style(|feature| match feature.property("kind") {
+///     Some("diagonal") => Some(FeatureStyle::new().color(Color::new(true, 0, 90, 200, 255))),
+///     _ => None,
+/// });
+///
+/// let mut map = StaticMapBuilder::new()
+///     .tile_fetcher(MockTileFetcher::new())
+///     .zoom(12)
+///     .lat_center(59.91)
+///     .lon_center(10.75)
+///     .build()
+///     .unwrap();
+/// map.add_layer(layer);
+/// map.render().unwrap();
+/// ```
+pub struct FeatureLayer {
+    source: Box<dyn FeatureSource>,
+    style: StyleFn,
+}
+
+impl FeatureLayer {
+    /// Create a layer drawing the features of `source` in the default
+    /// [FeatureStyle][FeatureStyle].
+    pub fn new(source: impl FeatureSource + 'static) -> Self {
+        Self {
+            source: Box::new(source),
+            style: Box::new(|_| Some(FeatureStyle::default())),
+        }
+    }
+
+    /// Closure choosing the style of each feature, e.g. from its properties.
+    /// Features it returns None for are left out.
+    pub fn style<F>(mut self, style: F) -> Self
+    where
+        F: Fn(&Feature) -> Option<FeatureStyle> + 'static,
+    {
+        self.style = Box::new(style);
+        self
+    }
+
+    /// Fetch and draw the features of the area visible within `bounds`, `scale` times larger.
+    pub(crate) fn draw(&self, bounds: &Bounds, scale: f32, pixmap: &mut PixmapMut) -> Result<()> {
+        for feature in self.source.features(&bounds.bbox())? {
+            if let Some(style) = (self.style)(&feature) {
+                style.draw(&feature.geometry, bounds, scale, pixmap);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for FeatureLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("FeatureLayer").finish_non_exhaustive()
+    }
+}
@@ -9,8 +9,23 @@
 //! - Render a map to a PNG image.
 //! - Draw features on a map, such as:
 //!     - Lines
-//!     - Circles
+//!     - Circles, ellipses and sectors
 //!     - PNG icons
+//!     - Vector markers, such as pins and stars
+//!     - Emoji markers
+//!
+//! ### Optional features:
+//! - `image`: Decode JPEG and WebP tiles, e.g. from satellite imagery providers.
+//! - `batch`: Render queues of many maps kept on disk, e.g. thumbnails.
+//! - `wmts`: Configure tile sources from WMTS capabilities documents.
+//! - `gtfs`: Draw routes and stops of GTFS transit feeds.
+//! - `gpx`: Read tracks from GPX documents.
+//! - `geo`: Union, intersection and difference of polygons, e.g. to clip datasets to the map.
+//! - `nominatim`: Find places by name or address with Nominatim.
+//! - `overpass`: Fetch OpenStreetMap data from the Overpass API and draw it as tools.
+//! - `postgis`: Draw features queried from a PostGIS database for the area of the map.
+//! - `svg`: Draw SVG images as symbols, recolored per symbol.
+//! - `wfs`: Draw features of WFS services, e.g. GeoServer, fetched for the area of the map.
 //!
 //! ## Example
 //! ```rust
@@ -48,27 +63,122 @@
 
 #![warn(missing_docs)]
 
+mod appearance;
+mod base_image;
+#[cfg(feature = "batch")]
+mod batch;
 mod bounds;
+mod cache;
+mod clip;
+mod coincident;
+mod compositor;
+mod coord;
+mod declutter;
+mod decode;
+mod digest;
 mod error;
+#[cfg(feature = "image")]
+mod exif;
+mod fetcher;
+mod geodesy;
+mod geojson;
+mod grid;
+#[cfg(feature = "gtfs")]
+mod gtfs;
+mod json;
+mod layer;
 mod map;
-
+#[cfg(feature = "nominatim")]
+mod nominatim;
+#[cfg(feature = "overpass")]
+mod overpass;
+mod placement;
+mod polylabel;
+mod polyline;
+mod pool;
+#[cfg(feature = "postgis")]
+mod postgis;
+mod processor;
+#[cfg(feature = "geo")]
+mod region;
+mod scene;
+#[cfg(any(feature = "nominatim", feature = "overpass", feature = "wfs"))]
+mod service;
+mod theme;
+mod tracks;
+mod viewport;
+#[cfg(feature = "wfs")]
+mod wfs;
+#[cfg(feature = "wmts")]
+mod wmts;
+
+pub mod effects;
+pub mod testing;
 /// Tools for drawing features onto the map.
 pub mod tools;
 
-pub use bounds::Bounds;
+pub use appearance::Appearance;
+pub use base_image::{BaseImage, BaseImageBuilder};
+#[cfg(feature = "batch")]
+pub use batch::{BatchReport, RenderQueue, RenderQueueBuilder};
+pub use bounds::{Bounds, Fit, PixelRect, PixelSnapping};
+pub use cache::{DiskImageCache, ImageCache};
+pub use coincident::CoincidentPoints;
+pub use coord::{BoundingBox, LatLon};
+pub use declutter::Declutter;
 pub use error::Error;
+pub use fetcher::{DefaultTileFetcher, DefaultTileFetcherBuilder, TileFetcher};
+pub use grid::{TileGrid, TileGridBuilder};
+#[cfg(feature = "gtfs")]
+pub use gtfs::{GtfsFeed, GtfsRoute, GtfsStop};
+pub use layer::{Feature, FeatureLayer, FeatureSource, FeatureStyle, Geometry};
 pub use map::{StaticMap, StaticMapBuilder};
+#[cfg(feature = "nominatim")]
+pub use nominatim::{Nominatim, NominatimBuilder, Place};
+#[cfg(feature = "overpass")]
+pub use overpass::{Overpass, OverpassBuilder, OverpassElement};
+pub use placement::TilePlacement;
+pub use pool::PixmapPool;
+#[cfg(feature = "postgis")]
+pub use postgis::{PostgisSource, PostgisSourceBuilder};
+pub use processor::{LineProcessor, NoProcessing};
+#[cfg(feature = "geo")]
+pub use region::{Region, RegionPolygon};
+pub use theme::Theme;
+pub use tracks::{Track, TrackComparison, TrackComparisonBuilder, TrackPoint};
+pub use viewport::Viewport;
+#[cfg(feature = "wfs")]
+pub use wfs::{WfsSource, WfsSourceBuilder};
+#[cfg(feature = "wmts")]
+pub use wmts::{WmtsSource, WmtsSourceBuilder};
 
 use std::f64::consts::PI;
 
 type Result<T> = std::result::Result<T, Error>;
 
 /// Longitude to x coordinate.
+/// Longitudes beyond -180.0 and 180.0 wrap around the antimeridian.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::lon_to_x;
+///
+/// assert_eq!(lon_to_x(190., 2), lon_to_x(-170., 2));
+/// ```
 pub fn lon_to_x(mut lon: f64, zoom: u8) -> f64 {
     if !(-180_f64..180_f64).contains(&lon) {
         lon = (lon + 180_f64) % 360_f64 - 180_f64;
     }
 
+    lon_to_x_continuous(lon, zoom)
+}
+
+/// Longitude to x coordinate, like [lon_to_x] but without wrapping.
+///
+/// Longitudes beyond -180.0 and 180.0 continue past the antimeridian instead of wrapping
+/// around, so lines crossing it, e.g. with longitudes 179.0 and 181.0, stay continuous.
+/// Used to draw on the map, where the tiles wrap around the antimeridian.
+pub(crate) fn lon_to_x_continuous(lon: f64, zoom: u8) -> f64 {
     ((lon + 180_f64) / 360_f64) * 2_f64.powi(zoom.into())
 }
 
@@ -82,7 +192,7 @@ pub fn lat_to_y(mut lat: f64, zoom: u8) -> f64 {
         * 2_f64.powi(zoom.into())
 }
 
-/// Meters to pixels.
+/// Meters to pixels, assuming 256px tiles.
 /// See https://wiki.openstreetmap.org/wiki/Zoom_levels#Distance_per_pixel_math
 pub fn m_to_px(meters: f64, lat: f64, zoom: u8) -> f64 {
     meters / (2.0 * PI * 6_378_137.0 * (lat * PI / 180.0).cos() / 2.0_f64.powi((zoom as i32) + 8))
@@ -120,9 +230,11 @@ fn simplify(points: Vec<(f64, f64)>, tolerance: f64) -> Vec<(f64, f64)> {
         }
     }
 
-    let last_point = points
-        .last()
-        .expect("Internal logic error - 'points' must have at least two points");
-    simplified_points.push(*last_point);
+    // Always keep the last point, unless it was just kept.
+    if let Some(last_point) = points.last() {
+        if simplified_points.last() != Some(last_point) {
+            simplified_points.push(*last_point);
+        }
+    }
     simplified_points
 }
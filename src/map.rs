@@ -1,11 +1,34 @@
+#[cfg(feature = "wmts")]
+use crate::wmts::WmtsSource;
 use crate::{
-    bounds::{Bounds, BoundsBuilder},
-    tools::Tool,
+    appearance::Appearance,
+    base_image::BaseImage,
+    bounds::{Bounds, BoundsBuilder, Fit, PixelRect, PixelSnapping},
+    cache::ImageCache,
+    coincident::CoincidentPoints,
+    compositor::TileCompositor,
+    declutter::{draw_scaled, scaled_extent, Declutter},
+    digest::{take_uncacheable, Digest, StableHasher},
+    effects::Effect,
+    fetcher::{DefaultTileFetcher, TileFetcher},
+    grid::TileGrid,
+    json::Json,
+    layer::FeatureLayer,
+    placement::TilePlacement,
+    pool::PixmapPool,
+    scene::{member, object, substitute, tool_from_json, Scene, SCENE_VERSION},
+    theme::Theme,
+    tools::{Alignment, Color, Tool},
+    viewport::Viewport,
     Error, Result,
 };
-use attohttpc::{Method, RequestBuilder, Response};
-use rayon::prelude::*;
-use tiny_skia::{Pixmap, PixmapMut, PixmapPaint, Transform};
+use std::{
+    collections::HashMap,
+    hash::Hasher,
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tiny_skia::{BlendMode, FilterQuality, IntRect, Pixmap, PixmapMut, PixmapPaint, Transform};
 
 /// Main type.
 /// Use [StaticMapBuilder][StaticMapBuilder] as an entrypoint.
@@ -26,20 +49,133 @@ use tiny_skia::{Pixmap, PixmapMut, PixmapPaint, Transform};
 /// ```
 pub struct StaticMap {
     url_template: String,
+    overlay_templates: Vec<String>,
     tools: Vec<Box<dyn Tool>>,
+    tool_layers: Vec<Option<String>>,
+    layers: Vec<FeatureLayer>,
+    effects: Vec<Box<dyn Effect>>,
     bounds: BoundsBuilder,
+    zoom_range: Option<(u8, u8)>,
+    overzoom: bool,
+    fetcher: Arc<dyn TileFetcher>,
+    time_budget: Option<Duration>,
+    base_image: Option<BaseImage>,
+    coincident_points: CoincidentPoints,
+    declutter: Declutter,
+    background: Option<Color>,
+    tile_paint: PixmapPaint,
+    expand_canvas: bool,
+    image_cache: Option<Arc<dyn ImageCache>>,
+    pixmap_pool: Option<PixmapPool>,
+    keep_last_render: bool,
+    #[cfg(feature = "image")]
+    geotag: bool,
+    /// Image, if kept, bounds and drawn extents of the last render.
+    last_render: Option<(Option<Pixmap>, Bounds, Vec<Option<PixelRect>>)>,
+    base_cache: Option<(BaseKey, Pixmap)>,
+}
+
+/// Digest of the bounds and tile sources of a base layer kept by
+/// [StaticMap::render_with_layers].
+type BaseKey = u64;
+
+/// Map drawn by [StaticMap::draw].
+struct Drawn {
+    image: Pixmap,
+    bounds: Bounds,
+    /// Pixel extents the tools were drawn at.
+    extents: Vec<Option<PixelRect>>,
+    /// Whether every tile was fetched, rather than some being left out, e.g. when out of time.
+    complete: bool,
 }
 
 /// Builder for [StaticMap][StaticMap].
+///
+/// Builders are cheap to clone, sharing the tile fetcher and image cache, so a base
+/// configuration can be defined once and varied per map.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{tools::Color, StaticMapBuilder};
+///
+/// let base = StaticMapBuilder::new()
+///     .url_template("https://tile.example.com/{z}/{x}/{y}.png")
+///     .background(Color::new(true, 255, 255, 255, 255))
+///     .padding((20, 20));
+///
+/// // E.g. one variant per request.
+/// let small = base.clone().width(200).height(200).build().unwrap();
+/// let large = base.clone().width(800).height(600).build().unwrap();
+///
+/// println!("{:?}", base);
+/// ```
+#[derive(Clone)]
 pub struct StaticMapBuilder {
     width: u32,
     height: u32,
     padding: (u32, u32),
+    fit: Fit,
+    fit_alignment: Alignment,
     zoom: Option<u8>,
+    zoom_offset: i8,
     lat_center: Option<f64>,
     lon_center: Option<f64>,
     url_template: String,
+    overlay_templates: Vec<String>,
     tile_size: u32,
+    grid: Option<TileGrid>,
+    zoom_range: Option<(u8, u8)>,
+    overzoom: bool,
+    fetcher: Arc<dyn TileFetcher>,
+    time_budget: Option<Duration>,
+    base_image: Option<BaseImage>,
+    coincident_points: CoincidentPoints,
+    declutter: Declutter,
+    pixel_snapping: PixelSnapping,
+    background: Option<Color>,
+    tile_paint: PixmapPaint,
+    expand_canvas: bool,
+    image_cache: Option<Arc<dyn ImageCache>>,
+    pixmap_pool: Option<PixmapPool>,
+    keep_last_render: bool,
+    #[cfg(feature = "image")]
+    geotag: bool,
+}
+
+impl std::fmt::Debug for StaticMapBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut debug = f.debug_struct("StaticMapBuilder");
+        debug
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("padding", &self.padding)
+            .field("fit", &self.fit)
+            .field("fit_alignment", &self.fit_alignment)
+            .field("zoom", &self.zoom)
+            .field("zoom_offset", &self.zoom_offset)
+            .field("lat_center", &self.lat_center)
+            .field("lon_center", &self.lon_center)
+            .field("url_template", &self.url_template)
+            .field("overlay_templates", &self.overlay_templates)
+            .field("tile_size", &self.tile_size)
+            .field("grid", &self.grid)
+            .field("zoom_range", &self.zoom_range)
+            .field("overzoom", &self.overzoom)
+            .field("time_budget", &self.time_budget)
+            .field("base_image", &self.base_image)
+            .field("coincident_points", &self.coincident_points)
+            .field("declutter", &self.declutter)
+            .field("pixel_snapping", &self.pixel_snapping)
+            .field("background", &self.background)
+            .field("tile_paint", &self.tile_paint)
+            .field("expand_canvas", &self.expand_canvas)
+            .field("image_cache", &self.image_cache.is_some())
+            .field("pixmap_pool", &self.pixmap_pool)
+            .field("keep_last_render", &self.keep_last_render);
+        #[cfg(feature = "image")]
+        debug.field("geotag", &self.geotag);
+        debug.finish_non_exhaustive()
+    }
 }
 
 impl Default for StaticMapBuilder {
@@ -48,11 +184,35 @@ impl Default for StaticMapBuilder {
             width: 300,
             height: 300,
             padding: (0, 0),
+            fit: Fit::Letterbox,
+            fit_alignment: Alignment::Center,
             zoom: None,
+            zoom_offset: 0,
             lat_center: None,
             lon_center: None,
             url_template: "https://a.tile.osm.org/{z}/{x}/{y}.png".to_string(),
+            overlay_templates: Vec::new(),
             tile_size: 256,
+            grid: None,
+            zoom_range: None,
+            overzoom: false,
+            fetcher: Arc::new(DefaultTileFetcher::default()),
+            time_budget: None,
+            base_image: None,
+            coincident_points: CoincidentPoints::Overlap,
+            declutter: Declutter::Off,
+            pixel_snapping: PixelSnapping::Round,
+            background: None,
+            tile_paint: PixmapPaint {
+                quality: FilterQuality::Bilinear,
+                ..Default::default()
+            },
+            expand_canvas: false,
+            image_cache: None,
+            pixmap_pool: None,
+            keep_last_render: false,
+            #[cfg(feature = "image")]
+            geotag: false,
         }
     }
 }
@@ -63,6 +223,35 @@ impl StaticMapBuilder {
         Default::default()
     }
 
+    /// Create a new builder with a 1200x630 image,
+    /// the size used by social media link previews.
+    pub fn social_card() -> Self {
+        Self::new().width(1200).height(630)
+    }
+
+    /// Create a new builder with a 150x150 image, suitable for thumbnails.
+    pub fn thumbnail() -> Self {
+        Self::new().width(150).height(150)
+    }
+
+    /// Create a new builder with an image filling a landscape A4 page at the given DPI.
+    /// Only the size is set, see [physical_size][StaticMapBuilder::physical_size].
+    pub fn a4_landscape(dpi: u32) -> Self {
+        Self::new().physical_size(29.7, 21.0, dpi)
+    }
+
+    /// Image width and height from a physical size in centimeters, at the given DPI.
+    ///
+    /// Only the size of the image is set. Stroke widths, radii, icons and text of the tools
+    /// are in pixels and are not scaled, so they print smaller the higher the DPI.
+    /// Multiply them by `dpi / 96` to print them at the size they have on a screen,
+    /// or set the size at 96 DPI and render with
+    /// [StaticMap::encode_png_multi][StaticMap::encode_png_multi] at a scale of `dpi / 96`.
+    pub fn physical_size(self, width_cm: f64, height_cm: f64, dpi: u32) -> Self {
+        let to_px = |cm: f64| (cm / 2.54 * f64::from(dpi)).round() as u32;
+        self.width(to_px(width_cm)).height(to_px(height_cm))
+    }
+
     /// Image width, in pixels.
     /// Default is 300.
     pub fn width(mut self, width: u32) -> Self {
@@ -84,6 +273,24 @@ impl StaticMapBuilder {
         self
     }
 
+    /// How the map features are fitted to the map when the zoom is not set,
+    /// if the shape of their extent differs from that of the map.
+    /// Default is [Fit::Letterbox][Fit::Letterbox].
+    pub fn fit(mut self, fit: Fit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Where the map features are placed within the padding when the center is not set,
+    /// e.g. [Alignment::Top][Alignment::Top] to keep a route at the top of the map,
+    /// leaving the space below it free for a panel.
+    /// When the features are [cropped][Fit::Crop], the side of them kept.
+    /// Default is [Alignment::Center][Alignment::Center].
+    pub fn fit_alignment(mut self, alignment: Alignment) -> Self {
+        self.fit_alignment = alignment;
+        self
+    }
+
     /// Map zoom, usually between 1-17.
     /// Determined based on map features if not specified.
     pub fn zoom(mut self, zoom: u8) -> Self {
@@ -91,6 +298,14 @@ impl StaticMapBuilder {
         self
     }
 
+    /// Offset added to the map zoom when requesting tiles.
+    /// Use -1 with 512px tiles to get the same scale as 256px tiles at the map zoom.
+    /// Default is 0.
+    pub fn zoom_offset(mut self, offset: i8) -> Self {
+        self.zoom_offset = offset;
+        self
+    }
+
     /// Latitude center of the map.
     /// Determined based on map features if not specified.
     pub fn lat_center(mut self, coordinate: f64) -> Self {
@@ -112,118 +327,2035 @@ impl StaticMapBuilder {
         self
     }
 
+    /// URL template of a tile layer drawn on top of the base layer,
+    /// e.g. hillshading or labels with a transparent background.
+    /// Can be called several times, layers are drawn in the order they are added.
+    ///
+    /// Tiles of all layers are fetched in a single call to the tile fetcher,
+    /// with duplicate URLs fetched once.
+    pub fn overlay_layer<I: Into<String>>(mut self, url_template: I) -> Self {
+        self.overlay_templates.push(url_template.into());
+        self
+    }
+
     /// Tile size, in pixels.
+    /// Fetched tiles of a different size are rescaled to this size.
     /// Default is 256.
     pub fn tile_size(mut self, tile_size: u32) -> Self {
         self.tile_size = tile_size;
         self
     }
 
+    /// Lowest and highest zoom level served by the tile source, e.g. (0, 19).
+    /// Automatically determined zoom stays within this range, and rendering at a
+    /// zoom outside of it returns [Error::ZoomOutOfRange][Error::ZoomOutOfRange]
+    /// instead of drawing missing tiles.
+    /// The range applies to the zoom of the tile grid, including any zoom offset.
+    /// Default is no limits.
+    pub fn zoom_range(mut self, min: u8, max: u8) -> Self {
+        self.zoom_range = Some((min, max.max(min)));
+        self
+    }
+
+    /// Whether to scale up tiles from the highest zoom level of the tile source,
+    /// set with [zoom_range][StaticMapBuilder::zoom_range], when the map zoom is higher,
+    /// as slippy map clients do. Only applies to Web Mercator tiles.
+    /// Default is false.
+    pub fn overzoom(mut self, overzoom: bool) -> Self {
+        self.overzoom = overzoom;
+        self
+    }
+
+    /// Custom tile grid for tile services not using Web Mercator tiles,
+    /// e.g. [TileGrid::swiss_lv95][TileGrid::swiss_lv95].
+    /// Zoom levels then refer to the resolutions of the grid.
+    pub fn tile_grid(mut self, grid: TileGrid) -> Self {
+        self.grid = Some(grid);
+        self
+    }
+
+    /// Use a tile source configured from a WMTS capabilities document,
+    /// setting the URL template, tile grid and tile size.
+    #[cfg(feature = "wmts")]
+    pub fn wmts_source(self, source: WmtsSource) -> Self {
+        self.url_template(source.url_template())
+            .tile_size(source.tile_size())
+            .tile_grid(source.tile_grid().clone())
+    }
+
+    /// Use a single georeferenced image as the base layer instead of tiles.
+    /// The center and zoom of the map are still determined by the map features,
+    /// or set explicitly.
+    pub fn base_image(mut self, base_image: BaseImage) -> Self {
+        self.base_image = Some(base_image);
+        self
+    }
+
+    /// How to draw point features at exactly the same coordinate.
+    /// Default is [CoincidentPoints::Overlap][CoincidentPoints::Overlap].
+    pub fn coincident_points(mut self, strategy: CoincidentPoints) -> Self {
+        self.coincident_points = strategy;
+        self
+    }
+
+    /// How to place point features with a priority overlapping each other.
+    /// Default is [Declutter::Off][Declutter::Off].
+    pub fn declutter(mut self, strategy: Declutter) -> Self {
+        self.declutter = strategy;
+        self
+    }
+
+    /// How pixel coordinates of tools are rounded.
+    /// Use [PixelSnapping::Crisp][PixelSnapping::Crisp] for sharp hairlines and grid overlays.
+    /// Default is [PixelSnapping::Round][PixelSnapping::Round].
+    pub fn pixel_snapping(mut self, snapping: PixelSnapping) -> Self {
+        self.pixel_snapping = snapping;
+        self
+    }
+
+    /// Color drawn beneath the tiles, showing through transparent and semi-transparent tiles.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is transparent.
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Apply the map-wide styles of a theme, i.e. its background color.
+    /// Create tools in the style of the theme with its builders, e.g. [Theme::line][Theme::line].
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        if let Some(ref color) = theme.background {
+            self.background = Some(color.clone());
+        }
+        self
+    }
+
+    /// Paint used to composite tiles of all layers onto the map: their opacity and blend mode,
+    /// and the filter quality of tiles which are rescaled, e.g. retina or overzoomed tiles.
+    /// Default is opaque source-over compositing with bilinear filtering.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{tools::Color, StaticMapBuilder};
+    /// use tiny_skia::{FilterQuality, PixmapPaint};
+    ///
+    /// // A faded base map, e.g. to make data stand out.
+    /// let map = StaticMapBuilder::new()
+    ///     .background(Color::new(true, 255, 255, 255, 255))
+    ///     .tile_paint(PixmapPaint {
+    ///         opacity: 0.5,
+    ///         quality: FilterQuality::Bilinear,
+    ///         ..Default::default()
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn tile_paint(mut self, paint: PixmapPaint) -> Self {
+        self.tile_paint = paint;
+        self
+    }
+
+    /// Filter quality of tiles which are rescaled, e.g. 512px retina tiles on a 256px grid
+    /// or overzoomed tiles, keeping the rest of the [tile paint][StaticMapBuilder::tile_paint].
+    /// Bicubic filtering is sharper, at a higher rendering cost.
+    /// Default is bilinear.
+    pub fn tile_quality(mut self, quality: FilterQuality) -> Self {
+        self.tile_paint.quality = quality;
+        self
+    }
+
+    /// Grow the image above and below the map to make room for decorations,
+    /// such as [stamps][crate::tools::Stamp], [legends][crate::tools::ProportionalLegend]
+    /// and [screen overlays][crate::tools::ScreenOverlay], instead of drawing them over the map.
+    ///
+    /// Decorations are tools placed on the image rather than at coordinates, with a
+    /// [pixel extent][Tool::pixel_extent]. Each one placed in the top half of the map adds
+    /// a band above it, and each one in the bottom half a band below it, tall enough to hold
+    /// the decoration with the same distance to the edge of the image on both sides.
+    /// The map keeps its size, and the bands are filled with the
+    /// [background][StaticMapBuilder::background].
+    /// Default is false.
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// use staticmap::{
+    ///     tools::{Color, Corner, Font, StampBuilder, TextStyle},
+    ///     StaticMapBuilder,
+    /// };
+    ///
+    /// let mut map = StaticMapBuilder::new()
+    ///     .width(400)
+    ///     .height(300)
+    ///     .expand_canvas(true)
+    ///     .background(Color::new(true, 255, 255, 255, 255))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap());
+    /// map.add_tool(
+    ///     StampBuilder::new()
+    ///         .text("Berlin")
+    ///         .style(style)
+    ///         .corner(Corner::TopLeft)
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    ///
+    /// // 300 pixels of map below a band holding the title.
+    /// let image = map.render().unwrap();
+    /// assert!(image.height() > 300);
+    /// ```
+    pub fn expand_canvas(mut self, expand: bool) -> Self {
+        self.expand_canvas = expand;
+        self
+    }
+
+    /// Type implementing [ImageCache][ImageCache], used by
+    /// [StaticMap::encode_png][StaticMap::encode_png] and [StaticMap::save_png][StaticMap::save_png]
+    /// to skip rendering maps with the same [render digest][StaticMap::render_digest].
+    pub fn image_cache(mut self, cache: impl ImageCache + 'static) -> Self {
+        self.image_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Image cache shared with other maps, e.g. by all maps rendered by a server.
+    /// See [image_cache][StaticMapBuilder::image_cache].
+    pub fn shared_image_cache(mut self, cache: Arc<dyn ImageCache>) -> Self {
+        self.image_cache = Some(cache);
+        self
+    }
+
+    /// Pool the canvases and decoded tiles of renders are allocated from, reusing their
+    /// buffers across renders instead of allocating new ones, see [PixmapPool][PixmapPool].
+    /// Return rendered images to the pool with [StaticMap::recycle][StaticMap::recycle].
+    /// Default is no pool.
+    pub fn pixmap_pool(mut self, pool: PixmapPool) -> Self {
+        self.pixmap_pool = Some(pool);
+        self
+    }
+
+    /// Whether to keep a copy of the image of each render, for
+    /// [StaticMap::last_render][StaticMap::last_render] and [StaticMap::annotate][StaticMap::annotate].
+    /// Copying costs a full image per render, so it is off unless needed.
+    /// The bounds and [drawn extents][StaticMap::drawn_extents] are always kept.
+    /// Default is false.
+    pub fn keep_last_render(mut self, keep: bool) -> Self {
+        self.keep_last_render = keep;
+        self
+    }
+
+    /// Whether to write EXIF GPS tags with the map center, and the extent of the map
+    /// as image description, when exporting JPEG images.
+    /// Default is false.
+    #[cfg(feature = "image")]
+    pub fn geotag(mut self, geotag: bool) -> Self {
+        self.geotag = geotag;
+        self
+    }
+
+    /// Longest time to wait for tiles. Tiles which have not arrived by then are drawn
+    /// as light gray placeholders, and the map is rendered without them.
+    /// Useful for latency sensitive previews.
+    ///
+    /// With a time budget, the tile fetcher is called once per tile, starting with the tiles
+    /// at the center of the map and working outwards, so the center is complete first.
+    /// Default is no time budget.
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Type implementing [TileFetcher][TileFetcher], used to fetch tiles.
+    /// Default is [DefaultTileFetcher][DefaultTileFetcher].
+    pub fn tile_fetcher(mut self, fetcher: impl TileFetcher + 'static) -> Self {
+        self.fetcher = Arc::new(fetcher);
+        self
+    }
+
+    /// Tile fetcher shared with other maps, e.g. so all maps rendered by a server
+    /// share the tiles of one caching fetcher.
+    /// See [tile_fetcher][StaticMapBuilder::tile_fetcher].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{DefaultTileFetcher, StaticMapBuilder, TileFetcher};
+    /// use std::sync::Arc;
+    ///
+    /// let fetcher: Arc<dyn TileFetcher> = Arc::new(DefaultTileFetcher::default());
+    ///
+    /// // E.g. one map per request.
+    /// for (lat, lon) in [(52.5, 13.4), (48.9, 2.35)] {
+    ///     let map = StaticMapBuilder::new()
+    ///         .lat_center(lat)
+    ///         .lon_center(lon)
+    ///         .zoom(10)
+    ///         .shared_tile_fetcher(Arc::clone(&fetcher))
+    ///         .build()
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn shared_tile_fetcher(mut self, fetcher: Arc<dyn TileFetcher>) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    /// Build a map from a scene written by [StaticMap::to_scene][StaticMap::to_scene],
+    /// consuming the builder. Settings in the scene replace the settings of the builder,
+    /// which provides everything scenes do not contain, such as the tile fetcher.
+    ///
+    /// Returns [Error::InvalidScene][Error::InvalidScene] if the scene can not be parsed,
+    /// has an unsupported version or contains tools which can not be loaded.
+    pub fn build_scene(self, scene: &str) -> Result<StaticMap> {
+        self.build_scene_json(Json::parse(scene).map_err(Error::InvalidScene)?)
+    }
+
+    /// Build a map from a scene template, replacing placeholders such as `${track}`
+    /// with the values of `variables`, given as (name, JSON value), consuming the builder.
+    /// Lets one stored scene serve many maps differing only in their data.
+    ///
+    /// A string which is only a placeholder, e.g. `"${lat}"`, is replaced by the value,
+    /// which may be a number, an array or an object.
+    /// Placeholders inside longer strings, e.g. in URL templates, are replaced by
+    /// the value of string variables, or the JSON text of other values.
+    ///
+    /// Returns [Error::InvalidScene][Error::InvalidScene] if a placeholder has no variable,
+    /// a value is not valid JSON, or the resulting scene is invalid.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::StaticMapBuilder;
+    ///
+    /// let template = r#"{
+    ///     "version": 1,
+    ///     "map": { "width": 400, "height": 300 },
+    ///     "tools": [{
+    ///         "type": "line",
+    ///         "lat_coordinates": "${lat}",
+    ///         "lon_coordinates": "${lon}",
+    ///         "color": { "rgba": [255, 0, 0, 255] }
+    ///     }]
+    /// }"#;
+    ///
+    /// let map = StaticMapBuilder::new()
+    ///     .build_template(
+    ///         template,
+    ///         &[("lat", "[52.50, 52.52]"), ("lon", "[13.40, 13.45]")],
+    ///     )
+    ///     .unwrap();
+    /// ```
+    pub fn build_template(self, template: &str, variables: &[(&str, &str)]) -> Result<StaticMap> {
+        let variables = variables
+            .iter()
+            .map(|(name, value)| Ok((*name, Json::parse(value).map_err(Error::InvalidScene)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let template = Json::parse(template).map_err(Error::InvalidScene)?;
+        self.build_scene_json(substitute(template, &variables)?)
+    }
+
+    fn build_scene_json(self, scene: Json) -> Result<StaticMap> {
+        match scene.number("version")? {
+            Some(version) if version == SCENE_VERSION => (),
+            Some(version) => {
+                return Err(Error::InvalidScene(format!(
+                    "Unsupported version {}",
+                    version
+                )))
+            }
+            None => return Err(Error::InvalidScene("Missing version".to_string())),
+        }
+
+        let (tools, tool_layers) = scene
+            .array("tools")?
+            .unwrap_or_default()
+            .iter()
+            .map(|json| {
+                Ok((
+                    tool_from_json(json)?,
+                    json.string("layer")?.map(String::from),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+
+        let mut map = match scene.get("map") {
+            Some(settings) => self.apply_scene(settings)?.build()?,
+            None => self.build()?,
+        };
+        map.tools = tools;
+        map.tool_layers = tool_layers;
+        Ok(map)
+    }
+
+    fn apply_scene(mut self, settings: &Json) -> Result<Self> {
+        // Sizes are whole pixels, checked here instead of saturating when cast.
+        let pixels = |key: &str, value: f64| match value >= 0.
+            && value.fract() == 0.
+            && value <= f64::from(u32::MAX)
+        {
+            true => Ok(value as u32),
+            false => Err(crate::json::invalid_member(key, "a non-negative integer")),
+        };
+        if let Some(width) = settings.number("width")? {
+            self.width = pixels("width", width)?;
+        }
+        if let Some(height) = settings.number("height")? {
+            self.height = pixels("height", height)?;
+        }
+        if let Some((x, y)) = settings.pair("padding")? {
+            self.padding = (pixels("padding", x)?, pixels("padding", y)?);
+        }
+        let (width, height) = (u64::from(self.width), u64::from(self.height));
+        let (x, y) = (u64::from(self.padding.0), u64::from(self.padding.1));
+        if (x > 0 && 2 * x >= width) || (y > 0 && 2 * y >= height) {
+            return Err(Error::InvalidScene(
+                "\"padding\" must be less than half the width and height".to_string(),
+            ));
+        }
+        if let Some(fit) = member(settings, "fit")? {
+            self.fit = fit;
+        }
+        if let Some(alignment) = member(settings, "fit_alignment")? {
+            self.fit_alignment = alignment;
+        }
+        self.zoom = settings.number("zoom")?.map(|x| x as u8);
+        if let Some(offset) = settings.number("zoom_offset")? {
+            self.zoom_offset = offset as i8;
+        }
+        self.lat_center = settings.number("lat_center")?;
+        self.lon_center = settings.number("lon_center")?;
+        if let Some(url_template) = settings.string("url_template")? {
+            self.url_template = url_template.to_string();
+        }
+        if let Some(layers) = settings.array("overlay_layers")? {
+            self.overlay_templates = layers
+                .iter()
+                .map(|x| match *x {
+                    Json::String(ref layer) => Ok(layer.clone()),
+                    _ => Err(crate::json::invalid_member(
+                        "overlay_layers",
+                        "an array of strings",
+                    )),
+                })
+                .collect::<Result<_>>()?;
+        }
+        if let Some(tile_size) = settings.number("tile_size")? {
+            self.tile_size = tile_size as u32;
+        }
+        self.zoom_range = settings
+            .pair("zoom_range")?
+            .map(|(min, max)| (min as u8, (max as u8).max(min as u8)));
+        if let Some(overzoom) = settings.boolean("overzoom")? {
+            self.overzoom = overzoom;
+        }
+        if let Some(strategy) = member(settings, "coincident_points")? {
+            self.coincident_points = strategy;
+        }
+        if let Some(strategy) = member(settings, "declutter")? {
+            self.declutter = strategy;
+        }
+        if let Some(snapping) = member(settings, "pixel_snapping")? {
+            self.pixel_snapping = snapping;
+        }
+        if let Some(background) = member(settings, "background")? {
+            self.background = Some(background);
+        }
+        if let Some(opacity) = settings.number("tile_opacity")? {
+            self.tile_paint.opacity = opacity as f32;
+        }
+        if let Some(quality) = member(settings, "tile_quality")? {
+            self.tile_paint.quality = quality;
+        }
+        if let Some(expand) = settings.boolean("expand_canvas")? {
+            self.expand_canvas = expand;
+        }
+        if let Some(budget) = settings.number("time_budget_ms")? {
+            self.time_budget = Some(Duration::from_secs_f64(budget.max(0.) / 1000.));
+        }
+        #[cfg(feature = "image")]
+        if let Some(geotag) = settings.boolean("geotag")? {
+            self.geotag = geotag;
+        }
+        Ok(self)
+    }
+
     /// Consumes the builder.
     pub fn build(self) -> Result<StaticMap> {
+        let bounds_zoom_range = match self.zoom_range {
+            // Overzoomed maps may use any zoom above the lowest one.
+            Some((min, _)) if self.overzoom && self.grid.is_none() => Some((min, u8::MAX)),
+            range => range,
+        };
+
         let bounds = BoundsBuilder::new()
             .zoom(self.zoom)
+            .zoom_offset(self.zoom_offset)
             .tile_size(self.tile_size)
+            .grid(self.grid)
+            .zoom_range(bounds_zoom_range)
             .lon_center(self.lon_center)
             .lat_center(self.lat_center)
             .padding(self.padding)
+            .fit(self.fit)
+            .fit_alignment(self.fit_alignment)
+            .pixel_snapping(self.pixel_snapping)
             .height(self.height)
             .width(self.width);
 
         Ok(StaticMap {
             url_template: self.url_template,
+            overlay_templates: self.overlay_templates,
             tools: Vec::new(),
+            tool_layers: Vec::new(),
+            layers: Vec::new(),
+            effects: Vec::new(),
             bounds,
+            zoom_range: self.zoom_range,
+            overzoom: self.overzoom,
+            fetcher: self.fetcher,
+            time_budget: self.time_budget,
+            base_image: self.base_image,
+            coincident_points: self.coincident_points,
+            declutter: self.declutter,
+            background: self.background,
+            tile_paint: self.tile_paint,
+            expand_canvas: self.expand_canvas,
+            image_cache: self.image_cache,
+            pixmap_pool: self.pixmap_pool,
+            keep_last_render: self.keep_last_render,
+            #[cfg(feature = "image")]
+            geotag: self.geotag,
+            last_render: None,
+            base_cache: None,
         })
     }
 }
 
+/// Whether the tool is placed on the image rather than at coordinates.
+fn is_decoration(tool: &dyn Tool, bounds: &Bounds) -> bool {
+    let (lon_min, lat_min, lon_max, lat_max) = tool.extent(bounds.zoom, bounds.tile_size.into());
+    ![lon_min, lat_min, lon_max, lat_max]
+        .iter()
+        .all(|x| x.is_finite())
+        && tool.pixel_extent(bounds).is_some()
+}
+
 impl StaticMap {
     /// Add a type implementing [Tool][Tool]. The map can contain several tools.
     pub fn add_tool(&mut self, tool: impl Tool + 'static) {
         self.tools.push(Box::new(tool));
+        self.tool_layers.push(None);
+    }
+
+    /// Add a tool to a named layer, e.g. "poi", which can be left out when rendering
+    /// variants of the map with [render_with_layers][StaticMap::render_with_layers].
+    /// Otherwise the tool is drawn like any other.
+    pub fn add_tool_to_layer(&mut self, layer: impl Into<String>, tool: impl Tool + 'static) {
+        self.tools.push(Box::new(tool));
+        self.tool_layers.push(Some(layer.into()));
+    }
+
+    /// Add a [FeatureLayer][FeatureLayer], fetching its features for the area visible on
+    /// the map each time it is rendered. Layers are drawn over the tiles and beneath the tools,
+    /// in the order they are added.
+    pub fn add_layer(&mut self, layer: FeatureLayer) {
+        self.layers.push(layer);
+    }
+
+    /// Add a type implementing [Effect][Effect], applied to the map after all tools are drawn.
+    /// Effects are applied in the order they are added.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{effects::Vignette, StaticMapBuilder};
+    /// use tiny_skia::PixmapMut;
+    ///
+    /// let mut map = StaticMapBuilder::new().build().unwrap();
+    /// map.add_effect(Vignette::new(0.4));
+    /// map.add_effect(|pixmap: &mut PixmapMut| {
+    ///     // Custom post-processing, e.g. inverting the colors.
+    ///     for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+    ///         for channel in 0..3 {
+    ///             pixel[channel] = pixel[3] - pixel[channel];
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    pub fn add_effect(&mut self, effect: impl Effect + 'static) {
+        self.effects.push(Box::new(effect));
+    }
+
+    /// Stable hash of everything affecting the rendered image, such as its size,
+    /// zoom and center, the URL templates, the base image, tools and effects.
+    /// Use it as a cache key for rendered maps, without rendering or hashing the image.
+    ///
+    /// The digest is the same across platforms and runs, but may change between releases.
+    /// Returns None if a tool or effect does not support digests, see [Tool::digest][Tool::digest],
+    /// if the map has [feature layers][StaticMap::add_layer], whose data may change,
+    /// or if anything is drawn with a [color][Color::from_paint] other than a solid color.
+    /// The tile fetcher, the projection of custom tile grids and the contents of the tiles
+    /// are not part of the digest.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{tools::CircleBuilder, StaticMapBuilder};
+    ///
+    /// let mut map = StaticMapBuilder::new().build().unwrap();
+    /// map.add_tool(
+    ///     CircleBuilder::new()
+    ///         .lat_coordinate(52.5)
+    ///         .lon_coordinate(13.4)
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    ///
+    /// let key = map.render_digest().unwrap();
+    ///
+    /// // Overzooming draws the tiles differently, so it is part of the digest.
+    /// let plain = StaticMapBuilder::new().build().unwrap();
+    /// let overzoomed = StaticMapBuilder::new().overzoom(true).build().unwrap();
+    /// assert_ne!(plain.render_digest(), overzoomed.render_digest());
+    /// ```
+    pub fn render_digest(&self) -> Option<u64> {
+        let mut state = StableHasher::default();
+        take_uncacheable();
+
+        env!("CARGO_PKG_VERSION").digest(&mut state);
+        self.url_template.digest(&mut state);
+        self.overlay_templates.digest(&mut state);
+        self.bounds.digest(&mut state);
+        self.base_image.digest(&mut state);
+        self.overzoom.digest(&mut state);
+        self.coincident_points.digest(&mut state);
+        self.declutter.digest(&mut state);
+        self.background.digest(&mut state);
+        self.tile_paint.digest(&mut state);
+        self.expand_canvas.digest(&mut state);
+
+        if !self.layers.is_empty() {
+            return None;
+        }
+
+        state.write_usize(self.tools.len());
+        for tool in self.tools.iter() {
+            if !tool.digest(&mut state) {
+                return None;
+            }
+        }
+
+        state.write_usize(self.effects.len());
+        for effect in self.effects.iter() {
+            if !effect.digest(&mut state) {
+                return None;
+            }
+        }
+
+        // E.g. a tool drawn with a gradient shader.
+        if take_uncacheable() {
+            return None;
+        }
+
+        Some(state.finish())
+    }
+
+    /// GeoJSON FeatureCollection of all tools, with their style as properties,
+    /// e.g. to show the same features in an interactive Leaflet or MapLibre map.
+    /// Tools without a geometry, such as compasses and insets, are left out.
+    ///
+    /// Circles and annuli are exported as points, with their radii and
+    /// `radius-unit` ("m" or "px") as properties.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{tools::CircleBuilder, StaticMapBuilder};
+    ///
+    /// let mut map = StaticMapBuilder::new().build().unwrap();
+    /// map.add_tool(
+    ///     CircleBuilder::new()
+    ///         .lat_coordinate(52.5)
+    ///         .lon_coordinate(13.4)
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    ///
+    /// let geojson = map.to_geojson();
+    /// assert!(geojson.starts_with("{\"type\":\"FeatureCollection\""));
+    /// ```
+    pub fn to_geojson(&self) -> String {
+        let features: Vec<String> = self.tools.iter().filter_map(|x| x.geojson()).collect();
+        format!(
+            "{{\"type\":\"FeatureCollection\",\"features\":[{}]}}",
+            features.join(",")
+        )
+    }
+
+    /// Versioned JSON scene with the map settings and tools,
+    /// to store map definitions and render them later with
+    /// [StaticMap::from_scene][StaticMap::from_scene].
+    ///
+    /// Scenes contain the settings of the builder which do not depend on code or files,
+    /// i.e. not the tile fetcher, image cache, custom tile grid or base image.
+    /// Returns [Error::InvalidScene][Error::InvalidScene] if the map uses a custom tile grid,
+    /// a base image or effects, or a tool which does not support scenes, see [Tool::scene][Tool::scene].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{tools::CircleBuilder, StaticMap, StaticMapBuilder};
+    ///
+    /// let mut map = StaticMapBuilder::new().width(400).build().unwrap();
+    /// map.add_tool(
+    ///     CircleBuilder::new()
+    ///         .lat_coordinate(52.5)
+    ///         .lon_coordinate(13.4)
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    ///
+    /// let scene = map.to_scene().unwrap();
+    /// let copy = StaticMap::from_scene(&scene).unwrap();
+    /// assert_eq!(copy.to_scene().unwrap(), scene);
+    /// ```
+    pub fn to_scene(&self) -> Result<String> {
+        let unsupported =
+            |what: &str| Error::InvalidScene(format!("{} can not be part of a scene", what));
+
+        if self.base_image.is_some() {
+            return Err(unsupported("Base images"));
+        }
+        if !self.effects.is_empty() {
+            return Err(unsupported("Effects"));
+        }
+        if self.tile_paint.blend_mode != BlendMode::SourceOver {
+            return Err(unsupported("Tile blend modes"));
+        }
+        if !self.background.iter().all(Color::is_solid) {
+            return Err(unsupported("Background shaders"));
+        }
+
+        let mut settings = match self.bounds.scene() {
+            Some(settings) => settings,
+            None => return Err(unsupported("Custom tile grids")),
+        };
+        settings.extend(vec![
+            ("url_template", self.url_template.as_str().into()),
+            ("overlay_layers", self.overlay_templates.clone().into()),
+            ("zoom_range", self.zoom_range.into()),
+            ("overzoom", self.overzoom.into()),
+            ("coincident_points", self.coincident_points.to_json()),
+            ("declutter", self.declutter.to_json()),
+            (
+                "background",
+                self.background.as_ref().map(Color::to_json).into(),
+            ),
+            ("tile_opacity", self.tile_paint.opacity.into()),
+            ("tile_quality", self.tile_paint.quality.to_json()),
+            ("expand_canvas", self.expand_canvas.into()),
+            (
+                "time_budget_ms",
+                self.time_budget.map(|x| x.as_secs_f64() * 1000.).into(),
+            ),
+            #[cfg(feature = "image")]
+            ("geotag", self.geotag.into()),
+        ]);
+
+        let tools = self
+            .tools
+            .iter()
+            .zip(self.tool_layers.iter())
+            .map(|(tool, layer)| match tool.scene() {
+                Some(scene) => match (Json::parse(&scene).map_err(Error::InvalidScene)?, layer) {
+                    (Json::Object(mut members), Some(layer)) => {
+                        members.push(("layer".to_string(), layer.as_str().into()));
+                        Ok(Json::Object(members))
+                    }
+                    (json, _) => Ok(json),
+                },
+                None => Err(unsupported("A tool")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let scene = object(vec![
+            ("version", SCENE_VERSION.into()),
+            ("map", object(settings)),
+            ("tools", Json::Array(tools)),
+        ]);
+        Ok(scene.to_string())
+    }
+
+    /// Map from a scene written by [StaticMap::to_scene][StaticMap::to_scene],
+    /// using the default tile fetcher.
+    /// Use [StaticMapBuilder::build_scene][StaticMapBuilder::build_scene] to configure
+    /// what scenes do not contain.
+    pub fn from_scene(scene: &str) -> Result<StaticMap> {
+        StaticMapBuilder::new().build_scene(scene)
+    }
+
+    /// Map from a scene template with the given variables, using the default tile fetcher.
+    /// See [StaticMapBuilder::build_template][StaticMapBuilder::build_template].
+    pub fn from_template(template: &str, variables: &[(&str, &str)]) -> Result<StaticMap> {
+        StaticMapBuilder::new().build_template(template, variables)
     }
 
     /// Render the map and encode as PNG.
     ///
     /// May panic if any feature has invalid bounds.
     pub fn encode_png(&mut self) -> Result<Vec<u8>> {
-        Ok(self.render()?.encode_png()?)
+        let digest = match self.image_cache {
+            Some(_) => self.render_digest(),
+            None => None,
+        };
+
+        if let (Some(cache), Some(digest)) = (self.image_cache.as_ref(), digest) {
+            if let Some(png) = cache.get(digest) {
+                return Ok(png);
+            }
+        }
+
+        let drawn = self.render_drawn()?;
+        let png = drawn.image.encode_png()?;
+        self.recycle(drawn.image);
+
+        // Renders with tiles missing, e.g. when out of time, are not kept.
+        if let (Some(cache), Some(digest), true) =
+            (self.image_cache.as_ref(), digest, drawn.complete)
+        {
+            cache.put(digest, &png);
+        }
+
+        Ok(png)
     }
 
     /// Render the map and save as PNG to a file.
     ///
     /// May panic if any feature has invalid bounds.
     pub fn save_png<P: AsRef<::std::path::Path>>(&mut self, path: P) -> Result<()> {
-        self.render()?.save_png(path)?;
+        std::fs::write(path, self.encode_png()?)?;
+        Ok(())
+    }
+
+    /// Render the map once and encode it as PNG at several scales, e.g. 1x, 2x and 3x
+    /// variants for screens of different pixel densities, sharing the tile fetching
+    /// and drawing of a single render.
+    ///
+    /// The map as configured is the 1x variant, so a scale of 2 is twice its width and height.
+    /// The map is rendered at the highest scale, with the tiles drawn at that resolution
+    /// and the tools, which are sized in pixels of the configured map, drawn that much larger,
+    /// see [Tool::draw_at_scale][Tool::draw_at_scale].
+    /// The variants of lower scales are downscaled from it.
+    /// Returns the images in the order of the scales.
+    /// Returns [Error::InvalidSize][Error::InvalidSize] if there are no scales
+    /// or if a scale is not positive.
+    /// The last render is the image of the highest scale.
+    ///
+    /// May panic if any feature has invalid bounds.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{testing::MockTileFetcher, StaticMapBuilder};
+    ///
+    /// let mut map = StaticMapBuilder::new()
+    ///     .width(300)
+    ///     .height(200)
+    ///     .zoom(12)
+    ///     .lat_center(52.5)
+    ///     .lon_center(13.4)
+    ///     .tile_fetcher(MockTileFetcher::new())
+    ///     .keep_last_render(true)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let pngs = map.encode_png_multi(&[1., 2., 3.]).unwrap();
+    /// assert_eq!(pngs.len(), 3);
+    ///
+    /// // The 3x variant, 900x600 pixels.
+    /// let image = map.last_render().unwrap();
+    /// assert_eq!((image.width(), image.height()), (900, 600));
+    /// ```
+    pub fn encode_png_multi(&mut self, scales: &[f64]) -> Result<Vec<Vec<u8>>> {
+        let max = scales.iter().copied().fold(0., f64::max);
+        if scales.is_empty() || !scales.iter().all(|&scale| scale > 0. && scale.is_finite()) {
+            return Err(Error::InvalidSize);
+        }
+
+        let (image, bounds) = self.render_scaled(max)?;
+        let pngs = scales
+            .iter()
+            .map(|&scale| {
+                if scale == max {
+                    return Ok(image.encode_png()?);
+                }
+                let width = (f64::from(bounds.width) * scale).round().max(1.) as u32;
+                let height = (f64::from(bounds.height) * scale).round().max(1.) as u32;
+                let variant = self.downscale(&image, width, height)?;
+                let png = variant.encode_png()?;
+                self.recycle(variant);
+                Ok(png)
+            })
+            .collect();
+        self.recycle(image);
+
+        pngs
+    }
+
+    /// Render the map at the given scale, returning the image and the bounds of the map
+    /// at 1x. The tiles and the tools are drawn at the scale, with the tools as much larger
+    /// as their sizes are in pixels of the map as configured.
+    fn render_scaled(&mut self, scale: f64) -> Result<(Pixmap, Bounds)> {
+        let bounds = self.bounds.build(&self.tools);
+        let offsets = self.coincident_points.offsets(&self.tools);
+        let visible = vec![true; self.tools.len()];
+
+        let (base, _) = self.draw_base(&bounds.scale(scale), &[])?;
+        let (mut image, bounds, extents) =
+            self.draw_tools(base, bounds, &offsets, &visible, scale as f32)?;
+        let scaled = bounds.scale(scale);
+
+        for effect in self.effects.iter() {
+            effect.apply(&mut image.as_mut());
+        }
+
+        let image_rect = scaled.pixel_rect();
+        let extents = extents
+            .into_iter()
+            .map(|extent| {
+                let rect = extent?;
+                PixelRect::new(
+                    rect.x_min * scale,
+                    rect.y_min * scale,
+                    rect.x_max * scale,
+                    rect.y_max * scale,
+                )
+                .intersection(&image_rect)
+            })
+            .collect();
+        self.keep_last_render(&image, scaled, extents)?;
+
+        Ok((image, bounds))
+    }
+
+    /// Copy of the image downscaled to the given size. The image is halved while it is
+    /// at least twice the size, so every pixel contributes to the result.
+    fn downscale(&self, image: &Pixmap, width: u32, height: u32) -> Result<Pixmap> {
+        let paint = PixmapPaint {
+            quality: FilterQuality::Bilinear,
+            ..Default::default()
+        };
+        let resize = |image: &Pixmap, target_width: u32, target_height: u32| -> Result<Pixmap> {
+            let mut target = self.new_pixmap(target_width, target_height)?;
+            target.draw_pixmap(
+                0,
+                0,
+                image.as_ref(),
+                &paint,
+                Transform::from_scale(
+                    target_width as f32 / image.width() as f32,
+                    target_height as f32 / image.height() as f32,
+                ),
+                None,
+            );
+            Ok(target)
+        };
+
+        let mut halved: Option<Pixmap> = None;
+        loop {
+            let current = halved.as_ref().unwrap_or(image);
+            if current.width() < 2 * width || current.height() < 2 * height {
+                let result = resize(current, width, height);
+                if let Some(halved) = halved {
+                    self.recycle(halved);
+                }
+                return result;
+            }
+
+            let next = resize(current, current.width() / 2, current.height() / 2)?;
+            if let Some(previous) = halved.replace(next) {
+                self.recycle(previous);
+            }
+        }
+    }
+
+    /// Render the map cropped to the tools drawn on it, with `margin` pixels around them,
+    /// e.g. for tight thumbnails of geometry without working out the size of the map up front.
+    ///
+    /// The crop is the union of the [drawn extents][Self::drawn_extents] of the tools,
+    /// kept within the image. The whole image is kept if no tools were drawn.
+    /// The last render is the uncropped image.
+    ///
+    /// May panic if any feature has invalid bounds.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{testing::MockTileFetcher, tools::CircleBuilder, StaticMapBuilder};
+    ///
+    /// let mut map = StaticMapBuilder::new()
+    ///     .width(400)
+    ///     .height(400)
+    ///     .zoom(10)
+    ///     .lat_center(52.5)
+    ///     .lon_center(13.4)
+    ///     .tile_fetcher(MockTileFetcher::new())
+    ///     .build()
+    ///     .unwrap();
+    /// map.add_tool(
+    ///     CircleBuilder::new()
+    ///         .lat_coordinate(52.5)
+    ///         .lon_coordinate(13.4)
+    ///         .radius(10.)
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    ///
+    /// let image = map.render_cropped_to_content(5).unwrap();
+    /// assert_eq!((image.width(), image.height()), (30, 30));
+    /// ```
+    pub fn render_cropped_to_content(&mut self, margin: u32) -> Result<Pixmap> {
+        let image = self.render()?;
+        let content = self.drawn_extents().into_iter().flatten().flatten().fold(
+            None,
+            |content: Option<PixelRect>, rect| match content {
+                Some(content) => Some(content.union(rect)),
+                None => Some(*rect),
+            },
+        );
+
+        let crop = content
+            .and_then(|rect| {
+                let rect = rect.outset(margin.into());
+                let (width, height) = (f64::from(image.width()), f64::from(image.height()));
+                let (left, top) = (rect.x_min.floor().max(0.), rect.y_min.floor().max(0.));
+                let (right, bottom) = (rect.x_max.ceil().min(width), rect.y_max.ceil().min(height));
+                IntRect::from_ltrb(left as i32, top as i32, right as i32, bottom as i32)
+            })
+            .and_then(|rect| image.clone_rect(rect));
+
+        match crop {
+            Some(crop) => {
+                self.recycle(image);
+                Ok(crop)
+            }
+            None => Ok(image),
+        }
+    }
+
+    /// Render the map cropped to the tools drawn on it and encode as PNG,
+    /// see [render_cropped_to_content][Self::render_cropped_to_content].
+    ///
+    /// May panic if any feature has invalid bounds.
+    pub fn encode_png_cropped_to_content(&mut self, margin: u32) -> Result<Vec<u8>> {
+        let image = self.render_cropped_to_content(margin)?;
+        let png = image.encode_png()?;
+        self.recycle(image);
+        Ok(png)
+    }
+
+    /// Render the map cropped to the tools drawn on it and save as PNG to a file,
+    /// see [render_cropped_to_content][Self::render_cropped_to_content].
+    ///
+    /// May panic if any feature has invalid bounds.
+    pub fn save_png_cropped_to_content<P: AsRef<::std::path::Path>>(
+        &mut self,
+        path: P,
+        margin: u32,
+    ) -> Result<()> {
+        std::fs::write(path, self.encode_png_cropped_to_content(margin)?)?;
+        Ok(())
+    }
+
+    /// Render the map and encode as JPEG, with `quality` between 1 and 100.
+    /// Transparent areas are drawn on white.
+    ///
+    /// May panic if any feature has invalid bounds.
+    #[cfg(feature = "image")]
+    pub fn encode_jpeg(&mut self, quality: u8) -> Result<Vec<u8>> {
+        use image::{codecs::jpeg::JpegEncoder, ExtendedColorType, ImageEncoder, ImageError};
+
+        let (pixmap, bounds) = self.render_with_bounds()?;
+
+        // Premultiplied colors composited over white.
+        let rgb: Vec<u8> = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|x| {
+                let background = 255 - x.alpha();
+                [
+                    x.red() + background,
+                    x.green() + background,
+                    x.blue() + background,
+                ]
+            })
+            .collect();
+
+        let mut jpeg = Vec::new();
+        let mut encoder = JpegEncoder::new_with_quality(&mut jpeg, quality.clamp(1, 100));
+
+        if self.geotag {
+            let (lon, lat) = bounds.center();
+            let (lon_min, lat_min, lon_max, lat_max) = bounds.extent();
+            let description = format!(
+                "Map extent (west, south, east, north): {:.6}, {:.6}, {:.6}, {:.6}",
+                lon_min, lat_min, lon_max, lat_max
+            );
+
+            encoder
+                .set_exif_metadata(crate::exif::gps_exif(lon, lat, &description))
+                .map_err(ImageError::Unsupported)?;
+        }
+
+        encoder.write_image(
+            &rgb,
+            pixmap.width(),
+            pixmap.height(),
+            ExtendedColorType::Rgb8,
+        )?;
+        self.recycle(pixmap);
+        Ok(jpeg)
+    }
+
+    /// Render the map and save as JPEG to a file, with `quality` between 1 and 100.
+    ///
+    /// May panic if any feature has invalid bounds.
+    #[cfg(feature = "image")]
+    pub fn save_jpeg<P: AsRef<::std::path::Path>>(&mut self, path: P, quality: u8) -> Result<()> {
+        std::fs::write(path, self.encode_jpeg(quality)?)?;
         Ok(())
     }
 
-    fn render(&mut self) -> Result<Pixmap> {
+    /// Render the map to a pixmap.
+    ///
+    /// May panic if any feature has invalid bounds.
+    pub fn render(&mut self) -> Result<Pixmap> {
+        Ok(self.render_with_bounds()?.0)
+    }
+
+    pub(crate) fn render_with_bounds(&mut self) -> Result<(Pixmap, Bounds)> {
+        let drawn = self.render_drawn()?;
+        Ok((drawn.image, drawn.bounds))
+    }
+
+    fn render_drawn(&mut self) -> Result<Drawn> {
         let bounds = self.bounds.build(&self.tools);
+        let offsets = self.coincident_points.offsets(&self.tools);
+
+        let drawn = self.draw(bounds, &offsets, &[], &[])?;
+        self.keep_last_render(&drawn.image, drawn.bounds.clone(), drawn.extents.clone())?;
+        Ok(drawn)
+    }
 
-        let mut image = Pixmap::new(bounds.width, bounds.height).ok_or(Error::InvalidSize)?;
+    /// Keep a render as the last render, with a copy of the image if
+    /// [enabled][StaticMapBuilder::keep_last_render], returning the previous image to the pool.
+    fn keep_last_render(
+        &mut self,
+        image: &Pixmap,
+        bounds: Bounds,
+        extents: Vec<Option<PixelRect>>,
+    ) -> Result<()> {
+        let copy = match (self.keep_last_render, &self.pixmap_pool) {
+            (false, _) => None,
+            (true, Some(pool)) => {
+                let mut copy = pool
+                    .pixmap(image.width(), image.height())
+                    .ok_or(Error::InvalidSize)?;
+                copy.data_mut().copy_from_slice(image.data());
+                Some(copy)
+            }
+            (true, None) => Some(image.clone()),
+        };
 
-        self.draw_base_layer(image.as_mut(), &bounds)?;
+        if let Some((Some(previous), _, _)) = self.last_render.replace((copy, bounds, extents)) {
+            self.recycle(previous);
+        }
+        Ok(())
+    }
 
-        for tool in self.tools.iter() {
-            tool.draw(&bounds, image.as_mut());
+    /// Return a rendered image to the [pixmap pool][StaticMapBuilder::pixmap_pool] once it is
+    /// no longer needed, e.g. after encoding it, so its buffer is reused by later renders.
+    /// Without a pool, the image is dropped.
+    pub fn recycle(&self, image: Pixmap) {
+        if let Some(ref pool) = self.pixmap_pool {
+            pool.recycle(image);
         }
+    }
 
-        Ok(image)
+    /// Transparent pixmap, from the pixmap pool if any.
+    fn new_pixmap(&self, width: u32, height: u32) -> Result<Pixmap> {
+        match self.pixmap_pool {
+            Some(ref pool) => pool.pixmap(width, height),
+            None => Pixmap::new(width, height),
+        }
+        .ok_or(Error::InvalidSize)
     }
 
-    fn draw_base_layer(&self, mut image: PixmapMut, bounds: &Bounds) -> Result<()> {
-        let max_tile: i32 = 2_i32.pow(bounds.zoom.into());
+    /// Render the map and an appearance at once, e.g. light and dark variants for apps
+    /// following the appearance of the system, returned as (map, appearance).
+    ///
+    /// The bounds and placement of the tools are worked out once, so both images
+    /// show the same extent. Tiles shared by both are fetched twice,
+    /// unless the map has a caching tile fetcher.
+    /// The map is the last render.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{
+    ///     effects::Invert,
+    ///     testing::MockTileFetcher,
+    ///     tools::{CircleBuilder, Color},
+    ///     Appearance, StaticMapBuilder,
+    /// };
+    ///
+    /// let mut map = StaticMapBuilder::new()
+    ///     .tile_fetcher(MockTileFetcher::new())
+    ///     .zoom(10)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// map.add_tool(
+    ///     CircleBuilder::new()
+    ///         .lat_coordinate(52.5)
+    ///         .lon_coordinate(13.4)
+    ///         .radius(8.)
+    ///         .color(Color::new(true, 230, 60, 40, 255))
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    ///
+    /// let dark = Appearance::new()
+    ///     .tile_effect(Invert::new())
+    ///     .background(Color::new(true, 20, 20, 25, 255));
+    ///
+    /// let (light, dark) = map.render_pair(&dark).unwrap();
+    /// assert_eq!(light.width(), dark.width());
+    /// ```
+    pub fn render_pair(&mut self, appearance: &Appearance) -> Result<(Pixmap, Pixmap)> {
+        let bounds = self.bounds.build(&self.tools);
+        let offsets = self.coincident_points.offsets(&self.tools);
+
+        let Drawn {
+            image,
+            bounds: expanded,
+            extents,
+            ..
+        } = self.draw(bounds.clone(), &offsets, &[], &[])?;
+        self.keep_last_render(&image, expanded, extents)?;
 
-        let tiles: Vec<(i32, i32, String)> = (bounds.x_min..bounds.x_max)
-            .map(|x| (x, bounds.y_min..bounds.y_max))
-            .flat_map(|(x, y_r)| {
-                y_r.map(move |y| {
-                    let tile_x = (x + max_tile) % max_tile;
-                    let tile_y = (y + max_tile) % max_tile;
+        let url_template = match appearance.url_template {
+            Some(ref url_template) => {
+                std::mem::replace(&mut self.url_template, url_template.clone())
+            }
+            None => self.url_template.clone(),
+        };
+        let overlay_templates = match appearance.overlay_templates {
+            Some(ref templates) => {
+                std::mem::replace(&mut self.overlay_templates, templates.clone())
+            }
+            None => self.overlay_templates.clone(),
+        };
+        let background = match appearance.background {
+            Some(ref color) => self.background.replace(color.clone()),
+            None => self.background.clone(),
+        };
 
-                    (
-                        x,
-                        y,
-                        self.url_template
-                            .replace("{z}", &bounds.zoom.to_string())
-                            .replace("{x}", &tile_x.to_string())
-                            .replace("{y}", &tile_y.to_string()),
-                    )
-                })
+        let variant = self.draw(
+            bounds,
+            &offsets,
+            &appearance.tile_effects,
+            &appearance.effects,
+        );
+
+        self.url_template = url_template;
+        self.overlay_templates = overlay_templates;
+        self.background = background;
+
+        Ok((image, variant?.image))
+    }
+
+    /// Render the map with the tools of the given [named layers][StaticMap::add_tool_to_layer]
+    /// only, e.g. variants with and without points of interest.
+    /// Tools added without a layer are always drawn.
+    ///
+    /// All variants show the same area, fitted to every tool of the map,
+    /// and hidden tools do not displace others when [decluttering][Declutter].
+    /// The tiles are kept between calls, so further variants with the same bounds and tile
+    /// sources reuse them instead of fetching and drawing them again. The tiles are not
+    /// kept if any were missing, e.g. when out of [time][StaticMapBuilder::time_budget].
+    /// The variant is the last render.
+    ///
+    /// May panic if any feature has invalid bounds.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{
+    ///     testing::MockTileFetcher,
+    ///     tools::{CircleBuilder, LineBuilder},
+    ///     StaticMapBuilder,
+    /// };
+    ///
+    /// let mut map = StaticMapBuilder::new()
+    ///     .tile_fetcher(MockTileFetcher::new())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// map.add_tool_to_layer(
+    ///     "route",
+    ///     LineBuilder::new()
+    ///         .coordinates(vec![(52.50, 13.40), (52.52, 13.45)])
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    /// map.add_tool_to_layer(
+    ///     "poi",
+    ///     CircleBuilder::new()
+    ///         .lat_coordinate(52.51)
+    ///         .lon_coordinate(13.42)
+    ///         .radius(6.)
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    ///
+    /// let with_pois = map.render_with_layers(&["route", "poi"]).unwrap();
+    /// let without_pois = map.render_with_layers(&["route"]).unwrap();
+    /// assert_ne!(with_pois.data(), without_pois.data());
+    /// ```
+    pub fn render_with_layers(&mut self, layers: &[&str]) -> Result<Pixmap> {
+        let bounds = self.bounds.build(&self.tools);
+        let offsets = self.coincident_points.offsets(&self.tools);
+        let visible: Vec<bool> = self
+            .tool_layers
+            .iter()
+            .map(|layer| match layer {
+                Some(layer) => layers.contains(&layer.as_str()),
+                None => true,
             })
             .collect();
 
-        let tile_images: Vec<_> = tiles
-            .par_iter()
-            .map(|x| {
-                RequestBuilder::try_new(Method::GET, &x.2)
-                    .and_then(RequestBuilder::send)
-                    .and_then(Response::bytes)
-                    .map_err(|error| Error::TileError {
-                        error,
-                        url: x.2.clone(),
-                    })
+        let key = self.base_key(&bounds);
+        let (base, complete) = match (self.base_cache.take(), key) {
+            (Some((cached, base)), Some(key)) if cached == key => (base, true),
+            (cached, _) => {
+                if let Some((_, base)) = cached {
+                    self.recycle(base);
+                }
+                self.draw_base(&bounds, &[])?
+            }
+        };
+        let mut image = self.new_pixmap(base.width(), base.height())?;
+        image.data_mut().copy_from_slice(base.data());
+
+        // Bases with missing tiles, e.g. when out of time, are drawn again by the next call.
+        match (key, complete) {
+            (Some(key), true) => self.base_cache = Some((key, base)),
+            _ => self.recycle(base),
+        }
+
+        let (image, bounds, extents) =
+            self.draw_over_base(image, bounds, &offsets, &visible, &[])?;
+        self.keep_last_render(&image, bounds, extents)?;
+        Ok(image)
+    }
+
+    /// Key of the base layer drawn within the bounds, or None if it can not be kept,
+    /// e.g. with a background other than a solid color.
+    fn base_key(&self, bounds: &Bounds) -> Option<BaseKey> {
+        let mut state = StableHasher::default();
+        take_uncacheable();
+
+        self.bounds.digest(&mut state);
+        // The area fitted to the tools.
+        bounds.width.digest(&mut state);
+        bounds.height.digest(&mut state);
+        bounds.x_center.digest(&mut state);
+        bounds.y_center.digest(&mut state);
+        bounds.zoom.digest(&mut state);
+
+        self.url_template.digest(&mut state);
+        self.overlay_templates.digest(&mut state);
+        self.base_image.digest(&mut state);
+        self.background.digest(&mut state);
+        self.tile_paint.digest(&mut state);
+        self.overzoom.digest(&mut state);
+
+        match take_uncacheable() {
+            true => None,
+            false => Some(state.finish()),
+        }
+    }
+
+    /// Draw the tiles and tools of the map within the bounds, with the tools displaced
+    /// by the offsets. Tile effects are applied to the tiles, and the effects after those
+    /// of the map.
+    fn draw(
+        &self,
+        bounds: Bounds,
+        offsets: &[(f64, f64)],
+        tile_effects: &[Box<dyn Effect>],
+        effects: &[Box<dyn Effect>],
+    ) -> Result<Drawn> {
+        let (image, complete) = self.draw_base(&bounds, tile_effects)?;
+        let visible = vec![true; self.tools.len()];
+        let (image, bounds, extents) =
+            self.draw_over_base(image, bounds, offsets, &visible, effects)?;
+        Ok(Drawn {
+            image,
+            bounds,
+            extents,
+            complete,
+        })
+    }
+
+    /// Draw the background and the tiles or base image within the bounds,
+    /// with the tile effects applied. Returns the image and whether every tile was fetched.
+    fn draw_base(
+        &self,
+        bounds: &Bounds,
+        tile_effects: &[Box<dyn Effect>],
+    ) -> Result<(Pixmap, bool)> {
+        let mut image = self.new_pixmap(bounds.width, bounds.height)?;
+        self.fill_background(&mut image);
+
+        let complete = match self.base_image {
+            Some(ref base_image) => {
+                base_image.draw(image.as_mut(), bounds);
+                true
+            }
+            None => self.draw_base_layer(image.as_mut(), bounds)?,
+        };
+
+        for effect in tile_effects {
+            effect.apply(&mut image.as_mut());
+        }
+
+        Ok((image, complete))
+    }
+
+    /// Draw the feature layers and the visible tools over a base drawn by
+    /// [draw_base][Self::draw_base], followed by the effects.
+    fn draw_over_base(
+        &self,
+        image: Pixmap,
+        bounds: Bounds,
+        offsets: &[(f64, f64)],
+        visible: &[bool],
+        effects: &[Box<dyn Effect>],
+    ) -> Result<(Pixmap, Bounds, Vec<Option<PixelRect>>)> {
+        let (mut image, bounds, extents) = self.draw_tools(image, bounds, offsets, visible, 1.)?;
+
+        for effect in self.effects.iter().chain(effects) {
+            effect.apply(&mut image.as_mut());
+        }
+
+        let image_rect = bounds.pixel_rect();
+        let extents = extents
+            .into_iter()
+            .map(|extent| extent.and_then(|rect| rect.intersection(&image_rect)))
+            .collect();
+
+        Ok((image, bounds, extents))
+    }
+
+    /// Draw the feature layers and the visible tools on the image, placing the decorations
+    /// on bands around it if the canvas is expanded. The image is `scale` times the size
+    /// of the bounds, and everything is drawn `scale` times larger. Returns the image,
+    /// its bounds and the unclipped pixel extents the tools were drawn at, at 1x.
+    fn draw_tools(
+        &self,
+        mut image: Pixmap,
+        bounds: Bounds,
+        offsets: &[(f64, f64)],
+        visible: &[bool],
+        scale: f32,
+    ) -> Result<(Pixmap, Bounds, Vec<Option<PixelRect>>)> {
+        // Decorations are drawn on the bands around the map instead of the map.
+        let (top, bottom) = match self.expand_canvas {
+            true => self.decoration_bands(&bounds, visible),
+            false => (0, 0),
+        };
+        let expanded = top > 0 || bottom > 0;
+
+        for layer in self.layers.iter() {
+            layer.draw(&bounds, scale, &mut image.as_mut())?;
+        }
+
+        // Hidden tools are not spread out, so they get no legs.
+        let leg_offsets: Vec<_> = offsets
+            .iter()
+            .zip(visible)
+            .map(|(&offset, &visible)| if visible { offset } else { (0., 0.) })
+            .collect();
+        self.coincident_points
+            .draw_legs(&self.tools, &leg_offsets, &bounds, scale, image.as_mut());
+
+        let shrinks = self
+            .declutter
+            .scales(&self.tools, offsets, visible, &bounds);
+        let map_rect = bounds.pixel_rect();
+        let mut extents = vec![None; self.tools.len()];
+        for ((((tool, &(dx, dy)), shrink), extent), &visible) in self
+            .tools
+            .iter()
+            .zip(offsets)
+            .zip(shrinks)
+            .zip(extents.iter_mut())
+            .zip(visible)
+        {
+            if !visible || expanded && is_decoration(tool, &bounds) {
+                continue;
+            }
+            let shrink = match shrink {
+                Some(shrink) => shrink,
+                None => continue,
+            };
+
+            let translated;
+            let tool_bounds = if dx == 0. && dy == 0. {
+                &bounds
+            } else {
+                translated = bounds.translate(dx, dy);
+                &translated
+            };
+
+            // Skip tools entirely outside the map.
+            let rect = tool.pixel_extent(tool_bounds);
+            if let Some(ref rect) = rect {
+                if !rect.intersects(&map_rect) {
+                    continue;
+                }
+            }
+
+            if shrink == 1. {
+                tool.draw_at_scale(tool_bounds, scale, image.as_mut());
+            } else {
+                draw_scaled(tool, tool_bounds, shrink, scale, image.as_mut());
+            }
+            *extent = rect.map(|rect| scaled_extent(tool, tool_bounds, &rect, shrink));
+        }
+
+        let (image, bounds) = match expanded {
+            true => self.expand(image, bounds, (top, bottom), visible, scale, &mut extents)?,
+            false => (image, bounds),
+        };
+
+        Ok((image, bounds, extents))
+    }
+
+    fn fill_background(&self, image: &mut Pixmap) {
+        if let Some(ref background) = self.background {
+            if let Some(rect) =
+                tiny_skia::Rect::from_xywh(0., 0., image.width() as f32, image.height() as f32)
+            {
+                image.fill_rect(rect, background.paint(), Transform::default(), None);
+            }
+        }
+    }
+
+    /// Heights of the bands above and below the map holding its visible decorations,
+    /// in pixels.
+    fn decoration_bands(&self, bounds: &Bounds, visible: &[bool]) -> (u32, u32) {
+        let height = f64::from(bounds.height);
+        let (mut top, mut bottom) = (0_f64, 0_f64);
+
+        for (tool, _) in self
+            .tools
+            .iter()
+            .zip(visible)
+            .filter(|&(tool, &visible)| visible && is_decoration(tool, bounds))
+        {
+            let rect = match tool.pixel_extent(bounds) {
+                Some(rect) => rect,
+                None => continue,
+            };
+
+            // The distance to the edge of the image is kept on the side facing the map.
+            if rect.y_min + rect.y_max < height {
+                top = top.max(rect.y_max + rect.y_min.max(0.));
+            } else {
+                bottom = bottom.max(2. * height - rect.y_min - rect.y_max.min(height));
+            }
+        }
+
+        (top.ceil() as u32, bottom.ceil() as u32)
+    }
+
+    /// Map placed between bands of the given heights, with the decorations drawn on them.
+    /// The map is `scale` times the size of the bounds, and the decorations are drawn
+    /// at the scale. The extents of the tools are moved along with the map, and those
+    /// of the decorations set to where they are drawn.
+    fn expand(
+        &self,
+        map: Pixmap,
+        bounds: Bounds,
+        (top, bottom): (u32, u32),
+        visible: &[bool],
+        scale: f32,
+        extents: &mut [Option<PixelRect>],
+    ) -> Result<(Pixmap, Bounds)> {
+        let height = bounds.height.saturating_add(top).saturating_add(bottom);
+        let scaled_height = (f64::from(height) * f64::from(scale)).round().max(1.) as u32;
+        let mut image = self.new_pixmap(map.width(), scaled_height)?;
+        self.fill_background(&mut image);
+        image.draw_pixmap(
+            0,
+            (top as f32 * scale).round() as i32,
+            map.as_ref(),
+            &PixmapPaint::default(),
+            Transform::default(),
+            None,
+        );
+        self.recycle(map);
+
+        // Coordinates stay where they are on the map, and decorations are placed on the image.
+        let expanded = Bounds {
+            height,
+            ..bounds.translate(0., (f64::from(top) - f64::from(bottom)) / 2.)
+        };
+
+        let top = f64::from(top);
+        for ((tool, extent), &visible) in self.tools.iter().zip(extents).zip(visible) {
+            if !visible {
+                continue;
+            } else if is_decoration(tool, &bounds) {
+                tool.draw_at_scale(&expanded, scale, image.as_mut());
+                *extent = tool.pixel_extent(&expanded);
+            } else if let Some(ref mut rect) = extent {
+                rect.y_min += top;
+                rect.y_max += top;
+            }
+        }
+
+        Ok((image, expanded))
+    }
+
+    /// The image of the last render, including tools and effects.
+    /// None unless [keep_last_render][StaticMapBuilder::keep_last_render] is enabled,
+    /// if the map has not been rendered, or if the last encoded image
+    /// was taken from the [image cache][StaticMapBuilder::image_cache].
+    pub fn last_render(&self) -> Option<&Pixmap> {
+        self.last_render
+            .as_ref()
+            .and_then(|(image, _, _)| image.as_ref())
+    }
+
+    /// Pixel rectangles the tools occupied in the last render, including strokes,
+    /// casings and icons, in the order the tools were added, e.g. to crop the image
+    /// to its content, lay out decorations around the tools or find the tool under a click.
+    ///
+    /// Rectangles are clipped to the image. A tool has no rectangle if it was not drawn,
+    /// e.g. when outside the map or hidden by [Declutter][Declutter], or if it has no
+    /// [pixel extent][Tool::pixel_extent]. Decluttered tools have the rectangle they were
+    /// shrunk to. None if the map has not been rendered, or if the last encoded image
+    /// was taken from the [image cache][StaticMapBuilder::image_cache].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{testing::MockTileFetcher, tools::CircleBuilder, StaticMapBuilder};
+    ///
+    /// let mut map = StaticMapBuilder::new()
+    ///     .width(200)
+    ///     .height(200)
+    ///     .zoom(10)
+    ///     .lat_center(52.5)
+    ///     .lon_center(13.4)
+    ///     .tile_fetcher(MockTileFetcher::new())
+    ///     .build()
+    ///     .unwrap();
+    /// map.add_tool(
+    ///     CircleBuilder::new()
+    ///         .lat_coordinate(52.5)
+    ///         .lon_coordinate(13.4)
+    ///         .radius(10.)
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    /// map.render().unwrap();
+    ///
+    /// // The circle is under a click at the center of the map.
+    /// let extents = map.drawn_extents().unwrap();
+    /// assert!(extents[0].unwrap().contains(100., 100.));
+    /// ```
+    pub fn drawn_extents(&self) -> Option<&[Option<PixelRect>]> {
+        self.last_render
+            .as_ref()
+            .map(|(_, _, extents)| extents.as_slice())
+    }
+
+    /// Copy of the last render with another tool drawn on top, without fetching tiles
+    /// or drawing the other tools again, e.g. to stamp a timestamp or counter
+    /// on each frame of a cached base map.
+    ///
+    /// The tool is drawn with the bounds of the last render and after its effects,
+    /// and is not added to the map. The last render is left unchanged.
+    /// Requires [keep_last_render][StaticMapBuilder::keep_last_render], and returns
+    /// [Error::NotRendered][Error::NotRendered] if there is no [last render][Self::last_render].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{testing::MockTileFetcher, tools::CircleBuilder, StaticMapBuilder};
+    ///
+    /// let mut map = StaticMapBuilder::new()
+    ///     .width(200)
+    ///     .height(200)
+    ///     .zoom(10)
+    ///     .lat_center(52.5)
+    ///     .lon_center(13.4)
+    ///     .tile_fetcher(MockTileFetcher::new())
+    ///     .keep_last_render(true)
+    ///     .build()
+    ///     .unwrap();
+    /// map.render().unwrap();
+    ///
+    /// for lon in [13.3, 13.4, 13.5] {
+    ///     let frame = map
+    ///         .annotate(
+    ///             CircleBuilder::new()
+    ///                 .lat_coordinate(52.5)
+    ///                 .lon_coordinate(lon)
+    ///                 .build()
+    ///                 .unwrap(),
+    ///         )
+    ///         .unwrap();
+    ///     assert_eq!(frame.width(), 200);
+    /// }
+    /// ```
+    pub fn annotate(&self, tool: impl Tool) -> Result<Pixmap> {
+        let (image, bounds) = match self.last_render {
+            Some((Some(ref image), ref bounds, _)) => (image, bounds),
+            _ => return Err(Error::NotRendered),
+        };
+        let mut image = image.clone();
+        tool.draw(bounds, image.as_mut());
+        Ok(image)
+    }
+
+    /// Bounds of the map with its current tools, as used when rendering.
+    pub fn bounds(&mut self) -> Bounds {
+        self.bounds.build(&self.tools)
+    }
+
+    /// Size, zoom and center of the map with its current tools, as used when rendering.
+    pub fn viewport(&mut self) -> Viewport {
+        let bounds = self.bounds();
+        let zoom = (i16::from(bounds.zoom) - i16::from(bounds.zoom_offset))
+            .clamp(0, i16::from(u8::MAX)) as u8;
+        Viewport::new(bounds.width, bounds.height, zoom, bounds.center())
+    }
+
+    /// Zoom in by the given number of levels from the current view, keeping its center,
+    /// e.g. after the zoom was determined from the tools.
+    ///
+    /// Like the other adjustments, this fixes the zoom and center of the map,
+    /// so tools added later no longer change them. Add the tools first.
+    pub fn zoom_in(&mut self, levels: u8) {
+        let mut viewport = self.viewport();
+        viewport.zoom_in(levels);
+        self.set_view(&viewport);
+    }
+
+    /// Zoom out by the given number of levels from the current view, keeping its center,
+    /// e.g. to show more surroundings than fitting the tools does.
+    /// See [zoom_in][StaticMap::zoom_in].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::{tools::CircleBuilder, StaticMapBuilder};
+    ///
+    /// let mut map = StaticMapBuilder::new().build().unwrap();
+    /// map.add_tool(
+    ///     CircleBuilder::new()
+    ///         .lat_coordinate(52.5)
+    ///         .lon_coordinate(13.4)
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    ///
+    /// // Fit the tools, then zoom out one level and nudge the view 2 km north.
+    /// let fitted = map.viewport();
+    /// map.zoom_out(1);
+    /// map.pan_meters(2000., 0.);
+    ///
+    /// let adjusted = map.viewport();
+    /// assert_eq!(adjusted.zoom, fitted.zoom - 1);
+    /// assert!(adjusted.center.1 > fitted.center.1);
+    /// ```
+    pub fn zoom_out(&mut self, levels: u8) {
+        let mut viewport = self.viewport();
+        viewport.zoom_out(levels);
+        self.set_view(&viewport);
+    }
+
+    /// Move the current view by the given number of pixels, to the right and down
+    /// for positive values. See [zoom_in][StaticMap::zoom_in].
+    pub fn pan_pixels(&mut self, dx: f64, dy: f64) {
+        let bounds = self.bounds();
+        let mut viewport = self.viewport();
+        viewport.center = bounds.px_to_lon_lat(
+            f64::from(bounds.width) / 2. + dx,
+            f64::from(bounds.height) / 2. + dy,
+        );
+        self.set_view(&viewport);
+    }
+
+    /// Move the current view by the given distance in meters, in the direction of the bearing
+    /// in degrees clockwise from north. See [zoom_in][StaticMap::zoom_in].
+    pub fn pan_meters(&mut self, distance: f64, bearing: f64) {
+        let mut viewport = self.viewport();
+        viewport.pan_meters(distance, bearing);
+        self.set_view(&viewport);
+    }
+
+    /// Fix the zoom and center of the map to those of the viewport.
+    fn set_view(&mut self, viewport: &Viewport) {
+        let (lon, lat) = viewport.center;
+        self.bounds = self
+            .bounds
+            .clone()
+            .zoom(Some(viewport.zoom))
+            .lon_center(Some(lon))
+            .lat_center(Some(lat));
+    }
+
+    /// Render the map with the given size, zoom and center instead of the configured ones,
+    /// e.g. for each frame of a sequence panning or zooming over the map.
+    /// The configured view is kept for later renders. See [Viewport][Viewport].
+    ///
+    /// May panic if any feature has invalid bounds.
+    pub fn render_with_viewport(&mut self, viewport: &Viewport) -> Result<Pixmap> {
+        let (lon, lat) = viewport.center;
+        let bounds = self
+            .bounds
+            .clone()
+            .width(viewport.width)
+            .height(viewport.height)
+            .zoom(Some(viewport.zoom))
+            .lon_center(Some(lon))
+            .lat_center(Some(lat));
+
+        let configured = std::mem::replace(&mut self.bounds, bounds);
+        let image = self.render();
+        self.bounds = configured;
+        image
+    }
+
+    /// Tiles of the base layer and overlay layers for the given bounds, with their URLs and
+    /// where they are placed on the map, e.g. for custom compositing
+    /// or to validate coverage before rendering.
+    /// Tiles are ordered by layer. Empty if a base image is used instead of tiles.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use staticmap::StaticMapBuilder;
+    ///
+    /// let mut map = StaticMapBuilder::new()
+    ///     .zoom(4)
+    ///     .lat_center(52.6)
+    ///     .lon_center(13.4)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let bounds = map.bounds();
+    /// for tile in map.tile_plan(&bounds) {
+    ///     println!("{} at ({}, {})", tile.url, tile.x_px, tile.y_px);
+    /// }
+    /// ```
+    pub fn tile_plan(&self, bounds: &Bounds) -> Vec<TilePlacement> {
+        if self.base_image.is_some() {
+            return Vec::new();
+        }
+
+        // Beyond the highest zoom of the tile source, parent tiles are scaled up.
+        let (source_zoom, factor) = match self.zoom_range {
+            Some((_, max)) if self.overzoom && bounds.grid.is_none() && bounds.zoom > max => (
+                max,
+                2_i32.saturating_pow((bounds.zoom - max).min(30).into()),
+            ),
+            _ => (bounds.zoom, 1),
+        };
+
+        let max_tile: i32 = 2_i32.saturating_pow(source_zoom.into());
+        let zoom = match bounds.grid {
+            Some(ref grid) => grid.zoom_identifier(source_zoom),
+            None => source_zoom.to_string(),
+        };
+
+        let x_range = bounds.x_min.div_euclid(factor)..=(bounds.x_max - 1).div_euclid(factor);
+        let y_range = bounds.y_min.div_euclid(factor)..=(bounds.y_max - 1).div_euclid(factor);
+
+        let tiles: Vec<(i32, i32)> = x_range
+            .map(|x| (x, y_range.clone()))
+            .flat_map(|(x, y_r)| y_r.map(move |y| (x, y)))
+            .filter(|(x, y)| match bounds.grid {
+                Some(ref grid) => grid.contains_tile(*x, *y, bounds.zoom, bounds.tile_size),
+                None => true,
             })
             .collect();
+        let zoom = &zoom;
 
-        for (tile, tile_image) in tiles.iter().zip(tile_images) {
-            let (x, y) = (tile.0, tile.1);
-            let (x_px, y_px) = (bounds.x_to_px(x.into()), bounds.y_to_px(y.into()));
+        std::iter::once(&self.url_template)
+            .chain(self.overlay_templates.iter())
+            .enumerate()
+            .flat_map(|(layer, url_template)| {
+                tiles.iter().map(move |&(x, y)| {
+                    // Web Mercator tiles wrap around the antimeridian, custom grids do not.
+                    let (tile_x, tile_y) = match bounds.grid {
+                        Some(_) => (x, y),
+                        None => (x.rem_euclid(max_tile), y.rem_euclid(max_tile)),
+                    };
+
+                    TilePlacement {
+                        x: tile_x,
+                        y: tile_y,
+                        layer,
+                        zoom: source_zoom,
+                        url: url_template
+                            .replace("{z}", zoom)
+                            .replace("{x}", &tile_x.to_string())
+                            .replace("{y}", &tile_y.to_string()),
+                        x_px: bounds.x_to_px((x * factor).into()),
+                        y_px: bounds.y_to_px((y * factor).into()),
+                        size: f64::from(bounds.tile_size) * f64::from(factor),
+                    }
+                })
+            })
+            .collect()
+    }
 
-            let pixmap = Pixmap::decode_png(&tile_image?)?;
+    /// Fetch tiles one by one in the background, compositing the tiles which arrive
+    /// within the budget. Tiles still being fetched are abandoned.
+    ///
+    /// The tiles are fetched on threads of their own rather than the rayon pool,
+    /// so abandoned fetches do not hold up other work on the pool. The threads stop
+    /// after their current tile once the budget is spent.
+    fn fetch_within(
+        &self,
+        urls: Vec<String>,
+        budget: Duration,
+        compositor: &mut TileCompositor,
+        image: &mut PixmapMut,
+    ) -> Result<()> {
+        const THREADS: usize = 8;
+        let deadline = Instant::now() + budget;
 
-            image.draw_pixmap(
-                x_px as i32,
-                y_px as i32,
-                pixmap.as_ref(),
-                &PixmapPaint::default(),
-                Transform::default(),
-                None,
+        let threads = THREADS.min(urls.len());
+        let queue = Arc::new(Mutex::new(urls.into_iter().enumerate()));
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..threads {
+            let (fetcher, queue, sender) = (
+                Arc::clone(&self.fetcher),
+                Arc::clone(&queue),
+                sender.clone(),
             );
+            std::thread::spawn(move || loop {
+                let next = queue.lock().map(|mut queue| queue.next());
+                let (index, url) = match next {
+                    Ok(Some(next)) => next,
+                    _ => break,
+                };
+                let tile_image = fetcher
+                    .fetch(std::slice::from_ref(&url))
+                    .pop()
+                    .unwrap_or_else(|| {
+                        Err(Error::InvalidTile {
+                            url,
+                            reason: "The tile fetcher returned no result".to_string(),
+                        })
+                    });
+                // The map is done with the tiles once out of time.
+                if sender.send((index, tile_image)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match receiver.recv_timeout(remaining) {
+                Ok((index, tile_image)) => compositor.add(index, tile_image?, image)?,
+                // Out of time, or all tiles arrived.
+                Err(_) => break,
+            }
         }
 
         Ok(())
     }
+
+    /// Fetch all tiles, compositing each as soon as it arrives.
+    fn fetch_all(
+        &self,
+        urls: &[String],
+        compositor: &mut TileCompositor,
+        image: &mut PixmapMut,
+    ) -> Result<()> {
+        let (sender, receiver) = mpsc::sync_channel(urls.len());
+        let fetcher = &self.fetcher;
+
+        // The fetcher runs on its own thread, as blocking a thread of the rayon pool
+        // while its parallel fetches wait for threads could deadlock.
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                fetcher.fetch_each(urls, &|index, tile_image| {
+                    let _ = sender.send((index, tile_image));
+                })
+            });
+
+            for (index, tile_image) in receiver.iter() {
+                compositor.add(index, tile_image?, image)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Draw the tiles within the bounds, returning whether every tile was fetched.
+    fn draw_base_layer(&self, mut image: PixmapMut, bounds: &Bounds) -> Result<bool> {
+        if let Some((min, max)) = self.zoom_range {
+            if bounds.zoom < min || (bounds.zoom > max && !(self.overzoom && bounds.grid.is_none()))
+            {
+                return Err(Error::ZoomOutOfRange {
+                    zoom: bounds.zoom,
+                    min,
+                    max,
+                });
+            }
+        }
+
+        let tiles = self.tile_plan(bounds);
+
+        // Tiles of all layers are fetched in one batch, fetching each URL once,
+        // e.g. when wrapping around the antimeridian.
+        // URLs are ordered from the center of the map outwards, base layer first,
+        // so the most visible tiles are fetched first.
+        let (center_x, center_y) = (f64::from(bounds.width) / 2., f64::from(bounds.height) / 2.);
+        let distance = |tile: &TilePlacement| {
+            (tile.x_px + tile.size / 2. - center_x).powi(2)
+                + (tile.y_px + tile.size / 2. - center_y).powi(2)
+        };
+        let mut order: Vec<usize> = (0..tiles.len()).collect();
+        order.sort_by(|&a, &b| {
+            distance(&tiles[a])
+                .total_cmp(&distance(&tiles[b]))
+                .then(tiles[a].layer.cmp(&tiles[b].layer))
+        });
+
+        let mut urls: Vec<String> = Vec::new();
+        let mut url_indices: HashMap<&str, usize> = HashMap::new();
+        let mut tile_indices = vec![0; tiles.len()];
+        for index in order {
+            let url = tiles[index].url.as_str();
+            tile_indices[index] = *url_indices.entry(url).or_insert_with(|| {
+                urls.push(url.to_string());
+                urls.len() - 1
+            });
+        }
+
+        let mut compositor = TileCompositor::new(
+            &tiles,
+            tile_indices,
+            urls.len(),
+            self.tile_paint,
+            self.pixmap_pool.as_ref(),
+        );
+        match self.time_budget {
+            Some(budget) => self.fetch_within(urls, budget, &mut compositor, &mut image)?,
+            None => self.fetch_all(&urls, &mut compositor, &mut image)?,
+        }
+
+        compositor.finish(&mut image)
+    }
 }
@@ -0,0 +1,231 @@
+use crate::{
+    bounds::Bounds,
+    json::Json,
+    service::{fetch_json, service_error},
+    tools::{CircleBuilder, Tool},
+    Error, Result,
+};
+use tiny_skia::PixmapMut;
+
+/// Public Nominatim instance used by default.
+const DEFAULT_ENDPOINT: &str = "https://nominatim.openstreetmap.org/search";
+
+/// Place found by [Nominatim][Nominatim].
+///
+/// A place is also a tool drawing nothing, with the bounding box of the place as its extent.
+/// Add it to a map to fit the map to the place, e.g. a city.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+    /// Full name of the place, e.g. "Brandenburger Tor, Pariser Platz, Mitte, Berlin, ...".
+    pub display_name: String,
+    /// Latitude of the place.
+    pub lat: f64,
+    /// Longitude of the place.
+    pub lon: f64,
+    /// Bounding box of the place, as (south, west, north, east).
+    pub bounding_box: (f64, f64, f64, f64),
+}
+
+impl Place {
+    /// Builder for a circle marker at the place, to be styled and built.
+    pub fn marker(&self) -> CircleBuilder {
+        CircleBuilder::new()
+            .lat_coordinate(self.lat)
+            .lon_coordinate(self.lon)
+    }
+
+    fn from_json(json: &Json) -> Option<Place> {
+        // Nominatim returns coordinates as strings.
+        let number = |json: &Json| match *json {
+            Json::String(ref value) => value.parse::<f64>().ok(),
+            Json::Number(value) => Some(value),
+            _ => None,
+        };
+
+        let bounding_box = match json.array("boundingbox").ok()?? {
+            [south, north, west, east] => {
+                (number(south)?, number(west)?, number(north)?, number(east)?)
+            }
+            _ => return None,
+        };
+
+        Some(Place {
+            display_name: json.string("display_name").ok()??.to_string(),
+            lat: number(json.get("lat")?)?,
+            lon: number(json.get("lon")?)?,
+            bounding_box,
+        })
+    }
+}
+
+impl Tool for Place {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        let (south, west, north, east) = self.bounding_box;
+        (west, south, east, north)
+    }
+
+    fn draw(&self, _: &Bounds, _: PixmapMut) {}
+
+    fn draw_at_scale(&self, _: &Bounds, _: f32, _: PixmapMut) {}
+}
+
+/// Geocoding client for Nominatim, finding places by name or address.
+/// Use [NominatimBuilder][NominatimBuilder] as an entrypoint.
+///
+/// Results are OpenStreetMap data, and maps showing them must credit the contributors,
+/// see [Nominatim::ATTRIBUTION][Nominatim::ATTRIBUTION].
+/// The public instance allows at most one request per second, and requires a user agent
+/// identifying the application, see the
+/// [usage policy](https://operations.osmfoundation.org/policies/nominatim/).
+///
+/// Requires the `nominatim` feature.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{tools::Color, NominatimBuilder, StaticMapBuilder};
+///
+/// let nominatim = NominatimBuilder::new()
+///     .user_agent("my-map-renderer/1.0 (maps@example.com)")
+///     .build()
+///     .unwrap();
+///
+/// let place = nominatim.place("Brandenburg Gate, Berlin").unwrap();
+///
+/// let mut map = StaticMapBuilder::new().zoom(16).build().unwrap();
+/// map.add_tool(
+///     place
+///         .marker()
+///         .radius(8.)
+///         .color(Color::new(true, 220, 40, 40, 255))
+///         .build()
+///         .unwrap(),
+/// );
+/// map.save_png("brandenburg-gate.png").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Nominatim {
+    endpoint: String,
+    user_agent: String,
+    email: Option<String>,
+    language: Option<String>,
+    limit: u32,
+}
+
+/// Builder for [Nominatim][Nominatim].
+#[derive(Debug, Clone)]
+pub struct NominatimBuilder {
+    endpoint: String,
+    user_agent: Option<String>,
+    email: Option<String>,
+    language: Option<String>,
+    limit: u32,
+}
+
+impl Default for NominatimBuilder {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            user_agent: None,
+            email: None,
+            language: None,
+            limit: 5,
+        }
+    }
+}
+
+impl NominatimBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// URL of the search endpoint.
+    /// Default is the public instance at <https://nominatim.openstreetmap.org/search>.
+    pub fn endpoint<I: Into<String>>(mut self, endpoint: I) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// **Required**.
+    /// User agent identifying the application, as required by the usage policy.
+    pub fn user_agent<I: Into<String>>(mut self, user_agent: I) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Contact email sent with each request, for the operators of the instance.
+    pub fn email<I: Into<String>>(mut self, email: I) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    /// Preferred language of the place names, e.g. "en" or "de,en".
+    pub fn language<I: Into<String>>(mut self, language: I) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Maximum number of places returned by [search][Nominatim::search].
+    /// Default is 5.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Build the client, consuming the builder.
+    /// Returns an error if the builder is missing required fields.
+    pub fn build(self) -> Result<Nominatim> {
+        Ok(Nominatim {
+            endpoint: self.endpoint,
+            user_agent: self
+                .user_agent
+                .ok_or(Error::BuildError("User agent not supplied."))?,
+            email: self.email,
+            language: self.language,
+            limit: self.limit,
+        })
+    }
+}
+
+impl Nominatim {
+    /// Attribution required on maps showing results from Nominatim.
+    pub const ATTRIBUTION: &'static str = "Data © OpenStreetMap contributors, ODbL";
+
+    /// Places matching a name or address, best match first.
+    pub fn search(&self, query: &str) -> Result<Vec<Place>> {
+        let mut request = attohttpc::get(&self.endpoint)
+            .header("User-Agent", self.user_agent.as_str())
+            .param("q", query)
+            .param("format", "jsonv2")
+            .param("limit", self.limit);
+        if let Some(ref email) = self.email {
+            request = request.param("email", email);
+        }
+        if let Some(ref language) = self.language {
+            request = request.param("accept-language", language);
+        }
+
+        match fetch_json(request, &self.endpoint)? {
+            Json::Array(places) => places
+                .iter()
+                .map(|place| {
+                    Place::from_json(place).ok_or_else(|| {
+                        service_error(&self.endpoint, "Invalid place in response".to_string())
+                    })
+                })
+                .collect(),
+            _ => Err(service_error(
+                &self.endpoint,
+                "Expected an array of places".to_string(),
+            )),
+        }
+    }
+
+    /// Best match for a name or address, e.g. "Brandenburg Gate, Berlin".
+    /// Returns an error if nothing is found.
+    pub fn place(&self, query: &str) -> Result<Place> {
+        self.search(query)?.into_iter().next().ok_or_else(|| {
+            service_error(&self.endpoint, format!("No place found for \"{}\"", query))
+        })
+    }
+}
@@ -0,0 +1,323 @@
+use crate::{
+    json::Json,
+    service::{fetch_json, service_error},
+    tools::{CircleBuilder, Color, LineBuilder, Tool},
+    Result,
+};
+use std::time::Duration;
+
+/// Public Overpass API instance used by default.
+const DEFAULT_ENDPOINT: &str = "https://overpass-api.de/api/interpreter";
+
+/// Element returned by the [Overpass API][Overpass].
+/// Relations are not returned, query their ways instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverpassElement {
+    /// A node, a single point.
+    Node {
+        /// OpenStreetMap id of the node.
+        id: u64,
+        /// Latitude of the node.
+        lat: f64,
+        /// Longitude of the node.
+        lon: f64,
+        /// Tags of the node, as (key, value).
+        tags: Vec<(String, String)>,
+    },
+    /// A way, a line or the outline of an area if closed.
+    Way {
+        /// OpenStreetMap id of the way.
+        id: u64,
+        /// Latitude coordinates of the nodes of the way.
+        lat_coordinates: Vec<f64>,
+        /// Longitude coordinates of the nodes of the way.
+        lon_coordinates: Vec<f64>,
+        /// Tags of the way, as (key, value).
+        tags: Vec<(String, String)>,
+    },
+}
+
+impl OverpassElement {
+    /// Tags of the element, as (key, value).
+    pub fn tags(&self) -> &[(String, String)] {
+        match *self {
+            OverpassElement::Node { ref tags, .. } | OverpassElement::Way { ref tags, .. } => tags,
+        }
+    }
+
+    /// Value of a tag, e.g. "name".
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags()
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the element is a way ending where it starts, e.g. a building.
+    pub fn is_closed(&self) -> bool {
+        match *self {
+            OverpassElement::Node { .. } => false,
+            OverpassElement::Way {
+                ref lat_coordinates,
+                ref lon_coordinates,
+                ..
+            } => {
+                lat_coordinates.len() > 2
+                    && lat_coordinates.first() == lat_coordinates.last()
+                    && lon_coordinates.first() == lon_coordinates.last()
+            }
+        }
+    }
+
+    /// Element from the JSON output of the Overpass API,
+    /// None for other element types, or an error describing an invalid element.
+    fn from_json(json: &Json) -> std::result::Result<Option<Self>, String> {
+        let id = match json.number("id") {
+            Ok(Some(id)) => id as u64,
+            _ => return Err("Element without an id".to_string()),
+        };
+
+        let tags = match json.get("tags") {
+            Some(Json::Object(members)) => members
+                .iter()
+                .filter_map(|(key, value)| match value {
+                    Json::String(value) => Some((key.clone(), value.clone())),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        match json.string("type") {
+            Ok(Some("node")) => match (json.number("lat"), json.number("lon")) {
+                (Ok(Some(lat)), Ok(Some(lon))) => {
+                    Ok(Some(OverpassElement::Node { id, lat, lon, tags }))
+                }
+                _ => Err(format!("Node {} without coordinates", id)),
+            },
+            Ok(Some("way")) => {
+                let geometry = match json.array("geometry") {
+                    Ok(Some(geometry)) => geometry,
+                    _ => return Err(format!("Way {} without geometry", id)),
+                };
+
+                let mut lat_coordinates = Vec::with_capacity(geometry.len());
+                let mut lon_coordinates = Vec::with_capacity(geometry.len());
+                for point in geometry {
+                    // Nodes outside the bounding box of the query may be null.
+                    if let (Ok(Some(lat)), Ok(Some(lon))) =
+                        (point.number("lat"), point.number("lon"))
+                    {
+                        lat_coordinates.push(lat);
+                        lon_coordinates.push(lon);
+                    }
+                }
+
+                Ok(Some(OverpassElement::Way {
+                    id,
+                    lat_coordinates,
+                    lon_coordinates,
+                    tags,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Client for the Overpass API, querying OpenStreetMap data and converting it to tools:
+/// nodes to circle markers and ways to lines. Closed ways, e.g. buildings, are drawn as outlines.
+/// Use [OverpassBuilder][OverpassBuilder] as an entrypoint.
+///
+/// Requires the `overpass` feature.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{tools::Color, OverpassBuilder, StaticMapBuilder};
+///
+/// let overpass = OverpassBuilder::new()
+///     .bbox(59.90, 10.70, 59.93, 10.77)
+///     .color(Color::new(true, 120, 60, 20, 255))
+///     .build()
+///     .unwrap();
+///
+/// let mut map = StaticMapBuilder::new().build().unwrap();
+/// for tool in overpass.fetch_tools("nwr[amenity=cafe];").unwrap() {
+///     map.add_tool(tool);
+/// }
+/// map.save_png("cafes.png").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Overpass {
+    endpoint: String,
+    bbox: Option<(f64, f64, f64, f64)>,
+    timeout: u32,
+    color: Color,
+    radius: f32,
+    width: f32,
+}
+
+/// Builder for [Overpass][Overpass].
+#[derive(Debug, Clone)]
+pub struct OverpassBuilder {
+    endpoint: String,
+    bbox: Option<(f64, f64, f64, f64)>,
+    timeout: u32,
+    color: Color,
+    radius: f32,
+    width: f32,
+}
+
+impl Default for OverpassBuilder {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            bbox: None,
+            timeout: 25,
+            color: Color::new(true, 220, 40, 40, 255),
+            radius: 4.,
+            width: 2.,
+        }
+    }
+}
+
+impl OverpassBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// URL of the Overpass API interpreter.
+    /// Default is the public instance at <https://overpass-api.de/api/interpreter>.
+    pub fn endpoint<I: Into<String>>(mut self, endpoint: I) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Limit all statements of the queries to a bounding box.
+    pub fn bbox(mut self, south: f64, west: f64, north: f64, east: f64) -> Self {
+        self.bbox = Some((south, west, north, east));
+        self
+    }
+
+    /// Time the server may spend on a query, in seconds.
+    /// Default is 25.
+    pub fn timeout(mut self, seconds: u32) -> Self {
+        self.timeout = seconds;
+        self
+    }
+
+    /// Color of the markers and lines.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a red color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Radius of the node markers, in pixels.
+    /// Default is 4.0.
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Width of the way lines, in pixels.
+    /// Default is 2.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Build the client, consuming the builder.
+    pub fn build(self) -> Result<Overpass> {
+        Ok(Overpass {
+            endpoint: self.endpoint,
+            bbox: self.bbox,
+            timeout: self.timeout,
+            color: self.color,
+            radius: self.radius,
+            width: self.width,
+        })
+    }
+}
+
+impl Overpass {
+    /// Overpass QL of a query, asking for JSON output with the geometry of ways.
+    fn query_ql(&self, query: &str) -> String {
+        let bbox = self
+            .bbox
+            .map(|(s, w, n, e)| format!("[bbox:{},{},{},{}]", s, w, n, e))
+            .unwrap_or_default();
+        format!(
+            "[out:json][timeout:{}]{};({});out geom;",
+            self.timeout, bbox, query
+        )
+    }
+
+    /// Run the statements of an Overpass QL query, e.g. `nwr[amenity=cafe];`,
+    /// returning the matching nodes and ways.
+    /// Output settings are added by the client, and should not be part of the query.
+    pub fn fetch(&self, query: &str) -> Result<Vec<OverpassElement>> {
+        let error = |reason: String| service_error(&self.endpoint, reason);
+
+        let request = attohttpc::get(&self.endpoint)
+            .param("data", self.query_ql(query))
+            .timeout(Duration::from_secs(u64::from(self.timeout) + 10));
+        let json = fetch_json(request, &self.endpoint)?;
+
+        // Errors at runtime, e.g. timeouts, are reported in the remark of a valid response.
+        if let Ok(Some(remark)) = json.string("remark") {
+            if remark.contains("error") {
+                return Err(error(remark.trim().to_string()));
+            }
+        }
+
+        let elements = match json.array("elements") {
+            Ok(Some(elements)) => elements,
+            _ => return Err(error("Response without elements".to_string())),
+        };
+        let mut result = Vec::with_capacity(elements.len());
+        for element in elements {
+            result.extend(OverpassElement::from_json(element).map_err(error)?);
+        }
+
+        Ok(result)
+    }
+
+    /// Run a query like [fetch][Overpass::fetch], converting the elements to tools.
+    pub fn fetch_tools(&self, query: &str) -> Result<Vec<Box<dyn Tool>>> {
+        self.fetch(query)?
+            .iter()
+            .filter_map(|element| self.tool(element).transpose())
+            .collect()
+    }
+
+    /// Tool drawing an element, a circle marker for nodes and a line for ways.
+    /// None for ways without coordinates.
+    pub fn tool(&self, element: &OverpassElement) -> Result<Option<Box<dyn Tool>>> {
+        match *element {
+            OverpassElement::Node { lat, lon, .. } => Ok(Some(Box::new(
+                CircleBuilder::new()
+                    .lat_coordinate(lat)
+                    .lon_coordinate(lon)
+                    .radius(self.radius)
+                    .color(self.color.clone())
+                    .build()?,
+            ))),
+            OverpassElement::Way {
+                ref lat_coordinates,
+                ref lon_coordinates,
+                ..
+            } if !lat_coordinates.is_empty() => Ok(Some(Box::new(
+                LineBuilder::new()
+                    .lat_coordinates(lat_coordinates.clone())
+                    .lon_coordinates(lon_coordinates.clone())
+                    .width(self.width)
+                    .color(self.color.clone())
+                    .build()?,
+            ))),
+            OverpassElement::Way { .. } => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+/// A tile of the base layer or an overlay layer, as placed on the map.
+/// Returned by [StaticMap::tile_plan][crate::StaticMap::tile_plan].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TilePlacement {
+    /// Column of the tile, as requested from the tile server.
+    pub x: i32,
+
+    /// Row of the tile, as requested from the tile server.
+    pub y: i32,
+
+    /// Layer of the tile, 0 for the base layer and 1 and up for overlay layers
+    /// in the order they were added.
+    pub layer: usize,
+
+    /// Zoom of the tile grid.
+    pub zoom: u8,
+
+    /// URL of the tile.
+    pub url: String,
+
+    /// Left edge of the tile on the map, in pixels.
+    pub x_px: f64,
+
+    /// Top edge of the tile on the map, in pixels.
+    pub y_px: f64,
+
+    /// Width and height of the tile on the map, in pixels.
+    /// Fetched tiles of a different size are rescaled to this size.
+    pub size: f64,
+}
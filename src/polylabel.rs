@@ -0,0 +1,179 @@
+//! Pole of inaccessibility of polygons, the inside point farthest from the outline,
+//! found with the grid search of Mapbox' polylabel.
+
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+/// Square cell of the search grid.
+struct Cell {
+    x: f64,
+    y: f64,
+    half: f64,
+    /// Signed distance from the center to the outline, negative outside.
+    distance: f64,
+    /// Largest distance possible within the cell.
+    max: f64,
+}
+
+impl Cell {
+    fn new(x: f64, y: f64, half: f64, rings: &[Vec<(f64, f64)>]) -> Self {
+        let distance = signed_distance(x, y, rings);
+        Cell {
+            x,
+            y,
+            half,
+            distance,
+            max: distance + half * std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.max == other.max
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.max.total_cmp(&other.max)
+    }
+}
+
+/// Pole of inaccessibility of a polygon, given as its outer ring followed by any holes,
+/// within `precision` of the true pole, and its distance to the outline.
+/// None if the polygon is empty or has no area.
+pub(crate) fn pole_of_inaccessibility(
+    rings: &[Vec<(f64, f64)>],
+    precision: f64,
+) -> Option<((f64, f64), f64)> {
+    let outer = rings.first()?;
+    let (x_min, y_min, x_max, y_max) = outer.iter().fold(
+        (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+    );
+    let (width, height) = (x_max - x_min, y_max - y_min);
+    let size = width.min(height);
+    if !size.is_finite() || size <= 0. {
+        return None;
+    }
+
+    let mut queue = BinaryHeap::new();
+    let half = size / 2.;
+    let mut x = x_min;
+    while x < x_max {
+        let mut y = y_min;
+        while y < y_max {
+            queue.push(Cell::new(x + half, y + half, half, rings));
+            y += size;
+        }
+        x += size;
+    }
+
+    // The centroid is a good first guess for regular shapes.
+    let mut best = match centroid(outer) {
+        Some((x, y)) => Cell::new(x, y, 0., rings),
+        None => Cell::new(x_min + width / 2., y_min + height / 2., 0., rings),
+    };
+
+    while let Some(cell) = queue.pop() {
+        if cell.distance > best.distance {
+            best = Cell::new(cell.x, cell.y, 0., rings);
+        }
+        // Cells which cannot hold a better point are not split further.
+        if cell.max - best.distance <= precision {
+            continue;
+        }
+
+        let half = cell.half / 2.;
+        for (dx, dy) in [(-1., -1.), (1., -1.), (-1., 1.), (1., 1.)] {
+            queue.push(Cell::new(
+                cell.x + dx * half,
+                cell.y + dy * half,
+                half,
+                rings,
+            ));
+        }
+    }
+
+    if best.distance > 0. {
+        Some(((best.x, best.y), best.distance))
+    } else {
+        None
+    }
+}
+
+/// Whether a point is inside a polygon, by the even-odd rule.
+pub(crate) fn contains(x: f64, y: f64, rings: &[Vec<(f64, f64)>]) -> bool {
+    let mut inside = false;
+    for ring in rings {
+        for (a, b) in edges(ring) {
+            if (a.1 > y) != (b.1 > y) && x < (b.0 - a.0) * (y - a.1) / (b.1 - a.1) + a.0 {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Distance from a point to the outline of a polygon, negative outside the polygon.
+fn signed_distance(x: f64, y: f64, rings: &[Vec<(f64, f64)>]) -> f64 {
+    let distance = rings
+        .iter()
+        .flat_map(|ring| edges(ring))
+        .map(|(a, b)| segment_distance((x, y), a, b))
+        .fold(f64::INFINITY, f64::min);
+
+    if contains(x, y, rings) {
+        distance
+    } else {
+        -distance
+    }
+}
+
+/// Edges of a ring, closing it if needed.
+fn edges(ring: &[(f64, f64)]) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length > 0. {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0., 1.)
+    } else {
+        0.
+    };
+    (p.0 - a.0 - t * dx).hypot(p.1 - a.1 - t * dy)
+}
+
+/// Area centroid of a ring.
+fn centroid(ring: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let (mut x, mut y, mut area) = (0., 0., 0.);
+    for (a, b) in edges(ring) {
+        let cross = a.0 * b.1 - b.0 * a.1;
+        x += (a.0 + b.0) * cross;
+        y += (a.1 + b.1) * cross;
+        area += 3. * cross;
+    }
+
+    if area != 0. {
+        Some((x / area, y / area))
+    } else {
+        None
+    }
+}
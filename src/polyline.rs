@@ -0,0 +1,49 @@
+//! Encoded polyline algorithm, used by routing engines such as OSRM and Valhalla.
+
+use std::str::Bytes;
+
+/// Decode an encoded polyline to (latitude, longitude) coordinates,
+/// with `precision` decimal places, usually 5 or 6.
+/// None if the polyline is malformed.
+pub(crate) fn decode(encoded: &str, precision: u32) -> Option<Vec<(f64, f64)>> {
+    let factor = 10_f64.powi(precision as i32);
+    let mut bytes = encoded.bytes();
+    let mut coordinates = Vec::new();
+    let (mut lat, mut lon) = (0_i64, 0_i64);
+
+    while let Some(d_lat) = value(&mut bytes)? {
+        let d_lon = value(&mut bytes)??;
+        lat += d_lat;
+        lon += d_lon;
+        coordinates.push((lat as f64 / factor, lon as f64 / factor));
+    }
+
+    Some(coordinates)
+}
+
+/// Next zigzag encoded value, in chunks of five bits.
+/// Some(None) at the end of the polyline, None if it is malformed.
+fn value(bytes: &mut Bytes) -> Option<Option<i64>> {
+    let (mut result, mut shift) = (0_i64, 0);
+    loop {
+        let chunk = match bytes.next() {
+            Some(byte) => i64::from(byte) - 63,
+            None if shift == 0 => return Some(None),
+            None => return None,
+        };
+        if !(0..64).contains(&chunk) || shift > 60 {
+            return None;
+        }
+        result |= (chunk & 0x1f) << shift;
+        shift += 5;
+        if chunk < 0x20 {
+            break;
+        }
+    }
+
+    Some(Some(if result & 1 == 1 {
+        !(result >> 1)
+    } else {
+        result >> 1
+    }))
+}
@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use tiny_skia::{IntSize, Pixmap};
+
+/// Pool of pixel buffers reused across renders, for long-running services rendering many maps.
+/// Canvases and decoded tiles are allocated from the pool and returned to it when no longer
+/// needed, instead of allocating and freeing megabytes of pixels per render.
+///
+/// The pool holds at most `max_bytes` of unused buffers, dropping buffers returned beyond that.
+/// Clones share their buffers, so one pool can serve all maps of a service.
+/// Set it on maps with [StaticMapBuilder::pixmap_pool][crate::StaticMapBuilder::pixmap_pool].
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{testing::MockTileFetcher, PixmapPool, StaticMapBuilder};
+///
+/// // Up to 64 MiB of buffers are kept between renders.
+/// let pool = PixmapPool::new(64 << 20);
+/// let base = StaticMapBuilder::new()
+///     .tile_fetcher(MockTileFetcher::new())
+///     .pixmap_pool(pool.clone());
+///
+/// for zoom in 3..6 {
+///     let mut map = base.clone().zoom(zoom).build().unwrap();
+///     let image = map.render().unwrap();
+///     // ... encode or post-process the image ...
+///     map.recycle(image);
+/// }
+///
+/// assert!(pool.pooled_bytes() > 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PixmapPool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+    max_bytes: usize,
+}
+
+impl PixmapPool {
+    /// Create an empty pool holding at most `max_bytes` of unused buffers.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            buffers: Arc::new(Mutex::new(Vec::new())),
+            max_bytes,
+        }
+    }
+
+    /// Transparent pixmap of the given size, reusing a pooled buffer if one is large enough.
+    /// None if the size is zero or too large.
+    pub fn pixmap(&self, width: u32, height: u32) -> Option<Pixmap> {
+        let size = IntSize::from_wh(width, height)?;
+        let len = (width as usize)
+            .checked_mul(height as usize)?
+            .checked_mul(4)?;
+        Pixmap::from_vec(self.buffer(len), size)
+    }
+
+    /// Return the buffer of a pixmap to the pool, once it is no longer needed.
+    pub fn recycle(&self, pixmap: Pixmap) {
+        self.give(pixmap.take());
+    }
+
+    /// Bytes held by the unused buffers of the pool.
+    pub fn pooled_bytes(&self) -> usize {
+        self.lock().iter().map(Vec::capacity).sum()
+    }
+
+    /// Zeroed buffer of `len` bytes, reusing the smallest pooled buffer large enough.
+    pub(crate) fn buffer(&self, len: usize) -> Vec<u8> {
+        let reused = {
+            let mut buffers = self.lock();
+            buffers
+                .iter()
+                .enumerate()
+                .filter(|(_, buffer)| buffer.capacity() >= len)
+                .min_by_key(|(_, buffer)| buffer.capacity())
+                .map(|(index, _)| index)
+                .map(|index| buffers.swap_remove(index))
+        };
+
+        match reused {
+            Some(mut buffer) => {
+                buffer.clear();
+                buffer.resize(len, 0);
+                buffer
+            }
+            None => vec![0; len],
+        }
+    }
+
+    /// Return a buffer to the pool, dropping it if the pool is full.
+    pub(crate) fn give(&self, buffer: Vec<u8>) {
+        let mut buffers = self.lock();
+        let pooled: usize = buffers.iter().map(Vec::capacity).sum();
+        if buffer.capacity() > 0 && pooled + buffer.capacity() <= self.max_bytes {
+            buffers.push(buffer);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Vec<u8>>> {
+        // Buffers are only moved in and out, so a poisoned pool is still consistent.
+        self.buffers.lock().unwrap_or_else(|x| x.into_inner())
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{Result, TrackPoint};
+use std::{fmt, sync::Arc};
+
+/// Trait implemented by types which pre-process the points of lines before they are drawn,
+/// e.g. snapping a GPS track to roads with a map-matching service.
+///
+/// Set a processor on a line with [LineBuilder::processor][crate::tools::LineBuilder::processor],
+/// or process a whole track with [Track::process][crate::Track::process].
+/// Closures taking and returning a `Vec<TrackPoint>` are processors too.
+///
+/// Errors of remote services are best reported as
+/// [Error::ServiceError][crate::Error::ServiceError].
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{tools::LineBuilder, Error, LineProcessor, TrackPoint};
+///
+/// /// Drops points less than 10 seconds after the previous point.
+/// struct Thin;
+///
+/// impl LineProcessor for Thin {
+///     fn process(&self, points: Vec<TrackPoint>) -> Result<Vec<TrackPoint>, Error> {
+///         let mut last = f64::NEG_INFINITY;
+///         Ok(points
+///             .into_iter()
+///             .filter(|point| match point.time {
+///                 Some(time) if time - last < 10. => false,
+///                 Some(time) => {
+///                     last = time;
+///                     true
+///                 }
+///                 None => true,
+///             })
+///             .collect())
+///     }
+/// }
+///
+/// let line = LineBuilder::new()
+///     .coordinates(vec![(52.50, 13.40), (52.51, 13.41), (52.52, 13.42)])
+///     .timestamps(vec![0., 5., 20.])
+///     .processor(Thin)
+///     .build()
+///     .unwrap();
+/// ```
+pub trait LineProcessor: Send + Sync {
+    /// Process the points of a line, in order, returning the points to draw.
+    /// The number of points may change, e.g. when a track is snapped to the roads it follows.
+    /// Default returns the points unchanged.
+    fn process(&self, points: Vec<TrackPoint>) -> Result<Vec<TrackPoint>> {
+        Ok(points)
+    }
+}
+
+impl<F> LineProcessor for F
+where
+    F: Fn(Vec<TrackPoint>) -> Result<Vec<TrackPoint>> + Send + Sync,
+{
+    fn process(&self, points: Vec<TrackPoint>) -> Result<Vec<TrackPoint>> {
+        self(points)
+    }
+}
+
+/// Processor leaving the points of lines unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProcessing;
+
+impl LineProcessor for NoProcessing {}
+
+/// Shared processor, so builders holding one can be cloned.
+#[derive(Clone)]
+pub(crate) struct SharedProcessor(pub(crate) Arc<dyn LineProcessor>);
+
+impl fmt::Debug for SharedProcessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LineProcessor")
+    }
+}
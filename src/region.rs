@@ -0,0 +1,228 @@
+use crate::{coord::LatLon, Bounds, Error, Result};
+use geo::{BooleanOps, Coord, LineString, MultiPolygon, Polygon};
+
+/// Polygon of a [Region][Region], as latitude and longitude coordinates of its outline and holes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionPolygon {
+    /// Latitude coordinates of the outline, ending with the first coordinate.
+    pub lat_coordinates: Vec<f64>,
+    /// Longitude coordinates of the outline, ending with the first coordinate.
+    pub lon_coordinates: Vec<f64>,
+    /// Holes of the polygon, as (latitude coordinates, longitude coordinates).
+    pub holes: Vec<(Vec<f64>, Vec<f64>)>,
+}
+
+/// Area made of polygons, for preparing overlays before rendering:
+/// merging adjacent zones with [union][Region::union], clipping a dataset to the map
+/// with [intersection][Region::intersection] and [from_bounds][Region::from_bounds],
+/// or cutting out areas with [difference][Region::difference].
+///
+/// Coordinates are treated as planar, which is accurate for areas of city or regional scale.
+/// Convert to and from [geo][geo] multipolygons, with x as longitude and y as latitude,
+/// for other operations.
+///
+/// Requires the `geo` feature.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::Region;
+///
+/// let west = Region::polygon(vec![59.0, 59.0, 60.0, 60.0], vec![10.0, 11.0, 11.0, 10.0]).unwrap();
+/// let east = Region::polygon(vec![59.0, 59.0, 60.0, 60.0], vec![11.0, 12.0, 12.0, 11.0]).unwrap();
+///
+/// // Adjacent zones merge into a single polygon.
+/// let merged = west.union(&east);
+/// assert_eq!(merged.polygons().len(), 1);
+///
+/// let clipped = merged.intersection(&Region::rect(59.5, 10.5, 61.0, 11.5));
+/// assert_eq!(clipped.polygons()[0].lat_coordinates.len(), 5);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region(MultiPolygon<f64>);
+
+/// Closed ring of coordinates, with x as longitude and y as latitude.
+fn ring(lat_coordinates: Vec<f64>, lon_coordinates: Vec<f64>) -> Result<LineString<f64>> {
+    if lat_coordinates.len() != lon_coordinates.len() {
+        return Err(Error::BuildError(
+            "Latitude and longitude coordinates must be of equal length.",
+        ));
+    }
+
+    // Rings are closed by geo.
+    Ok(lon_coordinates
+        .into_iter()
+        .zip(lat_coordinates)
+        .map(|(x, y)| Coord { x, y })
+        .collect())
+}
+
+/// Closed ring of coordinates, see [ring][ring].
+fn coordinate_ring<I, C>(coordinates: I) -> LineString<f64>
+where
+    I: IntoIterator<Item = C>,
+    C: Into<LatLon>,
+{
+    coordinates
+        .into_iter()
+        .map(|coordinate| {
+            let LatLon { lat, lon } = coordinate.into();
+            Coord { x: lon, y: lat }
+        })
+        .collect()
+}
+
+/// Latitude and longitude coordinates of a ring.
+fn coordinates(ring: &LineString<f64>) -> (Vec<f64>, Vec<f64>) {
+    ring.coords().map(|coord| (coord.y, coord.x)).unzip()
+}
+
+impl Region {
+    /// Region of a single polygon, from the coordinates of its outline.
+    /// Returns an error if the coordinates are not of equal length.
+    pub fn polygon<I, J>(lat_coordinates: I, lon_coordinates: J) -> Result<Region>
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+        J: IntoIterator,
+        J::Item: Into<f64>,
+    {
+        let outline = ring(
+            lat_coordinates.into_iter().map(Into::into).collect(),
+            lon_coordinates.into_iter().map(Into::into).collect(),
+        )?;
+        Ok(Region(MultiPolygon::new(vec![Polygon::new(
+            outline,
+            Vec::new(),
+        )])))
+    }
+
+    /// Region of a single polygon, from the coordinates of its outline,
+    /// e.g. (latitude, longitude) tuples, see [LatLon][LatLon].
+    pub fn polygon_coordinates<I, C>(coordinates: I) -> Region
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        Region(MultiPolygon::new(vec![Polygon::new(
+            coordinate_ring(coordinates),
+            Vec::new(),
+        )]))
+    }
+
+    /// Add a hole to the last polygon of the region.
+    /// Returns an error if the coordinates are not of equal length.
+    pub fn hole<I, J>(mut self, lat_coordinates: I, lon_coordinates: J) -> Result<Region>
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+        J: IntoIterator,
+        J::Item: Into<f64>,
+    {
+        let hole = ring(
+            lat_coordinates.into_iter().map(Into::into).collect(),
+            lon_coordinates.into_iter().map(Into::into).collect(),
+        )?;
+        if let Some(polygon) = self.0 .0.last_mut() {
+            polygon.interiors_push(hole);
+        }
+        Ok(self)
+    }
+
+    /// Add a hole to the last polygon of the region, from its coordinates.
+    pub fn hole_coordinates<I, C>(mut self, coordinates: I) -> Region
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        if let Some(polygon) = self.0 .0.last_mut() {
+            polygon.interiors_push(coordinate_ring(coordinates));
+        }
+        self
+    }
+
+    /// Region of a bounding box.
+    pub fn rect(south: f64, west: f64, north: f64, east: f64) -> Region {
+        Region(MultiPolygon::new(vec![geo::Rect::new(
+            Coord { x: west, y: south },
+            Coord { x: east, y: north },
+        )
+        .to_polygon()]))
+    }
+
+    /// Region covered by a map, e.g. from [StaticMap::bounds][crate::StaticMap::bounds],
+    /// for clipping datasets to the viewport.
+    pub fn from_bounds(bounds: &Bounds) -> Region {
+        let (lon_min, lat_min, lon_max, lat_max) = bounds.extent();
+        Region::rect(lat_min, lon_min, lat_max, lon_max)
+    }
+
+    /// Union of many regions, e.g. merging adjacent zones of a dataset.
+    /// Faster than merging them one by one.
+    pub fn union_all<I: IntoIterator<Item = Region>>(regions: I) -> Region {
+        let polygons: Vec<Polygon<f64>> = regions.into_iter().flat_map(|x| x.0).collect();
+        Region(geo::unary_union(&polygons))
+    }
+
+    /// Area covered by either region.
+    pub fn union(&self, other: &Region) -> Region {
+        Region(self.0.union(&other.0))
+    }
+
+    /// Area covered by both regions.
+    pub fn intersection(&self, other: &Region) -> Region {
+        Region(self.0.intersection(&other.0))
+    }
+
+    /// Area of this region not covered by the other.
+    pub fn difference(&self, other: &Region) -> Region {
+        Region(self.0.difference(&other.0))
+    }
+
+    /// Area covered by exactly one of the regions.
+    pub fn xor(&self, other: &Region) -> Region {
+        Region(self.0.xor(&other.0))
+    }
+
+    /// Whether the region covers no area, e.g. the intersection of disjoint regions.
+    pub fn is_empty(&self) -> bool {
+        self.0 .0.is_empty()
+    }
+
+    /// Polygons of the region, for drawing.
+    pub fn polygons(&self) -> Vec<RegionPolygon> {
+        self.0
+            .iter()
+            .map(|polygon| {
+                let (lat_coordinates, lon_coordinates) = coordinates(polygon.exterior());
+                RegionPolygon {
+                    lat_coordinates,
+                    lon_coordinates,
+                    holes: polygon.interiors().iter().map(coordinates).collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// The region as a geo multipolygon, with x as longitude and y as latitude.
+    pub fn as_geo(&self) -> &MultiPolygon<f64> {
+        &self.0
+    }
+}
+
+impl From<MultiPolygon<f64>> for Region {
+    fn from(polygons: MultiPolygon<f64>) -> Self {
+        Region(polygons)
+    }
+}
+
+impl From<Polygon<f64>> for Region {
+    fn from(polygon: Polygon<f64>) -> Self {
+        Region(MultiPolygon::new(vec![polygon]))
+    }
+}
+
+impl From<Region> for MultiPolygon<f64> {
+    fn from(region: Region) -> Self {
+        region.0
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{
+    bounds::{Fit, PixelSnapping},
+    coincident::CoincidentPoints,
+    declutter::Declutter,
+    json::{invalid_member, Json},
+    tools::{
+        Alignment, AnnulusBuilder, CircleBuilder, Color, EllipseBuilder, GeodesicLineBuilder,
+        GradientLineBuilder, LineBuilder, MarkerBuilder, MarkerShape, MultiPolygonBuilder,
+        PolygonBuilder, RectBuilder, SectorBuilder, Tool,
+    },
+    Error, Result,
+};
+use tiny_skia::{FillRule, FilterQuality};
+
+/// Version of the scene format written by this release.
+pub(crate) const SCENE_VERSION: f64 = 1.;
+
+/// Conversion of settings to and from their representation in scenes.
+/// See [StaticMap::to_scene][crate::StaticMap::to_scene].
+pub(crate) trait Scene: Sized {
+    fn to_json(&self) -> Json;
+    fn from_json(json: &Json) -> Result<Self>;
+}
+
+/// Member of an object, None if missing.
+pub(crate) fn member<T: Scene>(json: &Json, key: &str) -> Result<Option<T>> {
+    json.get(key).map(T::from_json).transpose()
+}
+
+/// JSON object from members, leaving out unset members.
+pub(crate) fn object(members: Vec<(&str, Json)>) -> Json {
+    Json::Object(
+        members
+            .into_iter()
+            .filter(|(_, value)| *value != Json::Null)
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    )
+}
+
+/// Replace `${name}` placeholders in all strings of a scene template with the variables.
+pub(crate) fn substitute(json: Json, variables: &[(&str, Json)]) -> Result<Json> {
+    let lookup = |name: &str| {
+        variables
+            .iter()
+            .find(|(variable, _)| *variable == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| Error::InvalidScene(format!("Undefined variable \"{}\"", name)))
+    };
+
+    match json {
+        Json::String(text) => {
+            // A placeholder on its own is replaced by the value of any type.
+            if let Some(name) = text.strip_prefix("${").and_then(|x| x.strip_suffix('}')) {
+                if !name.contains('}') {
+                    return lookup(name).cloned();
+                }
+            }
+
+            let mut result = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("${") {
+                let end = match rest[start..].find('}') {
+                    Some(end) => start + end,
+                    None => break,
+                };
+                result.push_str(&rest[..start]);
+                match lookup(&rest[start + 2..end])? {
+                    Json::String(value) => result.push_str(value),
+                    value => result.push_str(&value.to_string()),
+                }
+                rest = &rest[end + 1..];
+            }
+            result.push_str(rest);
+
+            Ok(Json::String(result))
+        }
+        Json::Array(values) => values
+            .into_iter()
+            .map(|x| substitute(x, variables))
+            .collect::<Result<_>>()
+            .map(Json::Array),
+        Json::Object(members) => members
+            .into_iter()
+            .map(|(name, value)| Ok((name, substitute(value, variables)?)))
+            .collect::<Result<_>>()
+            .map(Json::Object),
+        json => Ok(json),
+    }
+}
+
+/// Tool from its representation in a scene, for the built-in tools.
+pub(crate) fn tool_from_json(json: &Json) -> Result<Box<dyn Tool>> {
+    match json.string("type")? {
+        Some("circle") => Ok(Box::new(CircleBuilder::from_json(json)?.build()?)),
+        Some("annulus") => Ok(Box::new(AnnulusBuilder::from_json(json)?.build()?)),
+        Some("ellipse") => Ok(Box::new(EllipseBuilder::from_json(json)?.build()?)),
+        Some("sector") => Ok(Box::new(SectorBuilder::from_json(json)?.build()?)),
+        Some("line") => Ok(Box::new(LineBuilder::from_json(json)?.build()?)),
+        Some("geodesic_line") => Ok(Box::new(GeodesicLineBuilder::from_json(json)?.build()?)),
+        Some("gradient_line") => Ok(Box::new(GradientLineBuilder::from_json(json)?.build()?)),
+        Some("rect") => Ok(Box::new(RectBuilder::from_json(json)?.build()?)),
+        Some("polygon") => Ok(Box::new(PolygonBuilder::from_json(json)?.build()?)),
+        Some("multi_polygon") => Ok(Box::new(MultiPolygonBuilder::from_json(json)?.build()?)),
+        Some("marker") => Ok(Box::new(MarkerBuilder::from_json(json)?.build()?)),
+        Some(kind) => Err(Error::InvalidScene(format!(
+            "Unsupported tool type \"{}\"",
+            kind
+        ))),
+        None => Err(Error::InvalidScene("Tool without a type".to_string())),
+    }
+}
+
+impl Scene for FillRule {
+    fn to_json(&self) -> Json {
+        match *self {
+            FillRule::Winding => "winding".into(),
+            FillRule::EvenOdd => "even_odd".into(),
+        }
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        match *json {
+            Json::String(ref rule) if rule == "winding" => Ok(FillRule::Winding),
+            Json::String(ref rule) if rule == "even_odd" => Ok(FillRule::EvenOdd),
+            _ => Err(invalid_member("fill_rule", "\"winding\" or \"even_odd\"")),
+        }
+    }
+}
+
+impl Scene for FilterQuality {
+    fn to_json(&self) -> Json {
+        match *self {
+            FilterQuality::Nearest => "nearest".into(),
+            FilterQuality::Bilinear => "bilinear".into(),
+            FilterQuality::Bicubic => "bicubic".into(),
+        }
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        match *json {
+            Json::String(ref quality) if quality == "nearest" => Ok(FilterQuality::Nearest),
+            Json::String(ref quality) if quality == "bilinear" => Ok(FilterQuality::Bilinear),
+            Json::String(ref quality) if quality == "bicubic" => Ok(FilterQuality::Bicubic),
+            _ => Err(invalid_member(
+                "tile_quality",
+                "\"nearest\", \"bilinear\" or \"bicubic\"",
+            )),
+        }
+    }
+}
+
+impl Scene for MarkerShape {
+    fn to_json(&self) -> Json {
+        self.name().into()
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        let shapes = [
+            MarkerShape::Pin,
+            MarkerShape::Flag,
+            MarkerShape::Square,
+            MarkerShape::Diamond,
+            MarkerShape::Star,
+            MarkerShape::Cross,
+        ];
+        match *json {
+            Json::String(ref name) => shapes.iter().copied().find(|x| x.name() == name),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            invalid_member(
+                "shape",
+                "\"pin\", \"flag\", \"square\", \"diamond\", \"star\" or \"cross\"",
+            )
+        })
+    }
+}
+
+/// Casing color and width.
+impl Scene for (Color, f32) {
+    fn to_json(&self) -> Json {
+        object(vec![("color", self.0.to_json()), ("width", self.1.into())])
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        let color = member(json, "color")?.ok_or_else(|| invalid_member("casing", "an object"))?;
+        let width = json.number("width")?.unwrap_or(0.);
+        Ok((color, width as f32))
+    }
+}
+
+impl Scene for PixelSnapping {
+    fn to_json(&self) -> Json {
+        match *self {
+            PixelSnapping::Round => "round".into(),
+            PixelSnapping::Crisp => "crisp".into(),
+            PixelSnapping::Subpixel => "subpixel".into(),
+        }
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        match *json {
+            Json::String(ref snapping) if snapping == "round" => Ok(PixelSnapping::Round),
+            Json::String(ref snapping) if snapping == "crisp" => Ok(PixelSnapping::Crisp),
+            Json::String(ref snapping) if snapping == "subpixel" => Ok(PixelSnapping::Subpixel),
+            _ => Err(invalid_member(
+                "pixel_snapping",
+                "\"round\", \"crisp\" or \"subpixel\"",
+            )),
+        }
+    }
+}
+
+impl Scene for Fit {
+    fn to_json(&self) -> Json {
+        match *self {
+            Fit::Letterbox => "letterbox".into(),
+            Fit::Crop => "crop".into(),
+        }
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        match *json {
+            Json::String(ref fit) if fit == "letterbox" => Ok(Fit::Letterbox),
+            Json::String(ref fit) if fit == "crop" => Ok(Fit::Crop),
+            _ => Err(invalid_member("fit", "\"letterbox\" or \"crop\"")),
+        }
+    }
+}
+
+impl Scene for Alignment {
+    fn to_json(&self) -> Json {
+        ALIGNMENTS
+            .iter()
+            .find(|(alignment, _)| alignment == self)
+            .map_or("center", |(_, name)| name)
+            .into()
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        match *json {
+            Json::String(ref name) => ALIGNMENTS
+                .iter()
+                .find(|(_, x)| x == name)
+                .map(|(alignment, _)| *alignment),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            invalid_member(
+                "fit_alignment",
+                "\"top_left\", \"top\", \"top_right\", \"left\", \"center\", \"right\", \
+                 \"bottom_left\", \"bottom\" or \"bottom_right\"",
+            )
+        })
+    }
+}
+
+/// Names of the alignments in scenes.
+const ALIGNMENTS: [(Alignment, &str); 9] = [
+    (Alignment::TopLeft, "top_left"),
+    (Alignment::Top, "top"),
+    (Alignment::TopRight, "top_right"),
+    (Alignment::Left, "left"),
+    (Alignment::Center, "center"),
+    (Alignment::Right, "right"),
+    (Alignment::BottomLeft, "bottom_left"),
+    (Alignment::Bottom, "bottom"),
+    (Alignment::BottomRight, "bottom_right"),
+];
+
+impl Scene for CoincidentPoints {
+    fn to_json(&self) -> Json {
+        match *self {
+            CoincidentPoints::Overlap => object(vec![("type", "overlap".into())]),
+            CoincidentPoints::Spiderfy(distance) => object(vec![
+                ("type", "spiderfy".into()),
+                ("distance", distance.into()),
+            ]),
+            CoincidentPoints::Jitter(distance) => object(vec![
+                ("type", "jitter".into()),
+                ("distance", distance.into()),
+            ]),
+        }
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        let distance = json.number("distance")?.unwrap_or(0.);
+
+        match json.string("type")? {
+            Some("overlap") => Ok(CoincidentPoints::Overlap),
+            Some("spiderfy") => Ok(CoincidentPoints::Spiderfy(distance)),
+            Some("jitter") => Ok(CoincidentPoints::Jitter(distance)),
+            _ => Err(invalid_member(
+                "type",
+                "\"overlap\", \"spiderfy\" or \"jitter\"",
+            )),
+        }
+    }
+}
+
+impl Scene for Declutter {
+    fn to_json(&self) -> Json {
+        match *self {
+            Declutter::Off => object(vec![("type", "off".into())]),
+            Declutter::Hide => object(vec![("type", "hide".into())]),
+            Declutter::Shrink(min_scale) => object(vec![
+                ("type", "shrink".into()),
+                ("min_scale", min_scale.into()),
+            ]),
+        }
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        match json.string("type")? {
+            Some("off") => Ok(Declutter::Off),
+            Some("hide") => Ok(Declutter::Hide),
+            Some("shrink") => Ok(Declutter::Shrink(json.number("min_scale")?.unwrap_or(0.5))),
+            _ => Err(invalid_member("type", "\"off\", \"hide\" or \"shrink\"")),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! Requests to web services answering with JSON, e.g. the Overpass API.
+
+use crate::{fetcher::preview, json::Json, Error, Result};
+use attohttpc::RequestBuilder;
+
+/// Send a request, returning the JSON document of the response.
+/// `url` identifies the service in errors.
+pub(crate) fn fetch_json(request: RequestBuilder, url: &str) -> Result<Json> {
+    let response = request
+        .send()
+        .map_err(|error| service_error(url, error.to_string()))?;
+    let status = response.status();
+    let bytes = response
+        .bytes()
+        .map_err(|error| service_error(url, error.to_string()))?;
+
+    if !status.is_success() {
+        return Err(service_error(
+            url,
+            format!(
+                "Server responded with status {}: \"{}\"",
+                status,
+                preview(&bytes)
+            ),
+        ));
+    }
+
+    std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|text| Json::parse(text).ok())
+        .ok_or_else(|| service_error(url, format!("Expected JSON, got \"{}\"", preview(&bytes))))
+}
+
+pub(crate) fn service_error(url: &str, reason: String) -> Error {
+    Error::ServiceError {
+        url: url.to_string(),
+        reason,
+    }
+}
@@ -0,0 +1,273 @@
+//! Utilities for writing visual regression tests of rendered maps.
+//!
+//! Render maps with [MockTileFetcher][MockTileFetcher] to avoid network access,
+//! and compare the result against stored snapshots with [assert_snapshot][assert_snapshot].
+//! [Harness][Harness] combines both for golden image tests.
+//!
+//! ## Example
+//! ```rust
+//! use staticmap::{testing::{compare, MockTileFetcher}, StaticMapBuilder};
+//!
+//! let mut map = StaticMapBuilder::new()
+//!     .width(300)
+//!     .height(200)
+//!     .zoom(4)
+//!     .lat_center(52.6)
+//!     .lon_center(13.4)
+//!     .tile_fetcher(MockTileFetcher::new())
+//!     .build()
+//!     .unwrap();
+//!
+//! let first = map.render().unwrap();
+//! let second = map.render().unwrap();
+//!
+//! assert!(compare(&first, &second, 0).is_match());
+//! ```
+
+use crate::{
+    fetcher::TileFetcher,
+    map::{StaticMap, StaticMapBuilder},
+    Result,
+};
+use std::path::{Path, PathBuf};
+use tiny_skia::{Color, Paint, Pixmap, Rect, Transform};
+
+/// Tile fetcher returning generated tiles without network access.
+///
+/// Every tile is filled with a shade derived from its URL and outlined with a grid line,
+/// so the same URL always yields the same tile.
+#[derive(Debug, Clone)]
+pub struct MockTileFetcher {
+    tile_size: u32,
+}
+
+impl Default for MockTileFetcher {
+    fn default() -> Self {
+        Self { tile_size: 256 }
+    }
+}
+
+impl MockTileFetcher {
+    /// Create a new fetcher generating 256px tiles.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Size of the generated tiles, in pixels.
+    /// Default is 256.
+    pub fn tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+
+    fn tile(&self, url: &str) -> Result<Vec<u8>> {
+        let mut pixmap =
+            Pixmap::new(self.tile_size, self.tile_size).ok_or(crate::Error::InvalidSize)?;
+
+        // FNV-1a, stable across platforms and releases.
+        let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        let shade = 160 + (hash % 64) as u8;
+
+        pixmap.fill(Color::from_rgba8(shade, shade, shade, 255));
+
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(96, 96, 96, 255);
+        let size = self.tile_size as f32;
+        for rect in [
+            Rect::from_xywh(0., 0., size, 1.),
+            Rect::from_xywh(0., 0., 1., size),
+        ]
+        .iter()
+        .flatten()
+        {
+            pixmap.fill_rect(*rect, &paint, Transform::default(), None);
+        }
+
+        Ok(pixmap.encode_png()?)
+    }
+}
+
+impl TileFetcher for MockTileFetcher {
+    fn fetch(&self, urls: &[String]) -> Vec<Result<Vec<u8>>> {
+        urls.iter().map(|url| self.tile(url)).collect()
+    }
+}
+
+/// Result of comparing two pixmaps with [compare][compare].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difference {
+    /// Whether the pixmaps have the same dimensions.
+    pub same_size: bool,
+
+    /// Number of pixels where any channel differs by more than the tolerance.
+    pub differing_pixels: usize,
+
+    /// Largest difference of a single channel across all pixels.
+    pub max_channel_difference: u8,
+}
+
+impl Difference {
+    /// Whether the pixmaps are equal within the tolerance.
+    pub fn is_match(&self) -> bool {
+        self.same_size && self.differing_pixels == 0
+    }
+}
+
+/// Compare two pixmaps pixel by pixel.
+/// Channels differing by at most `tolerance` are considered equal.
+pub fn compare(actual: &Pixmap, expected: &Pixmap, tolerance: u8) -> Difference {
+    if actual.width() != expected.width() || actual.height() != expected.height() {
+        return Difference {
+            same_size: false,
+            differing_pixels: 0,
+            max_channel_difference: 0,
+        };
+    }
+
+    let mut differing_pixels = 0;
+    let mut max_channel_difference = 0;
+
+    for (a, b) in actual.data().chunks(4).zip(expected.data().chunks(4)) {
+        let difference = a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| (i16::from(*a) - i16::from(*b)).unsigned_abs() as u8)
+            .max()
+            .unwrap_or(0);
+
+        max_channel_difference = max_channel_difference.max(difference);
+        if difference > tolerance {
+            differing_pixels += 1;
+        }
+    }
+
+    Difference {
+        same_size: true,
+        differing_pixels,
+        max_channel_difference,
+    }
+}
+
+/// Compare a pixmap against the PNG snapshot at `path`.
+///
+/// The snapshot is written instead if it does not exist,
+/// or if the `STATICMAP_UPDATE_SNAPSHOTS` environment variable is set.
+pub fn compare_snapshot<P: AsRef<Path>>(
+    actual: &Pixmap,
+    path: P,
+    tolerance: u8,
+) -> Result<Difference> {
+    let path = path.as_ref();
+
+    if !path.exists() || std::env::var_os("STATICMAP_UPDATE_SNAPSHOTS").is_some() {
+        actual.save_png(path)?;
+    }
+
+    let expected = Pixmap::load_png(path)?;
+    Ok(compare(actual, &expected, tolerance))
+}
+
+/// Assert that a pixmap matches the PNG snapshot at `path`, see [compare_snapshot][compare_snapshot].
+///
+/// Panics if the snapshot cannot be read or written, or if the pixmaps differ.
+pub fn assert_snapshot<P: AsRef<Path>>(actual: &Pixmap, path: P, tolerance: u8) {
+    let path = path.as_ref();
+
+    let difference = compare_snapshot(actual, path, tolerance)
+        .unwrap_or_else(|error| panic!("Failed to compare snapshot {}: {}", path.display(), error));
+
+    assert!(
+        difference.is_match(),
+        "Render does not match snapshot {}: {:?}",
+        path.display(),
+        difference
+    );
+}
+
+/// Deterministic rendering of maps for golden image tests, comparing renders against
+/// PNG snapshots stored in a directory.
+///
+/// Maps from [builder][Harness::builder] fetch generated tiles from a
+/// [MockTileFetcher][MockTileFetcher], so renders depend only on the map and its tools.
+/// Text depends on the font, so load fonts from files stored with the tests
+/// rather than from the system.
+///
+/// Snapshots are written if they do not exist, see [compare_snapshot][compare_snapshot].
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{
+///     testing::Harness,
+///     tools::{CircleBuilder, Color},
+/// };
+///
+/// let harness = Harness::new("tests/golden").tolerance(2);
+///
+/// let mut map = harness.builder().width(200).height(200).zoom(5).build().unwrap();
+/// map.add_tool(
+///     CircleBuilder::new()
+///         .lat_coordinate(47.)
+///         .lon_coordinate(10.)
+///         .color(Color::new(true, 0, 0, 255, 255))
+///         .radius(6.)
+///         .build()
+///         .unwrap(),
+/// );
+///
+/// harness.assert_render("circle", &mut map);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Harness {
+    directory: PathBuf,
+    tolerance: u8,
+}
+
+impl Harness {
+    /// Create a new harness with snapshots in the given directory.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Harness {
+            directory: directory.into(),
+            tolerance: 0,
+        }
+    }
+
+    /// Largest difference of a channel considered equal, see [compare][compare].
+    /// Default is 0.
+    pub fn tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Builder for maps fetching tiles from a [MockTileFetcher][MockTileFetcher].
+    pub fn builder(&self) -> StaticMapBuilder {
+        StaticMapBuilder::new().tile_fetcher(MockTileFetcher::new())
+    }
+
+    /// Path of the snapshot with the given name.
+    pub fn snapshot_path(&self, name: &str) -> PathBuf {
+        self.directory.join(format!("{}.png", name))
+    }
+
+    /// Render the map and assert that it matches the snapshot with the given name,
+    /// see [assert_snapshot][assert_snapshot].
+    ///
+    /// Panics if the map cannot be rendered, if the snapshot cannot be read or written,
+    /// or if the images differ.
+    pub fn assert_render(&self, name: &str, map: &mut StaticMap) {
+        let image = map
+            .render()
+            .unwrap_or_else(|error| panic!("Failed to render {}: {}", name, error));
+
+        if let Err(error) = std::fs::create_dir_all(&self.directory) {
+            panic!(
+                "Failed to create snapshot directory {}: {}",
+                self.directory.display(),
+                error
+            );
+        }
+
+        assert_snapshot(&image, self.snapshot_path(name), self.tolerance);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::tools::{
+    CircleBuilder, Color, LineBuilder, MarkerBuilder, PolygonLabelBuilder, RectBuilder,
+    RouteBuilder, ShieldBuilder, StampBuilder, TextStyle, Units,
+};
+
+/// Shared visual identity of maps: default colors, stroke widths, casings and text style.
+///
+/// Tools created from a theme, e.g. with [line][Theme::line], start out with its styles,
+/// and settings made on the returned builder take precedence.
+/// Apply the map background with [StaticMapBuilder::theme][crate::StaticMapBuilder::theme].
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{
+///     tools::{Color, Font, TextStyle},
+///     StaticMapBuilder, Theme,
+/// };
+///
+/// let theme = Theme::new()
+///     .color(Color::new(true, 0, 90, 200, 255))
+///     .width(3.)
+///     .casing(Color::new(true, 255, 255, 255, 255), 1.)
+///     .text(
+///         TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap())
+///             .halo(Color::new(true, 255, 255, 255, 255), 2.),
+///     )
+///     .background(Color::new(true, 240, 240, 235, 255));
+///
+/// let mut map = StaticMapBuilder::new().theme(&theme).build().unwrap();
+///
+/// let line = theme
+///     .line()
+///     .coordinates(vec![(52.5, 13.4), (48.9, 2.3)])
+///     .build()
+///     .unwrap();
+/// map.add_tool(line);
+///
+/// // Explicit styles take precedence over the theme.
+/// let highlight = theme
+///     .circle()
+///     .lat_coordinate(52.5)
+///     .lon_coordinate(13.4)
+///     .radius(6.)
+///     .color(Color::new(true, 220, 40, 40, 255))
+///     .build()
+///     .unwrap();
+/// map.add_tool(highlight);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Theme {
+    color: Option<Color>,
+    width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    text: Option<TextStyle>,
+    units: Option<Units>,
+    pub(crate) background: Option<Color>,
+}
+
+impl Theme {
+    /// Create a new theme, leaving every tool with its own defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Color of lines, circles, rectangles and markers.
+    /// Use [Color][Color] to generate a color instance.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Width of lines and routes, in pixels.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Outline drawn beneath lines, circles, rectangles and routes, and around markers,
+    /// `width` in pixels.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Font, size, color and halo of stamps, shields and polygon labels.
+    pub fn text(mut self, style: TextStyle) -> Self {
+        self.text = Some(style);
+        self
+    }
+
+    /// Units and number format of the measurement labels of circles and rectangles.
+    pub fn units(mut self, units: Units) -> Self {
+        self.units = Some(units);
+        self
+    }
+
+    /// Color drawn beneath the tiles of maps.
+    /// Use [Color][Color] to generate a color instance.
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Builder for a line in the style of the theme.
+    pub fn line(&self) -> LineBuilder {
+        let mut builder = LineBuilder::new();
+        if let Some(ref color) = self.color {
+            builder = builder.color(color.clone());
+        }
+        if let Some(width) = self.width {
+            builder = builder.width(width);
+        }
+        if let Some((ref color, width)) = self.casing {
+            builder = builder.casing(color.clone(), width);
+        }
+        builder
+    }
+
+    /// Builder for a route in the style of the theme.
+    pub fn route(&self) -> RouteBuilder {
+        let mut builder = RouteBuilder::new();
+        if let Some(ref color) = self.color {
+            builder = builder.color(color.clone());
+        }
+        if let Some(width) = self.width {
+            builder = builder.width(width);
+        }
+        if let Some((ref color, width)) = self.casing {
+            builder = builder.casing(color.clone(), width);
+        }
+        builder
+    }
+
+    /// Builder for a circle in the style of the theme.
+    pub fn circle(&self) -> CircleBuilder {
+        let mut builder = CircleBuilder::new();
+        if let Some(ref color) = self.color {
+            builder = builder.color(color.clone());
+        }
+        if let Some((ref color, width)) = self.casing {
+            builder = builder.casing(color.clone(), width);
+        }
+        if let Some(ref units) = self.units {
+            builder = builder.measurement_units(units.clone());
+        }
+        builder
+    }
+
+    /// Builder for a rectangle in the style of the theme.
+    pub fn rect(&self) -> RectBuilder {
+        let mut builder = RectBuilder::new();
+        if let Some(ref color) = self.color {
+            builder = builder.color(color.clone());
+        }
+        if let Some((ref color, width)) = self.casing {
+            builder = builder.casing(color.clone(), width);
+        }
+        if let Some(ref units) = self.units {
+            builder = builder.measurement_units(units.clone());
+        }
+        builder
+    }
+
+    /// Builder for a marker in the style of the theme.
+    pub fn marker(&self) -> MarkerBuilder {
+        let mut builder = MarkerBuilder::new();
+        if let Some(ref color) = self.color {
+            builder = builder.color(color.clone());
+        }
+        if let Some((ref color, width)) = self.casing {
+            builder = builder.border(color.clone(), width);
+        }
+        builder
+    }
+
+    /// Builder for a stamp in the text style of the theme.
+    pub fn stamp(&self) -> StampBuilder {
+        match self.text {
+            Some(ref style) => StampBuilder::new().style(style.clone()),
+            None => StampBuilder::new(),
+        }
+    }
+
+    /// Builder for a shield in the text style of the theme.
+    pub fn shield(&self) -> ShieldBuilder {
+        match self.text {
+            Some(ref style) => ShieldBuilder::new().style(style.clone()),
+            None => ShieldBuilder::new(),
+        }
+    }
+
+    /// Builder for a polygon label in the text style of the theme.
+    pub fn polygon_label(&self) -> PolygonLabelBuilder {
+        match self.text {
+            Some(ref style) => PolygonLabelBuilder::new().style(style.clone()),
+            None => PolygonLabelBuilder::new(),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geojson::Feature,
+    json::Json,
+    lat_to_y, lon_to_x_continuous, m_to_px,
+    scene::{member, object, Scene},
+    tools::{draw_casing, edge_margin, solid_colors, Color, Gradient, Tool},
+    x_to_lon, y_to_lat, Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{FillRule, LineCap, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Annulus tool, a ring between two circles around the same center,
+/// e.g. a delivery zone between 5 and 10 km.
+/// Use [AnnulusBuilder][AnnulusBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{AnnulusBuilder, Color};
+///
+/// let annulus = AnnulusBuilder::new()
+///     .lat_coordinate(52.5)
+///     .lon_coordinate(13.4)
+///     .radii_in_meters(5000., 10000.)
+///     .color(Color::new(true, 0, 128, 255, 100))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Annulus {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    color: Color,
+    inner_radius: f64,
+    outer_radius: f64,
+    radii_in_meters: bool,
+    stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+}
+
+/// Builder for [Annulus][Annulus].
+#[derive(Debug, Clone, Default)]
+pub struct AnnulusBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    color: Color,
+    radii: Option<(f64, f64)>,
+    radii_in_meters: bool,
+    stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+}
+
+impl AnnulusBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// The center of the annulus as a latitude coordinate.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The center of the annulus as a longitude coordinate.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// **Required**.
+    /// Inner and outer radius in pixels.
+    pub fn radii(mut self, inner: f32, outer: f32) -> Self {
+        self.radii = Some((inner.into(), outer.into()));
+        self.radii_in_meters = false;
+        self
+    }
+
+    /// **Required**.
+    /// Inner and outer radius in meters.
+    pub fn radii_in_meters(mut self, inner: f64, outer: f64) -> Self {
+        self.radii = Some((inner, outer));
+        self.radii_in_meters = true;
+        self
+    }
+
+    /// Draw a filled ring (the default).
+    pub fn filled(mut self) -> Self {
+        self.stroke_width = None;
+        self
+    }
+
+    /// Draw only the inner and outer circles.
+    /// Stroke `width` is in pixels, and must be >= 0.0.
+    /// When set to 0, a hairline stroking will be used.
+    pub fn stroke_width(mut self, width: f32) -> Self {
+        self.stroke_width = Some(width);
+        self
+    }
+
+    /// Outline drawn beneath the annulus, extending `width` pixels beyond its edges.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Fill with a [Gradient][Gradient] instead of the color.
+    /// Only applies to filled annuluss.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the inner radius is not smaller than the outer radius.
+    pub fn build(self) -> Result<Annulus> {
+        let (inner_radius, outer_radius) =
+            self.radii.ok_or(Error::BuildError("Radii not supplied."))?;
+
+        if !(0. ..outer_radius).contains(&inner_radius) {
+            return Err(Error::BuildError(
+                "Inner radius must be positive and smaller than the outer radius.",
+            ));
+        }
+
+        Ok(Annulus {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            color: self.color,
+            inner_radius,
+            outer_radius,
+            radii_in_meters: self.radii_in_meters,
+            stroke_width: self.stroke_width,
+            casing: self.casing,
+            gradient: self.gradient,
+        })
+    }
+}
+
+impl Annulus {
+    fn radius_px_at(&self, radius: f64, bounds: &Bounds) -> f64 {
+        if self.radii_in_meters {
+            bounds.m_to_px(radius, self.lat_coordinate)
+        } else {
+            radius
+        }
+    }
+}
+
+impl AnnulusBuilder {
+    /// Builder with the settings of an annulus in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.lat_coordinate = json.number("lat_coordinate")?;
+        builder.lon_coordinate = json.number("lon_coordinate")?;
+        builder.radii = json.pair("radii")?;
+        builder.radii_in_meters = json.boolean("radii_in_meters")?.unwrap_or(false);
+        builder.stroke_width = json.number("stroke_width")?.map(|x| x as f32);
+        builder.casing = member(json, "casing")?;
+        builder.gradient = member(json, "gradient")?;
+        if let Some(color) = member(json, "color")? {
+            builder.color = color;
+        }
+        Ok(builder)
+    }
+}
+
+impl Tool for Annulus {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        let radius = if self.radii_in_meters {
+            // m_to_px assumes 256px tiles.
+            m_to_px(self.outer_radius, self.lat_coordinate, zoom) * tile_size / 256.
+        } else {
+            self.outer_radius
+        };
+
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
+        let y = lat_to_y(self.lat_coordinate, zoom);
+
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x - radius / tile_size, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + radius / tile_size, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + radius / tile_size, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y - radius / tile_size, zoom) - lat;
+
+        (lon_min, lat_min, lon_max, lat_max)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let radius = self.radius_px_at(self.outer_radius, bounds);
+        Some(
+            PixelRect::new(x, y, x, y)
+                .outset(radius + edge_margin(self.stroke_width, &self.casing)),
+        )
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "annulus".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.color.digest(state);
+        self.inner_radius.digest(state);
+        self.outer_radius.digest(state);
+        self.radii_in_meters.digest(state);
+        self.stroke_width.digest(state);
+        self.casing.digest(state);
+        self.gradient.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let unit = if self.radii_in_meters { "m" } else { "px" };
+        let feature = Feature::point(self.lon_coordinate, self.lat_coordinate)
+            .number("inner-radius", self.inner_radius)
+            .number("outer-radius", self.outer_radius)
+            .string("radius-unit", unit)
+            .style(&self.color, self.stroke_width);
+        Some(feature.to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        if !solid_colors(&self.color, &self.casing) {
+            return None;
+        }
+
+        let scene = object(vec![
+            ("type", "annulus".into()),
+            ("lat_coordinate", self.lat_coordinate.into()),
+            ("lon_coordinate", self.lon_coordinate.into()),
+            ("color", self.color.to_json()),
+            ("radii", (self.inner_radius, self.outer_radius).into()),
+            ("radii_in_meters", self.radii_in_meters.into()),
+            ("stroke_width", self.stroke_width.into()),
+            ("casing", self.casing.as_ref().map(Scene::to_json).into()),
+            (
+                "gradient",
+                self.gradient.as_ref().map(Scene::to_json).into(),
+            ),
+        ]);
+        Some(scene.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let mut path_builder = PathBuilder::new();
+
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (x, y) = (x as f32, y as f32);
+
+        path_builder.push_circle(x, y, self.radius_px_at(self.outer_radius, bounds) as f32);
+        if self.inner_radius > 0. {
+            path_builder.push_circle(x, y, self.radius_px_at(self.inner_radius, bounds) as f32);
+        }
+
+        if let Some(path) = path_builder.finish() {
+            if let Some(casing) = self.casing.as_ref() {
+                draw_casing(
+                    &mut pixmap,
+                    &path,
+                    casing,
+                    self.stroke_width,
+                    LineCap::Butt,
+                    transform,
+                );
+            }
+
+            if let Some(width) = self.stroke_width {
+                pixmap.stroke_path(
+                    &path,
+                    &self.color.0,
+                    &Stroke {
+                        width,
+                        ..Default::default()
+                    },
+                    transform,
+                    None,
+                );
+            } else {
+                // The inner circle is a hole with the even-odd rule.
+                let gradient = self.gradient.as_ref().and_then(|x| x.paint(bounds));
+                pixmap.fill_path(
+                    &path,
+                    gradient.as_ref().unwrap_or(&self.color.0),
+                    FillRule::EvenOdd,
+                    transform,
+                    None,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geojson::Feature,
+    tools::{Color, Tool},
+    Error, Result,
+};
+use std::hash::{Hash, Hasher};
+use tiny_skia::{FillRule, Path, PathBuilder, PixmapMut, Rect, Stroke, Transform};
+
+/// Kind of chart drawn by a [Chart][Chart].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChartKind {
+    /// Pie chart, with slices clockwise from the top.
+    Pie,
+    /// Bar chart, with bars from left to right.
+    Bar,
+}
+
+impl Digest for ChartKind {
+    fn digest(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
+}
+
+/// Chart symbol tool, a small pie or bar chart centered on a coordinate,
+/// e.g. to show regional statistics.
+/// Use [ChartBuilder][ChartBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{ChartBuilder, ChartKind, Color};
+///
+/// let chart = ChartBuilder::new()
+///     .lat_coordinate(60.39)
+///     .lon_coordinate(5.32)
+///     .values(vec![45., 30., 25.])
+///     .colors(vec![
+///         Color::new(true, 230, 80, 60, 255),
+///         Color::new(true, 60, 120, 220, 255),
+///         Color::new(true, 90, 180, 90, 255),
+///     ])
+///     .kind(ChartKind::Pie)
+///     .size(30.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Chart {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    values: Vec<f64>,
+    colors: Vec<Color>,
+    kind: ChartKind,
+    size: f32,
+    bar_width: f32,
+    max_value: Option<f64>,
+    border: Option<(Color, f32)>,
+}
+
+/// Builder for [Chart][Chart].
+#[derive(Debug, Clone)]
+pub struct ChartBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    values: Option<Vec<f64>>,
+    colors: Option<Vec<Color>>,
+    kind: ChartKind,
+    size: f32,
+    bar_width: f32,
+    max_value: Option<f64>,
+    border: Option<(Color, f32)>,
+}
+
+impl Default for ChartBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinate: None,
+            lon_coordinate: None,
+            values: None,
+            colors: None,
+            kind: ChartKind::Pie,
+            size: 24.,
+            bar_width: 6.,
+            max_value: None,
+            border: None,
+        }
+    }
+}
+
+impl ChartBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// The center of the chart as a latitude coordinate.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The center of the chart as a longitude coordinate.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// Values of the chart, which must be finite and >= 0.0.
+    pub fn values<I>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        self.values = Some(values.into_iter().collect());
+        self
+    }
+
+    /// **Required**.
+    /// Colors of the slices or bars, one for each value.
+    /// Use [Color][Color] to generate color instances.
+    pub fn colors<I>(mut self, colors: I) -> Self
+    where
+        I: IntoIterator<Item = Color>,
+    {
+        self.colors = Some(colors.into_iter().collect());
+        self
+    }
+
+    /// Kind of chart.
+    /// Default is [ChartKind::Pie][ChartKind::Pie].
+    pub fn kind(mut self, kind: ChartKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Diameter of a pie chart, or height of a bar chart, in pixels.
+    /// Default is 24.0.
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Width of each bar of a bar chart, in pixels.
+    /// Default is 6.0.
+    pub fn bar_width(mut self, width: f32) -> Self {
+        self.bar_width = width;
+        self
+    }
+
+    /// Value reaching the full height of a bar chart.
+    /// Set the same maximum on all charts of a map to make their bars comparable.
+    /// Default is the largest value of the chart.
+    pub fn max_value(mut self, value: f64) -> Self {
+        self.max_value = Some(value);
+        self
+    }
+
+    /// Outline around each slice or bar, `width` in pixels,
+    /// e.g. a thin white line to separate the slices.
+    pub fn border(mut self, color: Color, width: f32) -> Self {
+        self.border = Some((color, width));
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the values are invalid or do not match the colors.
+    pub fn build(self) -> Result<Chart> {
+        let values = self
+            .values
+            .ok_or(Error::BuildError("Values not supplied."))?;
+        let colors = self
+            .colors
+            .ok_or(Error::BuildError("Colors not supplied."))?;
+
+        if values.len() != colors.len() {
+            return Err(Error::BuildError(
+                "Values and colors must be of equal length.",
+            ));
+        }
+        if !values.iter().all(|x| x.is_finite() && *x >= 0.) {
+            return Err(Error::BuildError("Values must be finite and >= 0.0."));
+        }
+
+        Ok(Chart {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            values,
+            colors,
+            kind: self.kind,
+            size: self.size,
+            bar_width: self.bar_width,
+            max_value: self.max_value,
+            border: self.border,
+        })
+    }
+}
+
+impl Chart {
+    /// Width and height of the chart, in pixels.
+    fn dimensions(&self) -> (f32, f32) {
+        match self.kind {
+            ChartKind::Pie => (self.size, self.size),
+            ChartKind::Bar => (self.bar_width * self.values.len() as f32, self.size),
+        }
+    }
+
+    /// Path of each slice or bar, for a chart centered on (x, y).
+    fn paths(&self, x: f32, y: f32) -> Vec<Option<Path>> {
+        match self.kind {
+            ChartKind::Pie => {
+                let total: f64 = self.values.iter().sum();
+                let radius = self.size / 2.;
+                let mut start = 0_f64;
+
+                self.values
+                    .iter()
+                    .map(|value| {
+                        if total <= 0. || *value <= 0. {
+                            return None;
+                        }
+                        let sweep = value / total * std::f64::consts::TAU;
+                        let path = if sweep >= std::f64::consts::TAU - 1e-9 {
+                            PathBuilder::from_circle(x, y, radius)
+                        } else {
+                            wedge(x, y, radius, start as f32, sweep as f32)
+                        };
+                        start += sweep;
+                        path
+                    })
+                    .collect()
+            }
+            ChartKind::Bar => {
+                let max = self
+                    .max_value
+                    .unwrap_or_else(|| self.values.iter().copied().fold(0., f64::max));
+                let (width, height) = self.dimensions();
+                let (left, bottom) = (x - width / 2., y + height / 2.);
+
+                self.values
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| {
+                        if max <= 0. {
+                            return None;
+                        }
+                        let bar_height = (value / max).min(1.) as f32 * height;
+                        Rect::from_xywh(
+                            left + index as f32 * self.bar_width,
+                            bottom - bar_height,
+                            self.bar_width,
+                            bar_height,
+                        )
+                        .map(PathBuilder::from_rect)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Wedge of a circle centered on (x, y), from the angle `start` clockwise from the top,
+/// sweeping `sweep` radians, with the arc approximated by cubic curves.
+fn wedge(x: f32, y: f32, radius: f32, start: f32, sweep: f32) -> Option<Path> {
+    let point = |angle: f32| (x + radius * angle.sin(), y - radius * angle.cos());
+
+    let mut path = PathBuilder::new();
+    path.move_to(x, y);
+    let (x0, y0) = point(start);
+    path.line_to(x0, y0);
+
+    // At most a quarter circle per curve keeps the approximation accurate.
+    let segments = (sweep / std::f32::consts::FRAC_PI_2).ceil().max(1.);
+    let step = sweep / segments;
+    let k = 4. / 3. * (step / 4.).tan() * radius;
+    for i in 0..segments as u32 {
+        let a0 = start + i as f32 * step;
+        let a1 = a0 + step;
+        let (px0, py0) = point(a0);
+        let (px1, py1) = point(a1);
+        path.cubic_to(
+            px0 + k * a0.cos(),
+            py0 + k * a0.sin(),
+            px1 - k * a1.cos(),
+            py1 - k * a1.sin(),
+            px1,
+            py1,
+        );
+    }
+
+    path.close();
+    path.finish()
+}
+
+impl Tool for Chart {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        (
+            self.lon_coordinate,
+            self.lat_coordinate,
+            self.lon_coordinate,
+            self.lat_coordinate,
+        )
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (width, height) = self.dimensions();
+        let (half_width, half_height) = (f64::from(width) / 2., f64::from(height) / 2.);
+        let margin = f64::from(self.border.as_ref().map_or(0., |x| x.1 / 2.));
+
+        Some(
+            PixelRect::new(
+                x - half_width,
+                y - half_height,
+                x + half_width,
+                y + half_height,
+            )
+            .outset(margin),
+        )
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        Some((self.lon_coordinate, self.lat_coordinate))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "chart".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.values.digest(state);
+        self.colors.digest(state);
+        self.kind.digest(state);
+        self.size.digest(state);
+        self.bar_width.digest(state);
+        self.max_value.digest(state);
+        self.border.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        Some(
+            Feature::point(self.lon_coordinate, self.lat_coordinate)
+                .number("total", self.values.iter().sum())
+                .to_string(),
+        )
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let paths = self.paths(x as f32, y as f32);
+
+        for (path, color) in paths.iter().zip(&self.colors) {
+            if let Some(path) = path {
+                pixmap.fill_path(path, &color.0, FillRule::Winding, transform, None);
+            }
+        }
+
+        // Borders are drawn last, so neighbouring slices do not cover them.
+        if let Some((ref color, width)) = self.border {
+            for path in paths.iter().flatten() {
+                pixmap.stroke_path(
+                    path,
+                    &color.0,
+                    &Stroke {
+                        width,
+                        ..Default::default()
+                    },
+                    transform,
+                    None,
+                );
+            }
+        }
+    }
+}
@@ -1,10 +1,217 @@
 use crate::{
-    bounds::Bounds,
-    lat_to_y, lon_to_x, m_to_px,
-    tools::{Color, Tool},
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geojson::Feature,
+    json::Json,
+    lat_to_y, lon_to_x_continuous, m_to_px,
+    scene::{member, object, Scene},
+    tools::{draw_casing, edge_margin, solid_colors, Color, Gradient, TextStyle, Tool, Units},
     x_to_lon, y_to_lat, Error, Result,
 };
-use tiny_skia::{FillRule, PathBuilder, PixmapMut, Stroke, Transform};
+use std::hash::{Hash, Hasher};
+use tiny_skia::{FillRule, LineCap, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Space between the end of a tick and its label, in pixels.
+const LABEL_GAP: f64 = 3.;
+
+/// Labels of [CircleTicks][CircleTicks].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TickLabels {
+    /// Angle in degrees clockwise from north, e.g. "30°".
+    Degrees,
+    /// Hour of a clock face, with 12 at north, e.g. "1" at 30 degrees.
+    /// Ticks between the hours are left unlabeled.
+    Clock,
+}
+
+/// Ticks around a [Circle][Circle] at regular angles clockwise from north,
+/// with optional labels, e.g. compass degrees on an antenna plot or an orienteering map.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::tools::{CircleBuilder, CircleTicks, Color, Font, TextStyle, TickLabels};
+///
+/// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap()).size(11.);
+///
+/// let circle = CircleBuilder::new()
+///     .lat_coordinate(59.91)
+///     .lon_coordinate(10.75)
+///     .radius_in_meters(2000.)
+///     .stroke_width(2.)
+///     .ticks(
+///         CircleTicks::new()
+///             .interval(30.)
+///             .length(8.)
+///             .labels(style, TickLabels::Degrees),
+///     )
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircleTicks {
+    interval: f64,
+    length: f32,
+    width: f32,
+    color: Color,
+    labels: Option<(TextStyle, TickLabels)>,
+}
+
+impl Default for CircleTicks {
+    fn default() -> Self {
+        Self {
+            interval: 30.,
+            length: 6.,
+            width: 1.,
+            color: Color::default(),
+            labels: None,
+        }
+    }
+}
+
+impl CircleTicks {
+    /// Create new ticks with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Angle between ticks, in degrees.
+    /// Default is 30.0.
+    pub fn interval(mut self, degrees: f64) -> Self {
+        self.interval = degrees;
+        self
+    }
+
+    /// Length of the ticks outwards from the circle, in pixels.
+    /// Default is 6.0.
+    pub fn length(mut self, length: f32) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Width of the ticks, in pixels.
+    /// Default is 1.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Draw a label beyond each tick.
+    /// Default is no labels.
+    pub fn labels(mut self, style: TextStyle, labels: TickLabels) -> Self {
+        self.labels = Some((style, labels));
+        self
+    }
+
+    /// Angles of the ticks, in degrees clockwise from north.
+    fn angles(&self) -> impl Iterator<Item = f64> {
+        let interval = self.interval;
+        let count = match interval > 0. {
+            true => (360. / interval - 1e-9).ceil() as usize,
+            false => 0,
+        };
+        (0..count).map(move |index| index as f64 * interval)
+    }
+
+    fn label(&self, angle: f64, labels: TickLabels) -> Option<String> {
+        match labels {
+            TickLabels::Degrees => Some(format!("{}°", (angle * 100.).round() / 100.)),
+            TickLabels::Clock => {
+                let hour = angle / 30.;
+                if (hour - hour.round()).abs() > 1e-9 {
+                    return None;
+                }
+                match hour.round() as i64 % 12 {
+                    0 => Some("12".to_string()),
+                    hour => Some(hour.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Distance the ticks and labels reach beyond the circle, in pixels.
+    fn reach(&self) -> f64 {
+        let labels = match self.labels {
+            Some((ref style, labels)) => self
+                .angles()
+                .filter_map(|angle| self.label(angle, labels))
+                .map(|text| {
+                    let (width, height) = style.measure(&text);
+                    f64::from(width.max(height)) + LABEL_GAP
+                })
+                .fold(0., f64::max),
+            None => 0.,
+        };
+        f64::from(self.length) + labels
+    }
+
+    /// Draw the ticks around a circle with the given center and radius, in pixels,
+    /// `scale` times larger.
+    fn draw(&self, pixmap: &mut PixmapMut, x: f64, y: f64, radius: f64, scale: f32) {
+        let outer = radius + f64::from(self.length);
+
+        let mut path_builder = PathBuilder::new();
+        for angle in self.angles() {
+            let (dx, dy) = (angle.to_radians().sin(), -angle.to_radians().cos());
+            path_builder.move_to((x + dx * radius) as f32, (y + dy * radius) as f32);
+            path_builder.line_to((x + dx * outer) as f32, (y + dy * outer) as f32);
+        }
+        if let Some(path) = path_builder.finish() {
+            pixmap.stroke_path(
+                &path,
+                &self.color.0,
+                &Stroke {
+                    width: self.width,
+                    ..Default::default()
+                },
+                Transform::from_scale(scale, scale),
+                None,
+            );
+        }
+
+        if let Some((ref style, labels)) = self.labels {
+            for angle in self.angles() {
+                let text = match self.label(angle, labels) {
+                    Some(text) => text,
+                    None => continue,
+                };
+
+                // Labels are moved out until their box clears the end of the tick.
+                let (dx, dy) = (angle.to_radians().sin(), -angle.to_radians().cos());
+                let (width, height) = style.measure(&text);
+                let distance = outer
+                    + LABEL_GAP
+                    + (dx.abs() * f64::from(width) + dy.abs() * f64::from(height)) / 2.;
+                style.draw_centered(
+                    pixmap,
+                    &text,
+                    (x + dx * distance) as f32,
+                    (y + dy * distance) as f32,
+                    scale,
+                );
+            }
+        }
+    }
+}
+
+impl Digest for CircleTicks {
+    fn digest(&self, mut state: &mut dyn Hasher) {
+        self.interval.digest(state);
+        self.length.digest(state);
+        self.width.digest(state);
+        self.color.digest(state);
+        if let Some((ref style, labels)) = self.labels {
+            style.digest(state);
+            labels.hash(&mut state);
+        }
+    }
+}
 
 /// Circle tool.
 /// Use [CircleBuilder][CircleBuilder] as an entrypoint.
@@ -28,6 +235,12 @@ pub struct Circle {
     radius: f64,
     radius_in_meters: bool,
     stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+    fill_rule: FillRule,
+    label: Option<TextStyle>,
+    units: Units,
+    ticks: Option<CircleTicks>,
 }
 
 /// Builder for [Circle][Circle].
@@ -39,6 +252,12 @@ pub struct CircleBuilder {
     radius: f64,
     radius_in_meters: bool,
     stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+    fill_rule: FillRule,
+    label: Option<TextStyle>,
+    units: Units,
+    ticks: Option<CircleTicks>,
 }
 
 impl Default for CircleBuilder {
@@ -50,6 +269,12 @@ impl Default for CircleBuilder {
             radius: 1.,
             radius_in_meters: false,
             stroke_width: None,
+            casing: None,
+            gradient: None,
+            fill_rule: FillRule::Winding,
+            label: None,
+            units: Units::default(),
+            ticks: None,
         }
     }
 }
@@ -110,6 +335,46 @@ impl CircleBuilder {
         self
     }
 
+    /// Rule deciding which areas of the path are inside the circle when filling.
+    /// Default is [FillRule::Winding][FillRule::Winding].
+    pub fn fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// Draw a label with the radius of the circle, e.g. "5.0 km", at its center.
+    pub fn measurement_label(mut self, style: TextStyle) -> Self {
+        self.label = Some(style);
+        self
+    }
+
+    /// Units and number format of the measurement label.
+    /// Default is metric units with a decimal point.
+    pub fn measurement_units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Draw ticks, and optionally labels, around the circle at regular angles,
+    /// e.g. every 30 degrees for an antenna plot.
+    pub fn ticks(mut self, ticks: CircleTicks) -> Self {
+        self.ticks = Some(ticks);
+        self
+    }
+
+    /// Outline drawn beneath the circle, extending `width` pixels beyond its edges.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Fill with a [Gradient][Gradient] instead of the color.
+    /// Only applies to filled circles.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
     /// Build the tool, consuming the builder.
     /// Returns an error if the builder is missing required fields.
     pub fn build(self) -> Result<Circle> {
@@ -124,44 +389,170 @@ impl CircleBuilder {
             radius: self.radius,
             radius_in_meters: self.radius_in_meters,
             stroke_width: self.stroke_width,
+            casing: self.casing,
+            gradient: self.gradient,
+            fill_rule: self.fill_rule,
+            label: self.label,
+            units: self.units,
+            ticks: self.ticks,
         })
     }
 }
 
 impl Circle {
-    fn radius_px(&self, zoom: u8) -> f64 {
+    fn radius_px(&self, zoom: u8, tile_size: f64) -> f64 {
+        if self.radius_in_meters {
+            // m_to_px assumes 256px tiles.
+            m_to_px(self.radius, self.lat_coordinate, zoom) * tile_size / 256.
+        } else {
+            self.radius
+        }
+    }
+
+    fn radius_px_at(&self, bounds: &Bounds) -> f64 {
         if self.radius_in_meters {
-            m_to_px(self.radius, self.lat_coordinate, zoom)
+            bounds.m_to_px(self.radius, self.lat_coordinate)
         } else {
             self.radius
         }
     }
+
+    fn radius_m(&self, bounds: &Bounds) -> f64 {
+        if self.radius_in_meters {
+            self.radius
+        } else {
+            self.radius / bounds.m_to_px(1., self.lat_coordinate)
+        }
+    }
+}
+
+impl CircleBuilder {
+    /// Builder with the settings of a circle in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.lat_coordinate = json.number("lat_coordinate")?;
+        builder.lon_coordinate = json.number("lon_coordinate")?;
+        builder.radius = json.number("radius")?.unwrap_or(builder.radius);
+        builder.radius_in_meters = json.boolean("radius_in_meters")?.unwrap_or(false);
+        builder.stroke_width = json.number("stroke_width")?.map(|x| x as f32);
+        builder.casing = member(json, "casing")?;
+        builder.gradient = member(json, "gradient")?;
+        if let Some(color) = member(json, "color")? {
+            builder.color = color;
+        }
+        if let Some(fill_rule) = member(json, "fill_rule")? {
+            builder.fill_rule = fill_rule;
+        }
+        Ok(builder)
+    }
 }
 
 impl Tool for Circle {
     fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
-        let radius: f64 = self.radius_px(zoom);
+        let radius: f64 = self.radius_px(zoom, tile_size);
 
-        let x = lon_to_x(self.lon_coordinate, zoom);
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
         let y = lat_to_y(self.lat_coordinate, zoom);
 
-        let lon_min = x_to_lon(x - radius / tile_size, zoom);
-        let lat_min = y_to_lat(y + radius / tile_size, zoom);
-        let lon_max = x_to_lon(x + radius / tile_size, zoom);
-        let lat_max = y_to_lat(y - radius / tile_size, zoom);
+        // Offsets from the coordinate, so coordinates outside the geographic range,
+        // e.g. on a Cartesian grid, are not wrapped around.
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x - radius / tile_size, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + radius / tile_size, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + radius / tile_size, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y - radius / tile_size, zoom) - lat;
 
         (lon_min, lat_min, lon_max, lat_max)
     }
 
-    fn draw(&self, bounds: &Bounds, mut pixmap: PixmapMut) {
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let radius = self.radius_px_at(bounds);
+        let ticks = self.ticks.as_ref().map_or(0., CircleTicks::reach);
+        Some(
+            PixelRect::new(x, y, x, y)
+                .outset(radius + edge_margin(self.stroke_width, &self.casing).max(ticks)),
+        )
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        Some((self.lon_coordinate, self.lat_coordinate))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "circle".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.color.digest(state);
+        self.radius.digest(state);
+        self.radius_in_meters.digest(state);
+        self.stroke_width.digest(state);
+        self.casing.digest(state);
+        self.gradient.digest(state);
+        (self.fill_rule == FillRule::EvenOdd).digest(state);
+        self.label.digest(state);
+        self.units.digest(state);
+        self.ticks.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let unit = if self.radius_in_meters { "m" } else { "px" };
+        let feature = Feature::point(self.lon_coordinate, self.lat_coordinate)
+            .number("radius", self.radius)
+            .string("radius-unit", unit)
+            .style(&self.color, self.stroke_width);
+        Some(feature.to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        if self.label.is_some() || self.ticks.is_some() || !solid_colors(&self.color, &self.casing)
+        {
+            return None;
+        }
+
+        let scene = object(vec![
+            ("type", "circle".into()),
+            ("lat_coordinate", self.lat_coordinate.into()),
+            ("lon_coordinate", self.lon_coordinate.into()),
+            ("color", self.color.to_json()),
+            ("radius", self.radius.into()),
+            ("radius_in_meters", self.radius_in_meters.into()),
+            ("stroke_width", self.stroke_width.into()),
+            ("casing", self.casing.as_ref().map(Scene::to_json).into()),
+            (
+                "gradient",
+                self.gradient.as_ref().map(Scene::to_json).into(),
+            ),
+            ("fill_rule", self.fill_rule.to_json()),
+        ]);
+        Some(scene.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
         let mut path_builder = PathBuilder::new();
 
-        let x = bounds.x_to_px(lon_to_x(self.lon_coordinate, bounds.zoom));
-        let y = bounds.y_to_px(lat_to_y(self.lat_coordinate, bounds.zoom));
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
 
-        path_builder.push_circle(x as f32, y as f32, self.radius_px(bounds.zoom) as f32);
+        path_builder.push_circle(x as f32, y as f32, self.radius_px_at(bounds) as f32);
 
         if let Some(path) = path_builder.finish() {
+            if let Some(casing) = self.casing.as_ref() {
+                draw_casing(
+                    &mut pixmap,
+                    &path,
+                    casing,
+                    self.stroke_width,
+                    LineCap::Butt,
+                    transform,
+                );
+            }
+
             if let Some(width) = self.stroke_width {
                 pixmap.stroke_path(
                     &path,
@@ -170,18 +561,34 @@ impl Tool for Circle {
                         width,
                         ..Default::default()
                     },
-                    Transform::default(),
+                    transform,
                     None,
                 );
             } else {
+                let gradient = self.gradient.as_ref().and_then(|x| x.paint(bounds));
                 pixmap.fill_path(
                     &path,
-                    &self.color.0,
-                    FillRule::default(),
-                    Transform::default(),
+                    gradient.as_ref().unwrap_or(&self.color.0),
+                    self.fill_rule,
+                    transform,
                     None,
                 );
             }
         }
+
+        if let Some(ticks) = self.ticks.as_ref() {
+            ticks.draw(&mut pixmap, x, y, self.radius_px_at(bounds), scale);
+        }
+
+        if let Some(style) = self.label.as_ref() {
+            let meters = self.radius_m(bounds);
+            style.draw_centered(
+                &mut pixmap,
+                &self.units.format_distance(meters),
+                x as f32,
+                y as f32,
+                scale,
+            );
+        }
     }
 }
@@ -0,0 +1,242 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    tools::{Color, Corner, Tool},
+    Result,
+};
+use std::{
+    f32::consts::PI,
+    hash::{Hash, Hasher},
+};
+use tiny_skia::{FillRule, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Style of a [Compass][Compass].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompassStyle {
+    /// A single arrow pointing north.
+    Arrow,
+    /// A rose with four cardinal points.
+    Rose,
+    /// A rose with four cardinal and four intercardinal points.
+    Star,
+}
+
+/// Compass tool, a north arrow or compass rose placed in a corner of the map.
+/// Use [CompassBuilder][CompassBuilder] as an entrypoint.
+///
+/// The compass does not affect the bounds of the map.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{CompassBuilder, CompassStyle, Corner};
+///
+/// let compass = CompassBuilder::default()
+///     .style(CompassStyle::Rose)
+///     .corner(Corner::TopRight)
+///     .size(48.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Compass {
+    style: CompassStyle,
+    corner: Corner,
+    size: f32,
+    margin: f32,
+    color: Color,
+    secondary_color: Color,
+}
+
+/// Builder for [Compass][Compass].
+#[derive(Debug, Clone)]
+pub struct CompassBuilder {
+    style: CompassStyle,
+    corner: Corner,
+    size: f32,
+    margin: f32,
+    color: Color,
+    secondary_color: Color,
+}
+
+impl Default for CompassBuilder {
+    fn default() -> Self {
+        Self {
+            style: CompassStyle::Arrow,
+            corner: Corner::TopRight,
+            size: 40.,
+            margin: 10.,
+            color: Color::default(),
+            secondary_color: Color::new(true, 255, 255, 255, 255),
+        }
+    }
+}
+
+impl CompassBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Style of the compass.
+    /// Default is [CompassStyle::Arrow][CompassStyle::Arrow].
+    pub fn style(mut self, style: CompassStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Corner of the map to place the compass in.
+    /// Default is [Corner::TopRight][Corner::TopRight].
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// Width and height of the compass, in pixels.
+    /// Default is 40.0.
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Distance between the compass and the edges of the map, in pixels.
+    /// Default is 10.0.
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Color of the outline and the shaded half of each point.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Color of the light half of each point.
+    /// Default is a white color.
+    pub fn secondary_color(mut self, color: Color) -> Self {
+        self.secondary_color = color;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    pub fn build(self) -> Result<Compass> {
+        Ok(Compass {
+            style: self.style,
+            corner: self.corner,
+            size: self.size,
+            margin: self.margin,
+            color: self.color,
+            secondary_color: self.secondary_color,
+        })
+    }
+}
+
+impl Compass {
+    /// Points of the compass as (bearing in radians, length relative to the radius).
+    fn points(&self) -> &'static [(f32, f32)] {
+        match self.style {
+            CompassStyle::Arrow => &[(0., 1.)],
+            CompassStyle::Rose => &[(0., 1.), (0.5 * PI, 1.), (PI, 1.), (1.5 * PI, 1.)],
+            CompassStyle::Star => &[
+                (0., 1.),
+                (0.25 * PI, 0.6),
+                (0.5 * PI, 1.),
+                (0.75 * PI, 0.6),
+                (PI, 1.),
+                (1.25 * PI, 0.6),
+                (1.5 * PI, 1.),
+                (1.75 * PI, 0.6),
+            ],
+        }
+    }
+
+    fn draw_half(
+        &self,
+        pixmap: &mut PixmapMut,
+        points: [(f32, f32); 3],
+        color: &Color,
+        transform: Transform,
+    ) {
+        let mut path_builder = PathBuilder::new();
+        path_builder.move_to(points[0].0, points[0].1);
+        path_builder.line_to(points[1].0, points[1].1);
+        path_builder.line_to(points[2].0, points[2].1);
+        path_builder.close();
+
+        if let Some(path) = path_builder.finish() {
+            pixmap.fill_path(&path, &color.0, FillRule::Winding, transform, None);
+            pixmap.stroke_path(&path, &self.color.0, &Stroke::default(), transform, None);
+        }
+    }
+}
+
+impl Tool for Compass {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        (f64::NAN, f64::NAN, f64::NAN, f64::NAN)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (left, top) = self
+            .corner
+            .anchor(bounds, self.size, self.size, self.margin);
+        let (left, top, size) = (f64::from(left), f64::from(top), f64::from(self.size));
+        Some(PixelRect::new(left, top, left + size, top + size))
+    }
+
+    fn digest(&self, mut state: &mut dyn Hasher) -> bool {
+        "compass".digest(state);
+        self.style.hash(&mut state);
+        self.corner.digest(state);
+        self.size.digest(state);
+        self.margin.digest(state);
+        self.color.digest(state);
+        self.secondary_color.digest(state);
+        true
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let radius = self.size / 2.;
+        let (cx, cy) = self
+            .corner
+            .anchor(bounds, self.size, self.size, self.margin);
+        let (cx, cy) = (cx + radius, cy + radius);
+
+        // Point on the compass at the given bearing, clockwise from north.
+        let at = |bearing: f32, length: f32| {
+            (
+                cx + bearing.sin() * length * radius,
+                cy - bearing.cos() * length * radius,
+            )
+        };
+
+        for &(bearing, length) in self.points() {
+            let tip = at(bearing, length);
+            let (left, right, notch) = match self.style {
+                CompassStyle::Arrow => (
+                    (cx - 0.6 * radius, cy + radius),
+                    (cx + 0.6 * radius, cy + radius),
+                    (cx, cy + 0.5 * radius),
+                ),
+                _ => (
+                    at(bearing - 0.25 * PI, 0.2),
+                    at(bearing + 0.25 * PI, 0.2),
+                    (cx, cy),
+                ),
+            };
+
+            self.draw_half(&mut pixmap, [tip, left, notch], &self.color, transform);
+            self.draw_half(
+                &mut pixmap,
+                [tip, right, notch],
+                &self.secondary_color,
+                transform,
+            );
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{bounds::Bounds, tools::Tool};
+use tiny_skia::PixmapMut;
+
+type DrawFn = Box<dyn Fn(&Bounds, &mut PixmapMut)>;
+type ExtentFn = Box<dyn Fn(u8, f64) -> (f64, f64, f64, f64)>;
+
+/// Tool drawing with a closure, for one-off decorations which do not need
+/// a type implementing [Tool][Tool].
+///
+/// Without an extent, the tool does not affect the bounds of the map.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::CustomTool;
+/// use tiny_skia::{Paint, Rect, Transform};
+///
+/// // Red frame around the map, as a one-off decoration.
+/// let frame = CustomTool::new(|bounds, pixmap| {
+///     let mut paint = Paint::default();
+///     paint.set_color_rgba8(255, 0, 0, 255);
+///
+///     let (width, height) = (bounds.width as f32, bounds.height as f32);
+///     for rect in [
+///         Rect::from_xywh(0., 0., width, 4.),
+///         Rect::from_xywh(0., height - 4., width, 4.),
+///         Rect::from_xywh(0., 0., 4., height),
+///         Rect::from_xywh(width - 4., 0., 4., height),
+///     ] {
+///         if let Some(rect) = rect {
+///             pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+///         }
+///     }
+/// });
+/// ```
+pub struct CustomTool {
+    draw: DrawFn,
+    extent: Option<ExtentFn>,
+}
+
+impl CustomTool {
+    /// Creates a new [CustomTool][CustomTool] drawing with `draw`.
+    pub fn new<F>(draw: F) -> Self
+    where
+        F: Fn(&Bounds, &mut PixmapMut) + 'static,
+    {
+        CustomTool {
+            draw: Box::new(draw),
+            extent: None,
+        }
+    }
+
+    /// Closure returning the geographic extent of what is drawn,
+    /// see [Tool::extent][Tool::extent], so the map is fitted to it.
+    pub fn extent<F>(mut self, extent: F) -> Self
+    where
+        F: Fn(u8, f64) -> (f64, f64, f64, f64) + 'static,
+    {
+        self.extent = Some(Box::new(extent));
+        self
+    }
+}
+
+impl std::fmt::Debug for CustomTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("CustomTool")
+            .field("extent", &self.extent.is_some())
+            .finish()
+    }
+}
+
+impl Tool for CustomTool {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        match self.extent {
+            Some(ref extent) => extent(zoom, tile_size),
+            None => (f64::NAN, f64::NAN, f64::NAN, f64::NAN),
+        }
+    }
+
+    fn draw(&self, bounds: &Bounds, mut pixmap: PixmapMut) {
+        (self.draw)(bounds, &mut pixmap)
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geojson::Feature,
+    json::Json,
+    lat_to_y, lon_to_x_continuous, m_to_px,
+    scene::{member, object, Scene},
+    tools::{draw_casing, edge_margin, solid_colors, Color, Gradient, Tool},
+    x_to_lon, y_to_lat, Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{FillRule, LineCap, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Ellipse tool, with separate horizontal and vertical radii and an optional rotation,
+/// e.g. the uncertainty area of a position or the footprint of an antenna.
+/// Use [EllipseBuilder][EllipseBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, EllipseBuilder};
+///
+/// let ellipse = EllipseBuilder::new()
+///     .lat_coordinate(59.91)
+///     .lon_coordinate(10.75)
+///     .radii_in_meters(800., 300.)
+///     .rotation(30.)
+///     .color(Color::new(true, 255, 120, 0, 100))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ellipse {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    color: Color,
+    x_radius: f64,
+    y_radius: f64,
+    radii_in_meters: bool,
+    rotation: f64,
+    stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+}
+
+/// Builder for [Ellipse][Ellipse].
+#[derive(Debug, Clone, Default)]
+pub struct EllipseBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    color: Color,
+    radii: Option<(f64, f64)>,
+    radii_in_meters: bool,
+    rotation: f64,
+    stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+}
+
+impl EllipseBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// The center of the ellipse as a latitude coordinate.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The center of the ellipse as a longitude coordinate.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// **Required**.
+    /// Horizontal and vertical radius in pixels, before rotation.
+    pub fn radii(mut self, x: f32, y: f32) -> Self {
+        self.radii = Some((x.into(), y.into()));
+        self.radii_in_meters = false;
+        self
+    }
+
+    /// **Required**.
+    /// Horizontal and vertical radius in meters, before rotation.
+    pub fn radii_in_meters(mut self, x: f64, y: f64) -> Self {
+        self.radii = Some((x, y));
+        self.radii_in_meters = true;
+        self
+    }
+
+    /// Rotation of the ellipse around its center, in degrees clockwise.
+    /// Default is 0.0, with the horizontal radius pointing east.
+    pub fn rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    /// Draw a filled ellipse (the default).
+    pub fn filled(mut self) -> Self {
+        self.stroke_width = None;
+        self
+    }
+
+    /// Draw an open ellipse.
+    /// Stroke `width` is in pixels, and must be >= 0.0.
+    /// When set to 0, a hairline stroking will be used.
+    pub fn stroke_width(mut self, width: f32) -> Self {
+        self.stroke_width = Some(width);
+        self
+    }
+
+    /// Outline drawn beneath the ellipse, extending `width` pixels beyond its edges.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Fill with a [Gradient][Gradient] instead of the color.
+    /// Only applies to filled ellipses.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the radii are not positive.
+    pub fn build(self) -> Result<Ellipse> {
+        let (x_radius, y_radius) = self.radii.ok_or(Error::BuildError("Radii not supplied."))?;
+
+        if ![x_radius, y_radius].iter().all(|&radius| radius > 0.) {
+            return Err(Error::BuildError("Radii must be positive."));
+        }
+
+        Ok(Ellipse {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            color: self.color,
+            x_radius,
+            y_radius,
+            radii_in_meters: self.radii_in_meters,
+            rotation: self.rotation,
+            stroke_width: self.stroke_width,
+            casing: self.casing,
+            gradient: self.gradient,
+        })
+    }
+}
+
+impl Ellipse {
+    /// Half the width and height of the rotated ellipse, given the pixels per unit of the radii.
+    fn half_size(&self, scale: f64) -> (f64, f64) {
+        let (x_radius, y_radius) = (self.x_radius * scale, self.y_radius * scale);
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        (
+            (x_radius * x_radius * cos * cos + y_radius * y_radius * sin * sin).sqrt(),
+            (x_radius * x_radius * sin * sin + y_radius * y_radius * cos * cos).sqrt(),
+        )
+    }
+
+    fn scale_at(&self, bounds: &Bounds) -> f64 {
+        if self.radii_in_meters {
+            bounds.m_to_px(1., self.lat_coordinate)
+        } else {
+            1.
+        }
+    }
+}
+
+impl EllipseBuilder {
+    /// Builder with the settings of an ellipse in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.lat_coordinate = json.number("lat_coordinate")?;
+        builder.lon_coordinate = json.number("lon_coordinate")?;
+        builder.radii = json.pair("radii")?;
+        builder.radii_in_meters = json.boolean("radii_in_meters")?.unwrap_or(false);
+        builder.rotation = json.number("rotation")?.unwrap_or(0.);
+        builder.stroke_width = json.number("stroke_width")?.map(|x| x as f32);
+        builder.casing = member(json, "casing")?;
+        builder.gradient = member(json, "gradient")?;
+        if let Some(color) = member(json, "color")? {
+            builder.color = color;
+        }
+        Ok(builder)
+    }
+}
+
+impl Tool for Ellipse {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        let scale = if self.radii_in_meters {
+            // m_to_px assumes 256px tiles.
+            m_to_px(1., self.lat_coordinate, zoom) * tile_size / 256.
+        } else {
+            1.
+        };
+        let (half_width, half_height) = self.half_size(scale);
+
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
+        let y = lat_to_y(self.lat_coordinate, zoom);
+
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x - half_width / tile_size, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + half_height / tile_size, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + half_width / tile_size, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y - half_height / tile_size, zoom) - lat;
+
+        (lon_min, lat_min, lon_max, lat_max)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (half_width, half_height) = self.half_size(self.scale_at(bounds));
+        Some(
+            PixelRect::new(
+                x - half_width,
+                y - half_height,
+                x + half_width,
+                y + half_height,
+            )
+            .outset(edge_margin(self.stroke_width, &self.casing)),
+        )
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        Some((self.lon_coordinate, self.lat_coordinate))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "ellipse".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.color.digest(state);
+        self.x_radius.digest(state);
+        self.y_radius.digest(state);
+        self.radii_in_meters.digest(state);
+        self.rotation.digest(state);
+        self.stroke_width.digest(state);
+        self.casing.digest(state);
+        self.gradient.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let unit = if self.radii_in_meters { "m" } else { "px" };
+        let feature = Feature::point(self.lon_coordinate, self.lat_coordinate)
+            .number("x-radius", self.x_radius)
+            .number("y-radius", self.y_radius)
+            .string("radius-unit", unit)
+            .number("rotation", self.rotation)
+            .style(&self.color, self.stroke_width);
+        Some(feature.to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        if !solid_colors(&self.color, &self.casing) {
+            return None;
+        }
+
+        let scene = object(vec![
+            ("type", "ellipse".into()),
+            ("lat_coordinate", self.lat_coordinate.into()),
+            ("lon_coordinate", self.lon_coordinate.into()),
+            ("color", self.color.to_json()),
+            ("radii", (self.x_radius, self.y_radius).into()),
+            ("radii_in_meters", self.radii_in_meters.into()),
+            ("rotation", self.rotation.into()),
+            ("stroke_width", self.stroke_width.into()),
+            ("casing", self.casing.as_ref().map(Scene::to_json).into()),
+            (
+                "gradient",
+                self.gradient.as_ref().map(Scene::to_json).into(),
+            ),
+        ]);
+        Some(scene.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, pixel_scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(pixel_scale, pixel_scale);
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let scale = self.scale_at(bounds);
+        let (x_radius, y_radius) = (self.x_radius * scale, self.y_radius * scale);
+
+        let oval = tiny_skia::Rect::from_ltrb(
+            (x - x_radius) as f32,
+            (y - y_radius) as f32,
+            (x + x_radius) as f32,
+            (y + y_radius) as f32,
+        );
+        let path = oval.and_then(PathBuilder::from_oval).and_then(|path| {
+            path.transform(Transform::from_rotate_at(
+                self.rotation as f32,
+                x as f32,
+                y as f32,
+            ))
+        });
+
+        if let Some(path) = path {
+            if let Some(casing) = self.casing.as_ref() {
+                draw_casing(
+                    &mut pixmap,
+                    &path,
+                    casing,
+                    self.stroke_width,
+                    LineCap::Butt,
+                    transform,
+                );
+            }
+
+            if let Some(width) = self.stroke_width {
+                pixmap.stroke_path(
+                    &path,
+                    &self.color.0,
+                    &Stroke {
+                        width,
+                        ..Default::default()
+                    },
+                    transform,
+                    None,
+                );
+            } else {
+                let gradient = self.gradient.as_ref().and_then(|x| x.paint(bounds));
+                pixmap.fill_path(
+                    &path,
+                    gradient.as_ref().unwrap_or(&self.color.0),
+                    FillRule::Winding,
+                    transform,
+                    None,
+                );
+            }
+        }
+    }
+}
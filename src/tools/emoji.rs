@@ -0,0 +1,230 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geojson::Feature,
+    lat_to_y, lon_to_x_continuous,
+    tools::{Color, Font, Tool},
+    x_to_lon, y_to_lat, Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{FilterQuality, Pixmap, PixmapMut, PixmapPaint, Transform};
+
+/// Emoji tool, an emoji such as 📍 or 🏁 drawn as a marker at a coordinate.
+/// Use [EmojiBuilder][EmojiBuilder] as an entrypoint.
+///
+/// Emoji are drawn in color with fonts embedding color images, such as Noto Color Emoji
+/// or Apple Color Emoji, and in a single color with outline fonts.
+/// Sequences joining several emoji, e.g. flags of countries, are drawn as their first emoji.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::tools::{EmojiBuilder, Font};
+///
+/// let font = Font::from_path("NotoColorEmoji.ttf").unwrap();
+///
+/// // The tip of the pin at the coordinate.
+/// let pin = EmojiBuilder::new()
+///     .lat_coordinate(52.5)
+///     .lon_coordinate(13.4)
+///     .emoji("📍")
+///     .font(font)
+///     .size(32.)
+///     .anchor(0.3, 1.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Emoji {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    emoji: String,
+    image: Pixmap,
+    anchor: (f32, f32),
+}
+
+/// Builder for [Emoji][Emoji].
+#[derive(Debug, Clone)]
+pub struct EmojiBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    emoji: Option<String>,
+    font: Option<Font>,
+    size: f32,
+    color: Color,
+    anchor: (f32, f32),
+}
+
+impl Default for EmojiBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinate: None,
+            lon_coordinate: None,
+            emoji: None,
+            font: None,
+            size: 24.,
+            color: Color::default(),
+            anchor: (0.5, 0.5),
+        }
+    }
+}
+
+impl EmojiBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// Latitude coordinate of the emoji.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// Longitude coordinate of the emoji.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The emoji, e.g. "🏁".
+    pub fn emoji<I: Into<String>>(mut self, emoji: I) -> Self {
+        self.emoji = Some(emoji.into());
+        self
+    }
+
+    /// **Required**.
+    /// Font containing the emoji, preferably a color emoji font.
+    pub fn font(mut self, font: Font) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Font size of the emoji, in pixels.
+    /// Default is 24.0.
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Color of emoji from fonts without color images.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Point of the emoji placed at the coordinate, as fractions of its width and height
+    /// from the top left, e.g. (0.5, 1.0) for the middle of the bottom edge.
+    /// Default is (0.5, 0.5), the center.
+    pub fn anchor(mut self, x: f32, y: f32) -> Self {
+        self.anchor = (x, y);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the font does not contain the emoji.
+    pub fn build(self) -> Result<Emoji> {
+        let emoji = self.emoji.ok_or(Error::BuildError("Emoji not supplied."))?;
+        let font = self.font.ok_or(Error::BuildError("Font not supplied."))?;
+        let image = font
+            .render_emoji(&emoji, self.size, &self.color)
+            .ok_or(Error::BuildError("Emoji not found in font."))?;
+
+        Ok(Emoji {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            emoji,
+            image,
+            anchor: self.anchor,
+        })
+    }
+}
+
+impl Emoji {
+    /// Rectangle covered by the emoji, relative to its coordinate.
+    fn rect(&self) -> (f64, f64, f64, f64) {
+        let (width, height) = (
+            f64::from(self.image.width()),
+            f64::from(self.image.height()),
+        );
+        let left = -width * f64::from(self.anchor.0);
+        let top = -height * f64::from(self.anchor.1);
+        (left, top, left + width, top + height)
+    }
+}
+
+impl Tool for Emoji {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        let (left, top, right, bottom) = self.rect();
+
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
+        let y = lat_to_y(self.lat_coordinate, zoom);
+
+        // Offsets from the coordinate, so coordinates outside the geographic range,
+        // e.g. on a Cartesian grid, are not wrapped around.
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x + left / tile_size, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + bottom / tile_size, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + right / tile_size, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y + top / tile_size, zoom) - lat;
+
+        (lon_min, lat_min, lon_max, lat_max)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (left, top, right, bottom) = self.rect();
+        Some(PixelRect::new(x + left, y + top, x + right, y + bottom))
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        Some((self.lon_coordinate, self.lat_coordinate))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "emoji".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.image.digest(state);
+        self.anchor.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let feature = Feature::point(self.lon_coordinate, self.lat_coordinate)
+            .string("marker-symbol", &self.emoji);
+        Some(feature.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (left, top, _, _) = self.rect();
+
+        // Whole pixels keep the emoji sharp, and larger scales resample the rendered image.
+        let (x, y) = ((x + left).round() as f32, (y + top).round() as f32);
+        pixmap.draw_pixmap(
+            0,
+            0,
+            self.image.as_ref(),
+            &PixmapPaint {
+                quality: FilterQuality::Bicubic,
+                ..Default::default()
+            },
+            Transform::from_translate(x, y).post_scale(scale, scale),
+            None,
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geodesy::{distance, great_circle},
+    tools::{Circle, CircleBuilder, Color, Line, LineBuilder, TextStyle, Tool},
+    Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::PixmapMut;
+
+/// Greatest distance between the points of the legs, in meters.
+const SPACING: f64 = 20_000.;
+
+/// Flight route tool, drawing great circle legs between airports or other waypoints,
+/// with a marker and a label, e.g. the airport code, at each waypoint.
+/// The map is fitted to the curved legs like for any other line,
+/// and legs crossing the antimeridian continue across it instead of wrapping around.
+/// Use [FlightRouteBuilder][FlightRouteBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{
+///     tools::{Color, FlightRouteBuilder, Font, TextStyle},
+///     StaticMapBuilder,
+/// };
+///
+/// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap())
+///     .halo(Color::new(true, 255, 255, 255, 255), 2.);
+///
+/// let route = FlightRouteBuilder::new()
+///     .waypoint("OSL", 60.197, 11.100)
+///     .waypoint("KEF", 63.985, -22.605)
+///     .waypoint("JFK", 40.641, -73.778)
+///     .labels(style)
+///     .build()
+///     .unwrap();
+///
+/// let mut map = StaticMapBuilder::new().padding((30, 30)).build().unwrap();
+/// map.add_tool(route);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FlightRoute {
+    line: Line,
+    markers: Vec<Circle>,
+    /// Label of each waypoint, and its coordinate as (latitude, longitude).
+    labels: Vec<(String, (f64, f64))>,
+    style: Option<TextStyle>,
+    marker_radius: f32,
+    distance: f64,
+}
+
+/// Builder for [FlightRoute][FlightRoute].
+#[derive(Debug, Clone)]
+pub struct FlightRouteBuilder {
+    waypoints: Vec<(String, f64, f64)>,
+    color: Color,
+    width: f32,
+    marker_color: Color,
+    marker_radius: f32,
+    style: Option<TextStyle>,
+}
+
+impl Default for FlightRouteBuilder {
+    fn default() -> Self {
+        Self {
+            waypoints: Vec::new(),
+            color: Color::new(true, 200, 30, 60, 255),
+            width: 2.,
+            marker_color: Color::new(true, 255, 255, 255, 255),
+            marker_radius: 4.,
+            style: None,
+        }
+    }
+}
+
+impl FlightRouteBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**, at least two.
+    /// Add a waypoint, e.g. an airport, with its label, e.g. the IATA code "OSL",
+    /// and its latitude and longitude.
+    pub fn waypoint<I: Into<String>>(mut self, label: I, lat: f64, lon: f64) -> Self {
+        self.waypoints.push((label.into(), lat, lon));
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a crimson color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Width of the legs, in pixels.
+    /// Default is 2.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Fill color of the waypoint markers, outlined with the route color.
+    /// Default is a white color.
+    pub fn marker_color(mut self, color: Color) -> Self {
+        self.marker_color = color;
+        self
+    }
+
+    /// Radius of the waypoint markers, in pixels.
+    /// Default is 4.0.
+    pub fn marker_radius(mut self, radius: f32) -> Self {
+        self.marker_radius = radius;
+        self
+    }
+
+    /// Label each waypoint above its marker in the given style.
+    pub fn labels(mut self, style: TextStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if there are fewer than two waypoints.
+    pub fn build(self) -> Result<FlightRoute> {
+        if self.waypoints.len() < 2 {
+            return Err(Error::BuildError(
+                "At least two waypoints must be supplied.",
+            ));
+        }
+
+        // Longitudes continue across the antimeridian, following the shorter way
+        // between consecutive waypoints.
+        let mut waypoints: Vec<(String, (f64, f64))> = Vec::new();
+        for (label, lat, lon) in self.waypoints.iter() {
+            let lon = match waypoints.last() {
+                Some((_, (_, previous))) => lon + ((previous - lon) / 360.).round() * 360.,
+                None => *lon,
+            };
+            waypoints.push((label.clone(), (*lat, lon)));
+        }
+
+        let mut coordinates = vec![waypoints[0].1];
+        let mut total = 0.;
+        for leg in waypoints.windows(2) {
+            let ((lat1, lon1), (lat2, lon2)) = (leg[0].1, leg[1].1);
+            total += distance(lat1, lon1, lat2, lon2);
+            coordinates.extend(
+                great_circle(lat1, lon1, lat2, lon2, SPACING)
+                    .into_iter()
+                    .skip(1),
+            );
+        }
+
+        let line = LineBuilder::new()
+            .coordinates(coordinates)
+            .color(self.color.clone())
+            .width(self.width)
+            .build()?;
+
+        let markers = waypoints
+            .iter()
+            .map(|(_, (lat, lon))| {
+                CircleBuilder::new()
+                    .lat_coordinate(*lat)
+                    .lon_coordinate(*lon)
+                    .radius(self.marker_radius)
+                    .color(self.marker_color.clone())
+                    .casing(self.color.clone(), 1.5)
+                    .build()
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(FlightRoute {
+            line,
+            markers,
+            labels: waypoints,
+            style: self.style,
+            marker_radius: self.marker_radius,
+            distance: total,
+        })
+    }
+}
+
+impl FlightRoute {
+    /// Great circle distance along all legs, in meters.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Rectangle of each label, with its text, in pixels.
+    fn label_rects<'a>(
+        &'a self,
+        style: &'a TextStyle,
+        bounds: &'a Bounds,
+    ) -> impl Iterator<Item = (&'a str, PixelRect)> {
+        self.labels.iter().map(move |(label, (lat, lon))| {
+            let (x, y) = bounds.lon_lat_to_px(*lon, *lat);
+            let (width, height) = style.measure(label);
+            let (width, height) = (f64::from(width), f64::from(height));
+            // Above the marker and its casing.
+            let bottom = y - f64::from(self.marker_radius) - 1.5;
+            let rect = PixelRect::new(x - width / 2., bottom - height, x + width / 2., bottom);
+            (label.as_str(), rect)
+        })
+    }
+}
+
+impl Tool for FlightRoute {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        self.line.extent(zoom, tile_size)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let labels = self
+            .style
+            .iter()
+            .flat_map(|style| self.label_rects(style, bounds).map(|(_, rect)| rect));
+
+        self.markers
+            .iter()
+            .filter_map(|marker| marker.pixel_extent(bounds))
+            .chain(self.line.pixel_extent(bounds))
+            .chain(labels)
+            .reduce(|a, b| {
+                PixelRect::new(
+                    a.x_min.min(b.x_min),
+                    a.y_min.min(b.y_min),
+                    a.x_max.max(b.x_max),
+                    a.y_max.max(b.y_max),
+                )
+            })
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "flight_route".digest(state);
+        (self.markers.len() as u32).digest(state);
+        for (label, coordinate) in self.labels.iter() {
+            label.digest(state);
+            coordinate.digest(state);
+        }
+        self.style.digest(state);
+        self.marker_radius.digest(state);
+        self.line.digest(state) && self.markers.iter().all(|marker| marker.digest(state))
+    }
+
+    fn geojson(&self) -> Option<String> {
+        self.line.geojson()
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let (width, height) = (pixmap.width(), pixmap.height());
+        let tools = std::iter::once(&self.line as &dyn Tool)
+            .chain(self.markers.iter().map(|marker| marker as &dyn Tool));
+        for tool in tools {
+            // Each tool takes the pixmap by value, so it is borrowed anew for each.
+            if let Some(target) = PixmapMut::from_bytes(pixmap.data_mut(), width, height) {
+                tool.draw_at_scale(bounds, scale, target);
+            }
+        }
+
+        if let Some(ref style) = self.style {
+            for (label, rect) in self.label_rects(style, bounds) {
+                style.draw_centered(
+                    &mut pixmap,
+                    label,
+                    ((rect.x_min + rect.x_max) / 2.) as f32,
+                    ((rect.y_min + rect.y_max) / 2.) as f32,
+                    scale,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    coord::{unzip, LatLon},
+    digest::Digest,
+    geodesy::great_circle,
+    json::Json,
+    scene::{member, object, Scene},
+    tools::{solid_colors, Arrowheads, Color, Line, LineBuilder, Tool},
+    Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::PixmapMut;
+
+/// Geodesic line tool, following the great circle between each pair of coordinates,
+/// e.g. flight or shipping routes, which are curves on the map rather than
+/// the straight chords a [Line][Line] draws.
+/// Use [GeodesicLineBuilder][GeodesicLineBuilder] as an entrypoint.
+///
+/// Each segment is densified along the great circle before it is projected,
+/// and segments crossing the antimeridian continue across it instead of wrapping around.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, GeodesicLineBuilder};
+///
+/// // Oslo to Tokyo, curving north over Siberia.
+/// let route = GeodesicLineBuilder::new()
+///     .coordinates(vec![(59.91, 10.75), (35.68, 139.69)])
+///     .color(Color::new(true, 20, 90, 200, 255))
+///     .width(2.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct GeodesicLine {
+    lat_coordinates: Vec<f64>,
+    lon_coordinates: Vec<f64>,
+    color: Color,
+    width: f32,
+    casing: Option<(Color, f32)>,
+    arrowheads: Option<Arrowheads>,
+    spacing: f64,
+    line: Line,
+}
+
+/// Builder for [GeodesicLine][GeodesicLine].
+#[derive(Debug, Clone)]
+pub struct GeodesicLineBuilder {
+    lat_coordinates: Option<Vec<f64>>,
+    lon_coordinates: Option<Vec<f64>>,
+    color: Color,
+    width: f32,
+    casing: Option<(Color, f32)>,
+    arrowheads: Option<Arrowheads>,
+    spacing: f64,
+}
+
+impl Default for GeodesicLineBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinates: None,
+            lon_coordinates: None,
+            color: Color::default(),
+            width: 1.,
+            casing: None,
+            arrowheads: None,
+            spacing: 20_000.,
+        }
+    }
+}
+
+impl GeodesicLineBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// Takes a collection of latitude coordinates, e.g. `f64` or `f32`.
+    pub fn lat_coordinates<I>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.lat_coordinates = Some(coordinates.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// **Required**.
+    /// Takes a collection of longitude coordinates, e.g. `f64` or `f32`.
+    pub fn lon_coordinates<I>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.lon_coordinates = Some(coordinates.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// **Required**, unless latitude and longitude coordinates are supplied separately.
+    /// Takes a collection of coordinates, e.g. (latitude, longitude) tuples,
+    /// see [LatLon][LatLon].
+    pub fn coordinates<I, C>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        let (lat_coordinates, lon_coordinates) = unzip(coordinates);
+        self.lat_coordinates = Some(lat_coordinates);
+        self.lon_coordinates = Some(lon_coordinates);
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Line width.
+    /// Default is 1.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Outline drawn beneath the line, extending `width` pixels beyond it on each side.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Arrowheads at the ends of the line, showing its direction.
+    /// Default is no arrowheads.
+    pub fn arrowheads(mut self, arrowheads: Arrowheads) -> Self {
+        self.arrowheads = Some(arrowheads);
+        self
+    }
+
+    /// Greatest distance between the points the great circles are densified into,
+    /// in meters. Shorter distances give smoother curves at high zoom levels.
+    /// Default is 20 km.
+    pub fn spacing(mut self, meters: f64) -> Self {
+        self.spacing = meters;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// if the latitude and longitude coordinates do not match,
+    /// or if the spacing is not positive.
+    pub fn build(self) -> Result<GeodesicLine> {
+        let lat_coordinates = self
+            .lat_coordinates
+            .ok_or(Error::BuildError("Latitude coordinates not supplied."))?;
+        let lon_coordinates = self
+            .lon_coordinates
+            .ok_or(Error::BuildError("Longitude coordinates not supplied."))?;
+
+        if lat_coordinates.len() != lon_coordinates.len() {
+            return Err(Error::BuildError(
+                "Latitude and longitude coordinates must be of equal length.",
+            ));
+        }
+        if self.spacing.is_nan() || self.spacing <= 0. {
+            return Err(Error::BuildError("Spacing must be positive."));
+        }
+
+        // Longitudes continue across the antimeridian, following the shorter way
+        // between consecutive coordinates.
+        let mut coordinates: Vec<(f64, f64)> = Vec::new();
+        for (&lat, &lon) in lat_coordinates.iter().zip(lon_coordinates.iter()) {
+            match coordinates.last().copied() {
+                Some((previous_lat, previous_lon)) => {
+                    let lon = lon + ((previous_lon - lon) / 360.).round() * 360.;
+                    coordinates.extend(
+                        great_circle(previous_lat, previous_lon, lat, lon, self.spacing)
+                            .into_iter()
+                            .skip(1),
+                    );
+                }
+                None => coordinates.push((lat, lon)),
+            }
+        }
+
+        let mut line = LineBuilder::new()
+            .coordinates(coordinates)
+            .color(self.color.clone())
+            .width(self.width);
+        if let Some((ref color, width)) = self.casing {
+            line = line.casing(color.clone(), width);
+        }
+        if let Some(arrowheads) = self.arrowheads {
+            line = line.arrowheads(arrowheads);
+        }
+
+        Ok(GeodesicLine {
+            lat_coordinates,
+            lon_coordinates,
+            color: self.color,
+            width: self.width,
+            casing: self.casing,
+            arrowheads: self.arrowheads,
+            spacing: self.spacing,
+            line: line.build()?,
+        })
+    }
+}
+
+impl GeodesicLineBuilder {
+    /// Builder with the settings of a geodesic line in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.lat_coordinates = json.numbers("lat_coordinates")?;
+        builder.lon_coordinates = json.numbers("lon_coordinates")?;
+        builder.width = json.number("width")?.map_or(builder.width, |x| x as f32);
+        builder.casing = member(json, "casing")?;
+        builder.arrowheads = member(json, "arrowheads")?;
+        builder.spacing = json.number("spacing")?.unwrap_or(builder.spacing);
+        if let Some(color) = member(json, "color")? {
+            builder.color = color;
+        }
+        Ok(builder)
+    }
+}
+
+impl Tool for GeodesicLine {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        self.line.extent(zoom, tile_size)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        self.line.pixel_extent(bounds)
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "geodesic_line".digest(state);
+        self.line.digest(state)
+    }
+
+    fn geojson(&self) -> Option<String> {
+        self.line.geojson()
+    }
+
+    fn scene(&self) -> Option<String> {
+        if !solid_colors(&self.color, &self.casing) {
+            return None;
+        }
+
+        let scene = object(vec![
+            ("type", "geodesic_line".into()),
+            ("lat_coordinates", self.lat_coordinates.clone().into()),
+            ("lon_coordinates", self.lon_coordinates.clone().into()),
+            ("color", self.color.to_json()),
+            ("width", self.width.into()),
+            ("casing", self.casing.as_ref().map(Scene::to_json).into()),
+            (
+                "arrowheads",
+                self.arrowheads.as_ref().map(Scene::to_json).into(),
+            ),
+            ("spacing", self.spacing.into()),
+        ]);
+        Some(scene.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.line.draw(bounds, pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, pixmap: PixmapMut) {
+        self.line.draw_at_scale(bounds, scale, pixmap);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{
+    bounds::Bounds,
+    digest::Digest,
+    json::{invalid_member, Json},
+    scene::{member, object, Scene},
+    tools::Color,
+    Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{GradientStop, LinearGradient, Paint, Point, SpreadMode, Transform};
+
+/// Linear gradient between two geographic anchor points, used to fill areas
+/// with a value-graded color, e.g. flood depth increasing away from the shore.
+///
+/// Stops are positioned between 0.0 at the start anchor and 1.0 at the end anchor.
+/// Areas beyond the anchors use the color of the nearest stop.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, Gradient};
+///
+/// let depth = Gradient::new((13.40, 52.50), (13.45, 52.52))
+///     .stop(0., Color::new(true, 200, 230, 255, 180))
+///     .stop(1., Color::new(true, 0, 40, 160, 180));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Gradient {
+    start: (f64, f64),
+    end: (f64, f64),
+    stops: Vec<(f32, Color)>,
+    anti_alias: bool,
+}
+
+impl Gradient {
+    /// Creates a new [Gradient][Gradient] from `start` to `end`,
+    /// both as (longitude, latitude), without any stops.
+    pub fn new(start: (f64, f64), end: (f64, f64)) -> Self {
+        Gradient {
+            start,
+            end,
+            stops: Vec::new(),
+            anti_alias: true,
+        }
+    }
+
+    /// Add a color stop at `position`, between 0.0 and 1.0.
+    /// Use [Color][Color] to generate a color instance.
+    pub fn stop(mut self, position: f32, color: Color) -> Self {
+        self.anti_alias = self.anti_alias && color.0.anti_alias;
+        self.stops.push((position, color));
+        self
+    }
+
+    /// Paint for the gradient on the map, or None if it has no stops.
+    pub(crate) fn paint(&self, bounds: &Bounds) -> Option<Paint<'static>> {
+        let (start_x, start_y) = bounds.lon_lat_to_px(self.start.0, self.start.1);
+        let (end_x, end_y) = bounds.lon_lat_to_px(self.end.0, self.end.1);
+
+        let stops = self
+            .stops
+            .iter()
+            .map(|(position, color)| GradientStop::new(*position, color.solid()))
+            .collect();
+
+        let shader = LinearGradient::new(
+            Point::from_xy(start_x as f32, start_y as f32),
+            Point::from_xy(end_x as f32, end_y as f32),
+            stops,
+            SpreadMode::Pad,
+            Transform::identity(),
+        )?;
+
+        Some(Paint {
+            shader,
+            anti_alias: self.anti_alias,
+            ..Default::default()
+        })
+    }
+}
+
+impl Digest for Gradient {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.start.digest(state);
+        self.end.digest(state);
+        self.stops.digest(state);
+    }
+}
+
+impl Scene for Gradient {
+    fn to_json(&self) -> Json {
+        let stops = self
+            .stops
+            .iter()
+            .map(|(position, color)| {
+                object(vec![
+                    ("position", (*position).into()),
+                    ("color", color.to_json()),
+                ])
+            })
+            .collect();
+
+        object(vec![
+            ("start", self.start.into()),
+            ("end", self.end.into()),
+            ("stops", Json::Array(stops)),
+        ])
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        let start = json.pair("start")?;
+        let end = json.pair("end")?;
+        let mut gradient = match start.zip(end) {
+            Some((start, end)) => Gradient::new(start, end),
+            None => return Err(invalid_member("gradient", "an object with a start and end")),
+        };
+
+        for stop in json.array("stops")?.unwrap_or_default() {
+            let position = stop.number("position")?.unwrap_or(0.);
+            let color = member(stop, "color")?.unwrap_or_default();
+            gradient = gradient.stop(position as f32, color);
+        }
+
+        Ok(gradient)
+    }
+}
+
+/// Colors assigned to positions between 0.0 and 1.0, used to color a value by where
+/// it falls in a range, e.g. the speed along a track, see
+/// [GradientLineBuilder::ramp][crate::tools::GradientLineBuilder::ramp].
+///
+/// Colors between stops are interpolated, and positions beyond the first or last stop
+/// use the color of that stop. Only the solid color of each [Color][Color] is used.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, ColorRamp};
+///
+/// let speed = ColorRamp::new()
+///     .stop(0., Color::new(true, 40, 80, 220, 255))
+///     .stop(0.5, Color::new(true, 250, 210, 40, 255))
+///     .stop(1., Color::new(true, 220, 30, 30, 255));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ColorRamp {
+    stops: Vec<(f32, Color)>,
+}
+
+impl ColorRamp {
+    /// Creates a new [ColorRamp][ColorRamp] without any stops.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a color stop at `position`, between 0.0 and 1.0.
+    /// Use [Color][Color] to generate a color instance.
+    pub fn stop(mut self, position: f32, color: Color) -> Self {
+        let index = self.stops.partition_point(|(x, _)| *x <= position);
+        self.stops.insert(index, (position, color));
+        self
+    }
+
+    /// Whether the ramp has no stops.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.stops.is_empty()
+    }
+
+    /// Whether all colors of the ramp can be part of a scene.
+    pub(crate) fn is_solid(&self) -> bool {
+        self.stops.iter().all(|(_, color)| color.is_solid())
+    }
+
+    /// Whether all colors of the ramp are anti-aliased.
+    pub(crate) fn anti_alias(&self) -> bool {
+        self.stops.iter().all(|(_, color)| color.0.anti_alias)
+    }
+
+    /// Color at `position`, interpolated between the stops around it, or black without stops.
+    pub(crate) fn color(&self, position: f32) -> tiny_skia::Color {
+        let index = self.stops.partition_point(|(x, _)| *x <= position);
+        let (before, after) = match (self.stops.get(index.wrapping_sub(1)), self.stops.get(index)) {
+            (Some(before), Some(after)) => (before, after),
+            (Some((_, color)), None) | (None, Some((_, color))) => return color.solid(),
+            (None, None) => return tiny_skia::Color::BLACK,
+        };
+
+        let t = (position - before.0) / (after.0 - before.0);
+        let (a, b) = (before.1.solid(), after.1.solid());
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        tiny_skia::Color::from_rgba(
+            mix(a.red(), b.red()),
+            mix(a.green(), b.green()),
+            mix(a.blue(), b.blue()),
+            mix(a.alpha(), b.alpha()),
+        )
+        .unwrap_or(a)
+    }
+}
+
+impl Digest for ColorRamp {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.stops.digest(state);
+    }
+}
+
+impl Scene for ColorRamp {
+    fn to_json(&self) -> Json {
+        let stops = self
+            .stops
+            .iter()
+            .map(|(position, color)| {
+                object(vec![
+                    ("position", (*position).into()),
+                    ("color", color.to_json()),
+                ])
+            })
+            .collect();
+
+        object(vec![("stops", Json::Array(stops))])
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        let mut ramp = ColorRamp::new();
+        for stop in json.array("stops")?.unwrap_or_default() {
+            let position = stop.number("position")?.unwrap_or(0.);
+            let color = member(stop, "color")?.unwrap_or_default();
+            ramp = ramp.stop(position as f32, color);
+        }
+        Ok(ramp)
+    }
+}
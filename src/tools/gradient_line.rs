@@ -0,0 +1,392 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    clip::clip_line,
+    coord::{unzip, LatLon},
+    digest::Digest,
+    geojson::Feature,
+    json::Json,
+    scene::{member, object, Scene},
+    tools::{draw_casing, edge_margin, Color, ColorRamp, Tool},
+    Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{
+    GradientStop, LineCap, LinearGradient, Paint, PathBuilder, PixmapMut, Point, Shader,
+    SpreadMode, Stroke, Transform,
+};
+
+/// Line colored by a value at each coordinate, e.g. a GPS track colored by speed
+/// or elevation.
+/// Use [GradientLineBuilder][GradientLineBuilder] as an entrypoint.
+///
+/// Each value is placed on the [ColorRamp][ColorRamp] by where it falls in the value range,
+/// and each segment blends from the color of its first coordinate to that of its last.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, ColorRamp, GradientLineBuilder};
+///
+/// let track = GradientLineBuilder::new()
+///     .coordinates(vec![(52.50, 13.40), (52.51, 13.42), (52.52, 13.43), (52.53, 13.46)])
+///     .values(vec![12., 25., 31., 18.])
+///     .ramp(
+///         ColorRamp::new()
+///             .stop(0., Color::new(true, 40, 80, 220, 255))
+///             .stop(1., Color::new(true, 220, 30, 30, 255)),
+///     )
+///     .value_range(0., 40.)
+///     .width(4.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct GradientLine {
+    lat_coordinates: Vec<f64>,
+    lon_coordinates: Vec<f64>,
+    values: Vec<f64>,
+    ramp: ColorRamp,
+    value_range: (f64, f64),
+    width: f32,
+    casing: Option<(Color, f32)>,
+}
+
+/// Builder for [GradientLine][GradientLine].
+#[derive(Debug, Clone)]
+pub struct GradientLineBuilder {
+    lat_coordinates: Option<Vec<f64>>,
+    lon_coordinates: Option<Vec<f64>>,
+    values: Option<Vec<f64>>,
+    ramp: Option<ColorRamp>,
+    value_range: Option<(f64, f64)>,
+    width: f32,
+    casing: Option<(Color, f32)>,
+}
+
+impl Default for GradientLineBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinates: None,
+            lon_coordinates: None,
+            values: None,
+            ramp: None,
+            value_range: None,
+            width: 3.,
+            casing: None,
+        }
+    }
+}
+
+impl GradientLineBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// Takes a collection of latitude coordinates, e.g. `f64` or `f32`.
+    pub fn lat_coordinates<I>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.lat_coordinates = Some(coordinates.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// **Required**.
+    /// Takes a collection of longitude coordinates, e.g. `f64` or `f32`.
+    pub fn lon_coordinates<I>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.lon_coordinates = Some(coordinates.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// **Required**, unless latitude and longitude coordinates are supplied separately.
+    /// Takes a collection of coordinates, e.g. (latitude, longitude) tuples,
+    /// see [LatLon][LatLon].
+    pub fn coordinates<I, C>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        let (lat_coordinates, lon_coordinates) = unzip(coordinates);
+        self.lat_coordinates = Some(lat_coordinates);
+        self.lon_coordinates = Some(lon_coordinates);
+        self
+    }
+
+    /// **Required**.
+    /// Value at each coordinate, e.g. speed or elevation, deciding its color.
+    pub fn values<I>(mut self, values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.values = Some(values.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// **Required**.
+    /// Colors of the values, from the lower end of the value range at 0.0
+    /// to the upper end at 1.0.
+    pub fn ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = Some(ramp);
+        self
+    }
+
+    /// Values at the ends of the ramp, with values outside clamped to the ends,
+    /// e.g. to color several tracks alike.
+    /// Default is the lowest and highest value of the line.
+    pub fn value_range(mut self, min: f64, max: f64) -> Self {
+        self.value_range = Some((min, max));
+        self
+    }
+
+    /// Line width.
+    /// Default is 3.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Outline drawn beneath the line, extending `width` pixels beyond it on each side,
+    /// setting the colors apart from the map.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// if there are not as many coordinates and values,
+    /// if there are fewer than two coordinates, or if the ramp has no stops.
+    pub fn build(self) -> Result<GradientLine> {
+        let lat_coordinates = self
+            .lat_coordinates
+            .ok_or(Error::BuildError("Latitude coordinates not supplied."))?;
+        let lon_coordinates = self
+            .lon_coordinates
+            .ok_or(Error::BuildError("Longitude coordinates not supplied."))?;
+        let values = self
+            .values
+            .ok_or(Error::BuildError("Values not supplied."))?;
+        let ramp = self
+            .ramp
+            .ok_or(Error::BuildError("Color ramp not supplied."))?;
+
+        if lat_coordinates.len() != lon_coordinates.len() || values.len() != lat_coordinates.len() {
+            return Err(Error::BuildError(
+                "Coordinates and values must be of equal length.",
+            ));
+        }
+        if lat_coordinates.len() < 2 {
+            return Err(Error::BuildError(
+                "A gradient line needs at least two coordinates.",
+            ));
+        }
+        if ramp.is_empty() {
+            return Err(Error::BuildError("Color ramp has no stops."));
+        }
+
+        let value_range = self.value_range.unwrap_or_else(|| {
+            let finite = values.iter().copied().filter(|x| x.is_finite());
+            let min = finite.clone().fold(f64::INFINITY, f64::min);
+            let max = finite.fold(f64::NEG_INFINITY, f64::max);
+            match min <= max {
+                true => (min, max),
+                false => (0., 0.),
+            }
+        });
+
+        Ok(GradientLine {
+            lat_coordinates,
+            lon_coordinates,
+            values,
+            ramp,
+            value_range,
+            width: self.width,
+            casing: self.casing,
+        })
+    }
+}
+
+impl GradientLineBuilder {
+    /// Builder with the settings of a gradient line in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.lat_coordinates = json.numbers("lat_coordinates")?;
+        builder.lon_coordinates = json.numbers("lon_coordinates")?;
+        builder.values = json.numbers("values")?;
+        builder.ramp = member(json, "ramp")?;
+        builder.value_range = json.pair("value_range")?;
+        builder.width = json.number("width")?.map_or(builder.width, |x| x as f32);
+        builder.casing = member(json, "casing")?;
+        Ok(builder)
+    }
+}
+
+impl GradientLine {
+    /// Coordinates of the line on the map, in pixels.
+    fn points(&self, bounds: &Bounds) -> Vec<(f64, f64)> {
+        self.lon_coordinates
+            .iter()
+            .zip(self.lat_coordinates.iter())
+            .map(|(lon, lat)| bounds.snap_stroke(bounds.lon_lat_to_px(*lon, *lat), self.width))
+            .collect()
+    }
+
+    /// Color of a value, by its position in the value range.
+    fn color(&self, value: f64) -> tiny_skia::Color {
+        let (min, max) = self.value_range;
+        let position = match max > min {
+            true => ((value - min) / (max - min)).clamp(0., 1.),
+            false => 0.,
+        };
+        self.ramp.color(position as f32)
+    }
+}
+
+impl Tool for GradientLine {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        let min = |values: &[f64]| values.iter().copied().fold(f64::NAN, f64::min);
+        let max = |values: &[f64]| values.iter().copied().fold(f64::NAN, f64::max);
+        (
+            min(&self.lon_coordinates),
+            min(&self.lat_coordinates),
+            max(&self.lon_coordinates),
+            max(&self.lat_coordinates),
+        )
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let mut points = self.points(bounds).into_iter();
+        let (x, y) = points.next()?;
+        let rect = points.fold(PixelRect::new(x, y, x, y), |rect, (x, y)| {
+            PixelRect::new(
+                rect.x_min.min(x),
+                rect.y_min.min(y),
+                rect.x_max.max(x),
+                rect.y_max.max(y),
+            )
+        });
+        Some(rect.outset(edge_margin(Some(self.width), &self.casing)))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "gradient_line".digest(state);
+        self.lat_coordinates.digest(state);
+        self.lon_coordinates.digest(state);
+        self.values.digest(state);
+        self.ramp.digest(state);
+        self.value_range.digest(state);
+        self.width.digest(state);
+        self.casing.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let feature = Feature::line_string(&self.lon_coordinates, &self.lat_coordinates)
+            .number("stroke-width", self.width.into());
+        Some(feature.to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        if !self.ramp.is_solid() || !self.casing.iter().all(|x| x.0.is_solid()) {
+            return None;
+        }
+
+        let scene = object(vec![
+            ("type", "gradient_line".into()),
+            ("lat_coordinates", self.lat_coordinates.clone().into()),
+            ("lon_coordinates", self.lon_coordinates.clone().into()),
+            ("values", self.values.clone().into()),
+            ("ramp", self.ramp.to_json()),
+            ("value_range", self.value_range.into()),
+            ("width", self.width.into()),
+            ("casing", self.casing.as_ref().map(Scene::to_json).into()),
+        ]);
+        Some(scene.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let points = self.points(bounds);
+
+        // Only the segments on the map are drawn, with room for the caps and casing.
+        let viewport = bounds
+            .pixel_rect()
+            .outset(edge_margin(Some(self.width), &self.casing) + 1.);
+
+        if let Some(casing) = self.casing.as_ref() {
+            let mut path_builder = PathBuilder::new();
+            for run in clip_line(&points, &viewport) {
+                for (index, (x, y)) in run.into_iter().enumerate() {
+                    match index {
+                        0 => path_builder.move_to(x as f32, y as f32),
+                        _ => path_builder.line_to(x as f32, y as f32),
+                    }
+                }
+            }
+            if let Some(path) = path_builder.finish() {
+                draw_casing(
+                    &mut pixmap,
+                    &path,
+                    casing,
+                    Some(self.width),
+                    LineCap::Round,
+                    transform,
+                );
+            }
+        }
+
+        let stroke = Stroke {
+            width: self.width,
+            line_cap: LineCap::Round,
+            ..Default::default()
+        };
+        let anti_alias = self.ramp.anti_alias();
+
+        for (segment, values) in points.windows(2).zip(self.values.windows(2)) {
+            let (start, end) = (segment[0], segment[1]);
+            let (start_color, end_color) = (self.color(values[0]), self.color(values[1]));
+
+            // The gradient follows the whole segment, even where it is clipped.
+            let shader = LinearGradient::new(
+                Point::from_xy(start.0 as f32, start.1 as f32),
+                Point::from_xy(end.0 as f32, end.1 as f32),
+                vec![
+                    GradientStop::new(0., start_color),
+                    GradientStop::new(1., end_color),
+                ],
+                SpreadMode::Pad,
+                Transform::identity(),
+            )
+            .unwrap_or(Shader::SolidColor(start_color));
+            let paint = Paint {
+                shader,
+                anti_alias,
+                ..Default::default()
+            };
+
+            for run in clip_line(segment, &viewport) {
+                let mut path_builder = PathBuilder::new();
+                for (index, (x, y)) in run.into_iter().enumerate() {
+                    match index {
+                        0 => path_builder.move_to(x as f32, y as f32),
+                        _ => path_builder.line_to(x as f32, y as f32),
+                    }
+                }
+                if let Some(path) = path_builder.finish() {
+                    pixmap.stroke_path(&path, &paint, &stroke, transform, None);
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,13 @@
-use crate::{bounds::Bounds, lat_to_y, lon_to_x, tools::Tool, x_to_lon, y_to_lat, Error, Result};
-use tiny_skia::{Pixmap, PixmapMut, PixmapPaint, Transform};
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geojson::Feature,
+    lat_to_y, lon_to_x_continuous,
+    tools::{text::dilate, Color, Tool},
+    x_to_lon, y_to_lat, Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{FilterQuality, Pixmap, PixmapMut, PixmapPaint, PremultipliedColorU8, Transform};
 
 /// Icon tool.
 /// Use [IconBuilder][IconBuilder] as an entrypoint.
@@ -25,9 +33,12 @@ pub struct Icon {
     x_offset: f64,
     y_offset: f64,
     icon: Pixmap,
+    scale: f32,
+    quality: FilterQuality,
+    priority: Option<f64>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 /// Builder for [Icon][Icon].
 pub struct IconBuilder {
     lat_coordinate: Option<f64>,
@@ -35,6 +46,26 @@ pub struct IconBuilder {
     x_offset: f64,
     y_offset: f64,
     icon: Option<Pixmap>,
+    scale: f32,
+    quality: FilterQuality,
+    halo: Option<(Color, f32)>,
+    priority: Option<f64>,
+}
+
+impl Default for IconBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinate: None,
+            lon_coordinate: None,
+            x_offset: 0.,
+            y_offset: 0.,
+            icon: None,
+            scale: 1.,
+            quality: FilterQuality::Bilinear,
+            halo: None,
+            priority: None,
+        }
+    }
 }
 
 impl IconBuilder {
@@ -85,9 +116,60 @@ impl IconBuilder {
         Ok(self)
     }
 
+    /// Scale of the icon image, e.g. 0.5 to draw a 2x retina icon at its intended size.
+    /// Offsets are in pixels of the image and are scaled with it.
+    /// Default is 1.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Filter quality of scaled icons.
+    /// Bicubic filtering is sharper, at a higher rendering cost.
+    /// Default is bilinear.
+    pub fn quality(mut self, quality: FilterQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Outline drawn around the opaque parts of the icon, following its alpha edges,
+    /// to keep dark icons visible on dark maps and light icons on light maps.
+    /// `width` is in pixels on the map, regardless of the [scale][IconBuilder::scale].
+    /// Default is no halo.
+    pub fn halo(mut self, color: Color, width: f32) -> Self {
+        self.halo = Some((color, width));
+        self
+    }
+
+    /// Priority of the icon when overlapping icons are decluttered, higher is placed first,
+    /// see [Declutter][crate::Declutter].
+    /// Default is no priority, always drawing the icon.
+    pub fn priority(mut self, priority: f64) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
     /// Build the tool, consuming the builder.
     /// Return an error if the builder is missing required fields.
     pub fn build(self) -> Result<Icon> {
+        let mut icon = self
+            .icon
+            .ok_or(Error::BuildError("Icon image not supplied."))?;
+        let (mut x_offset, mut y_offset) = (self.x_offset, self.y_offset);
+
+        // The halo is drawn into the image once, growing it on every side,
+        // so it is placed and scaled along with the icon.
+        if let Some((color, width)) = self.halo.as_ref() {
+            let radius = width / self.scale;
+            if radius > 0. {
+                let pad = radius.ceil();
+                icon = with_halo(&icon, color, radius)
+                    .ok_or(Error::BuildError("Icon halo is too large."))?;
+                x_offset += f64::from(pad);
+                y_offset += f64::from(pad);
+            }
+        }
+
         Ok(Icon {
             lat_coordinate: self
                 .lat_coordinate
@@ -95,46 +177,147 @@ impl IconBuilder {
             lon_coordinate: self
                 .lon_coordinate
                 .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
-            x_offset: self.x_offset,
-            y_offset: self.y_offset,
-            icon: self
-                .icon
-                .ok_or(Error::BuildError("Icon image not supplied."))?,
+            x_offset,
+            y_offset,
+            icon,
+            scale: self.scale,
+            quality: self.quality,
+            priority: self.priority,
         })
     }
 }
 
+/// Icon drawn on a halo of `radius` pixels around its alpha edges,
+/// in an image grown by the radius, rounded up, on every side.
+fn with_halo(icon: &Pixmap, color: &Color, radius: f32) -> Option<Pixmap> {
+    let pad = radius.ceil() as u32;
+    let width = icon.width().checked_add(2 * pad)?;
+    let height = icon.height().checked_add(2 * pad)?;
+    let mut pixmap = Pixmap::new(width, height)?;
+
+    let mut coverage = vec![0_f32; (width * height) as usize];
+    for (index, pixel) in icon.pixels().iter().enumerate() {
+        let (x, y) = (index as u32 % icon.width(), index as u32 / icon.width());
+        coverage[((y + pad) * width + x + pad) as usize] = f32::from(pixel.alpha()) / 255.;
+    }
+
+    let color = color.solid();
+    let halo = dilate(&coverage, width, height, radius);
+    for (pixel, coverage) in pixmap.pixels_mut().iter_mut().zip(halo) {
+        let alpha = coverage * color.alpha();
+        let to_u8 = |x: f32| (x * 255.).round().clamp(0., 255.) as u8;
+        let a = to_u8(alpha);
+        if let Some(color) = PremultipliedColorU8::from_rgba(
+            to_u8(color.red() * alpha).min(a),
+            to_u8(color.green() * alpha).min(a),
+            to_u8(color.blue() * alpha).min(a),
+            a,
+        ) {
+            *pixel = color;
+        }
+    }
+
+    pixmap.draw_pixmap(
+        pad as i32,
+        pad as i32,
+        icon.as_ref(),
+        &PixmapPaint::default(),
+        Transform::default(),
+        None,
+    );
+    Some(pixmap)
+}
+
 impl Tool for Icon {
     fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        let scale = f64::from(self.scale);
         let (width, height): (f64, f64) = (self.icon.width().into(), self.icon.height().into());
         let extent = (
-            self.x_offset,
-            height - self.y_offset,
-            width - self.x_offset,
-            self.y_offset,
+            self.x_offset * scale,
+            (height - self.y_offset) * scale,
+            (width - self.x_offset) * scale,
+            self.y_offset * scale,
         );
 
-        let x = lon_to_x(self.lon_coordinate, zoom);
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
         let y = lat_to_y(self.lat_coordinate, zoom);
 
-        let lon_min = x_to_lon(x - extent.0 / tile_size, zoom);
-        let lat_min = y_to_lat(y + extent.1 / tile_size, zoom);
-        let lon_max = x_to_lon(x + extent.2 / tile_size, zoom);
-        let lat_max = y_to_lat(y - extent.3 / tile_size, zoom);
+        // Offsets from the coordinate, so coordinates outside the geographic range,
+        // e.g. on a Cartesian grid, are not wrapped around.
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x - extent.0 / tile_size, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + extent.1 / tile_size, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + extent.2 / tile_size, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y - extent.3 / tile_size, zoom) - lat;
 
         (lon_min, lat_min, lon_max, lat_max)
     }
 
-    fn draw(&self, bounds: &Bounds, mut pixmap: PixmapMut) {
-        let x = bounds.x_to_px(lon_to_x(self.lon_coordinate, bounds.zoom)) - self.x_offset;
-        let y = bounds.y_to_px(lat_to_y(self.lat_coordinate, bounds.zoom)) - self.y_offset;
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let scale = f64::from(self.scale);
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (x, y) = (x - self.x_offset * scale, y - self.y_offset * scale);
+        Some(PixelRect::new(
+            x,
+            y,
+            x + f64::from(self.icon.width()) * scale,
+            y + f64::from(self.icon.height()) * scale,
+        ))
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        Some((self.lon_coordinate, self.lat_coordinate))
+    }
+
+    fn priority(&self) -> Option<f64> {
+        self.priority
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "icon".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.x_offset.digest(state);
+        self.y_offset.digest(state);
+        self.icon.digest(state);
+        self.scale.digest(state);
+        self.quality.digest(state);
+        self.priority.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        Some(Feature::point(self.lon_coordinate, self.lat_coordinate).to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
 
+        // Unscaled icons are drawn pixel for pixel, without resampling.
+        let transform = if self.scale == 1. {
+            let (x, y) = (x - self.x_offset, y - self.y_offset);
+            Transform::from_translate(x as i32 as f32, y as i32 as f32)
+        } else {
+            let icon_scale = f64::from(self.scale);
+            let (x, y) = (
+                x - self.x_offset * icon_scale,
+                y - self.y_offset * icon_scale,
+            );
+            Transform::from_scale(self.scale, self.scale).post_translate(x as f32, y as f32)
+        };
         pixmap.draw_pixmap(
-            x as i32,
-            y as i32,
+            0,
+            0,
             self.icon.as_ref(),
-            &PixmapPaint::default(),
-            Transform::default(),
+            &PixmapPaint {
+                quality: self.quality,
+                ..Default::default()
+            },
+            transform.post_scale(scale, scale),
             None,
         );
     }
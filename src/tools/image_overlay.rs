@@ -0,0 +1,479 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    coord::LatLon,
+    decode::decode_image,
+    digest::Digest,
+    geojson::Feature,
+    tools::Tool,
+    Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{FilterQuality, Pixmap, PixmapMut, PixmapPaint, PremultipliedColorU8, Transform};
+
+/// Image overlay tool, a raster stretched between geographic coordinates on top of the map,
+/// e.g. a scanned historical map or a weather radar frame.
+/// Use [ImageOverlayBuilder][ImageOverlayBuilder] as an entrypoint.
+///
+/// The image is placed either by the coordinates of its edges, or by the coordinates
+/// of its four corners, e.g. from control points of a scanned map that is not
+/// north-up. With four corners the image is warped in pixel space to fit them.
+/// Unlike a [BaseImage][crate::BaseImage], the overlay is drawn above the tiles.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::ImageOverlayBuilder;
+///
+/// let radar = ImageOverlayBuilder::new()
+///     .path("examples/results/empty_map.png")
+///     .unwrap()
+///     .north_lat_coordinate(52.6)
+///     .south_lat_coordinate(52.4)
+///     .east_lon_coordinate(13.6)
+///     .west_lon_coordinate(13.2)
+///     .opacity(0.6)
+///     .build()
+///     .unwrap();
+///
+/// let scan = ImageOverlayBuilder::new()
+///     .path("examples/results/empty_map.png")
+///     .unwrap()
+///     .corners((52.61, 13.21), (52.63, 13.58), (52.41, 13.61), (52.39, 13.19))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ImageOverlay {
+    image: Pixmap,
+    /// Top left, top right, bottom right and bottom left corner of the image.
+    corners: [LatLon; 4],
+    opacity: f32,
+    quality: FilterQuality,
+}
+
+/// Builder for [ImageOverlay][ImageOverlay].
+#[derive(Debug, Clone)]
+pub struct ImageOverlayBuilder {
+    image: Option<Pixmap>,
+    north_lat_coordinate: Option<f64>,
+    south_lat_coordinate: Option<f64>,
+    east_lon_coordinate: Option<f64>,
+    west_lon_coordinate: Option<f64>,
+    corners: Option<[LatLon; 4]>,
+    opacity: f32,
+    quality: FilterQuality,
+}
+
+impl Default for ImageOverlayBuilder {
+    fn default() -> Self {
+        Self {
+            image: None,
+            north_lat_coordinate: None,
+            south_lat_coordinate: None,
+            east_lon_coordinate: None,
+            west_lon_coordinate: None,
+            corners: None,
+            opacity: 1.,
+            quality: FilterQuality::Bilinear,
+        }
+    }
+}
+
+impl ImageOverlayBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// Path to an image file.
+    /// PNG images are always supported, JPEG and WebP with the `image` feature.
+    pub fn path<P: AsRef<std::path::Path>>(self, path: P) -> Result<Self> {
+        self.data(std::fs::read(path)?)
+    }
+
+    /// **Required**.
+    /// Load an image from bytes.
+    /// PNG images are always supported, JPEG and WebP with the `image` feature.
+    pub fn data<D: AsRef<[u8]>>(mut self, data: D) -> Result<Self> {
+        self.image = Some(decode_image(data.as_ref())?);
+        Ok(self)
+    }
+
+    /// **Required**, unless the image is placed by its [corners][ImageOverlayBuilder::corners].
+    /// The latitude coordinate of the top edge of the image.
+    pub fn north_lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.north_lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**, unless the image is placed by its [corners][ImageOverlayBuilder::corners].
+    /// The latitude coordinate of the bottom edge of the image.
+    pub fn south_lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.south_lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**, unless the image is placed by its [corners][ImageOverlayBuilder::corners].
+    /// The longitude coordinate of the right edge of the image.
+    pub fn east_lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.east_lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**, unless the image is placed by its [corners][ImageOverlayBuilder::corners].
+    /// The longitude coordinate of the left edge of the image.
+    pub fn west_lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.west_lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// Place the image by the coordinates of its top left, top right, bottom right
+    /// and bottom left corners, e.g. (latitude, longitude) tuples, see [LatLon][LatLon].
+    /// Takes precedence over the edge coordinates.
+    pub fn corners<C: Into<LatLon>>(
+        mut self,
+        top_left: C,
+        top_right: C,
+        bottom_right: C,
+        bottom_left: C,
+    ) -> Self {
+        self.corners = Some([
+            top_left.into(),
+            top_right.into(),
+            bottom_right.into(),
+            bottom_left.into(),
+        ]);
+        self
+    }
+
+    /// Opacity of the image, from 0.0 for invisible to 1.0 for opaque.
+    /// Default is 1.0.
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0., 1.);
+        self
+    }
+
+    /// Filter quality of the scaled image.
+    /// [FilterQuality::Nearest][FilterQuality::Nearest] keeps the cells of radar frames
+    /// and other coarse rasters sharp.
+    /// Default is bilinear.
+    pub fn quality(mut self, quality: FilterQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields.
+    pub fn build(self) -> Result<ImageOverlay> {
+        let image = self
+            .image
+            .ok_or(Error::BuildError("Overlay image not supplied."))?;
+
+        let corners = match self.corners {
+            Some(corners) => corners,
+            None => {
+                let north = self
+                    .north_lat_coordinate
+                    .ok_or(Error::BuildError("North latitude coordinate not supplied."))?;
+                let south = self
+                    .south_lat_coordinate
+                    .ok_or(Error::BuildError("South latitude coordinate not supplied."))?;
+                let east = self
+                    .east_lon_coordinate
+                    .ok_or(Error::BuildError("East longitude coordinate not supplied."))?;
+                let west = self
+                    .west_lon_coordinate
+                    .ok_or(Error::BuildError("West longitude coordinate not supplied."))?;
+                [
+                    LatLon::new(north, west),
+                    LatLon::new(north, east),
+                    LatLon::new(south, east),
+                    LatLon::new(south, west),
+                ]
+            }
+        };
+
+        Ok(ImageOverlay {
+            image,
+            corners,
+            opacity: self.opacity,
+            quality: self.quality,
+        })
+    }
+}
+
+impl ImageOverlay {
+    /// Corners of the image on the map, in pixels.
+    fn pixel_corners(&self, bounds: &Bounds) -> [(f64, f64); 4] {
+        self.corners
+            .map(|corner| bounds.lon_lat_to_px(corner.lon, corner.lat))
+    }
+
+    /// Draw an image whose corners form a parallelogram, with a single affine transform.
+    fn draw_affine(&self, corners: &[(f64, f64); 4], pixmap: &mut PixmapMut) {
+        let (width, height) = (
+            f64::from(self.image.width()),
+            f64::from(self.image.height()),
+        );
+        let [(x0, y0), (x1, y1), _, (x3, y3)] = *corners;
+        let transform = Transform::from_row(
+            ((x1 - x0) / width) as f32,
+            ((y1 - y0) / width) as f32,
+            ((x3 - x0) / height) as f32,
+            ((y3 - y0) / height) as f32,
+            x0 as f32,
+            y0 as f32,
+        );
+
+        pixmap.draw_pixmap(0, 0, self.image.as_ref(), &self.paint(), transform, None);
+    }
+
+    /// Draw an image onto any four corners, mapping each pixel of the map
+    /// back into the image with the inverse of the projective transform between them.
+    fn draw_warped(&self, corners: &[(f64, f64); 4], bounds: &Bounds, pixmap: &mut PixmapMut) {
+        let inverse = match Homography::square_to_quad(corners).and_then(|x| x.inverse()) {
+            Some(inverse) => inverse,
+            None => return,
+        };
+
+        let rect = match quad_rect(corners) {
+            Some(rect) => rect,
+            None => return,
+        };
+        let left = rect.x_min.floor().max(0.);
+        let top = rect.y_min.floor().max(0.);
+        let right = rect.x_max.ceil().min(f64::from(bounds.width));
+        let bottom = rect.y_max.ceil().min(f64::from(bounds.height));
+        if right <= left || bottom <= top {
+            return;
+        }
+
+        let mut warped = match Pixmap::new((right - left) as u32, (bottom - top) as u32) {
+            Some(warped) => warped,
+            None => return,
+        };
+        let (width, height) = (
+            f64::from(self.image.width()),
+            f64::from(self.image.height()),
+        );
+        let warped_width = warped.width() as usize;
+
+        for (index, pixel) in warped.pixels_mut().iter_mut().enumerate() {
+            let x = left + (index % warped_width) as f64 + 0.5;
+            let y = top + (index / warped_width) as f64 + 0.5;
+            let (u, v) = match inverse.apply(x, y) {
+                Some(point) => point,
+                None => continue,
+            };
+            let (u, v) = (u * width - 0.5, v * height - 0.5);
+            if let Some(color) = self.sample(u, v) {
+                *pixel = color;
+            }
+        }
+
+        pixmap.draw_pixmap(
+            left as i32,
+            top as i32,
+            warped.as_ref(),
+            &PixmapPaint {
+                quality: FilterQuality::Nearest,
+                ..self.paint()
+            },
+            Transform::default(),
+            None,
+        );
+    }
+
+    /// Color of the image at a position, in pixels from the center of its top left pixel.
+    /// Pixels beyond the edges are transparent, which softens the edges of the image.
+    fn sample(&self, u: f64, v: f64) -> Option<PremultipliedColorU8> {
+        let (width, height) = (self.image.width() as i64, self.image.height() as i64);
+        let pixel = |x: i64, y: i64| match x >= 0 && y >= 0 && x < width && y < height {
+            true => self.image.pixels()[(y * width + x) as usize],
+            false => PremultipliedColorU8::TRANSPARENT,
+        };
+
+        if self.quality == FilterQuality::Nearest {
+            let (x, y) = (u.round() as i64, v.round() as i64);
+            return match x >= 0 && y >= 0 && x < width && y < height {
+                true => Some(pixel(x, y)),
+                false => None,
+            };
+        }
+
+        let (x, y) = (u.floor(), v.floor());
+        if x < -1. || y < -1. || x >= width as f64 || y >= height as f64 {
+            return None;
+        }
+        let (fx, fy) = (u - x, v - y);
+        let (x, y) = (x as i64, y as i64);
+
+        let samples = [
+            (pixel(x, y), (1. - fx) * (1. - fy)),
+            (pixel(x + 1, y), fx * (1. - fy)),
+            (pixel(x, y + 1), (1. - fx) * fy),
+            (pixel(x + 1, y + 1), fx * fy),
+        ];
+        let mut sums = [0_f64; 4];
+        for (color, weight) in samples.iter() {
+            sums[0] += f64::from(color.red()) * weight;
+            sums[1] += f64::from(color.green()) * weight;
+            sums[2] += f64::from(color.blue()) * weight;
+            sums[3] += f64::from(color.alpha()) * weight;
+        }
+        let [r, g, b, a] = sums.map(|x| x.round().clamp(0., 255.) as u8);
+        PremultipliedColorU8::from_rgba(r.min(a), g.min(a), b.min(a), a)
+    }
+
+    fn paint(&self) -> PixmapPaint {
+        PixmapPaint {
+            opacity: self.opacity,
+            quality: self.quality,
+            ..Default::default()
+        }
+    }
+}
+
+impl Tool for ImageOverlay {
+    fn extent(&self, _zoom: u8, _tile_size: f64) -> (f64, f64, f64, f64) {
+        self.corners.iter().fold(
+            (f64::NAN, f64::NAN, f64::NAN, f64::NAN),
+            |extent, corner| {
+                (
+                    extent.0.min(corner.lon),
+                    extent.1.min(corner.lat),
+                    extent.2.max(corner.lon),
+                    extent.3.max(corner.lat),
+                )
+            },
+        )
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        quad_rect(&self.pixel_corners(bounds))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "image_overlay".digest(state);
+        self.image.digest(state);
+        for corner in self.corners.iter() {
+            corner.lat.digest(state);
+            corner.lon.digest(state);
+        }
+        self.opacity.digest(state);
+        self.quality.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let ring = self.corners.map(|corner| (corner.lon, corner.lat));
+        Some(
+            Feature::polygon(&ring)
+                .number("opacity", self.opacity.into())
+                .to_string(),
+        )
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        // The image is mapped onto the corners at the scale, instead of scaling up the result.
+        let scale = f64::from(scale);
+        let corners = self
+            .pixel_corners(bounds)
+            .map(|(x, y)| (x * scale, y * scale));
+        let bounds = &bounds.scale(scale);
+        let [(x0, y0), (x1, y1), (x2, y2), (x3, y3)] = corners;
+
+        // Images placed by their edges, or by corners forming a parallelogram,
+        // need no warping, and are drawn by tiny-skia with its filtering.
+        let parallelogram = (x0 + x2 - x1 - x3).abs() < 0.01 && (y0 + y2 - y1 - y3).abs() < 0.01;
+        match parallelogram {
+            true => self.draw_affine(&corners, &mut pixmap),
+            false => self.draw_warped(&corners, bounds, &mut pixmap),
+        }
+    }
+}
+
+/// Pixels covered by four corners, None if a corner is not finite.
+fn quad_rect(corners: &[(f64, f64); 4]) -> Option<PixelRect> {
+    if !corners.iter().all(|(x, y)| x.is_finite() && y.is_finite()) {
+        return None;
+    }
+
+    let (x, y) = corners[0];
+    Some(
+        corners
+            .iter()
+            .fold(PixelRect::new(x, y, x, y), |rect, (x, y)| PixelRect {
+                x_min: rect.x_min.min(*x),
+                y_min: rect.y_min.min(*y),
+                x_max: rect.x_max.max(*x),
+                y_max: rect.y_max.max(*y),
+            }),
+    )
+}
+
+/// Projective transform of the plane, as a 3x3 matrix in row-major order.
+struct Homography([f64; 9]);
+
+impl Homography {
+    /// Transform of the unit square onto four corners, in the order top left, top right,
+    /// bottom right and bottom left, following Heckbert's "Fundamentals of Texture Mapping".
+    fn square_to_quad(corners: &[(f64, f64); 4]) -> Option<Self> {
+        let [(x0, y0), (x1, y1), (x2, y2), (x3, y3)] = *corners;
+        let (dx1, dx2, dx3) = (x1 - x2, x3 - x2, x0 - x1 + x2 - x3);
+        let (dy1, dy2, dy3) = (y1 - y2, y3 - y2, y0 - y1 + y2 - y3);
+
+        let determinant = dx1 * dy2 - dx2 * dy1;
+        if determinant == 0. {
+            return None;
+        }
+        let g = (dx3 * dy2 - dx2 * dy3) / determinant;
+        let h = (dx1 * dy3 - dx3 * dy1) / determinant;
+
+        Some(Self([
+            x1 - x0 + g * x1,
+            x3 - x0 + h * x3,
+            x0,
+            y1 - y0 + g * y1,
+            y3 - y0 + h * y3,
+            y0,
+            g,
+            h,
+            1.,
+        ]))
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        let [a, b, c, d, e, f, g, h, i] = self.0;
+        let cofactors = [
+            e * i - f * h,
+            c * h - b * i,
+            b * f - c * e,
+            f * g - d * i,
+            a * i - c * g,
+            c * d - a * f,
+            d * h - e * g,
+            b * g - a * h,
+            a * e - b * d,
+        ];
+        let determinant = a * cofactors[0] + b * cofactors[3] + c * cofactors[6];
+        if determinant.abs() < f64::EPSILON {
+            return None;
+        }
+        Some(Self(cofactors.map(|x| x / determinant)))
+    }
+
+    /// Transformed point, None if it is mapped to infinity.
+    fn apply(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let [a, b, c, d, e, f, g, h, i] = self.0;
+        let w = g * x + h * y + i;
+        if w.abs() < f64::EPSILON {
+            return None;
+        }
+        Some(((a * x + b * y + c) / w, (d * x + e * y + f) / w))
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    tools::{Color, Corner, Tool},
+    Error, Result, StaticMap,
+};
+use std::hash::Hasher;
+use tiny_skia::{FilterQuality, PathBuilder, Pixmap, PixmapMut, PixmapPaint, Stroke, Transform};
+
+/// Inset tool, showing a magnified view of a small area in a corner of the map.
+/// Use [InsetBuilder][InsetBuilder] as an entrypoint.
+///
+/// The inset is a separate [StaticMap][StaticMap] with its own size, zoom and tools,
+/// rendered when the tool is built.
+/// The area it covers is outlined on the main map and connected to the inset with leader lines.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{tools::{Corner, InsetBuilder}, StaticMapBuilder};
+///
+/// let detail = StaticMapBuilder::new()
+///     .width(100)
+///     .height(100)
+///     .zoom(14)
+///     .lat_center(52.516)
+///     .lon_center(13.378)
+///     .build()
+///     .unwrap();
+///
+/// let inset = InsetBuilder::default()
+///     .map(detail)
+///     .corner(Corner::BottomRight)
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// The inset in its corner, away from the outlined area, is part of the
+/// [drawn extents][StaticMap::drawn_extents], so cropping to content keeps it.
+/// ```rust
+/// use staticmap::{
+///     testing::MockTileFetcher,
+///     tools::{Corner, InsetBuilder},
+///     StaticMapBuilder,
+/// };
+///
+/// let detail = StaticMapBuilder::new()
+///     .width(100)
+///     .height(100)
+///     .zoom(14)
+///     .lat_center(52.5)
+///     .lon_center(13.4)
+///     .tile_fetcher(MockTileFetcher::new())
+///     .build()
+///     .unwrap();
+///
+/// let mut map = StaticMapBuilder::new()
+///     .width(400)
+///     .height(300)
+///     .zoom(10)
+///     .lat_center(52.5)
+///     .lon_center(13.4)
+///     .tile_fetcher(MockTileFetcher::new())
+///     .build()
+///     .unwrap();
+/// map.add_tool(
+///     InsetBuilder::default()
+///         .map(detail)
+///         .corner(Corner::BottomRight)
+///         .margin(10.)
+///         .build()
+///         .unwrap(),
+/// );
+///
+/// // The outlined area is a few pixels at the center, the inset is in the bottom right.
+/// let image = map.render_cropped_to_content(0).unwrap();
+/// let extent = map.drawn_extents().unwrap()[0].unwrap();
+/// assert!(extent.contains(200., 150.) && extent.contains(385., 285.));
+/// assert!(image.width() > 180 && image.height() > 130);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Inset {
+    image: Pixmap,
+    extent: (f64, f64, f64, f64),
+    corner: Corner,
+    margin: f32,
+    color: Color,
+    stroke_width: f32,
+}
+
+/// Builder for [Inset][Inset].
+pub struct InsetBuilder {
+    map: Option<StaticMap>,
+    corner: Corner,
+    margin: f32,
+    color: Color,
+    stroke_width: f32,
+}
+
+impl Default for InsetBuilder {
+    fn default() -> Self {
+        Self {
+            map: None,
+            corner: Corner::BottomRight,
+            margin: 10.,
+            color: Color::default(),
+            stroke_width: 1.,
+        }
+    }
+}
+
+impl InsetBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// The map shown in the inset.
+    pub fn map(mut self, map: StaticMap) -> Self {
+        self.map = Some(map);
+        self
+    }
+
+    /// Corner of the main map to place the inset in.
+    /// Default is [Corner::BottomRight][Corner::BottomRight].
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// Distance between the inset and the edges of the main map, in pixels.
+    /// Default is 10.0.
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Color of the inset border, the outline of the magnified area and the leader lines.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Width of the border, outline and leader lines, in pixels.
+    /// Default is 1.0.
+    pub fn stroke_width(mut self, width: f32) -> Self {
+        self.stroke_width = width;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Renders the inset map, returning an error if it is missing or fails to render.
+    pub fn build(self) -> Result<Inset> {
+        let (image, bounds) = self
+            .map
+            .ok_or(Error::BuildError("Inset map not supplied."))?
+            .render_with_bounds()?;
+
+        Ok(Inset {
+            image,
+            extent: bounds.extent(),
+            corner: self.corner,
+            margin: self.margin,
+            color: self.color,
+            stroke_width: self.stroke_width,
+        })
+    }
+}
+
+impl Tool for Inset {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        self.extent
+    }
+
+    /// Covers the outlined area, the inset in its corner and the leader lines between them.
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (lon_min, lat_min, lon_max, lat_max) = self.extent;
+        let (left, top) = bounds.lon_lat_to_px(lon_min, lat_max);
+        let (right, bottom) = bounds.lon_lat_to_px(lon_max, lat_min);
+        let source = PixelRect::new(left, top, right, bottom);
+
+        let (width, height) = (self.image.width() as f32, self.image.height() as f32);
+        let (left, top) = self.corner.anchor(bounds, width, height, self.margin);
+        let (left, top) = (f64::from(left), f64::from(top));
+        let target = PixelRect::new(left, top, left + f64::from(width), top + f64::from(height));
+
+        Some(
+            source
+                .union(&target)
+                .outset(f64::from(self.stroke_width) / 2.),
+        )
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "inset".digest(state);
+        self.image.digest(state);
+        self.extent.digest(state);
+        self.corner.digest(state);
+        self.margin.digest(state);
+        self.color.digest(state);
+        self.stroke_width.digest(state);
+        true
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let (lon_min, lat_min, lon_max, lat_max) = self.extent;
+        let (left, top) = bounds.lon_lat_to_px(lon_min, lat_max);
+        let (right, bottom) = bounds.lon_lat_to_px(lon_max, lat_min);
+        let source =
+            tiny_skia::Rect::from_ltrb(left as f32, top as f32, right as f32, bottom as f32);
+
+        let (width, height) = (self.image.width() as f32, self.image.height() as f32);
+        let (left, top) = self.corner.anchor(bounds, width, height, self.margin);
+        let target = tiny_skia::Rect::from_xywh(left, top, width, height);
+
+        let stroke = Stroke {
+            width: self.stroke_width,
+            ..Default::default()
+        };
+
+        if let (Some(source), Some(target)) = (source, target) {
+            // Connect the corners facing away from the inset corner, so the lines don't cross.
+            let pairs = match self.corner {
+                Corner::TopRight | Corner::BottomLeft => [
+                    ((source.left(), source.top()), (target.left(), target.top())),
+                    (
+                        (source.right(), source.bottom()),
+                        (target.right(), target.bottom()),
+                    ),
+                ],
+                Corner::TopLeft | Corner::BottomRight => [
+                    (
+                        (source.right(), source.top()),
+                        (target.right(), target.top()),
+                    ),
+                    (
+                        (source.left(), source.bottom()),
+                        (target.left(), target.bottom()),
+                    ),
+                ],
+            };
+
+            let mut path_builder = PathBuilder::new();
+            for (from, to) in pairs.iter() {
+                path_builder.move_to(from.0, from.1);
+                path_builder.line_to(to.0, to.1);
+            }
+            path_builder.push_rect(source);
+
+            if let Some(path) = path_builder.finish() {
+                pixmap.stroke_path(&path, &self.color.0, &stroke, transform, None);
+            }
+        }
+
+        // The inset is a rendered map, so larger scales resample it.
+        pixmap.draw_pixmap(
+            0,
+            0,
+            self.image.as_ref(),
+            &PixmapPaint {
+                quality: FilterQuality::Bicubic,
+                ..Default::default()
+            },
+            Transform::from_translate(left as i32 as f32, top as i32 as f32)
+                .post_scale(scale, scale),
+            None,
+        );
+
+        if let Some(target) = target {
+            pixmap.stroke_path(
+                &PathBuilder::from_rect(target),
+                &self.color.0,
+                &stroke,
+                transform,
+                None,
+            );
+        }
+    }
+}
@@ -1,10 +1,220 @@
 use crate::{
-    bounds::Bounds,
-    lat_to_y, lon_to_x, simplify,
-    tools::{Color, Tool},
-    Error, Result,
+    bounds::{Bounds, PixelRect},
+    clip::clip_line,
+    coord::{unzip, LatLon},
+    digest::Digest,
+    geodesy::distance,
+    geojson::Feature,
+    json::{invalid_member, Json},
+    processor::{LineProcessor, SharedProcessor},
+    scene::{member, object, Scene},
+    simplify,
+    tools::{draw_casing, edge_margin, solid_colors, Color, Tool},
+    Error, Result, TrackPoint,
 };
-use tiny_skia::{LineCap, PathBuilder, PixmapMut, Stroke, Transform};
+use std::{
+    hash::{Hash, Hasher},
+    iter::once,
+    ops::Range,
+    sync::Arc,
+    time::Duration,
+};
+use tiny_skia::{FillRule, LineCap, Path, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Ends of a line with [Arrowheads][Arrowheads].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArrowEnds {
+    /// Arrowhead at the last coordinate.
+    End,
+    /// Arrowhead at the first coordinate, pointing backwards.
+    Start,
+    /// Arrowheads at the first and last coordinate.
+    Both,
+}
+
+/// Shape of [Arrowheads][Arrowheads].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArrowStyle {
+    /// Filled triangle in the color of the line.
+    Filled,
+    /// Two strokes meeting at the tip, as wide as the line.
+    Open,
+}
+
+/// Arrowheads at the ends of a [Line][Line], showing its direction,
+/// e.g. the direction of travel or of a flow.
+///
+/// Arrowheads point along the last stretch of the line as long as the arrowhead,
+/// so noise in the last few points of a GPS track does not turn them.
+/// Lines split into parts get arrowheads at the ends of the whole line only.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{ArrowEnds, ArrowStyle, Arrowheads, LineBuilder};
+///
+/// let flow = LineBuilder::new()
+///     .coordinates(vec![(52.50, 13.40), (52.52, 13.45), (52.51, 13.50)])
+///     .width(3.)
+///     .arrowheads(
+///         Arrowheads::new()
+///             .ends(ArrowEnds::Both)
+///             .style(ArrowStyle::Open)
+///             .length(14.),
+///     )
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arrowheads {
+    ends: ArrowEnds,
+    style: ArrowStyle,
+    length: Option<f32>,
+}
+
+impl Default for Arrowheads {
+    fn default() -> Self {
+        Self {
+            ends: ArrowEnds::End,
+            style: ArrowStyle::Filled,
+            length: None,
+        }
+    }
+}
+
+impl Arrowheads {
+    /// Create new arrowheads with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Ends of the line with an arrowhead.
+    /// Default is [ArrowEnds::End][ArrowEnds::End].
+    pub fn ends(mut self, ends: ArrowEnds) -> Self {
+        self.ends = ends;
+        self
+    }
+
+    /// Shape of the arrowheads.
+    /// Default is [ArrowStyle::Filled][ArrowStyle::Filled].
+    pub fn style(mut self, style: ArrowStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Length of the arrowheads from base to tip, in pixels.
+    /// Default grows with the width of the line, 4 times the width plus 4.
+    pub fn length(mut self, length: f32) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Length of the arrowheads on a line of the given width.
+    fn length_for(&self, width: f32) -> f64 {
+        f64::from(self.length.unwrap_or(width * 4. + 4.))
+    }
+
+    /// Distance in pixels the arrowheads of a line of the given width may reach
+    /// beyond the coordinates of the line, not counting casing.
+    fn reach(&self, width: f32) -> f64 {
+        let length = self.length_for(width);
+        length.hypot(length / 2.5) + f64::from(width) / 2.
+    }
+
+    /// Arrowhead at the last point, pointing along the end of the line.
+    /// The points are trimmed so the line stops inside a filled arrowhead,
+    /// and its cap does not poke out of the tip. None if the line never moves.
+    fn head(&self, points: &mut Vec<(f64, f64)>, width: f32) -> Option<Path> {
+        let length = self.length_for(width);
+        let tip = *points.last()?;
+        let gap = |(x, y): (f64, f64)| (tip.0 - x).hypot(tip.1 - y);
+
+        // The first point back along the line at least an arrowhead away, or the first point.
+        let back = points
+            .iter()
+            .rev()
+            .copied()
+            .find(|&point| gap(point) >= length)
+            .unwrap_or(points[0]);
+        let distance = gap(back);
+        if length <= 0. || distance == 0. {
+            return None;
+        }
+        let (ux, uy) = ((tip.0 - back.0) / distance, (tip.1 - back.1) / distance);
+
+        if self.style == ArrowStyle::Filled {
+            let inset = (length / 2.).min(distance);
+            while points.len() > 1 && gap(points[points.len() - 1]) < inset {
+                points.pop();
+            }
+            points.push((tip.0 - ux * inset, tip.1 - uy * inset));
+        }
+
+        let (base_x, base_y) = (tip.0 - ux * length, tip.1 - uy * length);
+        let half = length / 2.5;
+        let left = ((base_x - uy * half) as f32, (base_y + ux * half) as f32);
+        let right = ((base_x + uy * half) as f32, (base_y - ux * half) as f32);
+
+        let mut path_builder = PathBuilder::new();
+        match self.style {
+            ArrowStyle::Filled => {
+                path_builder.move_to(tip.0 as f32, tip.1 as f32);
+                path_builder.line_to(left.0, left.1);
+                path_builder.line_to(right.0, right.1);
+                path_builder.close();
+            }
+            ArrowStyle::Open => {
+                path_builder.move_to(left.0, left.1);
+                path_builder.line_to(tip.0 as f32, tip.1 as f32);
+                path_builder.line_to(right.0, right.1);
+            }
+        }
+        path_builder.finish()
+    }
+}
+
+impl Digest for Arrowheads {
+    fn digest(&self, mut state: &mut dyn Hasher) {
+        self.ends.hash(&mut state);
+        self.style.hash(&mut state);
+        self.length.digest(state);
+    }
+}
+
+impl Scene for Arrowheads {
+    fn to_json(&self) -> Json {
+        let ends = match self.ends {
+            ArrowEnds::End => "end",
+            ArrowEnds::Start => "start",
+            ArrowEnds::Both => "both",
+        };
+        let style = match self.style {
+            ArrowStyle::Filled => "filled",
+            ArrowStyle::Open => "open",
+        };
+        object(vec![
+            ("ends", ends.into()),
+            ("style", style.into()),
+            ("length", self.length.into()),
+        ])
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        let mut arrowheads = Arrowheads::new();
+        arrowheads.ends = match json.string("ends")? {
+            None | Some("end") => ArrowEnds::End,
+            Some("start") => ArrowEnds::Start,
+            Some("both") => ArrowEnds::Both,
+            _ => return Err(invalid_member("ends", "\"end\", \"start\" or \"both\"")),
+        };
+        arrowheads.style = match json.string("style")? {
+            None | Some("filled") => ArrowStyle::Filled,
+            Some("open") => ArrowStyle::Open,
+            _ => return Err(invalid_member("style", "\"filled\" or \"open\"")),
+        };
+        arrowheads.length = json.number("length")?.map(|x| x as f32);
+        Ok(arrowheads)
+    }
+}
 
 /// Line tool.
 /// Use [LineBuilder][LineBuilder] as an entrypoint.
@@ -18,6 +228,32 @@ use tiny_skia::{LineCap, PathBuilder, PixmapMut, Stroke, Transform};
 ///     .build()
 ///     .unwrap();
 /// ```
+///
+/// Tracks with GPS dropouts can be split into separate parts at gaps,
+/// instead of drawing straight connectors across them:
+/// ```rust
+/// use staticmap::tools::LineBuilder;
+/// use std::time::Duration;
+///
+/// let track = LineBuilder::new()
+///     .coordinates(vec![(52.50, 13.40), (52.51, 13.41), (52.60, 13.55), (52.61, 13.56)])
+///     .timestamps(vec![0., 60., 1800., 1860.])
+///     .gap_distance(5000.)
+///     .gap_duration(Duration::from_secs(300))
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// Dense tracks can be simplified in meters, giving the same line at every zoom level:
+/// ```rust
+/// use staticmap::tools::LineBuilder;
+///
+/// let track = LineBuilder::new()
+///     .coordinates((0..1000).map(|i| (52.5 + i as f64 * 1e-5, 13.4)))
+///     .simplify_meters(10.)
+///     .build()
+///     .unwrap();
+/// ```
 #[derive(Debug, Clone)]
 pub struct Line {
     lat_coordinates: Vec<f64>,
@@ -26,6 +262,14 @@ pub struct Line {
     width: f32,
     simplify: bool,
     tolerance: f64,
+    simplify_meters: Option<f64>,
+    smooth: bool,
+    casing: Option<(Color, f32)>,
+    arrowheads: Option<Arrowheads>,
+    timestamps: Option<Vec<f64>>,
+    gap_distance: Option<f64>,
+    gap_duration: Option<f64>,
+    breaks: Vec<usize>,
 }
 
 /// Builder for [Line][Line].
@@ -37,6 +281,14 @@ pub struct LineBuilder {
     width: f32,
     simplify: bool,
     tolerance: f64,
+    simplify_meters: Option<f64>,
+    smooth: bool,
+    casing: Option<(Color, f32)>,
+    arrowheads: Option<Arrowheads>,
+    timestamps: Option<Vec<f64>>,
+    gap_distance: Option<f64>,
+    gap_duration: Option<f64>,
+    processor: Option<SharedProcessor>,
 }
 
 impl Default for LineBuilder {
@@ -48,6 +300,14 @@ impl Default for LineBuilder {
             width: 1.,
             simplify: false,
             tolerance: 5.,
+            simplify_meters: None,
+            smooth: false,
+            casing: None,
+            arrowheads: None,
+            timestamps: None,
+            gap_distance: None,
+            gap_duration: None,
+            processor: None,
         }
     }
 }
@@ -59,27 +319,43 @@ impl LineBuilder {
     }
 
     /// **Required**.
-    /// Takes a collection of latitude coordinates.
+    /// Takes a collection of latitude coordinates, e.g. `f64` or `f32`.
     pub fn lat_coordinates<I>(mut self, coordinates: I) -> Self
     where
-        I: IntoIterator<Item = f64>,
+        I: IntoIterator,
+        I::Item: Into<f64>,
     {
-        let coordinates = coordinates.into_iter().collect();
+        let coordinates = coordinates.into_iter().map(Into::into).collect();
         self.lat_coordinates = Some(coordinates);
         self
     }
 
     /// **Required**.
-    /// Takes a collection of longitude coordinates.
+    /// Takes a collection of longitude coordinates, e.g. `f64` or `f32`.
     pub fn lon_coordinates<I>(mut self, coordinates: I) -> Self
     where
-        I: IntoIterator<Item = f64>,
+        I: IntoIterator,
+        I::Item: Into<f64>,
     {
-        let coordinates = coordinates.into_iter().collect();
+        let coordinates = coordinates.into_iter().map(Into::into).collect();
         self.lon_coordinates = Some(coordinates);
         self
     }
 
+    /// **Required**, unless latitude and longitude coordinates are supplied separately.
+    /// Takes a collection of coordinates, e.g. (latitude, longitude) tuples,
+    /// see [LatLon][LatLon].
+    pub fn coordinates<I, C>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        let (lat_coordinates, lon_coordinates) = unzip(coordinates);
+        self.lat_coordinates = Some(lat_coordinates);
+        self.lon_coordinates = Some(lon_coordinates);
+        self
+    }
+
     /// Use [Color][Color] to to generate a color instance.
     /// Default is a black color.
     pub fn color(mut self, color: Color) -> Self {
@@ -112,21 +388,300 @@ impl LineBuilder {
         self
     }
 
+    /// Simplify the line when it is built, leaving out points closer than `meters`
+    /// to the previous point kept, along with their timestamps.
+    /// Unlike [simplify][LineBuilder::simplify], which works in pixels when drawing,
+    /// the result does not depend on the zoom level, so a line looks the same
+    /// across renders at different zoom levels. Both may be used together.
+    /// The first and last point of each part of the line are always kept.
+    /// Default is no simplification.
+    pub fn simplify_meters(mut self, meters: f64) -> Self {
+        self.simplify_meters = Some(meters);
+        self
+    }
+
+    /// Whether to draw the line as a smooth curve through its coordinates,
+    /// instead of straight segments between them, e.g. for sparse routes between cities.
+    /// The curve is a Catmull-Rom spline, applied after any simplification.
+    /// Disabled by default.
+    pub fn smooth(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+
+    /// Outline drawn beneath the line, extending `width` pixels beyond it on each side,
+    /// e.g. the white casing of a road.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Arrowheads at the ends of the line, showing its direction.
+    /// Default is no arrowheads.
+    pub fn arrowheads(mut self, arrowheads: Arrowheads) -> Self {
+        self.arrowheads = Some(arrowheads);
+        self
+    }
+
+    /// Time of each coordinate, in seconds, e.g. since the Unix epoch.
+    /// Used to split the line at gaps in time, see [gap_duration][LineBuilder::gap_duration].
+    pub fn timestamps<I>(mut self, timestamps: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.timestamps = Some(timestamps.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Split the line into separate parts where consecutive coordinates are more than
+    /// `meters` apart, e.g. at dropouts in GPS tracks.
+    /// Default is no splitting.
+    pub fn gap_distance(mut self, meters: f64) -> Self {
+        self.gap_distance = Some(meters);
+        self
+    }
+
+    /// Split the line into separate parts where the [timestamps][LineBuilder::timestamps]
+    /// of consecutive coordinates are further apart than `duration`.
+    /// Default is no splitting.
+    pub fn gap_duration(mut self, duration: Duration) -> Self {
+        self.gap_duration = Some(duration.as_secs_f64());
+        self
+    }
+
+    /// Pre-process the coordinates and timestamps of the line when it is built,
+    /// e.g. to snap a GPS track to roads, see [LineProcessor][LineProcessor].
+    /// The timestamps are kept if every processed point has a time.
+    /// Default is no processing.
+    pub fn processor<P: LineProcessor + 'static>(mut self, processor: P) -> Self {
+        self.processor = Some(SharedProcessor(Arc::new(processor)));
+        self
+    }
+
     /// Build the tool, consuming the builder.
-    /// Returns an error if the builder is missing required fields.
+    /// Returns an error if the builder is missing required fields,
+    /// if there are not as many timestamps as coordinates, or if the processor fails.
     pub fn build(self) -> Result<Line> {
-        Ok(Line {
-            lat_coordinates: self
-                .lat_coordinates
-                .ok_or(Error::BuildError("Latitude coordinates not supplied."))?,
-            lon_coordinates: self
-                .lon_coordinates
-                .ok_or(Error::BuildError("Longitude coordinates not supplied."))?,
+        let mut lat_coordinates = self
+            .lat_coordinates
+            .ok_or(Error::BuildError("Latitude coordinates not supplied."))?;
+        let mut lon_coordinates = self
+            .lon_coordinates
+            .ok_or(Error::BuildError("Longitude coordinates not supplied."))?;
+        let mut timestamps = self.timestamps;
+
+        if let Some(ref timestamps) = timestamps {
+            if timestamps.len() != lat_coordinates.len() {
+                return Err(Error::BuildError(
+                    "Timestamps and coordinates must be of equal length.",
+                ));
+            }
+        }
+
+        if let Some(SharedProcessor(processor)) = self.processor {
+            let points = lat_coordinates
+                .iter()
+                .zip(&lon_coordinates)
+                .enumerate()
+                .map(|(index, (&lat, &lon))| TrackPoint {
+                    lat,
+                    lon,
+                    time: timestamps.as_ref().map(|x| x[index]),
+                })
+                .collect();
+            let points = processor.process(points)?;
+
+            lat_coordinates = points.iter().map(|x| x.lat).collect();
+            lon_coordinates = points.iter().map(|x| x.lon).collect();
+            timestamps = points.iter().map(|x| x.time).collect();
+        }
+
+        let mut line = Line {
+            lat_coordinates,
+            lon_coordinates,
             color: self.color,
             width: self.width,
             simplify: self.simplify,
+            casing: self.casing,
+            arrowheads: self.arrowheads,
             tolerance: self.tolerance,
-        })
+            simplify_meters: self.simplify_meters,
+            smooth: self.smooth,
+            timestamps,
+            gap_distance: self.gap_distance,
+            gap_duration: self.gap_duration,
+            breaks: Vec::new(),
+        };
+        line.breaks = line.gaps();
+        if let Some(meters) = self.simplify_meters {
+            line.simplify_geographic(meters);
+        }
+        Ok(line)
+    }
+}
+
+impl LineBuilder {
+    /// Builder with the settings of a line in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.lat_coordinates = json.numbers("lat_coordinates")?;
+        builder.lon_coordinates = json.numbers("lon_coordinates")?;
+        builder.width = json.number("width")?.map_or(builder.width, |x| x as f32);
+        builder.simplify = json.boolean("simplify")?.unwrap_or(false);
+        builder.tolerance = json.number("tolerance")?.unwrap_or(builder.tolerance);
+        builder.simplify_meters = json.number("simplify_meters")?;
+        builder.smooth = json.boolean("smooth")?.unwrap_or(false);
+        builder.casing = member(json, "casing")?;
+        builder.arrowheads = member(json, "arrowheads")?;
+        if let Some(color) = member(json, "color")? {
+            builder.color = color;
+        }
+        builder.timestamps = json.numbers("timestamps")?;
+        builder.gap_distance = json.number("gap_distance")?;
+        builder.gap_duration = json.number("gap_duration")?;
+        Ok(builder)
+    }
+}
+
+impl Line {
+    /// Indices of the coordinates starting a new part, after a gap in distance or time.
+    fn gaps(&self) -> Vec<usize> {
+        let length = self.lat_coordinates.len().min(self.lon_coordinates.len());
+
+        (1..length)
+            .filter(|&index| {
+                let far = match self.gap_distance {
+                    Some(gap) => {
+                        distance(
+                            self.lat_coordinates[index - 1],
+                            self.lon_coordinates[index - 1],
+                            self.lat_coordinates[index],
+                            self.lon_coordinates[index],
+                        ) > gap
+                    }
+                    None => false,
+                };
+                let late = match (self.gap_duration, self.timestamps.as_ref()) {
+                    (Some(gap), Some(timestamps)) => {
+                        timestamps[index] - timestamps[index - 1] > gap
+                    }
+                    _ => false,
+                };
+                far || late
+            })
+            .collect()
+    }
+
+    /// Ranges of the coordinates of each part of the line.
+    fn parts(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        let length = self.lat_coordinates.len().min(self.lon_coordinates.len());
+        let starts = once(0).chain(self.breaks.iter().copied());
+        let ends = self.breaks.iter().copied().chain(once(length));
+        starts.zip(ends).map(|(start, end)| start..end)
+    }
+
+    /// Points of a part of the line on the map, in pixels, as they are drawn.
+    fn part_points(&self, part: Range<usize>, bounds: &Bounds) -> Vec<(f64, f64)> {
+        let mut points: Vec<(f64, f64)> = self.lon_coordinates[part.clone()]
+            .iter()
+            .zip(self.lat_coordinates[part].iter())
+            .map(|(x, y)| bounds.lon_lat_to_px(*x, *y))
+            .collect();
+
+        // Stationary periods in GPS data give repeated points, which add nothing to the path.
+        points.dedup();
+
+        if self.simplify {
+            points = simplify(points, self.tolerance);
+        }
+
+        if self.smooth {
+            points = smooth(&points);
+        }
+        points
+    }
+
+    /// Leave out points closer than `meters` to the previous point kept in their part.
+    /// Parts are simplified separately, after the gaps between them are found,
+    /// so simplification never joins or splits parts.
+    fn simplify_geographic(&mut self, meters: f64) {
+        let mut keep = Vec::new();
+        let mut breaks = Vec::new();
+
+        for part in self.parts() {
+            if part.is_empty() {
+                continue;
+            }
+            if !keep.is_empty() {
+                breaks.push(keep.len());
+            }
+
+            let (first, last) = (part.start, part.end - 1);
+            let mut kept = first;
+            keep.push(first);
+            for index in part.skip(1) {
+                let far = distance(
+                    self.lat_coordinates[kept],
+                    self.lon_coordinates[kept],
+                    self.lat_coordinates[index],
+                    self.lon_coordinates[index],
+                ) > meters;
+                if far || index == last {
+                    keep.push(index);
+                    kept = index;
+                }
+            }
+        }
+
+        let pick = |values: &[f64]| keep.iter().map(|&index| values[index]).collect();
+        self.lat_coordinates = pick(&self.lat_coordinates);
+        self.lon_coordinates = pick(&self.lon_coordinates);
+        self.timestamps = self.timestamps.as_deref().map(pick);
+        self.breaks = breaks;
+    }
+
+    /// Arrowheads at the ends of the line, trimming the points of the first and last part
+    /// to fit them. Parts which never move get no arrowhead.
+    fn heads(
+        &self,
+        arrowheads: &Arrowheads,
+        parts: &mut [Vec<(f64, f64)>],
+    ) -> Vec<(Path, ArrowStyle)> {
+        let mut heads = Vec::new();
+
+        if matches!(arrowheads.ends, ArrowEnds::Start | ArrowEnds::Both) {
+            if let Some(points) = parts.first_mut().filter(|x| x.len() > 1) {
+                points.reverse();
+                heads.extend(arrowheads.head(points, self.width));
+                points.reverse();
+            }
+        }
+        if matches!(arrowheads.ends, ArrowEnds::End | ArrowEnds::Both) {
+            if let Some(points) = parts.last_mut().filter(|x| x.len() > 1) {
+                heads.extend(arrowheads.head(points, self.width));
+            }
+        }
+
+        heads
+            .into_iter()
+            .map(|head| (head, arrowheads.style))
+            .collect()
+    }
+
+    /// Draw a part which never moves as a dot.
+    fn draw_dot(&self, (x, y): (f64, f64), pixmap: &mut PixmapMut, transform: Transform) {
+        if let Some((color, width)) = self.casing.as_ref() {
+            if let Some(casing) =
+                PathBuilder::from_circle(x as f32, y as f32, self.width / 2. + width)
+            {
+                pixmap.fill_path(&casing, &color.0, FillRule::Winding, transform, None);
+            }
+        }
+
+        if let Some(dot) = PathBuilder::from_circle(x as f32, y as f32, self.width / 2.) {
+            pixmap.fill_path(&dot, &self.color.0, FillRule::Winding, transform, None);
+        }
     }
 }
 
@@ -152,44 +707,210 @@ impl Tool for Line {
         )
     }
 
-    fn draw(&self, bounds: &Bounds, mut pixmap: PixmapMut) {
-        let mut path_builder = PathBuilder::new();
-        let mut points: Vec<(f64, f64)> = self
-            .lon_coordinates
-            .iter()
-            .zip(self.lat_coordinates.iter())
-            .map(|(x, y)| {
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        // Smooth curves may bulge beyond the coordinates, so the drawn points are used.
+        let points: Vec<(f64, f64)> = match self.smooth {
+            true => self
+                .parts()
+                .flat_map(|part| self.part_points(part, bounds))
+                .collect(),
+            false => self
+                .lon_coordinates
+                .iter()
+                .zip(self.lat_coordinates.iter())
+                .map(|(lon, lat)| bounds.lon_lat_to_px(*lon, *lat))
+                .collect(),
+        };
+        let mut points = points.into_iter();
+
+        let (x, y) = points.next()?;
+        let rect = points.fold(PixelRect::new(x, y, x, y), |rect, (x, y)| {
+            PixelRect::new(
+                rect.x_min.min(x),
+                rect.y_min.min(y),
+                rect.x_max.max(x),
+                rect.y_max.max(y),
+            )
+        });
+
+        // Miter joins reach up to the miter limit times half the width beyond the points.
+        let miter_limit = f64::from(Stroke::default().miter_limit);
+        let margin = miter_limit * edge_margin(Some(self.width), &self.casing);
+        let arrowheads = self.arrowheads.map_or(0., |x| {
+            x.reach(self.width) + edge_margin(None, &self.casing)
+        });
+        Some(rect.outset(margin.max(arrowheads)))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "line".digest(state);
+        self.lat_coordinates.digest(state);
+        self.lon_coordinates.digest(state);
+        self.color.digest(state);
+        self.width.digest(state);
+        self.simplify.digest(state);
+        self.tolerance.digest(state);
+        self.smooth.digest(state);
+        self.casing.digest(state);
+        self.arrowheads.digest(state);
+        self.breaks.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let feature = match self.breaks.is_empty() {
+            true => Feature::line_string(&self.lon_coordinates, &self.lat_coordinates),
+            false => Feature::multi_line_string(self.parts().map(|part| {
                 (
-                    bounds.x_to_px(lon_to_x(*x, bounds.zoom)),
-                    bounds.y_to_px(lat_to_y(*y, bounds.zoom)),
+                    &self.lon_coordinates[part.clone()],
+                    &self.lat_coordinates[part],
                 )
-            })
+            })),
+        };
+        let feature = feature
+            .color("stroke", &self.color)
+            .number("stroke-width", self.width.into());
+        Some(feature.to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        if !solid_colors(&self.color, &self.casing) {
+            return None;
+        }
+
+        let scene = object(vec![
+            ("type", "line".into()),
+            ("lat_coordinates", self.lat_coordinates.clone().into()),
+            ("lon_coordinates", self.lon_coordinates.clone().into()),
+            ("color", self.color.to_json()),
+            ("width", self.width.into()),
+            ("simplify", self.simplify.into()),
+            ("tolerance", self.tolerance.into()),
+            ("simplify_meters", self.simplify_meters.into()),
+            ("smooth", self.smooth.into()),
+            ("casing", self.casing.as_ref().map(Scene::to_json).into()),
+            (
+                "arrowheads",
+                self.arrowheads.as_ref().map(Scene::to_json).into(),
+            ),
+            ("timestamps", self.timestamps.clone().into()),
+            ("gap_distance", self.gap_distance.into()),
+            ("gap_duration", self.gap_duration.into()),
+        ]);
+        Some(scene.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let mut path_builder = PathBuilder::new();
+
+        // Only the parts on the map are drawn, with room for the caps and casing.
+        let viewport = bounds
+            .pixel_rect()
+            .outset(edge_margin(Some(self.width), &self.casing) + 1.);
+
+        let mut parts: Vec<_> = self
+            .parts()
+            .map(|part| self.part_points(part, bounds))
             .collect();
+        let heads = self
+            .arrowheads
+            .map(|arrowheads| self.heads(&arrowheads, &mut parts))
+            .unwrap_or_default();
 
-        if self.simplify {
-            points = simplify(points, self.tolerance);
+        for points in parts {
+            // A single point, or a part which never moves, is drawn as a dot.
+            if let [point] = points[..] {
+                self.draw_dot(point, &mut pixmap, transform);
+                continue;
+            }
+
+            for run in clip_line(&points, &viewport) {
+                for (index, point) in run.iter().enumerate() {
+                    let (x, y) = bounds.snap_stroke(*point, self.width);
+                    let (x, y) = (x as f32, y as f32);
+                    match index {
+                        0 => path_builder.move_to(x, y),
+                        _ => path_builder.line_to(x, y),
+                    }
+                }
+            }
         }
 
-        for (index, point) in points.iter().enumerate() {
-            let (x, y) = (point.0 as f32, point.1 as f32);
-            match index {
-                0 => path_builder.move_to(x, y),
-                _ => path_builder.line_to(x, y),
+        let stroke = Stroke {
+            width: self.width,
+            line_cap: LineCap::Round,
+            ..Default::default()
+        };
+        let path = path_builder.finish();
+
+        if let Some(casing) = self.casing.as_ref() {
+            if let Some(ref path) = path {
+                draw_casing(
+                    &mut pixmap,
+                    path,
+                    casing,
+                    Some(self.width),
+                    LineCap::Round,
+                    transform,
+                );
+            }
+            for (head, style) in heads.iter() {
+                let width = (*style == ArrowStyle::Open).then_some(self.width);
+                draw_casing(&mut pixmap, head, casing, width, LineCap::Round, transform);
+            }
+        }
+
+        if let Some(ref path) = path {
+            pixmap.stroke_path(path, &self.color.0, &stroke, transform, None);
+        }
+
+        for (head, style) in heads.iter() {
+            match style {
+                ArrowStyle::Filled => {
+                    pixmap.fill_path(head, &self.color.0, FillRule::Winding, transform, None)
+                }
+                ArrowStyle::Open => {
+                    pixmap.stroke_path(head, &self.color.0, &stroke, transform, None)
+                }
             }
         }
+    }
+}
+
+/// Points along a Catmull-Rom spline through the points, about two pixels apart.
+/// Each segment is drawn as the cubic Bézier curve between its ends, with control points
+/// from the neighbouring points, and the ends of the line repeated as their own neighbours.
+fn smooth(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let last = points.len() - 1;
+    let mut smoothed = vec![points[0]];
+    for index in 0..last {
+        let p0 = points[index.saturating_sub(1)];
+        let (p1, p2) = (points[index], points[index + 1]);
+        let p3 = points[(index + 2).min(last)];
+
+        let c1 = (p1.0 + (p2.0 - p0.0) / 6., p1.1 + (p2.1 - p0.1) / 6.);
+        let c2 = (p2.0 - (p3.0 - p1.0) / 6., p2.1 - (p3.1 - p1.1) / 6.);
 
-        if let Some(path) = path_builder.finish() {
-            pixmap.stroke_path(
-                &path,
-                &self.color.0,
-                &Stroke {
-                    width: self.width,
-                    line_cap: LineCap::Round,
-                    ..Default::default()
-                },
-                Transform::default(),
-                None,
-            );
+        let length = (p2.0 - p1.0).hypot(p2.1 - p1.1);
+        let steps = (length / 2.).ceil().clamp(1., 256.) as usize;
+        for step in 1..=steps {
+            let t = step as f64 / steps as f64;
+            let u = 1. - t;
+            let (a, b, c, d) = (u * u * u, 3. * u * u * t, 3. * u * t * t, t * t * t);
+            smoothed.push((
+                a * p1.0 + b * c1.0 + c * c2.0 + d * p2.0,
+                a * p1.1 + b * c1.1 + c * c2.1 + d * p2.1,
+            ));
         }
     }
+    smoothed
 }
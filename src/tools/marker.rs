@@ -0,0 +1,424 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geojson::Feature,
+    json::Json,
+    lat_to_y, lon_to_x_continuous,
+    scene::{member, object, Scene},
+    tools::{solid_colors, Color, Tool},
+    x_to_lon, y_to_lat, Error, Result,
+};
+use std::{
+    f32::consts::PI,
+    hash::{Hash, Hasher},
+};
+use tiny_skia::{FillRule, Path, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Shape of a [Marker][Marker].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarkerShape {
+    /// A teardrop shaped pin with a hole, with its tip at the coordinate.
+    Pin,
+    /// A flag on a pole, with the foot of the pole at the coordinate.
+    Flag,
+    /// A square centered on the coordinate.
+    Square,
+    /// A square standing on a corner, centered on the coordinate.
+    Diamond,
+    /// A five-pointed star centered on the coordinate.
+    Star,
+    /// A plus sign centered on the coordinate.
+    Cross,
+}
+
+impl MarkerShape {
+    /// Name of the shape in scenes and GeoJSON.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            MarkerShape::Pin => "pin",
+            MarkerShape::Flag => "flag",
+            MarkerShape::Square => "square",
+            MarkerShape::Diamond => "diamond",
+            MarkerShape::Star => "star",
+            MarkerShape::Cross => "cross",
+        }
+    }
+
+    /// Radius of the head of a pin of the given size.
+    fn pin_radius(size: f32) -> f32 {
+        0.36 * size
+    }
+
+    /// Width of the pole of a flag of the given size.
+    fn pole_width(size: f32) -> f32 {
+        (size / 12.).max(1.)
+    }
+
+    /// Rectangle covered by the shape, relative to the coordinate.
+    fn rect(&self, size: f32) -> (f32, f32, f32, f32) {
+        let half = size / 2.;
+        match self {
+            MarkerShape::Pin => {
+                let radius = Self::pin_radius(size);
+                (-radius, -size, radius, 0.)
+            }
+            MarkerShape::Flag => {
+                let pole = Self::pole_width(size) / 2.;
+                (-pole, -size, pole + 0.6 * size, 0.)
+            }
+            _ => (-half, -half, half, half),
+        }
+    }
+
+    /// Outline of the shape with its coordinate at (x, y).
+    fn path(&self, x: f32, y: f32, size: f32) -> Option<Path> {
+        let half = size / 2.;
+        let mut pb = PathBuilder::new();
+
+        let polygon = |pb: &mut PathBuilder, points: &[(f32, f32)]| {
+            for (index, &(px, py)) in points.iter().enumerate() {
+                if index == 0 {
+                    pb.move_to(x + px, y + py);
+                } else {
+                    pb.line_to(x + px, y + py);
+                }
+            }
+            pb.close();
+        };
+
+        match self {
+            MarkerShape::Pin => {
+                let radius = Self::pin_radius(size);
+                let (cx, cy) = (x, y - size + radius);
+                // Angle between the axis of the pin and the points where its sides
+                // touch the head.
+                let touch = (radius / (size - radius)).acos();
+
+                pb.move_to(x, y);
+                let steps = 32;
+                for step in 0..=steps {
+                    let angle = touch + (2. * PI - 2. * touch) * step as f32 / steps as f32;
+                    pb.line_to(cx + radius * angle.sin(), cy + radius * angle.cos());
+                }
+                pb.close();
+                pb.push_circle(cx, cy, 0.4 * radius);
+            }
+            MarkerShape::Flag => {
+                let pole = Self::pole_width(size) / 2.;
+                polygon(
+                    &mut pb,
+                    &[(-pole, -size), (pole, -size), (pole, 0.), (-pole, 0.)],
+                );
+                polygon(
+                    &mut pb,
+                    &[
+                        (pole, -size),
+                        (pole + 0.6 * size, -size),
+                        (pole + 0.6 * size, -0.6 * size),
+                        (pole, -0.6 * size),
+                    ],
+                );
+            }
+            MarkerShape::Square => {
+                polygon(
+                    &mut pb,
+                    &[(-half, -half), (half, -half), (half, half), (-half, half)],
+                );
+            }
+            MarkerShape::Diamond => {
+                polygon(&mut pb, &[(0., -half), (half, 0.), (0., half), (-half, 0.)]);
+            }
+            MarkerShape::Star => {
+                let points: Vec<(f32, f32)> = (0..10)
+                    .map(|index| {
+                        let radius = if index % 2 == 0 { half } else { 0.4 * half };
+                        let angle = index as f32 * PI / 5.;
+                        (radius * angle.sin(), -radius * angle.cos())
+                    })
+                    .collect();
+                polygon(&mut pb, &points);
+            }
+            MarkerShape::Cross => {
+                let arm = size / 6.;
+                polygon(
+                    &mut pb,
+                    &[
+                        (-arm, -half),
+                        (arm, -half),
+                        (arm, -arm),
+                        (half, -arm),
+                        (half, arm),
+                        (arm, arm),
+                        (arm, half),
+                        (-arm, half),
+                        (-arm, arm),
+                        (-half, arm),
+                        (-half, -arm),
+                        (-arm, -arm),
+                    ],
+                );
+            }
+        }
+
+        pb.finish()
+    }
+}
+
+/// Marker tool, a vector symbol such as a pin or a star at a coordinate,
+/// for common symbols without image files.
+/// Use [MarkerBuilder][MarkerBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, MarkerBuilder, MarkerShape};
+///
+/// let marker = MarkerBuilder::new()
+///     .lat_coordinate(52.5)
+///     .lon_coordinate(13.4)
+///     .shape(MarkerShape::Pin)
+///     .size(32.)
+///     .color(Color::new(true, 220, 40, 40, 255))
+///     .border(Color::new(true, 255, 255, 255, 255), 1.5)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Marker {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    shape: MarkerShape,
+    size: f32,
+    color: Color,
+    border: Option<(Color, f32)>,
+    priority: Option<f64>,
+}
+
+/// Builder for [Marker][Marker].
+#[derive(Debug, Clone)]
+pub struct MarkerBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    shape: MarkerShape,
+    size: f32,
+    color: Color,
+    border: Option<(Color, f32)>,
+    priority: Option<f64>,
+}
+
+impl Default for MarkerBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinate: None,
+            lon_coordinate: None,
+            shape: MarkerShape::Pin,
+            size: 24.,
+            color: Color::default(),
+            border: None,
+            priority: None,
+        }
+    }
+}
+
+impl MarkerBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// Latitude coordinate of the marker.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// Longitude coordinate of the marker.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// Shape of the marker.
+    /// Default is [MarkerShape::Pin][MarkerShape::Pin].
+    pub fn shape(mut self, shape: MarkerShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Height of the marker, in pixels.
+    /// Default is 24.0.
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Outline of the marker, `width` pixels wide, to set it apart from the map.
+    pub fn border(mut self, color: Color, width: f32) -> Self {
+        self.border = Some((color, width));
+        self
+    }
+
+    /// Priority of the marker when overlapping markers are decluttered, higher is placed first,
+    /// see [Declutter][crate::Declutter].
+    /// Default is no priority, always drawing the marker.
+    pub fn priority(mut self, priority: f64) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields.
+    pub fn build(self) -> Result<Marker> {
+        Ok(Marker {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            shape: self.shape,
+            size: self.size,
+            color: self.color,
+            border: self.border,
+            priority: self.priority,
+        })
+    }
+
+    /// Builder from the representation of a marker in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.lat_coordinate = json.number("lat_coordinate")?;
+        builder.lon_coordinate = json.number("lon_coordinate")?;
+        builder.size = json.number("size")?.map_or(builder.size, |x| x as f32);
+        builder.border = member(json, "border")?;
+        builder.priority = json.number("priority")?;
+        if let Some(shape) = member(json, "shape")? {
+            builder.shape = shape;
+        }
+        if let Some(color) = member(json, "color")? {
+            builder.color = color;
+        }
+        Ok(builder)
+    }
+}
+
+impl Marker {
+    /// Rectangle covered by the marker and its border, relative to its coordinate.
+    fn rect(&self) -> (f64, f64, f64, f64) {
+        let border = self.border.as_ref().map_or(0., |x| x.1 / 2.);
+        let (left, top, right, bottom) = self.shape.rect(self.size);
+        (
+            f64::from(left - border),
+            f64::from(top - border),
+            f64::from(right + border),
+            f64::from(bottom + border),
+        )
+    }
+}
+
+impl Tool for Marker {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        let (left, top, right, bottom) = self.rect();
+
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
+        let y = lat_to_y(self.lat_coordinate, zoom);
+
+        // Offsets from the coordinate, so coordinates outside the geographic range,
+        // e.g. on a Cartesian grid, are not wrapped around.
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x + left / tile_size, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + bottom / tile_size, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + right / tile_size, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y + top / tile_size, zoom) - lat;
+
+        (lon_min, lat_min, lon_max, lat_max)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (left, top, right, bottom) = self.rect();
+        Some(PixelRect::new(x + left, y + top, x + right, y + bottom))
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        Some((self.lon_coordinate, self.lat_coordinate))
+    }
+
+    fn priority(&self) -> Option<f64> {
+        self.priority
+    }
+
+    fn digest(&self, mut state: &mut dyn Hasher) -> bool {
+        "marker".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.shape.hash(&mut state);
+        self.size.digest(state);
+        self.color.digest(state);
+        self.border.digest(state);
+        self.priority.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let feature = Feature::point(self.lon_coordinate, self.lat_coordinate)
+            .string("marker-symbol", self.shape.name())
+            .color("marker-color", &self.color);
+        Some(feature.to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        if !solid_colors(&self.color, &self.border) {
+            return None;
+        }
+
+        let scene = object(vec![
+            ("type", "marker".into()),
+            ("lat_coordinate", self.lat_coordinate.into()),
+            ("lon_coordinate", self.lon_coordinate.into()),
+            ("shape", self.shape.to_json()),
+            ("size", self.size.into()),
+            ("color", self.color.to_json()),
+            ("border", self.border.as_ref().map(Scene::to_json).into()),
+            ("priority", self.priority.into()),
+        ]);
+        Some(scene.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let path = match self.shape.path(x as f32, y as f32, self.size) {
+            Some(path) => path,
+            None => return,
+        };
+
+        // The hole of a pin is an inner ring.
+        pixmap.fill_path(&path, &self.color.0, FillRule::EvenOdd, transform, None);
+
+        if let Some((ref color, width)) = self.border {
+            pixmap.stroke_path(
+                &path,
+                &color.0,
+                &Stroke {
+                    width,
+                    ..Default::default()
+                },
+                transform,
+                None,
+            );
+        }
+    }
+}
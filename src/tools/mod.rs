@@ -1,14 +1,84 @@
-use crate::bounds::Bounds;
-use tiny_skia::{Paint, PixmapMut, Shader};
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::{mark_uncacheable, Digest},
+    json::{invalid_member, Json},
+    scene::{object, Scene},
+    Result,
+};
+use std::hash::{Hash, Hasher};
+use tiny_skia::{
+    FilterQuality, LineCap, Paint, Path, Pixmap, PixmapMut, PixmapPaint, Shader, Stroke, Transform,
+};
 
+mod annulus;
+mod chart;
 mod circle;
+mod compass;
+mod custom;
+mod ellipse;
+mod emoji;
+mod flight;
+mod geodesic_line;
+mod gradient;
+mod gradient_line;
 mod icon;
+mod image_overlay;
+mod inset;
 mod line;
+mod marker;
+mod nautical;
+mod polygon;
+mod polygon_label;
+mod proportional;
 mod rect;
-pub use circle::{Circle, CircleBuilder};
+mod route;
+mod screen_overlay;
+mod sector;
+mod shield;
+mod stamp;
+#[cfg(feature = "svg")]
+mod svg;
+mod text;
+mod transformed;
+mod units;
+pub use annulus::{Annulus, AnnulusBuilder};
+pub use chart::{Chart, ChartBuilder, ChartKind};
+pub use circle::{Circle, CircleBuilder, CircleTicks, TickLabels};
+pub use compass::{Compass, CompassBuilder, CompassStyle};
+pub use custom::CustomTool;
+pub use ellipse::{Ellipse, EllipseBuilder};
+pub use emoji::{Emoji, EmojiBuilder};
+pub use flight::{FlightRoute, FlightRouteBuilder};
+pub use geodesic_line::{GeodesicLine, GeodesicLineBuilder};
+pub use gradient::{ColorRamp, Gradient};
+pub use gradient_line::{GradientLine, GradientLineBuilder};
 pub use icon::{Icon, IconBuilder};
-pub use line::{Line, LineBuilder};
+pub use image_overlay::{ImageOverlay, ImageOverlayBuilder};
+pub use inset::{Inset, InsetBuilder};
+pub use line::{ArrowEnds, ArrowStyle, Arrowheads, Line, LineBuilder};
+pub use marker::{Marker, MarkerBuilder, MarkerShape};
+pub use nautical::{
+    BearingLines, BearingLinesBuilder, BearingReference, CourseLine, CourseLineBuilder, RangeRings,
+    RangeRingsBuilder,
+};
+pub use polygon::{MultiPolygon, MultiPolygonBuilder, Polygon, PolygonBuilder};
+pub use polygon_label::{LabelOverflow, PolygonLabel, PolygonLabelBuilder};
+pub use proportional::{
+    ProportionalLegend, ProportionalLegendBuilder, ProportionalSymbols, ProportionalSymbolsBuilder,
+    SymbolScaling,
+};
 pub use rect::{Rect, RectBuilder};
+pub use route::{Route, RouteBuilder};
+pub use screen_overlay::{Alignment, ScreenOverlay};
+pub use sector::{Sector, SectorBuilder};
+pub use shield::{Shield, ShieldBuilder, ShieldShape};
+pub use stamp::{Stamp, StampBuilder};
+#[cfg(feature = "svg")]
+pub use svg::{SvgSymbol, SvgSymbolBuilder};
+pub use text::{Font, Text, TextBuilder, TextStyle};
+pub use tiny_skia::FillRule;
+pub use transformed::Transformed;
+pub use units::{UnitSystem, Units};
 
 #[derive(Debug, Clone, Default)]
 /// Path color.
@@ -21,6 +91,19 @@ pub use rect::{Rect, RectBuilder};
 /// let solid_red = Color::new(true, 255, 0, 0, 255);
 /// let semitransparent_blue = Color::new(true, 0, 255, 0, 125);
 /// ```
+///
+/// Use [Color::from_paint][Color::from_paint] for anything tiny-skia can paint,
+/// such as gradients, patterns and blend modes.
+///
+/// ```rust
+/// use staticmap::tools::Color;
+/// use tiny_skia::{BlendMode, Paint};
+///
+/// let mut paint = Paint::default();
+/// paint.set_color_rgba8(0, 0, 0, 255);
+/// paint.blend_mode = BlendMode::Multiply;
+/// let multiply = Color::from_paint(paint);
+/// ```
 pub struct Color(Paint<'static>);
 
 impl Color {
@@ -32,6 +115,156 @@ impl Color {
             ..Default::default()
         })
     }
+
+    /// Creates a new [Color][Color] instance from a tiny-skia paint,
+    /// keeping its shader, blend mode and other settings.
+    ///
+    /// Only solid colors can be part of scenes, so tools using other shaders return None
+    /// from [Tool::scene][Tool::scene], and are exported to GeoJSON as black.
+    pub fn from_paint(paint: Paint<'static>) -> Color {
+        Color(paint)
+    }
+
+    /// The tiny-skia paint of the color, e.g. for drawing in custom tools.
+    pub fn paint(&self) -> &Paint<'static> {
+        &self.0
+    }
+
+    /// Whether the paint is a solid color.
+    pub(crate) fn is_solid(&self) -> bool {
+        matches!(self.0.shader, Shader::SolidColor(_))
+    }
+
+    /// The solid color of the paint, or black if it uses another shader.
+    pub(crate) fn solid(&self) -> tiny_skia::Color {
+        match self.0.shader {
+            Shader::SolidColor(color) => color,
+            _ => tiny_skia::Color::BLACK,
+        }
+    }
+}
+
+/// Whether the color and casing of a tool can be part of a scene.
+pub(crate) fn solid_colors(color: &Color, casing: &Option<(Color, f32)>) -> bool {
+    color.is_solid() && casing.iter().all(|x| x.0.is_solid())
+}
+
+/// Distance in pixels a shape draws beyond its outline, with a stroke of the given width,
+/// or filled if None, and an optional casing.
+pub(crate) fn edge_margin(stroke_width: Option<f32>, casing: &Option<(Color, f32)>) -> f64 {
+    let stroke = stroke_width.map_or(0., |x| x / 2.);
+    let casing = casing.as_ref().map_or(0., |x| x.1);
+    f64::from(stroke + casing)
+}
+
+/// Stroke the casing of a shape, an outline extending `width` pixels beyond the shape,
+/// beneath it. `stroke_width` is the width of the shape's stroke, or None if it is filled.
+/// The path and widths are scaled by the transform.
+pub(crate) fn draw_casing(
+    pixmap: &mut PixmapMut,
+    path: &Path,
+    casing: &(Color, f32),
+    stroke_width: Option<f32>,
+    line_cap: LineCap,
+    transform: Transform,
+) {
+    let (color, width) = casing;
+    pixmap.stroke_path(
+        path,
+        &color.0,
+        &Stroke {
+            width: stroke_width.unwrap_or(0.) + 2. * width,
+            line_cap,
+            ..Default::default()
+        },
+        transform,
+        None,
+    );
+}
+
+/// Corner of the map, used to place decorations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corner {
+    /// Top left corner.
+    TopLeft,
+    /// Top right corner.
+    TopRight,
+    /// Bottom left corner.
+    BottomLeft,
+    /// Bottom right corner.
+    BottomRight,
+}
+
+/// Solid colors only, other shaders make the render uncacheable.
+impl Digest for Color {
+    fn digest(&self, state: &mut dyn Hasher) {
+        if !self.is_solid() {
+            mark_uncacheable();
+        }
+        let color = self.solid().to_color_u8();
+        state.write(&[color.red(), color.green(), color.blue(), color.alpha()]);
+        self.0.anti_alias.digest(state);
+        format!("{:?}", self.0.blend_mode).digest(state);
+    }
+}
+
+/// Solid colors only, see [Color::from_paint][Color::from_paint].
+impl Scene for Color {
+    fn to_json(&self) -> Json {
+        let color = self.solid().to_color_u8();
+        object(vec![
+            (
+                "rgba",
+                vec![color.red(), color.green(), color.blue(), color.alpha()].into(),
+            ),
+            ("anti_alias", self.0.anti_alias.into()),
+        ])
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        let rgba = json
+            .numbers("rgba")?
+            .filter(|x| x.len() == 4)
+            .ok_or_else(|| invalid_member("rgba", "an array of four numbers"))?;
+        let channel = |index: usize| rgba[index].clamp(0., 255.).round() as u8;
+
+        Ok(Color::new(
+            json.boolean("anti_alias")?.unwrap_or(true),
+            channel(0),
+            channel(1),
+            channel(2),
+            channel(3),
+        ))
+    }
+}
+
+impl Digest for Corner {
+    fn digest(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
+}
+
+impl Corner {
+    /// Top left position of an item with the given size placed in this corner.
+    pub(crate) fn anchor(
+        &self,
+        bounds: &Bounds,
+        width: f32,
+        height: f32,
+        margin: f32,
+    ) -> (f32, f32) {
+        let left = margin;
+        let top = margin;
+        let right = bounds.width as f32 - margin - width;
+        let bottom = bounds.height as f32 - margin - height;
+
+        match self {
+            Corner::TopLeft => (left, top),
+            Corner::TopRight => (right, top),
+            Corner::BottomLeft => (left, bottom),
+            Corner::BottomRight => (right, bottom),
+        }
+    }
 }
 
 /// Trait implemented by types which can be drawn to a map.
@@ -40,4 +273,139 @@ pub trait Tool {
     fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64);
     /// Draw the object to the pixmap using a PathBuilder.
     fn draw(&self, bounds: &Bounds, pixmap: PixmapMut);
+    /// Draw the object `scale` times larger, onto a pixmap `scale` times the size of the map
+    /// the bounds describe, e.g. for [StaticMap::encode_png_multi][crate::StaticMap::encode_png_multi].
+    ///
+    /// Default draws the object at 1x and scales up the pixels, which blurs strokes and text.
+    /// The built-in tools draw at the scale instead.
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        if scale == 1. {
+            return self.draw(bounds, pixmap);
+        }
+        let mut layer = match Pixmap::new(bounds.width, bounds.height) {
+            Some(layer) => layer,
+            None => return,
+        };
+        self.draw(bounds, layer.as_mut());
+        pixmap.draw_pixmap(
+            0,
+            0,
+            layer.as_ref(),
+            &PixmapPaint {
+                quality: FilterQuality::Bicubic,
+                ..Default::default()
+            },
+            Transform::from_scale(scale, scale),
+            None,
+        );
+    }
+    /// Rectangle on the map covering everything the object draws, in pixels.
+    /// Objects outside the map are not drawn, and the rectangle may be used
+    /// for hit regions and label placement.
+    ///
+    /// Default is derived from the geographic [extent][Tool::extent], or None if the extent
+    /// is not finite, e.g. for decorations. Objects without a pixel extent are always drawn.
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (lon_min, lat_min, lon_max, lat_max) =
+            self.extent(bounds.zoom, bounds.tile_size.into());
+        if ![lon_min, lat_min, lon_max, lat_max]
+            .iter()
+            .all(|x| x.is_finite())
+        {
+            return None;
+        }
+
+        // Projections of custom tile grids may not keep the extent axis aligned.
+        let corners = [
+            bounds.lon_lat_to_px(lon_min, lat_min),
+            bounds.lon_lat_to_px(lon_min, lat_max),
+            bounds.lon_lat_to_px(lon_max, lat_min),
+            bounds.lon_lat_to_px(lon_max, lat_max),
+        ];
+        if !corners.iter().all(|(x, y)| x.is_finite() && y.is_finite()) {
+            return None;
+        }
+
+        let (x, y) = corners[0];
+        Some(
+            corners
+                .iter()
+                .fold(PixelRect::new(x, y, x, y), |rect, (x, y)| PixelRect {
+                    x_min: rect.x_min.min(*x),
+                    y_min: rect.y_min.min(*y),
+                    x_max: rect.x_max.max(*x),
+                    y_max: rect.y_max.max(*y),
+                }),
+        )
+    }
+    /// Coordinate of point features, as (longitude, latitude).
+    /// Used to spread out points at the same coordinate, see
+    /// [CoincidentPoints][crate::CoincidentPoints]. Default is None.
+    fn point(&self) -> Option<(f64, f64)> {
+        None
+    }
+    /// Priority of the object when overlapping objects are decluttered, higher is placed first,
+    /// see [Declutter][crate::Declutter]. Default is None, meaning the object is always drawn.
+    fn priority(&self) -> Option<f64> {
+        None
+    }
+    /// Feed everything affecting how the object is drawn into `state`, returning true.
+    /// Used by [StaticMap::render_digest][crate::StaticMap::render_digest].
+    /// Default returns false, meaning the object can not be part of a digest.
+    fn digest(&self, _state: &mut dyn Hasher) -> bool {
+        false
+    }
+    /// GeoJSON Feature of the object, with its style as properties following the
+    /// simplestyle spec, e.g. "stroke" and "fill-opacity".
+    /// Used by [StaticMap::to_geojson][crate::StaticMap::to_geojson].
+    /// Default is None, leaving the object out, e.g. for decorations.
+    fn geojson(&self) -> Option<String> {
+        None
+    }
+    /// JSON representation of the object in a scene.
+    /// Used by [StaticMap::to_scene][crate::StaticMap::to_scene].
+    /// Default is None, meaning the object can not be part of a scene.
+    /// Only the built-in tools can be loaded from scenes.
+    fn scene(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Boxed tools, e.g. tools of different types collected in one vector.
+impl<T: Tool + ?Sized> Tool for Box<T> {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        (**self).extent(zoom, tile_size)
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        (**self).draw(bounds, pixmap)
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, pixmap: PixmapMut) {
+        (**self).draw_at_scale(bounds, scale, pixmap)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        (**self).pixel_extent(bounds)
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        (**self).point()
+    }
+
+    fn priority(&self) -> Option<f64> {
+        (**self).priority()
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        (**self).digest(state)
+    }
+
+    fn geojson(&self) -> Option<String> {
+        (**self).geojson()
+    }
+
+    fn scene(&self) -> Option<String> {
+        (**self).scene()
+    }
 }
@@ -0,0 +1,864 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geodesy::{destination, distance, initial_bearing},
+    geojson::Feature,
+    lat_to_y, lon_to_x_continuous, m_to_px,
+    tools::{edge_margin, Color, TextStyle, Tool, UnitSystem, Units},
+    x_to_lon, y_to_lat, Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{LineCap, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Length of a nautical mile, in meters.
+const NAUTICAL_MILE: f64 = 1852.;
+
+/// North reference of bearings and courses.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BearingReference {
+    /// Bearings relative to true north, labelled e.g. "045°T".
+    #[default]
+    True,
+
+    /// Bearings relative to magnetic north, labelled e.g. "045°M", with the magnetic
+    /// variation in degrees, positive when magnetic north is east of true north.
+    Magnetic(f64),
+}
+
+impl BearingReference {
+    /// True bearing of a bearing relative to this reference.
+    fn absolute(self, bearing: f64) -> f64 {
+        match self {
+            BearingReference::True => bearing,
+            BearingReference::Magnetic(variation) => bearing + variation,
+        }
+    }
+
+    /// Bearing relative to this reference of a true bearing.
+    fn relative(self, bearing: f64) -> f64 {
+        match self {
+            BearingReference::True => bearing,
+            BearingReference::Magnetic(variation) => bearing - variation,
+        }
+    }
+
+    /// Bearing in whole degrees with the reference, e.g. "045°T".
+    fn label(self, bearing: f64) -> String {
+        let suffix = match self {
+            BearingReference::True => 'T',
+            BearingReference::Magnetic(_) => 'M',
+        };
+        format!("{:03}°{}", (bearing.round() as i64).rem_euclid(360), suffix)
+    }
+}
+
+impl Digest for BearingReference {
+    fn digest(&self, state: &mut dyn Hasher) {
+        match self {
+            BearingReference::True => state.write_u8(0),
+            BearingReference::Magnetic(variation) => {
+                state.write_u8(1);
+                variation.digest(state);
+            }
+        }
+    }
+}
+
+fn nautical_units() -> Units {
+    Units::new().system(UnitSystem::Nautical)
+}
+
+/// Rectangle covering the points, extended by `margin` pixels.
+fn points_rect(points: &[(f64, f64)], margin: f64) -> Option<PixelRect> {
+    let (x, y) = *points.first()?;
+    let rect = points
+        .iter()
+        .fold(PixelRect::new(x, y, x, y), |rect, (x, y)| {
+            PixelRect::new(
+                rect.x_min.min(*x),
+                rect.y_min.min(*y),
+                rect.x_max.max(*x),
+                rect.y_max.max(*y),
+            )
+        });
+    Some(rect.outset(margin))
+}
+
+/// Geographic extent of the coordinates, as (lon_min, lat_min, lon_max, lat_max).
+fn coordinates_extent(coordinates: &[(f64, f64)]) -> (f64, f64, f64, f64) {
+    coordinates.iter().fold(
+        (f64::NAN, f64::NAN, f64::NAN, f64::NAN),
+        |(lon_min, lat_min, lon_max, lat_max), (lat, lon)| {
+            (
+                lon_min.min(*lon),
+                lat_min.min(*lat),
+                lon_max.max(*lon),
+                lat_max.max(*lat),
+            )
+        },
+    )
+}
+
+/// Center of a label placed just beyond `end`, continuing the direction from `start`.
+fn label_beyond(start: (f64, f64), end: (f64, f64), size: (u32, u32)) -> (f64, f64) {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = dx.hypot(dy);
+    if length == 0. {
+        return end;
+    }
+
+    let (width, height) = (f64::from(size.0), f64::from(size.1));
+    // Far enough for the label to clear the end in any direction.
+    let distance = (width * (dx / length).abs() + height * (dy / length).abs()) / 2. + 4.;
+    (
+        end.0 + dx / length * distance,
+        end.1 + dy / length * distance,
+    )
+}
+
+fn stroke(width: f32) -> Stroke {
+    Stroke {
+        width,
+        line_cap: LineCap::Round,
+        ..Default::default()
+    }
+}
+
+/// Range rings tool, circles at distances in nautical miles around a position,
+/// e.g. radar or VHF range around a vessel.
+/// Use [RangeRingsBuilder][RangeRingsBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, RangeRingsBuilder};
+///
+/// let rings = RangeRingsBuilder::new()
+///     .lat_coordinate(59.9)
+///     .lon_coordinate(10.7)
+///     .rings(1., 3)
+///     .color(Color::new(true, 200, 0, 120, 255))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RangeRings {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    radii: Vec<f64>,
+    color: Color,
+    width: f32,
+    label: Option<TextStyle>,
+    units: Units,
+}
+
+/// Builder for [RangeRings][RangeRings].
+#[derive(Debug, Clone)]
+pub struct RangeRingsBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    radii: Option<Vec<f64>>,
+    color: Color,
+    width: f32,
+    label: Option<TextStyle>,
+    units: Units,
+}
+
+impl Default for RangeRingsBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinate: None,
+            lon_coordinate: None,
+            radii: None,
+            color: Color::default(),
+            width: 1.,
+            label: None,
+            units: nautical_units(),
+        }
+    }
+}
+
+impl RangeRingsBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// The center of the rings as a latitude coordinate.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The center of the rings as a longitude coordinate.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**, or [radii][RangeRingsBuilder::radii].
+    /// Draw `count` rings, `interval` nautical miles apart.
+    pub fn rings(mut self, interval: f64, count: u32) -> Self {
+        self.radii = Some((1..=count).map(|x| interval * f64::from(x)).collect());
+        self
+    }
+
+    /// **Required**, or [rings][RangeRingsBuilder::rings].
+    /// Radii of the rings, in nautical miles.
+    pub fn radii<I>(mut self, radii: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.radii = Some(radii.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Width of the rings, in pixels.
+    /// Default is 1.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Label each ring with its radius, e.g. "2.0 NM", at its northernmost point.
+    pub fn labels(mut self, style: TextStyle) -> Self {
+        self.label = Some(style);
+        self
+    }
+
+    /// Units and number format of the labels.
+    /// Default is nautical miles with a decimal point.
+    pub fn units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if a radius is not positive.
+    pub fn build(self) -> Result<RangeRings> {
+        let radii = self.radii.ok_or(Error::BuildError("Radii not supplied."))?;
+        if !radii.iter().all(|x| *x > 0.) {
+            return Err(Error::BuildError("Radii must be positive."));
+        }
+
+        Ok(RangeRings {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            radii,
+            color: self.color,
+            width: self.width,
+            label: self.label,
+            units: self.units,
+        })
+    }
+}
+
+impl RangeRings {
+    fn outer_radius(&self) -> f64 {
+        self.radii.iter().copied().fold(0., f64::max) * NAUTICAL_MILE
+    }
+}
+
+impl Tool for RangeRings {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        // m_to_px assumes 256px tiles.
+        let radius = m_to_px(self.outer_radius(), self.lat_coordinate, zoom) * tile_size / 256.;
+
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
+        let y = lat_to_y(self.lat_coordinate, zoom);
+
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x - radius / tile_size, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + radius / tile_size, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + radius / tile_size, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y - radius / tile_size, zoom) - lat;
+
+        (lon_min, lat_min, lon_max, lat_max)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let radius = bounds.m_to_px(self.outer_radius(), self.lat_coordinate);
+        // Labels are centered on the rings.
+        let label = match self.label {
+            Some(ref style) => {
+                let text = self.units.format_distance(self.outer_radius());
+                let (width, height) = style.measure(&text);
+                f64::from(width.max(height)) / 2.
+            }
+            None => 0.,
+        };
+        Some(
+            PixelRect::new(x, y, x, y)
+                .outset(radius + edge_margin(Some(self.width), &None) + label),
+        )
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "range_rings".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.radii.digest(state);
+        self.color.digest(state);
+        self.width.digest(state);
+        self.label.digest(state);
+        self.units.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let mut feature = Feature::point(self.lon_coordinate, self.lat_coordinate)
+            .color("stroke", &self.color)
+            .number("stroke-width", self.width.into());
+        if let Some(radius) = self.radii.iter().copied().reduce(f64::max) {
+            feature = feature
+                .number("outer-radius", radius)
+                .string("radius-unit", "NM");
+        }
+        Some(feature.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+
+        let mut path_builder = PathBuilder::new();
+        for radius in self.radii.iter() {
+            let radius = bounds.m_to_px(radius * NAUTICAL_MILE, self.lat_coordinate);
+            path_builder.push_circle(x as f32, y as f32, radius as f32);
+        }
+
+        if let Some(path) = path_builder.finish() {
+            pixmap.stroke_path(&path, &self.color.0, &stroke(self.width), transform, None);
+        }
+
+        if let Some(ref style) = self.label {
+            for radius in self.radii.iter() {
+                let meters = radius * NAUTICAL_MILE;
+                let radius_px = bounds.m_to_px(meters, self.lat_coordinate);
+                style.draw_centered(
+                    &mut pixmap,
+                    &self.units.format_distance(meters),
+                    x as f32,
+                    (y - radius_px) as f32,
+                    scale,
+                );
+            }
+        }
+    }
+}
+
+/// Bearing lines tool, lines from a position at given bearings,
+/// e.g. lines of sight to landmarks or the sectors of a light.
+/// Use [BearingLinesBuilder][BearingLinesBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{BearingLinesBuilder, BearingReference};
+///
+/// let lines = BearingLinesBuilder::new()
+///     .lat_coordinate(59.9)
+///     .lon_coordinate(10.7)
+///     .bearings(vec![45., 120., 300.])
+///     .reference(BearingReference::Magnetic(3.5))
+///     .length(5.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BearingLines {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    bearings: Vec<f64>,
+    reference: BearingReference,
+    length: f64,
+    color: Color,
+    width: f32,
+    label: Option<TextStyle>,
+}
+
+/// Builder for [BearingLines][BearingLines].
+#[derive(Debug, Clone)]
+pub struct BearingLinesBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    bearings: Option<Vec<f64>>,
+    reference: BearingReference,
+    length: Option<f64>,
+    color: Color,
+    width: f32,
+    label: Option<TextStyle>,
+}
+
+impl Default for BearingLinesBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinate: None,
+            lon_coordinate: None,
+            bearings: None,
+            reference: BearingReference::True,
+            length: None,
+            color: Color::default(),
+            width: 1.5,
+            label: None,
+        }
+    }
+}
+
+impl BearingLinesBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// The origin of the lines as a latitude coordinate.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The origin of the lines as a longitude coordinate.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// Bearings of the lines, in degrees clockwise from the north of the
+    /// [reference][BearingLinesBuilder::reference].
+    pub fn bearings<I>(mut self, bearings: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.bearings = Some(bearings.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// North the bearings are relative to.
+    /// Default is [BearingReference::True][BearingReference::True].
+    pub fn reference(mut self, reference: BearingReference) -> Self {
+        self.reference = reference;
+        self
+    }
+
+    /// **Required**.
+    /// Length of the lines, in nautical miles.
+    pub fn length(mut self, length: f64) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Width of the lines, in pixels.
+    /// Default is 1.5.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Label each line with its bearing, e.g. "045°T", beyond its end.
+    pub fn labels(mut self, style: TextStyle) -> Self {
+        self.label = Some(style);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields.
+    pub fn build(self) -> Result<BearingLines> {
+        Ok(BearingLines {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            bearings: self
+                .bearings
+                .ok_or(Error::BuildError("Bearings not supplied."))?,
+            reference: self.reference,
+            length: self
+                .length
+                .ok_or(Error::BuildError("Length not supplied."))?,
+            color: self.color,
+            width: self.width,
+            label: self.label,
+        })
+    }
+}
+
+impl BearingLines {
+    /// End of each line, as (latitude, longitude).
+    fn ends(&self) -> Vec<(f64, f64)> {
+        self.bearings
+            .iter()
+            .map(|bearing| {
+                destination(
+                    self.lat_coordinate,
+                    self.lon_coordinate,
+                    self.length * NAUTICAL_MILE,
+                    self.reference.absolute(*bearing),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Tool for BearingLines {
+    fn extent(&self, _zoom: u8, _tile_size: f64) -> (f64, f64, f64, f64) {
+        let mut coordinates = self.ends();
+        coordinates.push((self.lat_coordinate, self.lon_coordinate));
+        coordinates_extent(&coordinates)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let origin = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let mut points = vec![origin];
+        for (bearing, (lat, lon)) in self.bearings.iter().zip(self.ends()) {
+            let end = bounds.lon_lat_to_px(lon, lat);
+            points.push(end);
+
+            if let Some(ref style) = self.label {
+                let size = style.measure(&self.reference.label(*bearing));
+                let (x, y) = label_beyond(origin, end, size);
+                let (width, height) = (f64::from(size.0) / 2., f64::from(size.1) / 2.);
+                points.extend([(x - width, y - height), (x + width, y + height)]);
+            }
+        }
+        points_rect(&points, edge_margin(Some(self.width), &None))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "bearing_lines".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.bearings.digest(state);
+        self.reference.digest(state);
+        self.length.digest(state);
+        self.color.digest(state);
+        self.width.digest(state);
+        self.label.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let lines: Vec<([f64; 2], [f64; 2])> = self
+            .ends()
+            .into_iter()
+            .map(|(lat, lon)| ([self.lon_coordinate, lon], [self.lat_coordinate, lat]))
+            .collect();
+        let feature = Feature::multi_line_string(
+            lines
+                .iter()
+                .map(|(lon, lat)| (lon.as_slice(), lat.as_slice())),
+        )
+        .color("stroke", &self.color)
+        .number("stroke-width", self.width.into());
+        Some(feature.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let origin = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let ends: Vec<(f64, f64)> = self
+            .ends()
+            .into_iter()
+            .map(|(lat, lon)| bounds.lon_lat_to_px(lon, lat))
+            .collect();
+
+        let mut path_builder = PathBuilder::new();
+        for end in ends.iter() {
+            path_builder.move_to(origin.0 as f32, origin.1 as f32);
+            path_builder.line_to(end.0 as f32, end.1 as f32);
+        }
+
+        if let Some(path) = path_builder.finish() {
+            pixmap.stroke_path(&path, &self.color.0, &stroke(self.width), transform, None);
+        }
+
+        if let Some(ref style) = self.label {
+            for (bearing, end) in self.bearings.iter().zip(ends) {
+                let text = self.reference.label(*bearing);
+                let (x, y) = label_beyond(origin, end, style.measure(&text));
+                style.draw_centered(&mut pixmap, &text, x as f32, y as f32, scale);
+            }
+        }
+    }
+}
+
+/// Course line tool, an arrow from a start to a destination labelled with
+/// the course and distance, e.g. "087°T 12.4 NM".
+/// Use [CourseLineBuilder][CourseLineBuilder] as an entrypoint.
+///
+/// The course is the initial bearing of the great circle to the destination.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::tools::{Color, CourseLineBuilder, Font, TextStyle};
+///
+/// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap())
+///     .halo(Color::new(true, 255, 255, 255, 255), 2.);
+///
+/// let course = CourseLineBuilder::new()
+///     .from(59.90, 10.70)
+///     .to(59.70, 10.55)
+///     .label(style)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CourseLine {
+    from: (f64, f64),
+    to: (f64, f64),
+    reference: BearingReference,
+    color: Color,
+    width: f32,
+    label: Option<TextStyle>,
+    units: Units,
+}
+
+/// Builder for [CourseLine][CourseLine].
+#[derive(Debug, Clone)]
+pub struct CourseLineBuilder {
+    from: Option<(f64, f64)>,
+    to: Option<(f64, f64)>,
+    reference: BearingReference,
+    color: Color,
+    width: f32,
+    label: Option<TextStyle>,
+    units: Units,
+}
+
+impl Default for CourseLineBuilder {
+    fn default() -> Self {
+        Self {
+            from: None,
+            to: None,
+            reference: BearingReference::True,
+            color: Color::default(),
+            width: 2.,
+            label: None,
+            units: nautical_units(),
+        }
+    }
+}
+
+impl CourseLineBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// Start of the course, as latitude and longitude.
+    pub fn from(mut self, lat: f64, lon: f64) -> Self {
+        self.from = Some((lat, lon));
+        self
+    }
+
+    /// **Required**.
+    /// Destination of the course, as latitude and longitude.
+    pub fn to(mut self, lat: f64, lon: f64) -> Self {
+        self.to = Some((lat, lon));
+        self
+    }
+
+    /// North the course is labelled relative to.
+    /// Default is [BearingReference::True][BearingReference::True].
+    pub fn reference(mut self, reference: BearingReference) -> Self {
+        self.reference = reference;
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Width of the line, in pixels. The arrowhead grows with it.
+    /// Default is 2.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Label the line with the course and distance at its middle.
+    pub fn label(mut self, style: TextStyle) -> Self {
+        self.label = Some(style);
+        self
+    }
+
+    /// Units and number format of the distance in the label.
+    /// Default is nautical miles with a decimal point.
+    pub fn units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields.
+    pub fn build(self) -> Result<CourseLine> {
+        Ok(CourseLine {
+            from: self
+                .from
+                .ok_or(Error::BuildError("Start coordinate not supplied."))?,
+            to: self
+                .to
+                .ok_or(Error::BuildError("Destination coordinate not supplied."))?,
+            reference: self.reference,
+            color: self.color,
+            width: self.width,
+            label: self.label,
+            units: self.units,
+        })
+    }
+}
+
+impl CourseLine {
+    /// Course and distance, e.g. "087°T 12.4 NM".
+    fn text(&self) -> String {
+        let ((lat1, lon1), (lat2, lon2)) = (self.from, self.to);
+        let course = self
+            .reference
+            .relative(initial_bearing(lat1, lon1, lat2, lon2));
+        format!(
+            "{} {}",
+            self.reference.label(course),
+            self.units.format_distance(distance(lat1, lon1, lat2, lon2))
+        )
+    }
+
+    /// Length of the arrowhead, in pixels.
+    fn head(&self) -> f64 {
+        f64::from(self.width) * 4. + 4.
+    }
+}
+
+impl Tool for CourseLine {
+    fn extent(&self, _zoom: u8, _tile_size: f64) -> (f64, f64, f64, f64) {
+        coordinates_extent(&[self.from, self.to])
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let start = bounds.lon_lat_to_px(self.from.1, self.from.0);
+        let end = bounds.lon_lat_to_px(self.to.1, self.to.0);
+        let mut points = vec![start, end];
+
+        if let Some(ref style) = self.label {
+            let (width, height) = style.measure(&self.text());
+            let (x, y) = ((start.0 + end.0) / 2., (start.1 + end.1) / 2.);
+            let (width, height) = (f64::from(width) / 2., f64::from(height) / 2.);
+            points.extend([(x - width, y - height), (x + width, y + height)]);
+        }
+        points_rect(&points, self.head())
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "course_line".digest(state);
+        self.from.digest(state);
+        self.to.digest(state);
+        self.reference.digest(state);
+        self.color.digest(state);
+        self.width.digest(state);
+        self.label.digest(state);
+        self.units.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let feature = Feature::line_string(&[self.from.1, self.to.1], &[self.from.0, self.to.0])
+            .color("stroke", &self.color)
+            .number("stroke-width", self.width.into())
+            .string("course", &self.text());
+        Some(feature.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let start = bounds.lon_lat_to_px(self.from.1, self.from.0);
+        let end = bounds.lon_lat_to_px(self.to.1, self.to.0);
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let length = dx.hypot(dy);
+
+        // The line stops inside the arrowhead, so its cap does not poke out of the tip.
+        let inset = match length > 0. {
+            true => self.head().min(length) / 2. / length,
+            false => 0.,
+        };
+        let mut path_builder = PathBuilder::new();
+        path_builder.move_to(start.0 as f32, start.1 as f32);
+        path_builder.line_to((end.0 - dx * inset) as f32, (end.1 - dy * inset) as f32);
+        if let Some(path) = path_builder.finish() {
+            pixmap.stroke_path(&path, &self.color.0, &stroke(self.width), transform, None);
+        }
+
+        // Arrowhead at the destination, pointing along the course.
+        if length > 0. {
+            let (ux, uy) = (dx / length, dy / length);
+            let head = self.head().min(length);
+            let (base_x, base_y) = (end.0 - ux * head, end.1 - uy * head);
+            let half = head / 2.5;
+
+            let mut path_builder = PathBuilder::new();
+            path_builder.move_to(end.0 as f32, end.1 as f32);
+            path_builder.line_to((base_x - uy * half) as f32, (base_y + ux * half) as f32);
+            path_builder.line_to((base_x + uy * half) as f32, (base_y - ux * half) as f32);
+            path_builder.close();
+            if let Some(path) = path_builder.finish() {
+                pixmap.fill_path(
+                    &path,
+                    &self.color.0,
+                    tiny_skia::FillRule::Winding,
+                    transform,
+                    None,
+                );
+            }
+        }
+
+        if let Some(ref style) = self.label {
+            style.draw_centered(
+                &mut pixmap,
+                &self.text(),
+                ((start.0 + end.0) / 2.) as f32,
+                ((start.1 + end.1) / 2.) as f32,
+                scale,
+            );
+        }
+    }
+}
@@ -0,0 +1,691 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    clip::clip_ring,
+    coord::{unzip, LatLon},
+    digest::Digest,
+    geojson::Feature,
+    json::{invalid_member, Json},
+    scene::{member, object, Scene},
+    tools::{draw_casing, edge_margin, solid_colors, Color, Gradient, Tool},
+    Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{FillRule, LineCap, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Polygon tool, a filled area with an optional outline, e.g. a zone, a boundary
+/// or a coverage area.
+/// Use [PolygonBuilder][PolygonBuilder] as an entrypoint.
+///
+/// The ring is closed automatically, so the first coordinate need not be repeated.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, PolygonBuilder};
+///
+/// let polygon = PolygonBuilder::new()
+///     .coordinates(vec![(59.90, 10.70), (59.95, 10.75), (59.92, 10.82), (59.88, 10.78)])
+///     .color(Color::new(true, 255, 140, 0, 90))
+///     .stroke(Color::new(true, 255, 140, 0, 255), 2.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    lat_coordinates: Vec<f64>,
+    lon_coordinates: Vec<f64>,
+    style: AreaStyle,
+    fill_rule: FillRule,
+}
+
+/// Builder for [Polygon][Polygon].
+#[derive(Debug, Clone)]
+pub struct PolygonBuilder {
+    lat_coordinates: Option<Vec<f64>>,
+    lon_coordinates: Option<Vec<f64>>,
+    style: AreaStyle,
+    fill_rule: FillRule,
+}
+
+impl Default for PolygonBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinates: None,
+            lon_coordinates: None,
+            style: AreaStyle::default(),
+            fill_rule: FillRule::Winding,
+        }
+    }
+}
+
+impl PolygonBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// Takes a collection of latitude coordinates of the vertices, e.g. `f64` or `f32`.
+    pub fn lat_coordinates<I>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.lat_coordinates = Some(coordinates.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// **Required**.
+    /// Takes a collection of longitude coordinates of the vertices, e.g. `f64` or `f32`.
+    pub fn lon_coordinates<I>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.lon_coordinates = Some(coordinates.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// **Required**, unless latitude and longitude coordinates are supplied separately.
+    /// Takes a collection of vertices, e.g. (latitude, longitude) tuples,
+    /// see [LatLon][LatLon].
+    pub fn coordinates<I, C>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        let (lat_coordinates, lon_coordinates) = unzip(coordinates);
+        self.lat_coordinates = Some(lat_coordinates);
+        self.lon_coordinates = Some(lon_coordinates);
+        self
+    }
+
+    /// Fill color of the polygon.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.style.color = color;
+        self
+    }
+
+    /// Outline drawn on top of the fill, `width` pixels wide.
+    /// A transparent fill color draws only the outline.
+    /// Default is no outline.
+    pub fn stroke(mut self, color: Color, width: f32) -> Self {
+        self.style.stroke = Some((color, width));
+        self
+    }
+
+    /// Outline drawn beneath the polygon, extending `width` pixels beyond its edges,
+    /// or beyond its stroke if there is one.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.style.casing = Some((color, width));
+        self
+    }
+
+    /// Fill with a [Gradient][Gradient] instead of the color.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.style.gradient = Some(gradient);
+        self
+    }
+
+    /// Rule deciding which areas of a self-intersecting polygon are inside when filling.
+    /// Default is [FillRule::Winding][FillRule::Winding].
+    pub fn fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// if the latitude and longitude coordinates do not match,
+    /// or if there are fewer than three vertices.
+    pub fn build(self) -> Result<Polygon> {
+        let lat_coordinates = self
+            .lat_coordinates
+            .ok_or(Error::BuildError("Latitude coordinates not supplied."))?;
+        let lon_coordinates = self
+            .lon_coordinates
+            .ok_or(Error::BuildError("Longitude coordinates not supplied."))?;
+
+        if lat_coordinates.len() != lon_coordinates.len() {
+            return Err(Error::BuildError(
+                "Latitude and longitude coordinates must be of equal length.",
+            ));
+        }
+        if lat_coordinates.len() < 3 {
+            return Err(Error::BuildError(
+                "A polygon needs at least three coordinates.",
+            ));
+        }
+
+        Ok(Polygon {
+            lat_coordinates,
+            lon_coordinates,
+            style: self.style,
+            fill_rule: self.fill_rule,
+        })
+    }
+}
+
+impl PolygonBuilder {
+    /// Builder with the settings of a polygon in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.lat_coordinates = json.numbers("lat_coordinates")?;
+        builder.lon_coordinates = json.numbers("lon_coordinates")?;
+        builder.style = AreaStyle::from_json(json)?;
+        if let Some(fill_rule) = member(json, "fill_rule")? {
+            builder.fill_rule = fill_rule;
+        }
+        Ok(builder)
+    }
+}
+
+impl Polygon {
+    /// Vertices of the polygon on the map, in pixels.
+    fn ring(&self, bounds: &Bounds) -> Vec<(f64, f64)> {
+        project(&self.lat_coordinates, &self.lon_coordinates, bounds)
+    }
+}
+
+impl Tool for Polygon {
+    fn extent(&self, _zoom: u8, _tile_size: f64) -> (f64, f64, f64, f64) {
+        extent(&self.lat_coordinates, &self.lon_coordinates)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        self.style.pixel_extent(&self.ring(bounds))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "polygon".digest(state);
+        self.lat_coordinates.digest(state);
+        self.lon_coordinates.digest(state);
+        self.style.digest(state);
+        (self.fill_rule == FillRule::EvenOdd).digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let ring = lon_lat(&self.lat_coordinates, &self.lon_coordinates);
+        Some(self.style.geojson(Feature::polygon(&ring)).to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        let mut members = vec![
+            ("type", "polygon".into()),
+            ("lat_coordinates", self.lat_coordinates.clone().into()),
+            ("lon_coordinates", self.lon_coordinates.clone().into()),
+            ("fill_rule", self.fill_rule.to_json()),
+        ];
+        members.extend(self.style.scene()?);
+        Some(object(members).to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, pixmap: PixmapMut) {
+        self.style
+            .draw(&[self.ring(bounds)], self.fill_rule, bounds, scale, pixmap);
+    }
+}
+
+/// MultiPolygon tool, one or more polygons with holes, e.g. a park around a lake
+/// or a country with islands.
+/// Use [MultiPolygonBuilder][MultiPolygonBuilder] as an entrypoint.
+///
+/// Polygons and holes are filled with the even-odd rule, so a hole is left out of the fill
+/// regardless of the direction of its ring, and areas where polygons overlap are left out too.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, MultiPolygonBuilder};
+///
+/// let park = MultiPolygonBuilder::new()
+///     .polygon(vec![(59.90, 10.70), (59.90, 10.80), (59.95, 10.80), (59.95, 10.70)])
+///     .hole(vec![(59.92, 10.73), (59.92, 10.76), (59.93, 10.76), (59.93, 10.73)])
+///     .polygon(vec![(59.96, 10.82), (59.96, 10.84), (59.97, 10.83)])
+///     .color(Color::new(true, 60, 160, 80, 140))
+///     .stroke(Color::new(true, 30, 100, 40, 255), 1.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiPolygon {
+    /// Rings of each polygon, outline first, as (latitude, longitude) coordinates.
+    polygons: Vec<Vec<(Vec<f64>, Vec<f64>)>>,
+    style: AreaStyle,
+}
+
+/// Builder for [MultiPolygon][MultiPolygon].
+#[derive(Debug, Clone, Default)]
+pub struct MultiPolygonBuilder {
+    polygons: Vec<Vec<(Vec<f64>, Vec<f64>)>>,
+    orphan_hole: bool,
+    style: AreaStyle,
+}
+
+impl MultiPolygonBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**, at least once.
+    /// Add a polygon from the coordinates of its outline, e.g. (latitude, longitude) tuples,
+    /// see [LatLon][LatLon].
+    pub fn polygon<I, C>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        self.polygons.push(vec![unzip(coordinates)]);
+        self
+    }
+
+    /// Add a hole to the polygon added last, from the coordinates of its outline.
+    pub fn hole<I, C>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        match self.polygons.last_mut() {
+            Some(polygon) => polygon.push(unzip(coordinates)),
+            None => self.orphan_hole = true,
+        }
+        self
+    }
+
+    /// Add the polygons of a [Region][crate::Region], with their holes.
+    ///
+    /// Requires the `geo` feature.
+    #[cfg(feature = "geo")]
+    pub fn region(mut self, region: &crate::Region) -> Self {
+        for polygon in region.polygons() {
+            let outline = (polygon.lat_coordinates, polygon.lon_coordinates);
+            self.polygons
+                .push(std::iter::once(outline).chain(polygon.holes).collect());
+        }
+        self
+    }
+
+    /// Fill color of the polygons.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.style.color = color;
+        self
+    }
+
+    /// Outline of the polygons and holes drawn on top of the fill, `width` pixels wide.
+    /// Default is no outline.
+    pub fn stroke(mut self, color: Color, width: f32) -> Self {
+        self.style.stroke = Some((color, width));
+        self
+    }
+
+    /// Outline drawn beneath the polygons, extending `width` pixels beyond their edges,
+    /// or beyond their stroke if there is one.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.style.casing = Some((color, width));
+        self
+    }
+
+    /// Fill with a [Gradient][Gradient] instead of the color.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.style.gradient = Some(gradient);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if no polygon is supplied, if a hole is added before any polygon,
+    /// or if a ring has fewer than three coordinates.
+    pub fn build(self) -> Result<MultiPolygon> {
+        if self.polygons.is_empty() {
+            return Err(Error::BuildError("Polygons not supplied."));
+        }
+        if self.orphan_hole {
+            return Err(Error::BuildError("Hole supplied before its polygon."));
+        }
+        for (lat_coordinates, lon_coordinates) in self.polygons.iter().flatten() {
+            if lat_coordinates.len() != lon_coordinates.len() {
+                return Err(Error::BuildError(
+                    "Latitude and longitude coordinates must be of equal length.",
+                ));
+            }
+            if lat_coordinates.len() < 3 {
+                return Err(Error::BuildError(
+                    "A ring needs at least three coordinates.",
+                ));
+            }
+        }
+
+        Ok(MultiPolygon {
+            polygons: self.polygons,
+            style: self.style,
+        })
+    }
+}
+
+impl MultiPolygonBuilder {
+    /// Builder with the settings of a multipolygon in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        for polygon in json.array("polygons")?.unwrap_or_default() {
+            let rings = match *polygon {
+                Json::Array(ref rings) => rings,
+                _ => return Err(invalid_member("polygons", "an array of arrays of rings")),
+            };
+            let rings = rings
+                .iter()
+                .map(|ring| {
+                    let lat_coordinates = ring.numbers("lat_coordinates")?;
+                    let lon_coordinates = ring.numbers("lon_coordinates")?;
+                    lat_coordinates
+                        .zip(lon_coordinates)
+                        .ok_or_else(|| invalid_member("polygons", "an array of arrays of rings"))
+                })
+                .collect::<Result<_>>()?;
+            builder.polygons.push(rings);
+        }
+        builder.style = AreaStyle::from_json(json)?;
+        Ok(builder)
+    }
+}
+
+impl MultiPolygon {
+    /// Rings of all polygons on the map, in pixels.
+    fn rings(&self, bounds: &Bounds) -> Vec<Vec<(f64, f64)>> {
+        self.polygons
+            .iter()
+            .flatten()
+            .map(|(lat_coordinates, lon_coordinates)| {
+                project(lat_coordinates, lon_coordinates, bounds)
+            })
+            .collect()
+    }
+
+    /// Outlines of the polygons, without their holes.
+    fn outlines(&self) -> impl Iterator<Item = &(Vec<f64>, Vec<f64>)> {
+        self.polygons.iter().filter_map(|polygon| polygon.first())
+    }
+}
+
+impl Tool for MultiPolygon {
+    fn extent(&self, _zoom: u8, _tile_size: f64) -> (f64, f64, f64, f64) {
+        self.outlines().fold(
+            (f64::NAN, f64::NAN, f64::NAN, f64::NAN),
+            |total, (lat_coordinates, lon_coordinates)| {
+                let (lon_min, lat_min, lon_max, lat_max) = extent(lat_coordinates, lon_coordinates);
+                (
+                    total.0.min(lon_min),
+                    total.1.min(lat_min),
+                    total.2.max(lon_max),
+                    total.3.max(lat_max),
+                )
+            },
+        )
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let points: Vec<(f64, f64)> = self
+            .outlines()
+            .flat_map(|(lat_coordinates, lon_coordinates)| {
+                project(lat_coordinates, lon_coordinates, bounds)
+            })
+            .collect();
+        self.style.pixel_extent(&points)
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "multi_polygon".digest(state);
+        for polygon in &self.polygons {
+            polygon.digest(state);
+        }
+        self.style.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let polygons: Vec<Vec<Vec<(f64, f64)>>> = self
+            .polygons
+            .iter()
+            .map(|polygon| {
+                polygon
+                    .iter()
+                    .map(|(lat_coordinates, lon_coordinates)| {
+                        lon_lat(lat_coordinates, lon_coordinates)
+                    })
+                    .collect()
+            })
+            .collect();
+        let feature = Feature::multi_polygon(&polygons);
+        Some(self.style.geojson(feature).to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        let polygons = self
+            .polygons
+            .iter()
+            .map(|polygon| {
+                Json::Array(
+                    polygon
+                        .iter()
+                        .map(|(lat_coordinates, lon_coordinates)| {
+                            object(vec![
+                                ("lat_coordinates", lat_coordinates.clone().into()),
+                                ("lon_coordinates", lon_coordinates.clone().into()),
+                            ])
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let mut members = vec![
+            ("type", "multi_polygon".into()),
+            ("polygons", Json::Array(polygons)),
+        ];
+        members.extend(self.style.scene()?);
+        Some(object(members).to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, pixmap: PixmapMut) {
+        self.style.draw(
+            &self.rings(bounds),
+            FillRule::EvenOdd,
+            bounds,
+            scale,
+            pixmap,
+        );
+    }
+}
+
+/// Fill, outline and casing of an area, shared by [Polygon][Polygon]
+/// and [MultiPolygon][MultiPolygon].
+#[derive(Debug, Clone, Default)]
+struct AreaStyle {
+    color: Color,
+    stroke: Option<(Color, f32)>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+}
+
+impl AreaStyle {
+    fn from_json(json: &Json) -> Result<Self> {
+        let mut style = Self {
+            stroke: member(json, "stroke")?,
+            casing: member(json, "casing")?,
+            gradient: member(json, "gradient")?,
+            ..Default::default()
+        };
+        if let Some(color) = member(json, "color")? {
+            style.color = color;
+        }
+        Ok(style)
+    }
+
+    fn stroke_width(&self) -> Option<f32> {
+        self.stroke.as_ref().map(|(_, width)| *width)
+    }
+
+    /// Members of the style in a scene, None if it can not be part of a scene.
+    fn scene(&self) -> Option<Vec<(&'static str, Json)>> {
+        let stroke_solid = self.stroke.iter().all(|x| x.0.is_solid());
+        if !stroke_solid || !solid_colors(&self.color, &self.casing) {
+            return None;
+        }
+
+        Some(vec![
+            ("color", self.color.to_json()),
+            ("stroke", self.stroke.as_ref().map(Scene::to_json).into()),
+            ("casing", self.casing.as_ref().map(Scene::to_json).into()),
+            (
+                "gradient",
+                self.gradient.as_ref().map(Scene::to_json).into(),
+            ),
+        ])
+    }
+
+    fn geojson(&self, feature: Feature) -> Feature {
+        let feature = feature.color("fill", &self.color);
+        match self.stroke.as_ref() {
+            Some((color, width)) => feature
+                .color("stroke", color)
+                .number("stroke-width", (*width).into()),
+            None => feature.number("stroke-width", 0.),
+        }
+    }
+
+    /// Pixels covered by an area with the given vertices.
+    fn pixel_extent(&self, points: &[(f64, f64)]) -> Option<PixelRect> {
+        let (x, y) = *points.first()?;
+        let rect = points
+            .iter()
+            .fold(PixelRect::new(x, y, x, y), |rect, (x, y)| PixelRect {
+                x_min: rect.x_min.min(*x),
+                y_min: rect.y_min.min(*y),
+                x_max: rect.x_max.max(*x),
+                y_max: rect.y_max.max(*y),
+            });
+        Some(rect.outset(edge_margin(self.stroke_width(), &self.casing)))
+    }
+
+    /// Draw an area made of rings on the map, in pixels, `scale` times larger.
+    fn draw(
+        &self,
+        rings: &[Vec<(f64, f64)>],
+        fill_rule: FillRule,
+        bounds: &Bounds,
+        scale: f32,
+        mut pixmap: PixmapMut,
+    ) {
+        let transform = Transform::from_scale(scale, scale);
+        // Large areas are clipped to the map, with room for the outline and casing,
+        // so edges far outside are never drawn.
+        let viewport = bounds
+            .pixel_rect()
+            .outset(edge_margin(self.stroke_width(), &self.casing) + 1.);
+
+        let mut path_builder = PathBuilder::new();
+        for ring in rings {
+            let mut ring = ring.clone();
+            if let Some(width) = self.stroke_width() {
+                ring = ring
+                    .into_iter()
+                    .map(|point| bounds.snap_stroke(point, width))
+                    .collect();
+            }
+
+            let ring = clip_ring(&ring, &viewport);
+            let mut points = ring.iter();
+            if let Some((x, y)) = points.next() {
+                path_builder.move_to(*x as f32, *y as f32);
+                for (x, y) in points {
+                    path_builder.line_to(*x as f32, *y as f32);
+                }
+                path_builder.close();
+            }
+        }
+
+        if let Some(path) = path_builder.finish() {
+            if let Some(casing) = self.casing.as_ref() {
+                draw_casing(
+                    &mut pixmap,
+                    &path,
+                    casing,
+                    self.stroke_width(),
+                    LineCap::Butt,
+                    transform,
+                );
+            }
+
+            let gradient = self.gradient.as_ref().and_then(|x| x.paint(bounds));
+            pixmap.fill_path(
+                &path,
+                gradient.as_ref().unwrap_or(&self.color.0),
+                fill_rule,
+                transform,
+                None,
+            );
+
+            if let Some((color, width)) = self.stroke.as_ref() {
+                pixmap.stroke_path(
+                    &path,
+                    &color.0,
+                    &Stroke {
+                        width: *width,
+                        ..Default::default()
+                    },
+                    transform,
+                    None,
+                );
+            }
+        }
+    }
+}
+
+impl Digest for AreaStyle {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.color.digest(state);
+        self.stroke.digest(state);
+        self.casing.digest(state);
+        self.gradient.digest(state);
+    }
+}
+
+/// Vertices of a ring on the map, in pixels.
+fn project(lat_coordinates: &[f64], lon_coordinates: &[f64], bounds: &Bounds) -> Vec<(f64, f64)> {
+    lon_coordinates
+        .iter()
+        .zip(lat_coordinates)
+        .map(|(lon, lat)| bounds.lon_lat_to_px(*lon, *lat))
+        .collect()
+}
+
+/// Vertices of a ring as (longitude, latitude), for GeoJSON.
+fn lon_lat(lat_coordinates: &[f64], lon_coordinates: &[f64]) -> Vec<(f64, f64)> {
+    lon_coordinates
+        .iter()
+        .copied()
+        .zip(lat_coordinates.iter().copied())
+        .collect()
+}
+
+/// Extent of a ring, as (west, south, east, north).
+fn extent(lat_coordinates: &[f64], lon_coordinates: &[f64]) -> (f64, f64, f64, f64) {
+    let min = |values: &[f64]| values.iter().copied().fold(f64::NAN, f64::min);
+    let max = |values: &[f64]| values.iter().copied().fold(f64::NAN, f64::max);
+    (
+        min(lon_coordinates),
+        min(lat_coordinates),
+        max(lon_coordinates),
+        max(lat_coordinates),
+    )
+}
@@ -0,0 +1,361 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    clip::clip_ring,
+    coord::{unzip, LatLon},
+    digest::Digest,
+    geojson::Feature,
+    polylabel,
+    tools::{TextStyle, Tool},
+    Error, Result,
+};
+use std::hash::{Hash, Hasher};
+use tiny_skia::{FillRule, Mask, PathBuilder, PixmapMut, PixmapPaint, Transform};
+
+/// What a [PolygonLabel][PolygonLabel] does when the label does not fit inside the polygon
+/// at the zoom of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LabelOverflow {
+    /// Leave out the label.
+    Hide,
+    /// Draw the part of the label inside the polygon.
+    Clip,
+    /// Draw the whole label.
+    Show,
+}
+
+/// Label of a polygon, e.g. a value or a name, placed at its pole of inaccessibility,
+/// the point inside the polygon farthest from its outline.
+/// Unlike the centroid, the pole is always inside, also for concave polygons.
+/// Use [PolygonLabelBuilder][PolygonLabelBuilder] as an entrypoint.
+///
+/// The label is placed at the zoom of the map, and by default left out
+/// if the polygon is too small for it.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::tools::{Font, LabelOverflow, PolygonLabelBuilder, TextStyle};
+///
+/// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap());
+///
+/// let label = PolygonLabelBuilder::new()
+///     .lat_coordinates(vec![59.0, 59.0, 60.0, 60.0, 59.5])
+///     .lon_coordinates(vec![10.0, 11.0, 11.0, 10.0, 10.5])
+///     .text("12.4 %")
+///     .style(style)
+///     .overflow(LabelOverflow::Clip)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PolygonLabel {
+    lat_coordinates: Vec<f64>,
+    lon_coordinates: Vec<f64>,
+    holes: Vec<(Vec<f64>, Vec<f64>)>,
+    text: String,
+    style: TextStyle,
+    overflow: LabelOverflow,
+}
+
+/// Builder for [PolygonLabel][PolygonLabel].
+#[derive(Debug, Clone)]
+pub struct PolygonLabelBuilder {
+    lat_coordinates: Option<Vec<f64>>,
+    lon_coordinates: Option<Vec<f64>>,
+    holes: Vec<(Vec<f64>, Vec<f64>)>,
+    text: Option<String>,
+    style: Option<TextStyle>,
+    overflow: LabelOverflow,
+}
+
+impl Default for PolygonLabelBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinates: None,
+            lon_coordinates: None,
+            holes: Vec::new(),
+            text: None,
+            style: None,
+            overflow: LabelOverflow::Hide,
+        }
+    }
+}
+
+impl PolygonLabelBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// Latitude coordinates of the outline of the polygon.
+    pub fn lat_coordinates<I>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.lat_coordinates = Some(coordinates.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// **Required**.
+    /// Longitude coordinates of the outline of the polygon.
+    pub fn lon_coordinates<I>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+    {
+        self.lon_coordinates = Some(coordinates.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// **Required**, unless latitude and longitude coordinates are supplied separately.
+    /// Coordinates of the outline of the polygon, e.g. (latitude, longitude) tuples,
+    /// see [LatLon][LatLon].
+    pub fn coordinates<I, C>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        let (lat_coordinates, lon_coordinates) = unzip(coordinates);
+        self.lat_coordinates = Some(lat_coordinates);
+        self.lon_coordinates = Some(lon_coordinates);
+        self
+    }
+
+    /// Add a hole in the polygon, which the label avoids.
+    pub fn hole<I, J>(mut self, lat_coordinates: I, lon_coordinates: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+        J: IntoIterator,
+        J::Item: Into<f64>,
+    {
+        self.holes.push((
+            lat_coordinates.into_iter().map(Into::into).collect(),
+            lon_coordinates.into_iter().map(Into::into).collect(),
+        ));
+        self
+    }
+
+    /// Add a hole in the polygon from its coordinates, see [hole][PolygonLabelBuilder::hole].
+    pub fn hole_coordinates<I, C>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        self.holes.push(unzip(coordinates));
+        self
+    }
+
+    /// **Required**.
+    /// Text of the label.
+    pub fn text<I: Into<String>>(mut self, text: I) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// **Required**.
+    /// Style of the label.
+    pub fn style(mut self, style: TextStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// What to do when the label does not fit inside the polygon.
+    /// Default is [LabelOverflow::Hide][LabelOverflow::Hide].
+    pub fn overflow(mut self, overflow: LabelOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the coordinates of a ring do not match.
+    pub fn build(self) -> Result<PolygonLabel> {
+        let lat_coordinates = self
+            .lat_coordinates
+            .ok_or(Error::BuildError("Latitude coordinates not supplied."))?;
+        let lon_coordinates = self
+            .lon_coordinates
+            .ok_or(Error::BuildError("Longitude coordinates not supplied."))?;
+
+        let rings = std::iter::once((&lat_coordinates, &lon_coordinates))
+            .chain(self.holes.iter().map(|(lat, lon)| (lat, lon)));
+        for (lat, lon) in rings {
+            if lat.len() != lon.len() {
+                return Err(Error::BuildError(
+                    "Latitude and longitude coordinates must be of equal length.",
+                ));
+            }
+        }
+
+        Ok(PolygonLabel {
+            lat_coordinates,
+            lon_coordinates,
+            holes: self.holes,
+            text: self.text.ok_or(Error::BuildError("Text not supplied."))?,
+            style: self
+                .style
+                .ok_or(Error::BuildError("Text style not supplied."))?,
+            overflow: self.overflow,
+        })
+    }
+}
+
+impl PolygonLabel {
+    /// Outline and holes of the polygon on the map, in pixels.
+    fn rings(&self, bounds: &Bounds) -> Vec<Vec<(f64, f64)>> {
+        std::iter::once((&self.lat_coordinates, &self.lon_coordinates))
+            .chain(self.holes.iter().map(|(lat, lon)| (lat, lon)))
+            .map(|(lat, lon)| {
+                lon.iter()
+                    .zip(lat)
+                    .map(|(lon, lat)| bounds.lon_lat_to_px(*lon, *lat))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Box of the label on the map, or None if it is left out.
+    fn placement(&self, rings: &[Vec<(f64, f64)>]) -> Option<PixelRect> {
+        let ((x, y), distance) = polylabel::pole_of_inaccessibility(rings, 1.)?;
+        let (width, height) = self.style.measure(&self.text);
+        let (half_width, half_height) = (f64::from(width) / 2., f64::from(height) / 2.);
+        let rect = PixelRect::new(
+            x - half_width,
+            y - half_height,
+            x + half_width,
+            y + half_height,
+        );
+
+        let fits = distance >= half_height
+            && [
+                (rect.x_min, rect.y_min),
+                (rect.x_max, rect.y_min),
+                (rect.x_min, rect.y_max),
+                (rect.x_max, rect.y_max),
+            ]
+            .iter()
+            .all(|(x, y)| polylabel::contains(*x, *y, rings));
+
+        match self.overflow {
+            LabelOverflow::Hide if !fits => None,
+            _ => Some(rect),
+        }
+    }
+
+    /// Mask of the inside of the polygon, for clipping the label on a pixmap of the given size,
+    /// with the polygon `scale` times larger.
+    fn mask(&self, rings: &[Vec<(f64, f64)>], width: u32, height: u32, scale: f32) -> Option<Mask> {
+        let (right, bottom) = (
+            f64::from(width) / f64::from(scale),
+            f64::from(height) / f64::from(scale),
+        );
+        let viewport = PixelRect::new(0., 0., right, bottom).outset(1.);
+        let mut path_builder = PathBuilder::new();
+        for ring in rings {
+            let ring = clip_ring(ring, &viewport);
+            let mut points = ring.iter();
+            if let Some((x, y)) = points.next() {
+                path_builder.move_to(*x as f32, *y as f32);
+                for (x, y) in points {
+                    path_builder.line_to(*x as f32, *y as f32);
+                }
+                path_builder.close();
+            }
+        }
+
+        let mut mask = Mask::new(width, height)?;
+        mask.fill_path(
+            &path_builder.finish()?,
+            FillRule::EvenOdd,
+            true,
+            Transform::from_scale(scale, scale),
+        );
+        Some(mask)
+    }
+}
+
+impl Tool for PolygonLabel {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        (
+            self.lon_coordinates
+                .iter()
+                .copied()
+                .fold(f64::NAN, f64::min),
+            self.lat_coordinates
+                .iter()
+                .copied()
+                .fold(f64::NAN, f64::min),
+            self.lon_coordinates
+                .iter()
+                .copied()
+                .fold(f64::NAN, f64::max),
+            self.lat_coordinates
+                .iter()
+                .copied()
+                .fold(f64::NAN, f64::max),
+        )
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        self.placement(&self.rings(bounds))
+    }
+
+    fn digest(&self, mut state: &mut dyn Hasher) -> bool {
+        "polygon_label".digest(state);
+        self.lat_coordinates.digest(state);
+        self.lon_coordinates.digest(state);
+        for hole in &self.holes {
+            hole.digest(state);
+        }
+        self.text.digest(state);
+        self.style.digest(state);
+        self.overflow.hash(&mut state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let ring: Vec<(f64, f64)> = self
+            .lon_coordinates
+            .iter()
+            .copied()
+            .zip(self.lat_coordinates.iter().copied())
+            .collect();
+        Some(
+            Feature::polygon(&ring)
+                .string("label", &self.text)
+                .to_string(),
+        )
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let rings = self.rings(bounds);
+        let rect = match self.placement(&rings) {
+            Some(rect) => rect,
+            None => return,
+        };
+        let rendered = match self.style.scaled(scale).render(&self.text) {
+            Some(rendered) => rendered,
+            None => return,
+        };
+        let mask = match self.overflow {
+            LabelOverflow::Clip => self.mask(&rings, pixmap.width(), pixmap.height(), scale),
+            _ => None,
+        };
+
+        let scale = f64::from(scale);
+        pixmap.draw_pixmap(
+            (rect.x_min * scale).round() as i32,
+            (rect.y_min * scale).round() as i32,
+            rendered.as_ref(),
+            &PixmapPaint::default(),
+            Transform::default(),
+            mask.as_ref(),
+        );
+    }
+}
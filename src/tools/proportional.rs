@@ -0,0 +1,374 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    tools::{Circle, CircleBuilder, Color, Corner, TextStyle, Tool},
+    Error, Result,
+};
+use std::hash::{Hash, Hasher};
+use tiny_skia::{PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Scaling of symbol radii to values, see [ProportionalSymbols][ProportionalSymbols].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolScaling {
+    /// Symbol areas proportional to the values, the radius growing with the square root.
+    SquareRoot,
+    /// Apparent magnitude scaling after Flannery (1971), with the radius growing
+    /// with the value to the power of 0.5716, compensating for readers
+    /// underestimating the size of large circles.
+    Flannery,
+}
+
+impl SymbolScaling {
+    fn exponent(&self) -> f64 {
+        match self {
+            SymbolScaling::SquareRoot => 0.5,
+            SymbolScaling::Flannery => 0.5716,
+        }
+    }
+}
+
+/// Helper for proportional symbol maps, creating circles with radii scaled
+/// to values, and a legend explaining the sizes.
+/// Use [ProportionalSymbolsBuilder][ProportionalSymbolsBuilder] as an entrypoint.
+///
+/// Scaling the radius linearly with the value makes large values look far larger
+/// than they are, as readers judge circles by their area. The radii are therefore
+/// scaled by the square root of the value, or with [SymbolScaling::Flannery][SymbolScaling::Flannery].
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{
+///     tools::{Color, ProportionalSymbolsBuilder},
+///     StaticMapBuilder,
+/// };
+///
+/// let mut map = StaticMapBuilder::default()
+///     .width(300)
+///     .height(300)
+///     .build()
+///     .unwrap();
+///
+/// let symbols = ProportionalSymbolsBuilder::new()
+///     .max_value(700_000.)
+///     .max_radius(25.)
+///     .color(Color::new(true, 200, 40, 40, 160))
+///     .build()
+///     .unwrap();
+///
+/// for (lat, lon, population) in [(59.91, 10.75, 700_000.), (60.39, 5.32, 285_000.)] {
+///     map.add_tool(symbols.circle(lat, lon, population).unwrap());
+/// }
+/// map.add_tool(symbols.legend(vec![100_000., 700_000.]).build().unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProportionalSymbols {
+    max_value: f64,
+    max_radius: f32,
+    scaling: SymbolScaling,
+    color: Color,
+    casing: Option<(Color, f32)>,
+}
+
+/// Builder for [ProportionalSymbols][ProportionalSymbols].
+#[derive(Debug, Clone)]
+pub struct ProportionalSymbolsBuilder {
+    max_value: Option<f64>,
+    max_radius: f32,
+    scaling: SymbolScaling,
+    color: Color,
+    casing: Option<(Color, f32)>,
+}
+
+impl Default for ProportionalSymbolsBuilder {
+    fn default() -> Self {
+        Self {
+            max_value: None,
+            max_radius: 20.,
+            scaling: SymbolScaling::SquareRoot,
+            color: Color::default(),
+            casing: None,
+        }
+    }
+}
+
+impl ProportionalSymbolsBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// Value drawn with the maximum radius, usually the largest value of the data.
+    /// Must be > 0.0.
+    pub fn max_value(mut self, value: f64) -> Self {
+        self.max_value = Some(value);
+        self
+    }
+
+    /// Radius of the symbol for the maximum value, in pixels.
+    /// Default is 20.0.
+    pub fn max_radius(mut self, radius: f32) -> Self {
+        self.max_radius = radius;
+        self
+    }
+
+    /// Scaling of radii to values.
+    /// Default is [SymbolScaling::SquareRoot][SymbolScaling::SquareRoot].
+    pub fn scaling(mut self, scaling: SymbolScaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Outline drawn beneath each symbol, extending `width` pixels beyond it,
+    /// to separate overlapping symbols.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Build the helper, consuming the builder.
+    /// Returns an error if the maximum value is missing or not > 0.0.
+    pub fn build(self) -> Result<ProportionalSymbols> {
+        let max_value = self
+            .max_value
+            .ok_or(Error::BuildError("Maximum value not supplied."))?;
+        if !(max_value.is_finite() && max_value > 0.) {
+            return Err(Error::BuildError("Maximum value must be finite and > 0.0."));
+        }
+
+        Ok(ProportionalSymbols {
+            max_value,
+            max_radius: self.max_radius,
+            scaling: self.scaling,
+            color: self.color,
+            casing: self.casing,
+        })
+    }
+}
+
+impl ProportionalSymbols {
+    /// Radius of the symbol for a value, in pixels.
+    /// Values <= 0.0 have a radius of 0.0.
+    pub fn radius(&self, value: f64) -> f32 {
+        if value <= 0. {
+            return 0.;
+        }
+        let ratio = value / self.max_value;
+        (f64::from(self.max_radius) * ratio.powf(self.scaling.exponent())) as f32
+    }
+
+    /// Circle for a value at a coordinate.
+    pub fn circle(&self, lat_coordinate: f64, lon_coordinate: f64, value: f64) -> Result<Circle> {
+        let mut builder = CircleBuilder::new()
+            .lat_coordinate(lat_coordinate)
+            .lon_coordinate(lon_coordinate)
+            .radius(self.radius(value))
+            .color(self.color.clone());
+        if let Some((ref color, width)) = self.casing {
+            builder = builder.casing(color.clone(), width);
+        }
+        builder.build()
+    }
+
+    /// Builder for a legend with nested circles for the given values,
+    /// e.g. a small, medium and large value of the data.
+    pub fn legend<I>(&self, values: I) -> ProportionalLegendBuilder
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        ProportionalLegendBuilder {
+            symbols: self.clone(),
+            values: values.into_iter().collect(),
+            corner: Corner::BottomLeft,
+            margin: 10.,
+            label: None,
+        }
+    }
+}
+
+/// Legend of a [ProportionalSymbols][ProportionalSymbols] map, nested circles
+/// placed in a corner of the map, labeled with their values.
+/// Use [ProportionalSymbols::legend][ProportionalSymbols::legend] as an entrypoint.
+///
+/// The legend does not affect the bounds of the map.
+#[derive(Debug, Clone)]
+pub struct ProportionalLegend {
+    symbols: ProportionalSymbols,
+    values: Vec<f64>,
+    corner: Corner,
+    margin: f32,
+    label: Option<TextStyle>,
+}
+
+/// Builder for [ProportionalLegend][ProportionalLegend].
+#[derive(Debug, Clone)]
+pub struct ProportionalLegendBuilder {
+    symbols: ProportionalSymbols,
+    values: Vec<f64>,
+    corner: Corner,
+    margin: f32,
+    label: Option<TextStyle>,
+}
+
+impl ProportionalLegendBuilder {
+    /// Corner of the map to place the legend in.
+    /// Default is [Corner::BottomLeft][Corner::BottomLeft].
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// Distance between the legend and the edges of the map, in pixels.
+    /// Default is 10.0.
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Label each circle with its value, drawn in the given style.
+    pub fn label(mut self, style: TextStyle) -> Self {
+        self.label = Some(style);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if no values are supplied.
+    pub fn build(mut self) -> Result<ProportionalLegend> {
+        if self.values.is_empty() {
+            return Err(Error::BuildError("Legend values not supplied."));
+        }
+        // Largest first, so smaller circles are drawn on top.
+        self.values.sort_by(|a, b| b.total_cmp(a));
+
+        Ok(ProportionalLegend {
+            symbols: self.symbols,
+            values: self.values,
+            corner: self.corner,
+            margin: self.margin,
+            label: self.label,
+        })
+    }
+}
+
+impl ProportionalLegend {
+    /// Space between the circles and their labels, in pixels.
+    const LABEL_GAP: f32 = 6.;
+
+    fn labels(&self) -> Vec<String> {
+        self.values.iter().map(|value| value.to_string()).collect()
+    }
+
+    /// Width and height of the legend, in pixels.
+    fn size(&self) -> (f32, f32) {
+        let diameter = 2. * self.symbols.radius(self.values[0]);
+        let label_width = match self.label {
+            Some(ref style) => self
+                .labels()
+                .iter()
+                .map(|label| style.measure(label).0 as f32)
+                .fold(0., f32::max),
+            None => 0.,
+        };
+
+        if label_width > 0. {
+            (diameter + Self::LABEL_GAP + label_width, diameter)
+        } else {
+            (diameter, diameter)
+        }
+    }
+}
+
+impl Tool for ProportionalLegend {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        (f64::NAN, f64::NAN, f64::NAN, f64::NAN)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (width, height) = self.size();
+        let (left, top) = self.corner.anchor(bounds, width, height, self.margin);
+        let (left, top) = (f64::from(left), f64::from(top));
+        Some(PixelRect::new(
+            left,
+            top,
+            left + f64::from(width),
+            top + f64::from(height),
+        ))
+    }
+
+    fn digest(&self, mut state: &mut dyn Hasher) -> bool {
+        "proportional_legend".digest(state);
+        self.symbols.max_value.digest(state);
+        self.symbols.max_radius.digest(state);
+        self.symbols.scaling.hash(&mut state);
+        self.symbols.color.digest(state);
+        self.symbols.casing.digest(state);
+        self.values.digest(state);
+        self.corner.digest(state);
+        self.margin.digest(state);
+        self.label.digest(state);
+        true
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let (width, height) = self.size();
+        let (left, top) = self.corner.anchor(bounds, width, height, self.margin);
+        let max_radius = height / 2.;
+        let (cx, bottom) = (left + max_radius, top + height);
+
+        for (value, label) in self.values.iter().zip(self.labels()) {
+            let radius = self.symbols.radius(*value);
+            let circle = match PathBuilder::from_circle(cx, bottom - radius, radius) {
+                Some(circle) => circle,
+                None => continue,
+            };
+
+            pixmap.fill_path(
+                &circle,
+                &self.symbols.color.0,
+                tiny_skia::FillRule::Winding,
+                transform,
+                None,
+            );
+            pixmap.stroke_path(
+                &circle,
+                &Color::default().0,
+                &Stroke::default(),
+                transform,
+                None,
+            );
+
+            if let Some(ref style) = self.label {
+                // Leader line from the top of the circle to its label.
+                let y = bottom - 2. * radius;
+                let x = left + 2. * max_radius + Self::LABEL_GAP;
+                let mut leader = PathBuilder::new();
+                leader.move_to(cx, y);
+                leader.line_to(x - 2., y);
+                if let Some(leader) = leader.finish() {
+                    pixmap.stroke_path(
+                        &leader,
+                        &Color::default().0,
+                        &Stroke::default(),
+                        transform,
+                        None,
+                    );
+                }
+
+                let label_width = style.measure(&label).0 as f32;
+                style.draw_centered(&mut pixmap, &label, x + label_width / 2., y, scale);
+            }
+        }
+    }
+}
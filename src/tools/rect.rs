@@ -1,10 +1,15 @@
 use crate::{
-    bounds::Bounds,
-    lat_to_y, lon_to_x,
-    tools::{Color, Tool},
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geodesy,
+    geojson::Feature,
+    json::Json,
+    scene::{member, object, Scene},
+    tools::{draw_casing, edge_margin, solid_colors, Color, Gradient, TextStyle, Tool, Units},
     Error, Result,
 };
-use tiny_skia::{self, PathBuilder, PixmapMut, Stroke, Transform};
+use std::hash::Hasher;
+use tiny_skia::{self, LineCap, PathBuilder, PixmapMut, Stroke, Transform};
 
 /// Rect tool.
 /// Use [RectBuilder][RectBuilder] as an entrypoint.
@@ -30,6 +35,10 @@ pub struct Rect {
     west_lon_coordinate: f64,
     color: Color,
     stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+    label: Option<TextStyle>,
+    units: Units,
 }
 
 /// Builder for [Rect][Rect].
@@ -41,6 +50,10 @@ pub struct RectBuilder {
     west_lon_coordinate: Option<f64>,
     color: Color,
     stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+    label: Option<TextStyle>,
+    units: Units,
 }
 
 impl RectBuilder {
@@ -98,6 +111,33 @@ impl RectBuilder {
         self
     }
 
+    /// Draw a label with the dimensions and area of the rectangle,
+    /// e.g. "3.1 km × 4.0 km, 12.4 km²", at its center.
+    pub fn measurement_label(mut self, style: TextStyle) -> Self {
+        self.label = Some(style);
+        self
+    }
+
+    /// Units and number format of the measurement label.
+    /// Default is metric units with a decimal point.
+    pub fn measurement_units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Outline drawn beneath the rectangle, extending `width` pixels beyond its edges.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Fill with a [Gradient][Gradient] instead of the color.
+    /// Only applies to filled rectangles.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
     /// Build the tool, consuming the builder.
     /// Returns an error if the builder is missing required fields.
     pub fn build(self) -> Result<Rect> {
@@ -116,10 +156,48 @@ impl RectBuilder {
                 .ok_or(Error::BuildError("West longitude coordinate not supplied."))?,
             color: self.color,
             stroke_width: self.stroke_width,
+            casing: self.casing,
+            gradient: self.gradient,
+            label: self.label,
+            units: self.units,
         })
     }
 }
 
+impl Rect {
+    fn measurement(&self) -> String {
+        let (north, south) = (self.north_lat_coordinate, self.south_lat_coordinate);
+        let (east, west) = (self.east_lon_coordinate, self.west_lon_coordinate);
+        let middle = (north + south) / 2.;
+        let units = &self.units;
+
+        format!(
+            "{} × {}, {}",
+            units.format_distance(geodesy::distance(middle, west, middle, east)),
+            units.format_distance(geodesy::distance(north, west, south, west)),
+            units.format_area(geodesy::rect_area(north, south, east, west))
+        )
+    }
+}
+
+impl RectBuilder {
+    /// Builder with the settings of a rectangle in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.north_lat_coordinate = json.number("north_lat_coordinate")?;
+        builder.south_lat_coordinate = json.number("south_lat_coordinate")?;
+        builder.east_lon_coordinate = json.number("east_lon_coordinate")?;
+        builder.west_lon_coordinate = json.number("west_lon_coordinate")?;
+        builder.stroke_width = json.number("stroke_width")?.map(|x| x as f32);
+        builder.casing = member(json, "casing")?;
+        builder.gradient = member(json, "gradient")?;
+        if let Some(color) = member(json, "color")? {
+            builder.color = color;
+        }
+        Ok(builder)
+    }
+}
+
 impl Tool for Rect {
     fn extent(&self, _zoom: u8, _tile_size: f64) -> (f64, f64, f64, f64) {
         (
@@ -130,14 +208,86 @@ impl Tool for Rect {
         )
     }
 
-    fn draw(&self, bounds: &Bounds, mut pixmap: PixmapMut) {
-        let left = bounds.x_to_px(lon_to_x(self.west_lon_coordinate, bounds.zoom));
-        let top = bounds.y_to_px(lat_to_y(self.north_lat_coordinate, bounds.zoom));
-        let right = bounds.x_to_px(lon_to_x(self.east_lon_coordinate, bounds.zoom));
-        let bottom = bounds.y_to_px(lat_to_y(self.south_lat_coordinate, bounds.zoom));
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x0, y0) = bounds.lon_lat_to_px(self.west_lon_coordinate, self.north_lat_coordinate);
+        let (x1, y1) = bounds.lon_lat_to_px(self.east_lon_coordinate, self.south_lat_coordinate);
+        Some(PixelRect::new(x0, y0, x1, y1).outset(edge_margin(self.stroke_width, &self.casing)))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "rect".digest(state);
+        self.north_lat_coordinate.digest(state);
+        self.south_lat_coordinate.digest(state);
+        self.east_lon_coordinate.digest(state);
+        self.west_lon_coordinate.digest(state);
+        self.color.digest(state);
+        self.stroke_width.digest(state);
+        self.casing.digest(state);
+        self.gradient.digest(state);
+        self.label.digest(state);
+        self.units.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let (north, south) = (self.north_lat_coordinate, self.south_lat_coordinate);
+        let (east, west) = (self.east_lon_coordinate, self.west_lon_coordinate);
+        let feature =
+            Feature::polygon(&[(west, north), (east, north), (east, south), (west, south)])
+                .style(&self.color, self.stroke_width);
+        Some(feature.to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        if self.label.is_some() || !solid_colors(&self.color, &self.casing) {
+            return None;
+        }
+
+        let scene = object(vec![
+            ("type", "rect".into()),
+            ("north_lat_coordinate", self.north_lat_coordinate.into()),
+            ("south_lat_coordinate", self.south_lat_coordinate.into()),
+            ("east_lon_coordinate", self.east_lon_coordinate.into()),
+            ("west_lon_coordinate", self.west_lon_coordinate.into()),
+            ("color", self.color.to_json()),
+            ("stroke_width", self.stroke_width.into()),
+            ("casing", self.casing.as_ref().map(Scene::to_json).into()),
+            (
+                "gradient",
+                self.gradient.as_ref().map(Scene::to_json).into(),
+            ),
+        ]);
+        Some(scene.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let mut corners = [
+            bounds.lon_lat_to_px(self.west_lon_coordinate, self.north_lat_coordinate),
+            bounds.lon_lat_to_px(self.east_lon_coordinate, self.south_lat_coordinate),
+        ];
+        if let Some(width) = self.stroke_width {
+            corners = corners.map(|corner| bounds.snap_stroke(corner, width));
+        }
+        let [(left, top), (right, bottom)] = corners;
 
         let rect = tiny_skia::Rect::from_ltrb(left as f32, top as f32, right as f32, bottom as f32);
         if let Some(rect) = rect {
+            if let Some(casing) = self.casing.as_ref() {
+                draw_casing(
+                    &mut pixmap,
+                    &PathBuilder::from_rect(rect),
+                    casing,
+                    self.stroke_width,
+                    LineCap::Butt,
+                    transform,
+                );
+            }
+
             if let Some(width) = self.stroke_width {
                 pixmap.stroke_path(
                     &PathBuilder::from_rect(rect),
@@ -146,11 +296,23 @@ impl Tool for Rect {
                         width,
                         ..Default::default()
                     },
-                    Transform::default(),
+                    transform,
                     None,
                 );
             } else {
-                pixmap.fill_rect(rect, &self.color.0, Transform::default(), None);
+                let gradient = self.gradient.as_ref().and_then(|x| x.paint(bounds));
+                let paint = gradient.as_ref().unwrap_or(&self.color.0);
+                pixmap.fill_rect(rect, paint, transform, None);
+            }
+
+            if let Some(style) = self.label.as_ref() {
+                style.draw_centered(
+                    &mut pixmap,
+                    &self.measurement(),
+                    rect.left() + rect.width() / 2.,
+                    rect.top() + rect.height() / 2.,
+                    scale,
+                );
             }
         }
     }
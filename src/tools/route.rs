@@ -0,0 +1,391 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    json::Json,
+    polyline,
+    tools::{Circle, CircleBuilder, Color, Line, LineBuilder, Tool},
+    Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::PixmapMut;
+
+/// Route response of a routing engine.
+#[derive(Debug, Clone)]
+enum Response {
+    Osrm(String),
+    Valhalla(String),
+}
+
+/// Route tool, drawing the route line and maneuver markers of a response from
+/// the OSRM or Valhalla routing engines, e.g. for a delivery receipt.
+/// The map is fitted to the route like for any other line.
+/// Use [RouteBuilder][RouteBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{tools::RouteBuilder, StaticMapBuilder};
+///
+/// // Response of an OSRM route request, e.g. /route/v1/driving/13.388,52.517;13.397,52.529?steps=true
+/// let response = r#"{
+///     "code": "Ok",
+///     "routes": [{
+///         "geometry": "gfp_I_zupAg@g@whA_v@",
+///         "distance": 1890.5,
+///         "duration": 260.1,
+///         "legs": [{ "steps": [
+///             { "maneuver": { "location": [13.388, 52.517], "type": "depart" } },
+///             { "maneuver": { "location": [13.3882, 52.5172], "type": "arrive" } }
+///         ]}]
+///     }]
+/// }"#;
+///
+/// let route = RouteBuilder::new().osrm(response).build().unwrap();
+/// assert_eq!(route.distance(), 1890.5);
+///
+/// let mut map = StaticMapBuilder::new().build().unwrap();
+/// map.add_tool(route);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Route {
+    line: Line,
+    markers: Vec<Circle>,
+    distance: f64,
+    duration: f64,
+}
+
+/// Builder for [Route][Route].
+#[derive(Debug, Clone)]
+pub struct RouteBuilder {
+    response: Option<Response>,
+    precision: Option<u32>,
+    color: Color,
+    width: f32,
+    casing: Option<(Color, f32)>,
+    maneuvers: bool,
+    maneuver_color: Color,
+    maneuver_radius: f32,
+}
+
+impl Default for RouteBuilder {
+    fn default() -> Self {
+        Self {
+            response: None,
+            precision: None,
+            color: Color::new(true, 30, 110, 230, 255),
+            width: 4.,
+            casing: Some((Color::new(true, 255, 255, 255, 255), 1.5)),
+            maneuvers: true,
+            maneuver_color: Color::new(true, 255, 255, 255, 255),
+            maneuver_radius: 3.,
+        }
+    }
+}
+
+impl RouteBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**, or [valhalla][RouteBuilder::valhalla].
+    /// JSON response of the OSRM route service. The first route is drawn,
+    /// with maneuver markers if the request asked for steps.
+    pub fn osrm<I: Into<String>>(mut self, response: I) -> Self {
+        self.response = Some(Response::Osrm(response.into()));
+        self
+    }
+
+    /// **Required**, or [osrm][RouteBuilder::osrm].
+    /// JSON response of the Valhalla route service.
+    pub fn valhalla<I: Into<String>>(mut self, response: I) -> Self {
+        self.response = Some(Response::Valhalla(response.into()));
+        self
+    }
+
+    /// Decimal places of encoded polylines in the response.
+    /// Default is 5 for OSRM and 6 for Valhalla, set 6 for OSRM requests with `geometries=polyline6`.
+    pub fn precision(mut self, precision: u32) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Color of the route line.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a blue color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Width of the route line, in pixels.
+    /// Default is 4.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Outline drawn beneath the route line, extending `width` pixels beyond it on each side.
+    /// Default is a white casing of 1.5 pixels.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Whether to draw a marker at each maneuver, e.g. turns.
+    /// Enabled by default.
+    pub fn maneuvers(mut self, maneuvers: bool) -> Self {
+        self.maneuvers = maneuvers;
+        self
+    }
+
+    /// Fill color of the maneuver markers, outlined with the route color.
+    /// Default is a white color.
+    pub fn maneuver_color(mut self, color: Color) -> Self {
+        self.maneuver_color = color;
+        self
+    }
+
+    /// Radius of the maneuver markers, in pixels.
+    /// Default is 3.0.
+    pub fn maneuver_radius(mut self, radius: f32) -> Self {
+        self.maneuver_radius = radius;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the response is not a valid route.
+    pub fn build(self) -> Result<Route> {
+        let parsed = match self.response {
+            Some(Response::Osrm(ref response)) => {
+                parse_osrm(response, self.precision.unwrap_or(5))?
+            }
+            Some(Response::Valhalla(ref response)) => {
+                parse_valhalla(response, self.precision.unwrap_or(6))?
+            }
+            None => return Err(Error::BuildError("Route response not supplied.")),
+        };
+
+        let mut line = LineBuilder::new()
+            .lat_coordinates(parsed.coordinates.iter().map(|x| x.0))
+            .lon_coordinates(parsed.coordinates.iter().map(|x| x.1))
+            .color(self.color.clone())
+            .width(self.width);
+        if let Some((ref color, width)) = self.casing {
+            line = line.casing(color.clone(), width);
+        }
+
+        let markers = match self.maneuvers {
+            true => parsed
+                .maneuvers
+                .iter()
+                .map(|(lat, lon)| {
+                    CircleBuilder::new()
+                        .lat_coordinate(*lat)
+                        .lon_coordinate(*lon)
+                        .radius(self.maneuver_radius)
+                        .color(self.maneuver_color.clone())
+                        .casing(self.color.clone(), 1.5)
+                        .build()
+                })
+                .collect::<Result<_>>()?,
+            false => Vec::new(),
+        };
+
+        Ok(Route {
+            line: line.build()?,
+            markers,
+            distance: parsed.distance,
+            duration: parsed.duration,
+        })
+    }
+}
+
+impl Route {
+    /// Length of the route, in meters.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Expected travel time of the route, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+}
+
+/// Route read from a response.
+struct Parsed {
+    /// Coordinates of the route, as (latitude, longitude).
+    coordinates: Vec<(f64, f64)>,
+    /// Locations of the maneuvers, as (latitude, longitude).
+    maneuvers: Vec<(f64, f64)>,
+    distance: f64,
+    duration: f64,
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidRoute(reason.to_string())
+}
+
+fn parse_json(response: &str) -> Result<Json> {
+    Json::parse(response).map_err(|_| invalid("The response is not valid JSON"))
+}
+
+fn parse_osrm(response: &str, precision: u32) -> Result<Parsed> {
+    let json = parse_json(response)?;
+
+    if let Ok(Some(code)) = json.string("code") {
+        if code != "Ok" {
+            let message = json.string("message").ok().flatten().unwrap_or(code);
+            return Err(Error::InvalidRoute(message.to_string()));
+        }
+    }
+
+    let route = match json.array("routes") {
+        Ok(Some([route, ..])) => route,
+        _ => return Err(invalid("The response contains no routes")),
+    };
+
+    // Geometries are encoded polylines by default, or GeoJSON line strings.
+    let coordinates = match route.get("geometry") {
+        Some(Json::String(encoded)) => {
+            polyline::decode(encoded, precision).ok_or_else(|| invalid("Invalid polyline"))?
+        }
+        Some(geometry) => match geometry.array("coordinates") {
+            Ok(Some(positions)) => positions
+                .iter()
+                .map(|position| match position {
+                    Json::Array(position) => match position.as_slice() {
+                        [Json::Number(lon), Json::Number(lat), ..] => Ok((*lat, *lon)),
+                        _ => Err(invalid("Invalid position in geometry")),
+                    },
+                    _ => Err(invalid("Invalid position in geometry")),
+                })
+                .collect::<Result<_>>()?,
+            _ => return Err(invalid("Unsupported geometry")),
+        },
+        None => return Err(invalid("The route has no geometry")),
+    };
+
+    let steps = route
+        .array("legs")
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|leg| leg.array("steps").ok().flatten().unwrap_or_default());
+    let maneuvers = steps
+        .filter_map(|step| step.get("maneuver")?.pair("location").ok().flatten())
+        .map(|(lon, lat)| (lat, lon))
+        .collect();
+
+    Ok(Parsed {
+        coordinates,
+        maneuvers,
+        distance: route.number("distance").ok().flatten().unwrap_or(0.),
+        duration: route.number("duration").ok().flatten().unwrap_or(0.),
+    })
+}
+
+fn parse_valhalla(response: &str, precision: u32) -> Result<Parsed> {
+    let json = parse_json(response)?;
+
+    if let Ok(Some(message)) = json.string("error") {
+        return Err(Error::InvalidRoute(message.to_string()));
+    }
+
+    let trip = json
+        .get("trip")
+        .ok_or_else(|| invalid("The response contains no trip"))?;
+    let legs = match trip.array("legs") {
+        Ok(Some(legs)) if !legs.is_empty() => legs,
+        _ => return Err(invalid("The trip has no legs")),
+    };
+
+    let mut coordinates = Vec::new();
+    let mut maneuvers = Vec::new();
+    for leg in legs {
+        let shape = match leg.string("shape") {
+            Ok(Some(shape)) => {
+                polyline::decode(shape, precision).ok_or_else(|| invalid("Invalid polyline"))?
+            }
+            _ => return Err(invalid("A leg has no shape")),
+        };
+
+        // Maneuvers refer to the shape of their leg by index.
+        let indices = leg
+            .array("maneuvers")
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|maneuver| maneuver.number("begin_shape_index").ok().flatten());
+        maneuvers.extend(indices.filter_map(|index| shape.get(index as usize).copied()));
+        coordinates.extend(shape);
+    }
+
+    let summary = trip.get("summary");
+    let number = |key: &str| {
+        summary
+            .and_then(|summary| summary.number(key).ok().flatten())
+            .unwrap_or(0.)
+    };
+    let meters_per_unit = match trip.string("units") {
+        Ok(Some("miles")) => 1609.344,
+        _ => 1000.,
+    };
+
+    Ok(Parsed {
+        coordinates,
+        maneuvers,
+        distance: number("length") * meters_per_unit,
+        duration: number("time"),
+    })
+}
+
+impl Tool for Route {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        self.line.extent(zoom, tile_size)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        self.markers
+            .iter()
+            .filter_map(|marker| marker.pixel_extent(bounds))
+            .chain(self.line.pixel_extent(bounds))
+            .reduce(|a, b| {
+                PixelRect::new(
+                    a.x_min.min(b.x_min),
+                    a.y_min.min(b.y_min),
+                    a.x_max.max(b.x_max),
+                    a.y_max.max(b.y_max),
+                )
+            })
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "route".digest(state);
+        (self.markers.len() as u32).digest(state);
+        self.line.digest(state) && self.markers.iter().all(|marker| marker.digest(state))
+    }
+
+    fn geojson(&self) -> Option<String> {
+        self.line.geojson()
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let (width, height) = (pixmap.width(), pixmap.height());
+        let tools = std::iter::once(&self.line as &dyn Tool)
+            .chain(self.markers.iter().map(|marker| marker as &dyn Tool));
+        for tool in tools {
+            // Each tool takes the pixmap by value, so it is borrowed anew for each.
+            if let Some(target) = PixmapMut::from_bytes(pixmap.data_mut(), width, height) {
+                tool.draw_at_scale(bounds, scale, target);
+            }
+        }
+    }
+}
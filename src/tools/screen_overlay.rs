@@ -0,0 +1,304 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    tools::Tool,
+};
+use std::hash::{Hash, Hasher};
+use tiny_skia::{FilterQuality, Pixmap, PixmapMut, PixmapPaint, Transform};
+
+/// Position of an item of a [ScreenOverlay][ScreenOverlay] on the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Alignment {
+    /// Top left corner.
+    TopLeft,
+    /// Center of the top edge.
+    Top,
+    /// Top right corner.
+    TopRight,
+    /// Center of the left edge.
+    Left,
+    /// Center of the image.
+    #[default]
+    Center,
+    /// Center of the right edge.
+    Right,
+    /// Bottom left corner.
+    BottomLeft,
+    /// Center of the bottom edge.
+    Bottom,
+    /// Bottom right corner.
+    BottomRight,
+}
+
+impl Alignment {
+    /// Top left of an item of the given size, `margin` pixels from the edges of the image.
+    fn anchor(&self, bounds: &Bounds, width: f64, height: f64, margin: f64) -> (f64, f64) {
+        let (image_width, image_height) = (f64::from(bounds.width), f64::from(bounds.height));
+        let left = margin;
+        let center = (image_width - width) / 2.;
+        let right = image_width - margin - width;
+        let top = margin;
+        let middle = (image_height - height) / 2.;
+        let bottom = image_height - margin - height;
+
+        match self {
+            Alignment::TopLeft => (left, top),
+            Alignment::Top => (center, top),
+            Alignment::TopRight => (right, top),
+            Alignment::Left => (left, middle),
+            Alignment::Center => (center, middle),
+            Alignment::Right => (right, middle),
+            Alignment::BottomLeft => (left, bottom),
+            Alignment::Bottom => (center, bottom),
+            Alignment::BottomRight => (right, bottom),
+        }
+    }
+
+    /// Point of an item at the alignment, as fractions of its width and height
+    /// from its top left corner, e.g. (0.5, 1.) for the center of its bottom edge.
+    pub(crate) fn fractions(&self) -> (f64, f64) {
+        match self {
+            Alignment::TopLeft => (0., 0.),
+            Alignment::Top => (0.5, 0.),
+            Alignment::TopRight => (1., 0.),
+            Alignment::Left => (0., 0.5),
+            Alignment::Center => (0.5, 0.5),
+            Alignment::Right => (1., 0.5),
+            Alignment::BottomLeft => (0., 1.),
+            Alignment::Bottom => (0.5, 1.),
+            Alignment::BottomRight => (1., 1.),
+        }
+    }
+
+    /// Whether items are moved up, rather than down, to make room for earlier items.
+    fn stacks_up(&self) -> bool {
+        matches!(
+            self,
+            Alignment::BottomLeft | Alignment::Bottom | Alignment::BottomRight
+        )
+    }
+}
+
+/// Layer of items placed relative to the image rather than coordinates,
+/// such as legends, compasses, text stamps and logos.
+///
+/// Each item is placed at its alignment, and moved away from the edge of the image
+/// past any earlier item it would overlap, so items at the same alignment are stacked
+/// and items at neighbouring alignments do not collide on small images.
+///
+/// Items are tools with a [pixel extent][Tool::pixel_extent], e.g.
+/// [Compass][crate::tools::Compass], [Stamp][crate::tools::Stamp] and
+/// [ProportionalLegend][crate::tools::ProportionalLegend]. Their own corner and margin
+/// are replaced by the layout of the overlay. Items without a pixel extent are left out.
+///
+/// The overlay does not affect the bounds of the map.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Alignment, CompassBuilder, CompassStyle, ScreenOverlay};
+///
+/// let overlay = ScreenOverlay::new()
+///     .margin(8.)
+///     .item(Alignment::TopRight, CompassBuilder::new().build().unwrap())
+///     // Stacked below the first compass.
+///     .item(
+///         Alignment::TopRight,
+///         CompassBuilder::new()
+///             .style(CompassStyle::Rose)
+///             .build()
+///             .unwrap(),
+///     );
+/// ```
+pub struct ScreenOverlay {
+    items: Vec<(Alignment, Box<dyn Tool>)>,
+    margin: f32,
+    gap: f32,
+}
+
+impl Default for ScreenOverlay {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            margin: 10.,
+            gap: 6.,
+        }
+    }
+}
+
+/// Image item of a [ScreenOverlay][ScreenOverlay], e.g. a logo.
+struct Picture(Pixmap);
+
+impl Tool for Picture {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        (f64::NAN, f64::NAN, f64::NAN, f64::NAN)
+    }
+
+    fn pixel_extent(&self, _: &Bounds) -> Option<PixelRect> {
+        Some(PixelRect::new(
+            0.,
+            0.,
+            self.0.width().into(),
+            self.0.height().into(),
+        ))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "picture".digest(state);
+        self.0.digest(state);
+        true
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, _: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        pixmap.draw_pixmap(
+            0,
+            0,
+            self.0.as_ref(),
+            &PixmapPaint {
+                quality: FilterQuality::Bicubic,
+                ..Default::default()
+            },
+            Transform::from_scale(scale, scale),
+            None,
+        );
+    }
+}
+
+impl ScreenOverlay {
+    /// Creates a new, empty [ScreenOverlay][ScreenOverlay].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an item at the given alignment. Items are placed in the order they are added.
+    pub fn item(mut self, alignment: Alignment, tool: impl Tool + 'static) -> Self {
+        self.items.push((alignment, Box::new(tool)));
+        self
+    }
+
+    /// Add an image at the given alignment, e.g. a logo.
+    pub fn image(mut self, alignment: Alignment, image: Pixmap) -> Self {
+        self.items.push((alignment, Box::new(Picture(image))));
+        self
+    }
+
+    /// Distance between the items and the edges of the image, in pixels.
+    /// Default is 10.0.
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Distance between stacked items, in pixels.
+    /// Default is 6.0.
+    pub fn gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Where each item is drawn by itself, and where it is placed by the overlay.
+    fn layout(&self, bounds: &Bounds) -> Vec<Option<(PixelRect, PixelRect)>> {
+        let (margin, gap) = (f64::from(self.margin), f64::from(self.gap));
+        let mut placed: Vec<PixelRect> = Vec::new();
+
+        self.items
+            .iter()
+            .map(|(alignment, tool)| {
+                let drawn = tool.pixel_extent(bounds)?;
+                let (width, height) = (drawn.width(), drawn.height());
+                let (x, mut y) = alignment.anchor(bounds, width, height, margin);
+
+                // Each move clears one earlier item, so the loop ends.
+                for _ in 0..=placed.len() {
+                    let rect = PixelRect::new(x, y, x + width, y + height);
+                    let blocking = placed.iter().find(|other| {
+                        rect.x_min < other.x_max + gap
+                            && other.x_min < rect.x_max + gap
+                            && rect.y_min < other.y_max + gap
+                            && other.y_min < rect.y_max + gap
+                    });
+                    match blocking {
+                        Some(other) if alignment.stacks_up() => y = other.y_min - gap - height,
+                        Some(other) => y = other.y_max + gap,
+                        None => break,
+                    }
+                }
+
+                let target = PixelRect::new(x, y, x + width, y + height);
+                placed.push(target);
+                Some((drawn, target))
+            })
+            .collect()
+    }
+}
+
+impl Tool for ScreenOverlay {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        (f64::NAN, f64::NAN, f64::NAN, f64::NAN)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        self.layout(bounds)
+            .into_iter()
+            .flatten()
+            .map(|(_, target)| target)
+            .reduce(|a, b| {
+                PixelRect::new(
+                    a.x_min.min(b.x_min),
+                    a.y_min.min(b.y_min),
+                    a.x_max.max(b.x_max),
+                    a.y_max.max(b.y_max),
+                )
+            })
+    }
+
+    fn digest(&self, mut state: &mut dyn Hasher) -> bool {
+        "screen_overlay".digest(state);
+        self.margin.digest(state);
+        self.gap.digest(state);
+        state.write_usize(self.items.len());
+        for (alignment, tool) in self.items.iter() {
+            alignment.hash(&mut state);
+            if !tool.digest(state) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        for ((_, tool), layout) in self.items.iter().zip(self.layout(bounds)) {
+            let (drawn, target) = match layout {
+                Some(layout) => layout,
+                None => continue,
+            };
+
+            // Items are drawn where they place themselves, and moved by whole pixels
+            // to their place in the overlay, so they are not resampled.
+            let mut layer = match Pixmap::new(pixmap.width(), pixmap.height()) {
+                Some(layer) => layer,
+                None => return,
+            };
+            tool.draw_at_scale(bounds, scale, layer.as_mut());
+
+            let offset = |target: f64, drawn: f64| {
+                ((target - drawn).round() * f64::from(scale)).round() as i32
+            };
+            pixmap.draw_pixmap(
+                offset(target.x_min, drawn.x_min),
+                offset(target.y_min, drawn.y_min),
+                layer.as_ref(),
+                &PixmapPaint::default(),
+                Transform::default(),
+                None,
+            );
+        }
+    }
+}
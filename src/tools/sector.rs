@@ -0,0 +1,388 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geojson::Feature,
+    json::Json,
+    lat_to_y, lon_to_x_continuous, m_to_px,
+    scene::{member, object, Scene},
+    tools::{draw_casing, edge_margin, solid_colors, Color, Gradient, Tool},
+    x_to_lon, y_to_lat, Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{FillRule, LineCap, PathBuilder, PixmapMut, Stroke, Transform};
+
+/// Greatest angle between the points the arc is drawn through, in degrees.
+const ARC_STEP: f64 = 1.;
+
+/// Sector tool, a wedge of a circle between two bearings, or the arc along its edge,
+/// e.g. the coverage of an antenna sector or the field of view of a camera.
+/// Use [SectorBuilder][SectorBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, SectorBuilder};
+///
+/// // A 60 degree wedge facing east.
+/// let sector = SectorBuilder::new()
+///     .lat_coordinate(59.91)
+///     .lon_coordinate(10.75)
+///     .radius_in_meters(1500.)
+///     .bearings(60., 120.)
+///     .color(Color::new(true, 40, 120, 220, 120))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sector {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    color: Color,
+    radius: f64,
+    radius_in_meters: bool,
+    start_bearing: f64,
+    end_bearing: f64,
+    stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+}
+
+/// Builder for [Sector][Sector].
+#[derive(Debug, Clone, Default)]
+pub struct SectorBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    color: Color,
+    radius: Option<f64>,
+    radius_in_meters: bool,
+    bearings: Option<(f64, f64)>,
+    stroke_width: Option<f32>,
+    casing: Option<(Color, f32)>,
+    gradient: Option<Gradient>,
+}
+
+impl SectorBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// The center of the sector as a latitude coordinate.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The center of the sector as a longitude coordinate.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// **Required**.
+    /// Sector radius in pixels.
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = Some(radius.into());
+        self.radius_in_meters = false;
+        self
+    }
+
+    /// **Required**.
+    /// Sector radius in meters.
+    pub fn radius_in_meters(mut self, radius: f64) -> Self {
+        self.radius = Some(radius);
+        self.radius_in_meters = true;
+        self
+    }
+
+    /// **Required**.
+    /// Bearings the sector spans clockwise from `start` to `end`,
+    /// in degrees clockwise from north, e.g. (330.0, 30.0) for a sector facing north.
+    /// Equal bearings span the whole circle.
+    pub fn bearings(mut self, start: f64, end: f64) -> Self {
+        self.bearings = Some((start, end));
+        self
+    }
+
+    /// Draw a filled wedge (the default).
+    pub fn filled(mut self) -> Self {
+        self.stroke_width = None;
+        self
+    }
+
+    /// Draw only the arc along the edge of the sector.
+    /// Stroke `width` is in pixels, and must be >= 0.0.
+    /// When set to 0, a hairline stroking will be used.
+    pub fn stroke_width(mut self, width: f32) -> Self {
+        self.stroke_width = Some(width);
+        self
+    }
+
+    /// Outline drawn beneath the sector, extending `width` pixels beyond its edges.
+    pub fn casing(mut self, color: Color, width: f32) -> Self {
+        self.casing = Some((color, width));
+        self
+    }
+
+    /// Fill with a [Gradient][Gradient] instead of the color.
+    /// Only applies to filled wedges.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the radius is not positive.
+    pub fn build(self) -> Result<Sector> {
+        let radius = self
+            .radius
+            .ok_or(Error::BuildError("Radius not supplied."))?;
+        let (start_bearing, end_bearing) = self
+            .bearings
+            .ok_or(Error::BuildError("Bearings not supplied."))?;
+
+        if radius.is_nan() || radius <= 0. {
+            return Err(Error::BuildError("Radius must be positive."));
+        }
+
+        Ok(Sector {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            color: self.color,
+            radius,
+            radius_in_meters: self.radius_in_meters,
+            start_bearing,
+            end_bearing,
+            stroke_width: self.stroke_width,
+            casing: self.casing,
+            gradient: self.gradient,
+        })
+    }
+}
+
+impl Sector {
+    fn radius_px_at(&self, bounds: &Bounds) -> f64 {
+        if self.radius_in_meters {
+            bounds.m_to_px(self.radius, self.lat_coordinate)
+        } else {
+            self.radius
+        }
+    }
+
+    /// Outline of the sector on a unit circle, as offsets from the center with y pointing down.
+    /// The arc is followed clockwise, and wedges start and end at the center.
+    fn outline(&self) -> Vec<(f64, f64)> {
+        let sweep = match (self.end_bearing - self.start_bearing).rem_euclid(360.) {
+            0. => 360.,
+            sweep => sweep,
+        };
+        let steps = (sweep / ARC_STEP).ceil().max(1.) as usize;
+
+        let arc = (0..=steps).map(|step| {
+            let bearing = (self.start_bearing + sweep * step as f64 / steps as f64).to_radians();
+            (bearing.sin(), -bearing.cos())
+        });
+
+        match self.stroke_width.is_none() && sweep < 360. {
+            true => std::iter::once((0., 0.))
+                .chain(arc)
+                .chain(std::iter::once((0., 0.)))
+                .collect(),
+            false => arc.collect(),
+        }
+    }
+
+    /// Smallest and largest offsets of the outline, scaled by the radius.
+    fn offsets(&self, radius: f64) -> (f64, f64, f64, f64) {
+        self.outline().into_iter().fold(
+            (
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(x_min, y_min, x_max, y_max), (x, y)| {
+                (
+                    x_min.min(x * radius),
+                    y_min.min(y * radius),
+                    x_max.max(x * radius),
+                    y_max.max(y * radius),
+                )
+            },
+        )
+    }
+}
+
+impl SectorBuilder {
+    /// Builder with the settings of a sector in a scene.
+    pub(crate) fn from_json(json: &Json) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.lat_coordinate = json.number("lat_coordinate")?;
+        builder.lon_coordinate = json.number("lon_coordinate")?;
+        builder.radius = json.number("radius")?;
+        builder.radius_in_meters = json.boolean("radius_in_meters")?.unwrap_or(false);
+        builder.bearings = json.pair("bearings")?;
+        builder.stroke_width = json.number("stroke_width")?.map(|x| x as f32);
+        builder.casing = member(json, "casing")?;
+        builder.gradient = member(json, "gradient")?;
+        if let Some(color) = member(json, "color")? {
+            builder.color = color;
+        }
+        Ok(builder)
+    }
+}
+
+impl Tool for Sector {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        let radius = if self.radius_in_meters {
+            // m_to_px assumes 256px tiles.
+            m_to_px(self.radius, self.lat_coordinate, zoom) * tile_size / 256.
+        } else {
+            self.radius
+        };
+        let (dx_min, dy_min, dx_max, dy_max) = self.offsets(radius / tile_size);
+
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
+        let y = lat_to_y(self.lat_coordinate, zoom);
+
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x + dx_min, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + dy_max, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + dx_max, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y + dy_min, zoom) - lat;
+
+        (lon_min, lat_min, lon_max, lat_max)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (dx_min, dy_min, dx_max, dy_max) = self.offsets(self.radius_px_at(bounds));
+        Some(
+            PixelRect::new(x + dx_min, y + dy_min, x + dx_max, y + dy_max)
+                .outset(edge_margin(self.stroke_width, &self.casing)),
+        )
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        Some((self.lon_coordinate, self.lat_coordinate))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "sector".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.color.digest(state);
+        self.radius.digest(state);
+        self.radius_in_meters.digest(state);
+        self.start_bearing.digest(state);
+        self.end_bearing.digest(state);
+        self.stroke_width.digest(state);
+        self.casing.digest(state);
+        self.gradient.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let unit = if self.radius_in_meters { "m" } else { "px" };
+        let feature = Feature::point(self.lon_coordinate, self.lat_coordinate)
+            .number("radius", self.radius)
+            .string("radius-unit", unit)
+            .number("start-bearing", self.start_bearing)
+            .number("end-bearing", self.end_bearing)
+            .style(&self.color, self.stroke_width);
+        Some(feature.to_string())
+    }
+
+    fn scene(&self) -> Option<String> {
+        if !solid_colors(&self.color, &self.casing) {
+            return None;
+        }
+
+        let scene = object(vec![
+            ("type", "sector".into()),
+            ("lat_coordinate", self.lat_coordinate.into()),
+            ("lon_coordinate", self.lon_coordinate.into()),
+            ("color", self.color.to_json()),
+            ("radius", self.radius.into()),
+            ("radius_in_meters", self.radius_in_meters.into()),
+            ("bearings", (self.start_bearing, self.end_bearing).into()),
+            ("stroke_width", self.stroke_width.into()),
+            ("casing", self.casing.as_ref().map(Scene::to_json).into()),
+            (
+                "gradient",
+                self.gradient.as_ref().map(Scene::to_json).into(),
+            ),
+        ]);
+        Some(scene.to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let radius = self.radius_px_at(bounds);
+
+        let mut path_builder = PathBuilder::new();
+        for (index, (dx, dy)) in self.outline().into_iter().enumerate() {
+            let (px, py) = ((x + dx * radius) as f32, (y + dy * radius) as f32);
+            match index {
+                0 => path_builder.move_to(px, py),
+                _ => path_builder.line_to(px, py),
+            }
+        }
+        if self.stroke_width.is_none() {
+            path_builder.close();
+        }
+
+        if let Some(path) = path_builder.finish() {
+            if let Some(casing) = self.casing.as_ref() {
+                draw_casing(
+                    &mut pixmap,
+                    &path,
+                    casing,
+                    self.stroke_width,
+                    LineCap::Butt,
+                    transform,
+                );
+            }
+
+            if let Some(width) = self.stroke_width {
+                pixmap.stroke_path(
+                    &path,
+                    &self.color.0,
+                    &Stroke {
+                        width,
+                        ..Default::default()
+                    },
+                    transform,
+                    None,
+                );
+            } else {
+                let gradient = self.gradient.as_ref().and_then(|x| x.paint(bounds));
+                pixmap.fill_path(
+                    &path,
+                    gradient.as_ref().unwrap_or(&self.color.0),
+                    FillRule::Winding,
+                    transform,
+                    None,
+                );
+            }
+        }
+    }
+}
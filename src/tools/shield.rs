@@ -0,0 +1,495 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    coord::{unzip, LatLon},
+    digest::Digest,
+    geojson::Feature,
+    tools::{Color, TextStyle, Tool},
+    Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{Path, PathBuilder, PixmapMut, Rect, Stroke, Transform};
+
+/// Shape of a [Shield][Shield].
+#[derive(Debug, Clone)]
+pub enum ShieldShape {
+    /// A plain rectangle.
+    Rectangle,
+    /// A rectangle with corners rounded by the given radius, in pixels.
+    RoundedRectangle(f32),
+    /// A custom path within the unit square from (0, 0) to (1, 1),
+    /// stretched to the size of the shield, e.g. the outline of a highway shield.
+    Custom(Path),
+}
+
+impl Digest for ShieldShape {
+    fn digest(&self, state: &mut dyn Hasher) {
+        match *self {
+            ShieldShape::Rectangle => state.write_u8(0),
+            ShieldShape::RoundedRectangle(radius) => {
+                state.write_u8(1);
+                radius.digest(state);
+            }
+            ShieldShape::Custom(ref path) => {
+                state.write_u8(2);
+                format!("{:?}", path).digest(state);
+            }
+        }
+    }
+}
+
+/// Where a [Shield][Shield] is placed.
+#[derive(Debug, Clone)]
+enum Placement {
+    /// At a coordinate, as (longitude, latitude).
+    Point(f64, f64),
+    /// Along a line, every `spacing` pixels, or once at its middle if None.
+    Line {
+        lat_coordinates: Vec<f64>,
+        lon_coordinates: Vec<f64>,
+        spacing: Option<f32>,
+    },
+}
+
+impl Digest for Placement {
+    fn digest(&self, state: &mut dyn Hasher) {
+        match *self {
+            Placement::Point(lon, lat) => {
+                state.write_u8(0);
+                (lon, lat).digest(state);
+            }
+            Placement::Line {
+                ref lat_coordinates,
+                ref lon_coordinates,
+                spacing,
+            } => {
+                state.write_u8(1);
+                lat_coordinates.digest(state);
+                lon_coordinates.digest(state);
+                spacing.digest(state);
+            }
+        }
+    }
+}
+
+/// Shield tool, a badge with centered text such as a route number,
+/// placed at a coordinate or along a line.
+/// Use [ShieldBuilder][ShieldBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::tools::{Color, Font, ShieldBuilder, ShieldShape, TextStyle};
+///
+/// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap())
+///     .color(Color::new(true, 255, 255, 255, 255));
+///
+/// let shield = ShieldBuilder::new()
+///     .text("E6")
+///     .style(style)
+///     .along_line(vec![59.91, 60.39], vec![10.75, 11.21])
+///     .shape(ShieldShape::RoundedRectangle(3.))
+///     .color(Color::new(true, 0, 120, 60, 255))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Shield {
+    text: String,
+    style: TextStyle,
+    placement: Placement,
+    shape: ShieldShape,
+    color: Color,
+    border: Option<(Color, f32)>,
+    padding: (f32, f32),
+}
+
+/// Builder for [Shield][Shield].
+#[derive(Debug, Clone)]
+pub struct ShieldBuilder {
+    text: Option<String>,
+    style: Option<TextStyle>,
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    line: Option<(Vec<f64>, Vec<f64>)>,
+    spacing: Option<f32>,
+    shape: ShieldShape,
+    color: Color,
+    border: Option<(Color, f32)>,
+    padding: (f32, f32),
+}
+
+impl Default for ShieldBuilder {
+    fn default() -> Self {
+        Self {
+            text: None,
+            style: None,
+            lat_coordinate: None,
+            lon_coordinate: None,
+            line: None,
+            spacing: None,
+            shape: ShieldShape::RoundedRectangle(3.),
+            color: Color::new(true, 255, 255, 255, 255),
+            border: Some((Color::default(), 1.)),
+            padding: (4., 2.),
+        }
+    }
+}
+
+impl ShieldBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// Text of the shield, e.g. a route number.
+    pub fn text<I: Into<String>>(mut self, text: I) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// **Required**.
+    /// Style of the text.
+    pub fn style(mut self, style: TextStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// **Required**, unless placed [along a line][ShieldBuilder::along_line].
+    /// The center of the shield as a latitude coordinate.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**, unless placed [along a line][ShieldBuilder::along_line].
+    /// The center of the shield as a longitude coordinate.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// Place the shield at the middle of a line instead of at a coordinate,
+    /// e.g. along the route it labels.
+    pub fn along_line<I, J>(mut self, lat_coordinates: I, lon_coordinates: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<f64>,
+        J: IntoIterator,
+        J::Item: Into<f64>,
+    {
+        self.line = Some((
+            lat_coordinates.into_iter().map(Into::into).collect(),
+            lon_coordinates.into_iter().map(Into::into).collect(),
+        ));
+        self
+    }
+
+    /// Place the shield at the middle of a line given by its coordinates,
+    /// see [along_line][ShieldBuilder::along_line] and [LatLon][LatLon].
+    pub fn along_line_coordinates<I, C>(mut self, coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<LatLon>,
+    {
+        self.line = Some(unzip(coordinates));
+        self
+    }
+
+    /// Repeat the shield every `spacing` pixels along the line, instead of once at its middle.
+    /// Only applies to shields placed [along a line][ShieldBuilder::along_line].
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = Some(spacing);
+        self
+    }
+
+    /// Shape of the shield.
+    /// Default is [ShieldShape::RoundedRectangle][ShieldShape::RoundedRectangle] with radius 3.0.
+    pub fn shape(mut self, shape: ShieldShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Fill color of the shield.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is white.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Border of the shield, `width` in pixels.
+    /// Default is a black 1.0 pixel border.
+    pub fn border(mut self, color: Color, width: f32) -> Self {
+        self.border = Some((color, width));
+        self
+    }
+
+    /// Draw the shield without a border.
+    pub fn no_border(mut self) -> Self {
+        self.border = None;
+        self
+    }
+
+    /// Space between the text and the edge of the shield in x and y direction, in pixels.
+    /// Default is (4.0, 2.0).
+    pub fn padding(mut self, padding: (f32, f32)) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the line coordinates do not match.
+    pub fn build(self) -> Result<Shield> {
+        let placement = match (self.line, self.lat_coordinate, self.lon_coordinate) {
+            (Some((lat_coordinates, lon_coordinates)), _, _) => {
+                if lat_coordinates.len() != lon_coordinates.len() || lat_coordinates.is_empty() {
+                    return Err(Error::BuildError(
+                        "Line coordinates must be non-empty and of equal length.",
+                    ));
+                }
+                Placement::Line {
+                    lat_coordinates,
+                    lon_coordinates,
+                    spacing: self.spacing,
+                }
+            }
+            (None, Some(lat), Some(lon)) => Placement::Point(lon, lat),
+            (None, None, _) => return Err(Error::BuildError("Latitude coordinate not supplied.")),
+            (None, _, None) => return Err(Error::BuildError("Longitude coordinate not supplied.")),
+        };
+
+        Ok(Shield {
+            text: self.text.ok_or(Error::BuildError("Text not supplied."))?,
+            style: self
+                .style
+                .ok_or(Error::BuildError("Text style not supplied."))?,
+            placement,
+            shape: self.shape,
+            color: self.color,
+            border: self.border,
+            padding: self.padding,
+        })
+    }
+}
+
+impl Shield {
+    /// Width and height of the shield, in pixels.
+    fn size(&self) -> (f32, f32) {
+        let (width, height) = self.style.measure(&self.text);
+        (
+            width as f32 + 2. * self.padding.0,
+            height as f32 + 2. * self.padding.1,
+        )
+    }
+
+    /// Centers of the shields on the map, in pixels.
+    fn centers(&self, bounds: &Bounds) -> Vec<(f64, f64)> {
+        let (lat_coordinates, lon_coordinates, spacing) = match self.placement {
+            Placement::Point(lon, lat) => return vec![bounds.lon_lat_to_px(lon, lat)],
+            Placement::Line {
+                ref lat_coordinates,
+                ref lon_coordinates,
+                spacing,
+            } => (lat_coordinates, lon_coordinates, spacing),
+        };
+
+        let points: Vec<(f64, f64)> = lon_coordinates
+            .iter()
+            .zip(lat_coordinates)
+            .map(|(lon, lat)| bounds.lon_lat_to_px(*lon, *lat))
+            .collect();
+        let length: f64 = points
+            .windows(2)
+            .map(|x| (x[1].0 - x[0].0).hypot(x[1].1 - x[0].1))
+            .sum();
+
+        // Shields are spread evenly, starting half the spacing into the line.
+        let distances: Vec<f64> = match spacing.map(f64::from).filter(|x| *x > 0.) {
+            Some(spacing) => (0..)
+                .map(|i| (f64::from(i) + 0.5) * spacing)
+                .take_while(|x| *x <= length)
+                .collect(),
+            None => vec![length / 2.],
+        };
+
+        distances
+            .into_iter()
+            .filter_map(|distance| point_along(&points, distance))
+            .collect()
+    }
+
+    fn path(&self, x: f32, y: f32, width: f32, height: f32) -> Option<Path> {
+        match self.shape {
+            ShieldShape::Rectangle => {
+                Rect::from_xywh(x, y, width, height).map(PathBuilder::from_rect)
+            }
+            ShieldShape::RoundedRectangle(radius) => rounded_rect(x, y, width, height, radius),
+            ShieldShape::Custom(ref path) => path
+                .clone()
+                .transform(Transform::from_row(width, 0., 0., height, x, y)),
+        }
+    }
+}
+
+/// Point at `distance` pixels along a polyline.
+fn point_along(points: &[(f64, f64)], distance: f64) -> Option<(f64, f64)> {
+    let mut remaining = distance;
+    for segment in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+        let length = (x1 - x0).hypot(y1 - y0);
+        if remaining <= length && length > 0. {
+            let t = remaining / length;
+            return Some((x0 + t * (x1 - x0), y0 + t * (y1 - y0)));
+        }
+        remaining -= length;
+    }
+    points.last().copied()
+}
+
+/// Rectangle with rounded corners, approximated with cubic curves.
+fn rounded_rect(x: f32, y: f32, width: f32, height: f32, radius: f32) -> Option<Path> {
+    let r = radius.max(0.).min(width / 2.).min(height / 2.);
+    // Distance of the control points from the corners, for circular arcs.
+    let k = r * (1. - 0.552_284_8);
+    let (right, bottom) = (x + width, y + height);
+
+    let mut path = PathBuilder::new();
+    path.move_to(x + r, y);
+    path.line_to(right - r, y);
+    path.cubic_to(right - k, y, right, y + k, right, y + r);
+    path.line_to(right, bottom - r);
+    path.cubic_to(right, bottom - k, right - k, bottom, right - r, bottom);
+    path.line_to(x + r, bottom);
+    path.cubic_to(x + k, bottom, x, bottom - k, x, bottom - r);
+    path.line_to(x, y + r);
+    path.cubic_to(x, y + k, x + k, y, x + r, y);
+    path.close();
+    path.finish()
+}
+
+impl Tool for Shield {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        match self.placement {
+            Placement::Point(lon, lat) => (lon, lat, lon, lat),
+            Placement::Line {
+                ref lat_coordinates,
+                ref lon_coordinates,
+                ..
+            } => (
+                lon_coordinates.iter().copied().fold(f64::NAN, f64::min),
+                lat_coordinates.iter().copied().fold(f64::NAN, f64::min),
+                lon_coordinates.iter().copied().fold(f64::NAN, f64::max),
+                lat_coordinates.iter().copied().fold(f64::NAN, f64::max),
+            ),
+        }
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (width, height) = self.size();
+        let margin = f64::from(self.border.as_ref().map_or(0., |x| x.1 / 2.));
+        let (half_width, half_height) = (f64::from(width) / 2., f64::from(height) / 2.);
+
+        self.centers(bounds)
+            .into_iter()
+            .map(|(x, y)| {
+                PixelRect::new(
+                    x - half_width,
+                    y - half_height,
+                    x + half_width,
+                    y + half_height,
+                )
+                .outset(margin)
+            })
+            .reduce(|a, b| {
+                PixelRect::new(
+                    a.x_min.min(b.x_min),
+                    a.y_min.min(b.y_min),
+                    a.x_max.max(b.x_max),
+                    a.y_max.max(b.y_max),
+                )
+            })
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        match self.placement {
+            Placement::Point(lon, lat) => Some((lon, lat)),
+            Placement::Line { .. } => None,
+        }
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "shield".digest(state);
+        self.text.digest(state);
+        self.style.digest(state);
+        self.placement.digest(state);
+        self.shape.digest(state);
+        self.color.digest(state);
+        self.border.digest(state);
+        self.padding.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        let feature = match self.placement {
+            Placement::Point(lon, lat) => Feature::point(lon, lat),
+            Placement::Line {
+                ref lat_coordinates,
+                ref lon_coordinates,
+                ..
+            } => Feature::line_string(lon_coordinates, lat_coordinates),
+        };
+        Some(
+            feature
+                .string("label", &self.text)
+                .color("fill", &self.color)
+                .to_string(),
+        )
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let (width, height) = self.size();
+
+        for (x, y) in self.centers(bounds) {
+            // Whole pixels keep the text sharp.
+            let left = (x as f32 - width / 2.).round();
+            let top = (y as f32 - height / 2.).round();
+
+            if let Some(path) = self.path(left, top, width, height) {
+                pixmap.fill_path(
+                    &path,
+                    &self.color.0,
+                    tiny_skia::FillRule::Winding,
+                    transform,
+                    None,
+                );
+
+                if let Some((ref color, width)) = self.border {
+                    pixmap.stroke_path(
+                        &path,
+                        &color.0,
+                        &Stroke {
+                            width,
+                            ..Default::default()
+                        },
+                        transform,
+                        None,
+                    );
+                }
+            }
+
+            self.style.draw_centered(
+                &mut pixmap,
+                &self.text,
+                left + width / 2.,
+                top + height / 2.,
+                scale,
+            );
+        }
+    }
+}
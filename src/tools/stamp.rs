@@ -0,0 +1,241 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    tools::{Color, Corner, TextStyle, Tool},
+    Error, Result,
+};
+use std::hash::Hasher;
+use tiny_skia::{PixmapMut, PixmapPaint, Transform};
+
+/// Stamp tool, text placed at a position on the image rather than a coordinate,
+/// e.g. a timestamp, a frame counter or a note on the data source.
+/// Use [StampBuilder][StampBuilder] as an entrypoint.
+///
+/// The text may span several lines, separated by `\n`.
+/// The stamp does not affect the bounds of the map.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::tools::{Color, Corner, Font, StampBuilder, TextStyle};
+///
+/// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap());
+///
+/// let stamp = StampBuilder::new()
+///     .text("2024-05-17 14:00 UTC\nFrame 12 of 48")
+///     .style(style)
+///     .corner(Corner::TopLeft)
+///     .background(Color::new(true, 255, 255, 255, 200))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Stamp {
+    text: String,
+    style: TextStyle,
+    corner: Corner,
+    position: Option<(f32, f32)>,
+    margin: f32,
+    background: Option<Color>,
+    padding: f32,
+}
+
+/// Builder for [Stamp][Stamp].
+#[derive(Debug, Clone)]
+pub struct StampBuilder {
+    text: Option<String>,
+    style: Option<TextStyle>,
+    corner: Corner,
+    position: Option<(f32, f32)>,
+    margin: f32,
+    background: Option<Color>,
+    padding: f32,
+}
+
+impl Default for StampBuilder {
+    fn default() -> Self {
+        Self {
+            text: None,
+            style: None,
+            corner: Corner::BottomLeft,
+            position: None,
+            margin: 10.,
+            background: None,
+            padding: 4.,
+        }
+    }
+}
+
+impl StampBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// Text of the stamp, with lines separated by `\n`.
+    pub fn text<I: Into<String>>(mut self, text: I) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// **Required**.
+    /// Style of the text.
+    pub fn style(mut self, style: TextStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Corner of the image to place the stamp in.
+    /// Default is [Corner::BottomLeft][Corner::BottomLeft].
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// Position of the top left of the stamp, in pixels from the top left of the image,
+    /// instead of a corner.
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// Distance between the stamp and the edges of the image, in pixels.
+    /// Not used with a [position][StampBuilder::position].
+    /// Default is 10.0.
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Box drawn behind the text.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is no box.
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Distance between the text and the edges of its box, in pixels.
+    /// Default is 4.0.
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields.
+    pub fn build(self) -> Result<Stamp> {
+        Ok(Stamp {
+            text: self.text.ok_or(Error::BuildError("Text not supplied."))?,
+            style: self
+                .style
+                .ok_or(Error::BuildError("Text style not supplied."))?,
+            corner: self.corner,
+            position: self.position,
+            margin: self.margin,
+            background: self.background,
+            padding: self.padding,
+        })
+    }
+}
+
+impl Stamp {
+    /// Padding of the text, which only applies with a box.
+    fn padding(&self) -> f32 {
+        match self.background {
+            Some(_) => self.padding.max(0.),
+            None => 0.,
+        }
+    }
+
+    /// Width and height of each line of text.
+    fn lines(&self) -> Vec<(&str, (u32, u32))> {
+        self.text
+            .lines()
+            .map(|line| (line, self.style.measure(line)))
+            .collect()
+    }
+
+    /// Top left of the stamp and its width and height, including the box.
+    fn rect(&self, bounds: &Bounds, lines: &[(&str, (u32, u32))]) -> (f32, f32, f32, f32) {
+        let padding = self.padding();
+        let width = lines.iter().map(|(_, (w, _))| *w).max().unwrap_or(0) as f32;
+        let height = lines.iter().map(|(_, (_, h))| *h).sum::<u32>() as f32;
+        let (width, height) = (width + 2. * padding, height + 2. * padding);
+
+        let (x, y) = match self.position {
+            Some(position) => position,
+            None => self.corner.anchor(bounds, width, height, self.margin),
+        };
+        (x, y, width, height)
+    }
+}
+
+impl Tool for Stamp {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        (f64::NAN, f64::NAN, f64::NAN, f64::NAN)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y, width, height) = self.rect(bounds, &self.lines());
+        let (x, y) = (f64::from(x), f64::from(y));
+        Some(PixelRect::new(
+            x,
+            y,
+            x + f64::from(width),
+            y + f64::from(height),
+        ))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "stamp".digest(state);
+        self.text.digest(state);
+        self.style.digest(state);
+        self.corner.digest(state);
+        self.position.digest(state);
+        self.margin.digest(state);
+        self.background.digest(state);
+        self.padding.digest(state);
+        true
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let lines = self.lines();
+        let (x, y, width, height) = self.rect(bounds, &lines);
+
+        if let Some(ref background) = self.background {
+            if let Some(rect) = tiny_skia::Rect::from_xywh(x, y, width, height) {
+                pixmap.fill_rect(rect, &background.0, transform, None);
+            }
+        }
+
+        let padding = self.padding();
+        let mut top = y + padding;
+        for (line, (line_width, line_height)) in lines {
+            // Lines are aligned to the side of the image the stamp is placed at.
+            let left = match (self.position, self.corner) {
+                (None, Corner::TopRight) | (None, Corner::BottomRight) => {
+                    x + width - padding - line_width as f32
+                }
+                _ => x + padding,
+            };
+
+            if let Some(rendered) = self.style.scaled(scale).render(line) {
+                pixmap.draw_pixmap(
+                    (left * scale).round() as i32,
+                    (top * scale).round() as i32,
+                    rendered.as_ref(),
+                    &PixmapPaint::default(),
+                    Transform::default(),
+                    None,
+                );
+            }
+            top += line_height as f32;
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    geojson::Feature,
+    lat_to_y, lon_to_x_continuous,
+    tools::{Color, Tool},
+    x_to_lon, y_to_lat, Error, Result,
+};
+use resvg::usvg;
+use std::hash::Hasher;
+use tiny_skia::{Pixmap, PixmapMut, PixmapPaint, Transform};
+
+/// SVG symbol tool, an SVG image drawn as a marker at a coordinate,
+/// recolored per symbol so one asset can be drawn in any color and size.
+/// Use [SvgSymbolBuilder][SvgSymbolBuilder] as an entrypoint.
+///
+/// Colors are set with CSS variables, `var(--name)` or `var(--name, fallback)`
+/// anywhere in the SVG, with [variable][SvgSymbolBuilder::variable],
+/// by overriding the fill of all shapes with [fill][SvgSymbolBuilder::fill],
+/// or through `currentColor` with [current_color][SvgSymbolBuilder::current_color].
+///
+/// Requires the `svg` feature.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{Color, SvgSymbolBuilder};
+///
+/// let pin = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 20 30">
+///     <path d="M10 30 L0 10 A10 10 0 1 1 20 10 Z" fill="var(--body, black)"/>
+///     <circle cx="10" cy="10" r="4" fill="white"/>
+/// </svg>"#;
+///
+/// let symbol = SvgSymbolBuilder::new()
+///     .lat_coordinate(52.5)
+///     .lon_coordinate(13.4)
+///     .data(pin)
+///     .variable("body", Color::new(true, 0, 90, 200, 255))
+///     .size(36.)
+///     .anchor(0.5, 1.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SvgSymbol {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    image: Pixmap,
+    tree: usvg::Tree,
+    scale: f32,
+    anchor: (f32, f32),
+}
+
+/// Builder for [SvgSymbol][SvgSymbol].
+#[derive(Debug, Clone)]
+pub struct SvgSymbolBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    data: Option<Vec<u8>>,
+    size: f32,
+    anchor: (f32, f32),
+    variables: Vec<(String, Color)>,
+    fill: Option<Color>,
+    current_color: Option<Color>,
+}
+
+impl Default for SvgSymbolBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinate: None,
+            lon_coordinate: None,
+            data: None,
+            size: 24.,
+            anchor: (0.5, 0.5),
+            variables: Vec::new(),
+            fill: None,
+            current_color: None,
+        }
+    }
+}
+
+/// Color as a CSS value.
+fn css(color: &Color) -> String {
+    let color = color.solid().to_color_u8();
+    format!(
+        "rgba({},{},{},{})",
+        color.red(),
+        color.green(),
+        color.blue(),
+        f32::from(color.alpha()) / 255.
+    )
+}
+
+/// Replace `var(--name)` and `var(--name, fallback)` with the value of the variable,
+/// or the fallback if the variable is not set.
+fn substitute(svg: &str, variables: &[(String, String)]) -> String {
+    let mut result = String::with_capacity(svg.len());
+    let mut rest = svg;
+
+    while let Some(start) = rest.find("var(") {
+        // Closing parenthesis, skipping those of nested values, e.g. rgb(...).
+        let mut depth = 0;
+        let end = rest[start + 4..].char_indices().find_map(|(index, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => return Some(start + 4 + index),
+                ')' => depth -= 1,
+                _ => (),
+            }
+            None
+        });
+        let end = match end {
+            Some(end) => end,
+            None => break,
+        };
+
+        let inner = &rest[start + 4..end];
+        let (name, fallback) = match inner.split_once(',') {
+            Some((name, fallback)) => (name, Some(fallback)),
+            None => (inner, None),
+        };
+        let name = name.trim().trim_start_matches("--");
+        let value = match variables.iter().find(|(x, _)| x == name) {
+            Some((_, value)) => Some(value.clone()),
+            None => fallback.map(|x| substitute(x.trim(), variables)),
+        };
+
+        result.push_str(&rest[..start]);
+        match value {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+impl SvgSymbolBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**.
+    /// Latitude coordinate of the symbol.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// Longitude coordinate of the symbol.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// Path to an SVG or SVGZ file.
+    pub fn path<P: AsRef<std::path::Path>>(mut self, path: P) -> Result<Self> {
+        self.data = Some(std::fs::read(path)?);
+        Ok(self)
+    }
+
+    /// **Required**.
+    /// SVG or SVGZ data.
+    pub fn data<D: AsRef<[u8]>>(mut self, data: D) -> Self {
+        self.data = Some(data.as_ref().to_vec());
+        self
+    }
+
+    /// Height of the symbol, in pixels. The width follows the aspect ratio of the SVG.
+    /// Default is 24.0.
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Point of the symbol placed at the coordinate, as fractions of its width and height
+    /// from the top left, e.g. (0.5, 1.0) for the tip of a pin.
+    /// Default is (0.5, 0.5), the center.
+    pub fn anchor(mut self, x: f32, y: f32) -> Self {
+        self.anchor = (x, y);
+        self
+    }
+
+    /// Set the CSS variable `--name` to a color.
+    /// Use [Color][Color] to generate a color instance.
+    pub fn variable<I: Into<String>>(mut self, name: I, color: Color) -> Self {
+        let name = name.into();
+        self.variables
+            .push((name.trim_start_matches("--").to_string(), color));
+        self
+    }
+
+    /// Fill every shape with a color, replacing the fills of the SVG.
+    /// Use [Color][Color] to generate a color instance.
+    pub fn fill(mut self, color: Color) -> Self {
+        self.fill = Some(color);
+        self
+    }
+
+    /// Color used by `currentColor` in the SVG, as in many icon sets.
+    /// Use [Color][Color] to generate a color instance.
+    pub fn current_color(mut self, color: Color) -> Self {
+        self.current_color = Some(color);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the SVG can not be parsed.
+    pub fn build(self) -> Result<SvgSymbol> {
+        let data = self.data.ok_or(Error::BuildError("SVG not supplied."))?;
+
+        let data = if data.starts_with(&[0x1f, 0x8b]) {
+            usvg::decompress_svgz(&data).map_err(|x| Error::InvalidSvg(x.to_string()))?
+        } else {
+            data
+        };
+        let text =
+            String::from_utf8(data).map_err(|_| Error::InvalidSvg("not UTF-8 text".to_string()))?;
+
+        let variables: Vec<(String, String)> = self
+            .variables
+            .iter()
+            .map(|(name, color)| (name.clone(), css(color)))
+            .collect();
+        let text = substitute(&text, &variables);
+
+        let mut style_sheet = String::new();
+        if let Some(ref color) = self.current_color {
+            style_sheet.push_str(&format!("svg {{ color: {} !important }}\n", css(color)));
+        }
+        if let Some(ref color) = self.fill {
+            style_sheet.push_str(&format!("* {{ fill: {} !important }}\n", css(color)));
+        }
+
+        let options = usvg::Options {
+            style_sheet: Some(style_sheet).filter(|x| !x.is_empty()),
+            ..Default::default()
+        };
+        let tree =
+            usvg::Tree::from_str(&text, &options).map_err(|x| Error::InvalidSvg(x.to_string()))?;
+
+        let scale = self.size / tree.size().height();
+        let mut image = Pixmap::new(
+            (tree.size().width() * scale).ceil() as u32,
+            (tree.size().height() * scale).ceil() as u32,
+        )
+        .ok_or(Error::InvalidSvg("empty image".to_string()))?;
+        resvg::render(
+            &tree,
+            Transform::from_scale(scale, scale),
+            &mut image.as_mut(),
+        );
+
+        Ok(SvgSymbol {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            image,
+            tree,
+            scale,
+            anchor: self.anchor,
+        })
+    }
+}
+
+impl SvgSymbol {
+    /// The SVG rendered `scale` times the size of the symbol.
+    fn render(&self, scale: f32) -> Option<Pixmap> {
+        let mut image = Pixmap::new(
+            (self.image.width() as f32 * scale).ceil() as u32,
+            (self.image.height() as f32 * scale).ceil() as u32,
+        )?;
+        let scale = self.scale * scale;
+        resvg::render(
+            &self.tree,
+            Transform::from_scale(scale, scale),
+            &mut image.as_mut(),
+        );
+        Some(image)
+    }
+
+    /// Rectangle covered by the symbol, relative to its coordinate.
+    fn rect(&self) -> (f64, f64, f64, f64) {
+        let (width, height) = (
+            f64::from(self.image.width()),
+            f64::from(self.image.height()),
+        );
+        let left = -width * f64::from(self.anchor.0);
+        let top = -height * f64::from(self.anchor.1);
+        (left, top, left + width, top + height)
+    }
+}
+
+impl Tool for SvgSymbol {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        let (left, top, right, bottom) = self.rect();
+
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
+        let y = lat_to_y(self.lat_coordinate, zoom);
+
+        // Offsets from the coordinate, so coordinates outside the geographic range,
+        // e.g. on a Cartesian grid, are not wrapped around.
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x + left / tile_size, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + bottom / tile_size, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + right / tile_size, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y + top / tile_size, zoom) - lat;
+
+        (lon_min, lat_min, lon_max, lat_max)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (left, top, right, bottom) = self.rect();
+        Some(PixelRect::new(x + left, y + top, x + right, y + bottom))
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        Some((self.lon_coordinate, self.lat_coordinate))
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "svg_symbol".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.image.digest(state);
+        self.anchor.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        Some(Feature::point(self.lon_coordinate, self.lat_coordinate).to_string())
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (left, top, _, _) = self.rect();
+        let (x, y) = ((x + left).round() as f32, (y + top).round() as f32);
+
+        // Larger scales render the SVG again, instead of resampling the image.
+        let scaled;
+        let image = if scale == 1. {
+            &self.image
+        } else {
+            scaled = match self.render(scale) {
+                Some(image) => image,
+                None => return,
+            };
+            &scaled
+        };
+
+        // Whole pixels keep the symbol sharp.
+        pixmap.draw_pixmap(
+            (x * scale).round() as i32,
+            (y * scale).round() as i32,
+            image.as_ref(),
+            &PixmapPaint::default(),
+            Transform::default(),
+            None,
+        );
+    }
+}
@@ -0,0 +1,545 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::{Digest, StableHasher},
+    geojson::Feature,
+    lat_to_y, lon_to_x_continuous,
+    tools::{Alignment, Color, Tool},
+    x_to_lon, y_to_lat, Error, Result,
+};
+use ab_glyph::{Font as _, FontArc, GlyphImageFormat, ScaleFont};
+use std::hash::{Hash, Hasher};
+use tiny_skia::{FilterQuality, Pixmap, PixmapMut, PixmapPaint, PremultipliedColorU8, Transform};
+
+/// Font used to render text, loaded from TrueType or OpenType data.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::tools::Font;
+///
+/// let font = Font::from_path("DejaVuSans.ttf").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Font(FontArc, u64);
+
+impl Font {
+    /// Load a font from TrueType or OpenType data.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Font> {
+        // The font data is hashed once, for the render digest.
+        let mut state = StableHasher::default();
+        state.write(&data);
+
+        Ok(Font(FontArc::try_from_vec(data)?, state.finish()))
+    }
+
+    /// Load a font from a TrueType or OpenType file.
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Font> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Render an emoji at the font size `size`, using the embedded color image of
+    /// color emoji fonts, or the outline in `color` for other fonts.
+    /// Variation selectors and joiners are skipped, so sequences are drawn as their first emoji.
+    /// None if the font has no glyph for the emoji.
+    pub(crate) fn render_emoji(&self, emoji: &str, size: f32, color: &Color) -> Option<Pixmap> {
+        let c = emoji
+            .chars()
+            .find(|c| !matches!(c, '\u{fe0e}' | '\u{fe0f}' | '\u{200d}'))?;
+        let id = self.0.glyph_id(c);
+        if id.0 == 0 {
+            return None;
+        }
+
+        let strike = size.ceil().clamp(1., f32::from(u16::MAX)) as u16;
+        if let Some(image) = self.0.glyph_raster_image2(id, strike) {
+            let source = match image.format {
+                GlyphImageFormat::Png => Pixmap::decode_png(image.data).ok(),
+                GlyphImageFormat::BitmapPremulBgra32 => {
+                    let mut pixmap = Pixmap::new(image.width.into(), image.height.into())?;
+                    for (pixel, bgra) in pixmap
+                        .pixels_mut()
+                        .iter_mut()
+                        .zip(image.data.chunks_exact(4))
+                    {
+                        *pixel =
+                            PremultipliedColorU8::from_rgba(bgra[2], bgra[1], bgra[0], bgra[3])
+                                .unwrap_or(PremultipliedColorU8::TRANSPARENT);
+                    }
+                    Some(pixmap)
+                }
+                _ => None,
+            };
+
+            if let Some(source) = source {
+                // Strikes are only available at some sizes, so the image is scaled to the size.
+                let scale = size / f32::from(image.pixels_per_em.max(1));
+                let mut pixmap = Pixmap::new(
+                    (source.width() as f32 * scale).ceil().max(1.) as u32,
+                    (source.height() as f32 * scale).ceil().max(1.) as u32,
+                )?;
+                pixmap.draw_pixmap(
+                    0,
+                    0,
+                    source.as_ref(),
+                    &PixmapPaint {
+                        quality: FilterQuality::Bicubic,
+                        ..Default::default()
+                    },
+                    Transform::from_scale(scale, scale),
+                    None,
+                );
+                return Some(pixmap);
+            }
+        }
+
+        TextStyle::new(self.clone())
+            .size(size)
+            .color(color.clone())
+            .render(&c.to_string())
+    }
+}
+
+impl Digest for Font {
+    fn digest(&self, state: &mut dyn Hasher) {
+        state.write_u64(self.1);
+    }
+}
+
+/// Style of text drawn onto the map.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::tools::{Color, Font, TextStyle};
+///
+/// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap())
+///     .size(14.)
+///     .halo(Color::new(true, 255, 255, 255, 255), 2.);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextStyle {
+    font: Font,
+    size: f32,
+    color: Color,
+    halo: Option<(Color, f32)>,
+}
+
+impl Digest for TextStyle {
+    fn digest(&self, state: &mut dyn Hasher) {
+        self.font.digest(state);
+        self.size.digest(state);
+        self.color.digest(state);
+        self.halo.digest(state);
+    }
+}
+
+impl TextStyle {
+    /// Create a new black text style with the given font.
+    pub fn new(font: Font) -> Self {
+        Self {
+            font,
+            size: 12.,
+            color: Color::default(),
+            halo: None,
+        }
+    }
+
+    /// Font size, in pixels.
+    /// Default is 12.0.
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a black color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Outline drawn around the text to keep it readable on busy backgrounds.
+    /// `width` is in pixels.
+    pub fn halo(mut self, color: Color, width: f32) -> Self {
+        self.halo = Some((color, width));
+        self
+    }
+
+    /// Copy of the style `scale` times larger, e.g. to draw text at a higher resolution.
+    pub(crate) fn scaled(&self, scale: f32) -> TextStyle {
+        TextStyle {
+            font: self.font.clone(),
+            size: self.size * scale,
+            color: self.color.clone(),
+            halo: self
+                .halo
+                .as_ref()
+                .map(|(color, width)| (color.clone(), width * scale)),
+        }
+    }
+
+    /// Draw a single line of text centered on (x, y), `scale` times larger on a pixmap
+    /// `scale` times the size.
+    pub(crate) fn draw_centered(
+        &self,
+        pixmap: &mut PixmapMut,
+        text: &str,
+        x: f32,
+        y: f32,
+        scale: f32,
+    ) {
+        if let Some(rendered) = self.scaled(scale).render(text) {
+            pixmap.draw_pixmap(
+                (x * scale - rendered.width() as f32 / 2.).round() as i32,
+                (y * scale - rendered.height() as f32 / 2.).round() as i32,
+                rendered.as_ref(),
+                &PixmapPaint::default(),
+                Transform::default(),
+                None,
+            );
+        }
+    }
+
+    /// Width and height of a single line of text and its halo, in pixels.
+    pub(crate) fn measure(&self, text: &str) -> (u32, u32) {
+        let (_, width, height) = self.layout(text);
+        (width, height)
+    }
+
+    /// Glyphs of a single line of text, positioned inside its halo,
+    /// and the size of the text with its halo.
+    fn layout(&self, text: &str) -> (Vec<ab_glyph::Glyph>, u32, u32) {
+        let font = self.font.0.as_scaled(self.size);
+        let pad = self.halo_width().ceil();
+
+        let mut glyphs = Vec::new();
+        let mut caret = pad;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += font.kern(previous, id);
+            }
+            glyphs.push(id.with_scale_and_position(
+                font.scale(),
+                ab_glyph::point(caret, pad + font.ascent()),
+            ));
+            caret += font.h_advance(id);
+            previous = Some(id);
+        }
+
+        let width = (caret + pad).ceil() as u32;
+        let height = (font.height() + 2. * pad).ceil() as u32;
+        (glyphs, width, height)
+    }
+
+    fn halo_width(&self) -> f32 {
+        self.halo.as_ref().map(|x| x.1.max(0.)).unwrap_or(0.)
+    }
+
+    /// Render a single line of text to a pixmap fitting the text and its halo.
+    pub(crate) fn render(&self, text: &str) -> Option<Pixmap> {
+        let font = self.font.0.as_scaled(self.size);
+        let halo_width = self.halo_width();
+        let (glyphs, width, height) = self.layout(text);
+        let mut pixmap = Pixmap::new(width, height)?;
+
+        let mut coverage = vec![0_f32; (width * height) as usize];
+        for glyph in glyphs {
+            if let Some(outline) = font.outline_glyph(glyph) {
+                let bounds = outline.px_bounds();
+                outline.draw(|x, y, c| {
+                    let x = bounds.min.x as i64 + i64::from(x);
+                    let y = bounds.min.y as i64 + i64::from(y);
+                    if x >= 0 && y >= 0 && x < i64::from(width) && y < i64::from(height) {
+                        let index = (y as u32 * width + x as u32) as usize;
+                        coverage[index] = coverage[index].max(c.min(1.));
+                    }
+                });
+            }
+        }
+
+        let halo = self
+            .halo
+            .as_ref()
+            .map(|(color, _)| (color.solid(), dilate(&coverage, width, height, halo_width)));
+        let color = self.color.solid();
+
+        for (index, pixel) in pixmap.pixels_mut().iter_mut().enumerate() {
+            // Source-over composition of the text onto its halo.
+            let text_alpha = coverage[index] * color.alpha();
+            let (mut r, mut g, mut b, mut a) = (
+                color.red() * text_alpha,
+                color.green() * text_alpha,
+                color.blue() * text_alpha,
+                text_alpha,
+            );
+
+            if let Some((halo_color, halo_coverage)) = halo.as_ref() {
+                let halo_alpha = halo_coverage[index] * halo_color.alpha() * (1. - text_alpha);
+                r += halo_color.red() * halo_alpha;
+                g += halo_color.green() * halo_alpha;
+                b += halo_color.blue() * halo_alpha;
+                a += halo_alpha;
+            }
+
+            let to_u8 = |x: f32| (x * 255.).round().clamp(0., 255.) as u8;
+            let a = to_u8(a);
+            if let Some(color) = PremultipliedColorU8::from_rgba(
+                to_u8(r).min(a),
+                to_u8(g).min(a),
+                to_u8(b).min(a),
+                a,
+            ) {
+                *pixel = color;
+            }
+        }
+
+        Some(pixmap)
+    }
+}
+
+/// Text tool, a single line of text at a coordinate, e.g. the name of a marker.
+/// Use [TextBuilder][TextBuilder] as an entrypoint.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::tools::{Alignment, Color, Font, TextBuilder, TextStyle};
+///
+/// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap())
+///     .size(14.)
+///     .halo(Color::new(true, 255, 255, 255, 255), 2.);
+///
+/// // The name is placed above the coordinate, e.g. above a marker.
+/// let text = TextBuilder::new()
+///     .lat_coordinate(59.91)
+///     .lon_coordinate(10.75)
+///     .text("Oslo")
+///     .style(style)
+///     .anchor(Alignment::Bottom)
+///     .offset(0., -12.)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Text {
+    lat_coordinate: f64,
+    lon_coordinate: f64,
+    text: String,
+    style: TextStyle,
+    anchor: Alignment,
+    offset: (f32, f32),
+}
+
+/// Builder for [Text][Text].
+#[derive(Debug, Clone)]
+pub struct TextBuilder {
+    lat_coordinate: Option<f64>,
+    lon_coordinate: Option<f64>,
+    text: Option<String>,
+    style: Option<TextStyle>,
+    anchor: Alignment,
+    offset: (f32, f32),
+}
+
+impl Default for TextBuilder {
+    fn default() -> Self {
+        Self {
+            lat_coordinate: None,
+            lon_coordinate: None,
+            text: None,
+            style: None,
+            anchor: Alignment::Center,
+            offset: (0., 0.),
+        }
+    }
+}
+
+impl TextBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// Latitude coordinate of the text.
+    pub fn lat_coordinate(mut self, coordinate: f64) -> Self {
+        self.lat_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// Longitude coordinate of the text.
+    pub fn lon_coordinate(mut self, coordinate: f64) -> Self {
+        self.lon_coordinate = Some(coordinate);
+        self
+    }
+
+    /// **Required**.
+    /// The text, drawn on a single line.
+    pub fn text<I: Into<String>>(mut self, text: I) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// **Required**.
+    /// Font, size, color and halo of the text.
+    pub fn style(mut self, style: TextStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Point of the text placed at the coordinate, e.g.
+    /// [Alignment::Left][Alignment::Left] for text starting at the coordinate.
+    /// Default is [Alignment::Center][Alignment::Center].
+    pub fn anchor(mut self, anchor: Alignment) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Offset of the text from the coordinate in pixels, right and down,
+    /// e.g. to place a name beside a marker.
+    /// Default is no offset.
+    pub fn offset(mut self, x: f32, y: f32) -> Self {
+        self.offset = (x, y);
+        self
+    }
+
+    /// Build the tool, consuming the builder.
+    /// Returns an error if the builder is missing required fields.
+    pub fn build(self) -> Result<Text> {
+        Ok(Text {
+            lat_coordinate: self
+                .lat_coordinate
+                .ok_or(Error::BuildError("Latitude coordinate not supplied."))?,
+            lon_coordinate: self
+                .lon_coordinate
+                .ok_or(Error::BuildError("Longitude coordinate not supplied."))?,
+            text: self.text.ok_or(Error::BuildError("Text not supplied."))?,
+            style: self
+                .style
+                .ok_or(Error::BuildError("Text style not supplied."))?,
+            anchor: self.anchor,
+            offset: self.offset,
+        })
+    }
+}
+
+impl Text {
+    /// Top left corner of the text, relative to the coordinate, in pixels.
+    fn corner(&self) -> (f64, f64) {
+        let (width, height) = self.style.measure(&self.text);
+        let (x, y) = self.anchor.fractions();
+        (
+            f64::from(self.offset.0) - x * f64::from(width),
+            f64::from(self.offset.1) - y * f64::from(height),
+        )
+    }
+}
+
+impl Tool for Text {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        let (width, height) = self.style.measure(&self.text);
+        let (left, top) = self.corner();
+        let (right, bottom) = (left + f64::from(width), top + f64::from(height));
+
+        let x = lon_to_x_continuous(self.lon_coordinate, zoom);
+        let y = lat_to_y(self.lat_coordinate, zoom);
+
+        // Offsets from the coordinate, so coordinates outside the geographic range,
+        // e.g. on a Cartesian grid, are not wrapped around.
+        let (lon, lat) = (x_to_lon(x, zoom), y_to_lat(y, zoom));
+        let lon_min = self.lon_coordinate + x_to_lon(x + left / tile_size, zoom) - lon;
+        let lat_min = self.lat_coordinate + y_to_lat(y + bottom / tile_size, zoom) - lat;
+        let lon_max = self.lon_coordinate + x_to_lon(x + right / tile_size, zoom) - lon;
+        let lat_max = self.lat_coordinate + y_to_lat(y + top / tile_size, zoom) - lat;
+
+        (lon_min, lat_min, lon_max, lat_max)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let (x, y) = bounds.lon_lat_to_px(self.lon_coordinate, self.lat_coordinate);
+        let (width, height) = self.style.measure(&self.text);
+        let (left, top) = self.corner();
+        let (left, top) = ((x + left).round(), (y + top).round());
+        Some(PixelRect::new(
+            left,
+            top,
+            left + f64::from(width),
+            top + f64::from(height),
+        ))
+    }
+
+    fn digest(&self, mut state: &mut dyn Hasher) -> bool {
+        "text".digest(state);
+        self.lat_coordinate.digest(state);
+        self.lon_coordinate.digest(state);
+        self.text.digest(state);
+        self.style.digest(state);
+        self.anchor.hash(&mut state);
+        self.offset.digest(state);
+        true
+    }
+
+    fn geojson(&self) -> Option<String> {
+        Some(
+            Feature::point(self.lon_coordinate, self.lat_coordinate)
+                .string("label", &self.text)
+                .to_string(),
+        )
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let rect = match self.pixel_extent(bounds) {
+            Some(rect) => rect,
+            None => return,
+        };
+        let scale = f64::from(scale);
+        if let Some(rendered) = self.style.scaled(scale as f32).render(&self.text) {
+            pixmap.draw_pixmap(
+                (rect.x_min * scale) as i32,
+                (rect.y_min * scale) as i32,
+                rendered.as_ref(),
+                &PixmapPaint::default(),
+                Transform::default(),
+                None,
+            );
+        }
+    }
+}
+
+/// Grow the coverage mask by `radius` pixels in every direction.
+pub(crate) fn dilate(coverage: &[f32], width: u32, height: u32, radius: f32) -> Vec<f32> {
+    let reach = radius.ceil() as i64;
+    let (width, height) = (i64::from(width), i64::from(height));
+    let mut dilated = coverage.to_vec();
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut value = 0_f32;
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                    let (sx, sy) = (x + dx, y + dy);
+                    if distance > radius + 0.5 || sx < 0 || sy < 0 || sx >= width || sy >= height {
+                        continue;
+                    }
+                    // Soften the outer edge of the halo for anti-aliasing.
+                    let falloff = (radius + 0.5 - distance).min(1.);
+                    value = value.max(coverage[(sy * width + sx) as usize] * falloff);
+                }
+            }
+            dilated[(y * width + x) as usize] = value;
+        }
+    }
+
+    dilated
+}
+
+impl From<ab_glyph::InvalidFont> for Error {
+    fn from(_: ab_glyph::InvalidFont) -> Self {
+        Error::InvalidFont
+    }
+}
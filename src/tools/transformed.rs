@@ -0,0 +1,183 @@
+use crate::{
+    bounds::{Bounds, PixelRect},
+    digest::Digest,
+    tools::Tool,
+};
+use std::hash::Hasher;
+use tiny_skia::{FilterQuality, Pixmap, PixmapMut, PixmapPaint, Point, Transform};
+
+/// Wraps any tool to draw it with a transform in pixels, e.g. to nudge a label
+/// a few pixels or scale an icon, without adjusting its coordinates.
+///
+/// Scaling and custom transforms are relative to the anchor of the tool:
+/// its coordinate for point features, see [Tool::point][Tool::point],
+/// otherwise the center of its [pixel extent][Tool::pixel_extent].
+/// The transform does not affect the bounds of the map.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{CircleBuilder, Transformed};
+///
+/// let circle = CircleBuilder::new()
+///     .lat_coordinate(52.5)
+///     .lon_coordinate(13.4)
+///     .radius(6.)
+///     .build()
+///     .unwrap();
+///
+/// // 5 pixels up and to the right, twice as large.
+/// let nudged = Transformed::new(circle).offset(5., -5.).scale(2.);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transformed<T: Tool> {
+    tool: T,
+    offset: (f32, f32),
+    transform: Transform,
+    quality: FilterQuality,
+}
+
+impl<T: Tool> Transformed<T> {
+    /// Creates a new [Transformed][Transformed] tool, drawn as is until a transform is set.
+    pub fn new(tool: T) -> Self {
+        Transformed {
+            tool,
+            offset: (0., 0.),
+            transform: Transform::identity(),
+            quality: FilterQuality::Bilinear,
+        }
+    }
+
+    /// Move the tool by `dx` pixels to the right and `dy` pixels down.
+    pub fn offset(mut self, dx: f32, dy: f32) -> Self {
+        self.offset = (self.offset.0 + dx, self.offset.1 + dy);
+        self
+    }
+
+    /// Scale the tool by `factor` around its anchor.
+    pub fn scale(mut self, factor: f32) -> Self {
+        self.transform = self.transform.post_scale(factor, factor);
+        self
+    }
+
+    /// Apply a custom transform around the anchor of the tool, e.g. a rotation,
+    /// after any scaling set so far.
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.transform = self.transform.post_concat(transform);
+        self
+    }
+
+    /// Filter quality when the tool is resampled, i.e. scaled, rotated
+    /// or moved by fractions of a pixel.
+    /// Default is bilinear.
+    pub fn quality(mut self, quality: FilterQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Anchor of the tool on the map, in pixels.
+    fn anchor(&self, bounds: &Bounds) -> (f32, f32) {
+        let (x, y) = match (self.tool.point(), self.tool.pixel_extent(bounds)) {
+            (Some((lon, lat)), _) => bounds.lon_lat_to_px(lon, lat),
+            (None, Some(rect)) => (
+                (rect.x_min + rect.x_max) / 2.,
+                (rect.y_min + rect.y_max) / 2.,
+            ),
+            (None, None) => (f64::from(bounds.width) / 2., f64::from(bounds.height) / 2.),
+        };
+        (x as f32, y as f32)
+    }
+
+    /// Transform from where the tool is drawn to where it ends up on the map.
+    fn full_transform(&self, bounds: &Bounds) -> Transform {
+        let (x, y) = self.anchor(bounds);
+        Transform::from_translate(-x, -y)
+            .post_concat(self.transform)
+            .post_translate(x + self.offset.0, y + self.offset.1)
+    }
+}
+
+impl<T: Tool> Tool for Transformed<T> {
+    fn extent(&self, zoom: u8, tile_size: f64) -> (f64, f64, f64, f64) {
+        self.tool.extent(zoom, tile_size)
+    }
+
+    fn pixel_extent(&self, bounds: &Bounds) -> Option<PixelRect> {
+        let rect = self.tool.pixel_extent(bounds)?;
+        let transform = self.full_transform(bounds);
+
+        let mut corners = [
+            Point::from_xy(rect.x_min as f32, rect.y_min as f32),
+            Point::from_xy(rect.x_max as f32, rect.y_min as f32),
+            Point::from_xy(rect.x_min as f32, rect.y_max as f32),
+            Point::from_xy(rect.x_max as f32, rect.y_max as f32),
+        ];
+        transform.map_points(&mut corners);
+
+        let (x, y) = (f64::from(corners[0].x), f64::from(corners[0].y));
+        Some(corners.iter().fold(PixelRect::new(x, y, x, y), |rect, p| {
+            let (x, y) = (f64::from(p.x), f64::from(p.y));
+            PixelRect::new(
+                rect.x_min.min(x),
+                rect.y_min.min(y),
+                rect.x_max.max(x),
+                rect.y_max.max(y),
+            )
+        }))
+    }
+
+    fn point(&self) -> Option<(f64, f64)> {
+        self.tool.point()
+    }
+
+    fn priority(&self) -> Option<f64> {
+        self.tool.priority()
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "transformed".digest(state);
+        self.offset.digest(state);
+        self.quality.digest(state);
+        let t = self.transform;
+        [t.sx, t.kx, t.ky, t.sy, t.tx, t.ty][..].digest(state);
+        self.tool.digest(state)
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let (dx, dy) = self.offset;
+
+        // Plain offsets by whole pixels move the tool without resampling.
+        if self.transform.is_identity() && dx.fract() == 0. && dy.fract() == 0. {
+            if dx == 0. && dy == 0. {
+                self.tool.draw_at_scale(bounds, scale, pixmap);
+            } else {
+                let bounds = bounds.translate(dx.into(), dy.into());
+                self.tool.draw_at_scale(&bounds, scale, pixmap);
+            }
+            return;
+        }
+
+        let mut layer = match Pixmap::new(pixmap.width(), pixmap.height()) {
+            Some(layer) => layer,
+            None => return,
+        };
+        self.tool.draw_at_scale(bounds, scale, layer.as_mut());
+
+        pixmap.draw_pixmap(
+            0,
+            0,
+            layer.as_ref(),
+            &PixmapPaint {
+                quality: self.quality,
+                ..Default::default()
+            },
+            Transform::from_scale(1. / scale, 1. / scale)
+                .post_concat(self.full_transform(bounds))
+                .post_scale(scale, scale),
+            None,
+        );
+    }
+}
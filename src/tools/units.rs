@@ -0,0 +1,167 @@
+use crate::digest::Digest;
+use std::hash::Hasher;
+
+/// System of units of measurement labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitSystem {
+    /// Meters and kilometers.
+    #[default]
+    Metric,
+
+    /// Feet and miles, with areas in acres.
+    Imperial,
+
+    /// Nautical miles, with short distances in meters.
+    Nautical,
+}
+
+/// Units and number format of measurement labels, e.g. the radius of a circle.
+///
+/// ## Example
+/// ```rust
+/// use staticmap::tools::{UnitSystem, Units};
+///
+/// let german = Units::new().decimal_separator(',').thousands_separator('.');
+/// assert_eq!(german.format_distance(5240.), "5,2 km");
+/// assert_eq!(german.format_area(5400.), "5.400 m²");
+///
+/// let imperial = Units::new().system(UnitSystem::Imperial);
+/// assert_eq!(imperial.format_distance(8046.7), "5.0 mi");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Units {
+    system: UnitSystem,
+    decimal_separator: char,
+    thousands_separator: Option<char>,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self {
+            system: UnitSystem::Metric,
+            decimal_separator: '.',
+            thousands_separator: None,
+        }
+    }
+}
+
+impl Digest for Units {
+    fn digest(&self, state: &mut dyn Hasher) {
+        format!("{:?}", self.system).digest(state);
+        state.write_u32(self.decimal_separator.into());
+        self.thousands_separator.map(u32::from).digest(state);
+    }
+}
+
+const FOOT: f64 = 0.3048;
+const MILE: f64 = 1609.344;
+const ACRE: f64 = 4_046.856_422_4;
+const NAUTICAL_MILE: f64 = 1852.;
+
+impl Units {
+    /// Create metric units with a decimal point and no thousands separator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// System of units.
+    /// Default is [UnitSystem::Metric][UnitSystem::Metric].
+    pub fn system(mut self, system: UnitSystem) -> Self {
+        self.system = system;
+        self
+    }
+
+    /// Character between the whole and fractional part of numbers, e.g. ',' in German.
+    /// Default is '.'.
+    pub fn decimal_separator(mut self, separator: char) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// Character between groups of thousands, e.g. ',' in English or a narrow
+    /// no-break space in French.
+    /// Default is no separator.
+    pub fn thousands_separator(mut self, separator: char) -> Self {
+        self.thousands_separator = Some(separator);
+        self
+    }
+
+    /// Human readable distance, e.g. "850 m", "5.2 km" or "1.3 mi".
+    pub fn format_distance(&self, meters: f64) -> String {
+        match self.system {
+            UnitSystem::Metric if meters < 1000. => self.quantity(meters, 1., 0, "m"),
+            UnitSystem::Metric => self.scaled(meters, 1000., "km"),
+            UnitSystem::Imperial if meters < 1000. * FOOT => self.quantity(meters, FOOT, 0, "ft"),
+            UnitSystem::Imperial => self.scaled(meters, MILE, "mi"),
+            UnitSystem::Nautical if meters < NAUTICAL_MILE / 10. => {
+                self.quantity(meters, 1., 0, "m")
+            }
+            UnitSystem::Nautical => self.scaled(meters, NAUTICAL_MILE, "NM"),
+        }
+    }
+
+    /// Human readable area, e.g. "5400 m²", "12.3 km²" or "2.5 ac".
+    pub fn format_area(&self, square_meters: f64) -> String {
+        match self.system {
+            UnitSystem::Metric if square_meters < 1_000_000. => {
+                self.quantity(square_meters, 1., 0, "m²")
+            }
+            UnitSystem::Metric => self.scaled(square_meters, 1_000_000., "km²"),
+            UnitSystem::Imperial if square_meters < ACRE => {
+                self.quantity(square_meters, FOOT * FOOT, 0, "ft²")
+            }
+            UnitSystem::Imperial if square_meters < 640. * ACRE => {
+                self.scaled(square_meters, ACRE, "ac")
+            }
+            UnitSystem::Imperial => self.scaled(square_meters, MILE * MILE, "mi²"),
+            UnitSystem::Nautical if square_meters < NAUTICAL_MILE * NAUTICAL_MILE / 10. => {
+                self.quantity(square_meters, 1., 0, "m²")
+            }
+            UnitSystem::Nautical => {
+                self.scaled(square_meters, NAUTICAL_MILE * NAUTICAL_MILE, "NM²")
+            }
+        }
+    }
+
+    /// Value in a unit of `unit` base units, with one decimal below 10 and none above.
+    fn scaled(&self, value: f64, unit: f64, symbol: &str) -> String {
+        let decimals = match value / unit < 10. {
+            true => 1,
+            false => 0,
+        };
+        self.quantity(value, unit, decimals, symbol)
+    }
+
+    /// Value in a unit of `unit` base units, with its symbol.
+    fn quantity(&self, value: f64, unit: f64, decimals: usize, symbol: &str) -> String {
+        format!("{} {}", self.number(value / unit, decimals), symbol)
+    }
+
+    /// Number with the separators of the units.
+    fn number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value);
+        let (whole, fraction) = match formatted.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (formatted.as_str(), None),
+        };
+        let (sign, digits) = match whole.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", whole),
+        };
+
+        let mut result = sign.to_string();
+        for (index, digit) in digits.chars().enumerate() {
+            if let Some(separator) = self.thousands_separator {
+                if index > 0 && (digits.len() - index) % 3 == 0 {
+                    result.push(separator);
+                }
+            }
+            result.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            result.push(self.decimal_separator);
+            result.push_str(fraction);
+        }
+        result
+    }
+}
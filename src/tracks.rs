@@ -0,0 +1,627 @@
+use crate::{
+    bounds::Bounds,
+    digest::Digest,
+    tools::{CircleBuilder, Color, Corner, LineBuilder, TextStyle, Tool},
+    Error, LineProcessor, Result, StaticMapBuilder,
+};
+use std::{hash::Hasher, time::Duration};
+use tiny_skia::{PathBuilder, Pixmap, PixmapMut, PixmapPaint, Stroke, Transform};
+
+/// Point of a [Track][Track].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackPoint {
+    /// Latitude of the point.
+    pub lat: f64,
+    /// Longitude of the point.
+    pub lon: f64,
+    /// Time of the point, in seconds since the Unix epoch.
+    pub time: Option<f64>,
+}
+
+/// Recorded track, e.g. an activity or the trip of a vehicle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    name: String,
+    points: Vec<TrackPoint>,
+}
+
+impl Track {
+    /// Create a track from its points, in the order they were recorded.
+    pub fn new<I: Into<String>>(name: I, points: Vec<TrackPoint>) -> Self {
+        Self {
+            name: name.into(),
+            points,
+        }
+    }
+
+    /// Tracks of a GPX document, one for each `trk` element with its segments joined.
+    /// Tracks without a name are named by their position in the document, e.g. "Track 1".
+    ///
+    /// Requires the `gpx` feature.
+    #[cfg(feature = "gpx")]
+    pub fn from_gpx(gpx: &str) -> Result<Vec<Track>> {
+        let document = roxmltree::Document::parse(gpx)
+            .map_err(|error| Error::InvalidGpx(error.to_string()))?;
+        let mut tracks = Vec::new();
+        for (index, trk) in elements(document.root_element(), "trk").enumerate() {
+            let name = elements(trk, "name")
+                .next()
+                .and_then(|name| name.text())
+                .map(|name| name.trim().to_string())
+                .unwrap_or_else(|| format!("Track {}", index + 1));
+
+            let mut points = Vec::new();
+            for trkpt in elements(trk, "trkseg").flat_map(|trkseg| elements(trkseg, "trkpt")) {
+                let coordinate = |name| {
+                    trkpt
+                        .attribute(name)
+                        .and_then(|value| value.trim().parse::<f64>().ok())
+                        .ok_or_else(|| {
+                            Error::InvalidGpx(format!("Track point without a valid {}", name))
+                        })
+                };
+                let time = elements(trkpt, "time")
+                    .next()
+                    .and_then(|time| time.text())
+                    .and_then(parse_time);
+
+                points.push(TrackPoint {
+                    lat: coordinate("lat")?,
+                    lon: coordinate("lon")?,
+                    time,
+                });
+            }
+
+            tracks.push(Track { name, points });
+        }
+
+        match tracks.is_empty() {
+            true => Err(Error::InvalidGpx(
+                "The document contains no tracks".to_string(),
+            )),
+            false => Ok(tracks),
+        }
+    }
+
+    /// Name of the track.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Points of the track.
+    pub fn points(&self) -> &[TrackPoint] {
+        &self.points
+    }
+
+    /// Time between the first and the last point, if both are timed.
+    pub fn duration(&self) -> Option<Duration> {
+        let start = self.points.first()?.time?;
+        let end = self.points.last()?.time?;
+        Some(Duration::from_secs_f64((end - start).max(0.)))
+    }
+
+    /// Track with its points pre-processed, e.g. snapped to roads with a map-matching
+    /// service, see [LineProcessor][LineProcessor].
+    pub fn process(self, processor: &dyn LineProcessor) -> Result<Track> {
+        Ok(Track {
+            name: self.name,
+            points: processor.process(self.points)?,
+        })
+    }
+
+    /// Points recorded within `elapsed` of the start of the track, ending with the position
+    /// at that time, interpolated between points.
+    /// All points if the track is not timed.
+    fn slice(&self, elapsed: Option<Duration>) -> Vec<(f64, f64)> {
+        let start = self.points.first().and_then(|point| point.time);
+        let (start, elapsed) = match (start, elapsed) {
+            (Some(start), Some(elapsed)) => (start, elapsed.as_secs_f64()),
+            _ => return self.points.iter().map(|x| (x.lat, x.lon)).collect(),
+        };
+
+        let mut coordinates = Vec::new();
+        let mut previous: Option<(f64, &TrackPoint)> = None;
+        for point in &self.points {
+            // Points without a time are kept with the preceding timed point.
+            let time = match point.time.map(|x| x - start).or(previous.map(|x| x.0)) {
+                Some(time) => time,
+                None => continue,
+            };
+
+            if time > elapsed {
+                if let Some((previous_time, previous)) = previous {
+                    let t = ((elapsed - previous_time) / (time - previous_time)).clamp(0., 1.);
+                    coordinates.push((
+                        previous.lat + t * (point.lat - previous.lat),
+                        previous.lon + t * (point.lon - previous.lon),
+                    ));
+                }
+                break;
+            }
+
+            coordinates.push((point.lat, point.lon));
+            previous = Some((time, point));
+        }
+
+        coordinates
+    }
+}
+
+#[cfg(feature = "gpx")]
+fn elements<'a, 'input: 'a>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    node.children()
+        .filter(move |x| x.is_element() && x.tag_name().name() == name)
+}
+
+/// Seconds since the Unix epoch of an ISO 8601 date and time as used by GPX,
+/// e.g. "2024-05-01T08:30:00Z" or "2024-05-01T10:30:00.5+02:00".
+#[cfg(feature = "gpx")]
+fn parse_time(time: &str) -> Option<f64> {
+    let time = time.trim();
+    let (date, clock) = time.split_once('T')?;
+
+    let mut date = date.splitn(3, '-').map(|x| x.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+
+    let (clock, offset) = match clock.find(['Z', '+', '-']) {
+        Some(index) => clock.split_at(index),
+        None => (clock, ""),
+    };
+    let mut clock = clock.splitn(3, ':');
+    let hours = clock.next()?.parse::<f64>().ok()?;
+    let minutes = clock.next()?.parse::<f64>().ok()?;
+    let seconds = clock.next().map_or(Some(0.), |x| x.parse::<f64>().ok())?;
+
+    let offset = match offset.split_at(offset.len().min(1)) {
+        ("", _) | ("Z", "") => 0.,
+        (sign, offset) => {
+            let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+            let offset = hours.parse::<f64>().ok()? * 3600. + minutes.parse::<f64>().ok()? * 60.;
+            match sign {
+                "-" => -offset,
+                _ => offset,
+            }
+        }
+    };
+
+    // Days since the epoch of a date in the proleptic Gregorian calendar.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days as f64 * 86_400. + hours * 3600. + minutes * 60. + seconds - offset)
+}
+
+/// Distinct colors used for the tracks by default.
+const PALETTE: [(u8, u8, u8); 10] = [
+    (31, 119, 180),
+    (255, 127, 14),
+    (44, 160, 44),
+    (214, 39, 40),
+    (148, 103, 189),
+    (140, 86, 75),
+    (227, 119, 194),
+    (127, 127, 127),
+    (188, 189, 34),
+    (23, 190, 207),
+];
+
+/// Several tracks drawn for comparison, e.g. activities on the same course or the routes of
+/// a fleet of vehicles, each in a distinct color and named in a legend.
+/// Use [TrackComparisonBuilder][TrackComparisonBuilder] as an entrypoint.
+///
+/// The tracks share a single map, or are drawn as small multiples, one map per track,
+/// see [small_multiples][TrackComparisonBuilder::small_multiples].
+/// Timed tracks can be cut at the same time since their start, showing where each one was,
+/// see [elapsed][TrackComparisonBuilder::elapsed].
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{
+///     tools::{Font, TextStyle},
+///     Track, TrackComparisonBuilder, StaticMapBuilder,
+/// };
+/// use std::time::Duration;
+///
+/// let gpx = std::fs::read_to_string("race.gpx").unwrap();
+/// let style = TextStyle::new(Font::from_path("DejaVuSans.ttf").unwrap());
+///
+/// let comparison = TrackComparisonBuilder::new()
+///     .tracks(Track::from_gpx(&gpx).unwrap())
+///     .legend(style)
+///     .elapsed(Duration::from_secs(30 * 60))
+///     .small_multiples(2)
+///     .build()
+///     .unwrap();
+///
+/// let image = comparison
+///     .render(|| StaticMapBuilder::new().width(300).height(300))
+///     .unwrap();
+/// image.save_png("race.png").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrackComparison {
+    tracks: Vec<Track>,
+    colors: Vec<Color>,
+    width: f32,
+    legend: Option<TextStyle>,
+    legend_corner: Corner,
+    elapsed: Option<Duration>,
+    columns: Option<u32>,
+    gap: u32,
+}
+
+/// Builder for [TrackComparison][TrackComparison].
+#[derive(Debug, Clone)]
+pub struct TrackComparisonBuilder {
+    tracks: Vec<Track>,
+    colors: Option<Vec<Color>>,
+    width: f32,
+    legend: Option<TextStyle>,
+    legend_corner: Corner,
+    elapsed: Option<Duration>,
+    columns: Option<u32>,
+    gap: u32,
+}
+
+impl Default for TrackComparisonBuilder {
+    fn default() -> Self {
+        Self {
+            tracks: Vec::new(),
+            colors: None,
+            width: 3.,
+            legend: None,
+            legend_corner: Corner::TopRight,
+            elapsed: None,
+            columns: None,
+            gap: 10,
+        }
+    }
+}
+
+impl TrackComparisonBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// **Required**, or [tracks][TrackComparisonBuilder::tracks].
+    /// Add a track to compare.
+    pub fn track(mut self, track: Track) -> Self {
+        self.tracks.push(track);
+        self
+    }
+
+    /// Add several tracks to compare, e.g. the tracks of a GPX document.
+    pub fn tracks<I: IntoIterator<Item = Track>>(mut self, tracks: I) -> Self {
+        self.tracks.extend(tracks);
+        self
+    }
+
+    /// Colors of the tracks, in order, repeated if there are more tracks than colors.
+    /// Use [Color][Color] to generate a color instance.
+    /// Default is a palette of ten distinct colors.
+    pub fn colors(mut self, colors: Vec<Color>) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// Width of the track lines, in pixels.
+    /// Default is 3.0.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Name the tracks in a legend, drawn in the given style.
+    /// In small multiples, each map is labelled with the name of its track.
+    pub fn legend(mut self, style: TextStyle) -> Self {
+        self.legend = Some(style);
+        self
+    }
+
+    /// Corner of the map to place the legend in.
+    /// Default is [Corner::TopRight][Corner::TopRight].
+    pub fn legend_corner(mut self, corner: Corner) -> Self {
+        self.legend_corner = corner;
+        self
+    }
+
+    /// Draw each track up to the given time since its start, with a marker at its position,
+    /// e.g. to compare the progress of activities started at different times.
+    /// Tracks without times are drawn whole.
+    pub fn elapsed(mut self, elapsed: Duration) -> Self {
+        self.elapsed = Some(elapsed);
+        self
+    }
+
+    /// Render one map per track, arranged in a grid with the given number of columns,
+    /// instead of a single map with all tracks.
+    /// The maps share the extent of all tracks, so they are drawn at the same scale.
+    pub fn small_multiples(mut self, columns: u32) -> Self {
+        self.columns = Some(columns);
+        self
+    }
+
+    /// Space between the maps of small multiples, in pixels.
+    /// Default is 10.
+    pub fn gap(mut self, gap: u32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Build the comparison, consuming the builder.
+    /// Returns an error if no tracks or colors are supplied, or if small multiples
+    /// have no columns.
+    pub fn build(self) -> Result<TrackComparison> {
+        if self.tracks.is_empty() {
+            return Err(Error::BuildError("Tracks not supplied."));
+        }
+        if self.columns == Some(0) {
+            return Err(Error::BuildError(
+                "Small multiples must have at least one column.",
+            ));
+        }
+
+        let colors = match self.colors {
+            Some(colors) if colors.is_empty() => {
+                return Err(Error::BuildError("Track colors not supplied."))
+            }
+            Some(colors) => colors,
+            None => PALETTE
+                .iter()
+                .map(|(r, g, b)| Color::new(true, *r, *g, *b, 255))
+                .collect(),
+        };
+
+        Ok(TrackComparison {
+            tracks: self.tracks,
+            colors,
+            width: self.width,
+            legend: self.legend,
+            legend_corner: self.legend_corner,
+            elapsed: self.elapsed,
+            columns: self.columns,
+            gap: self.gap,
+        })
+    }
+}
+
+impl TrackComparison {
+    /// Color of the track at an index.
+    pub fn color(&self, index: usize) -> &Color {
+        &self.colors[index % self.colors.len()]
+    }
+
+    /// Tools drawing all tracks and the legend on a single map.
+    pub fn tools(&self) -> Result<Vec<Box<dyn Tool>>> {
+        let mut tools = Vec::new();
+        for index in 0..self.tracks.len() {
+            tools.extend(self.track_tools(index)?);
+        }
+        if let Some(legend) = self.legend_tool(0..self.tracks.len()) {
+            tools.push(legend);
+        }
+        Ok(tools)
+    }
+
+    /// Render the comparison, as a single map or as small multiples.
+    /// `map` returns a builder for each map, setting its size, tile source and other options.
+    pub fn render<F>(&self, map: F) -> Result<Pixmap>
+    where
+        F: Fn() -> StaticMapBuilder,
+    {
+        let columns = match self.columns {
+            Some(columns) => columns as usize,
+            None => {
+                let mut map = map().build()?;
+                for tool in self.tools()? {
+                    map.add_tool(tool);
+                }
+                return map.render();
+            }
+        };
+
+        let extent = self.tracks.iter().flat_map(|track| &track.points).fold(
+            (f64::NAN, f64::NAN, f64::NAN, f64::NAN),
+            |extent, point| {
+                (
+                    extent.0.min(point.lon),
+                    extent.1.min(point.lat),
+                    extent.2.max(point.lon),
+                    extent.3.max(point.lat),
+                )
+            },
+        );
+
+        let mut panels = Vec::with_capacity(self.tracks.len());
+        for index in 0..self.tracks.len() {
+            let mut panel = map().build()?;
+            panel.add_tool(SharedExtent(extent));
+            for tool in self.track_tools(index)? {
+                panel.add_tool(tool);
+            }
+            if let Some(legend) = self.legend_tool(index..index + 1) {
+                panel.add_tool(legend);
+            }
+            panels.push(panel.render()?);
+        }
+
+        let panel_width = panels.iter().map(Pixmap::width).max().unwrap_or(0);
+        let panel_height = panels.iter().map(Pixmap::height).max().unwrap_or(0);
+        let columns = columns.min(panels.len()) as u32;
+        let rows = (panels.len() as u32).div_ceil(columns);
+
+        let mut image = Pixmap::new(
+            columns * panel_width + (columns - 1) * self.gap,
+            rows * panel_height + (rows - 1) * self.gap,
+        )
+        .ok_or(Error::InvalidSize)?;
+        image.fill(tiny_skia::Color::WHITE);
+
+        for (index, panel) in panels.iter().enumerate() {
+            let (column, row) = (index as u32 % columns, index as u32 / columns);
+            image.draw_pixmap(
+                (column * (panel_width + self.gap)) as i32,
+                (row * (panel_height + self.gap)) as i32,
+                panel.as_ref(),
+                &PixmapPaint::default(),
+                Transform::default(),
+                None,
+            );
+        }
+
+        Ok(image)
+    }
+
+    /// Line of a track, and a marker at its position if cut at an elapsed time.
+    fn track_tools(&self, index: usize) -> Result<Vec<Box<dyn Tool>>> {
+        let coordinates = self.tracks[index].slice(self.elapsed);
+        let color = self.color(index);
+
+        let mut tools: Vec<Box<dyn Tool>> = Vec::new();
+        if coordinates.is_empty() {
+            return Ok(tools);
+        }
+
+        tools.push(Box::new(
+            LineBuilder::new()
+                .lat_coordinates(coordinates.iter().map(|x| x.0))
+                .lon_coordinates(coordinates.iter().map(|x| x.1))
+                .color(color.clone())
+                .width(self.width)
+                .build()?,
+        ));
+
+        if let (Some(_), Some((lat, lon))) = (self.elapsed, coordinates.last()) {
+            tools.push(Box::new(
+                CircleBuilder::new()
+                    .lat_coordinate(*lat)
+                    .lon_coordinate(*lon)
+                    .radius(self.width * 1.5)
+                    .color(color.clone())
+                    .casing(Color::new(true, 255, 255, 255, 255), 1.5)
+                    .build()?,
+            ));
+        }
+
+        Ok(tools)
+    }
+
+    fn legend_tool(&self, range: std::ops::Range<usize>) -> Option<Box<dyn Tool>> {
+        let style = self.legend.clone()?;
+        Some(Box::new(TrackLegend {
+            entries: range
+                .map(|index| (self.tracks[index].name.clone(), self.color(index).clone()))
+                .collect(),
+            style,
+            corner: self.legend_corner,
+            width: self.width,
+        }))
+    }
+}
+
+/// Extent of all tracks, fitting each map of small multiples to the same area.
+struct SharedExtent((f64, f64, f64, f64));
+
+impl Tool for SharedExtent {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        self.0
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "shared_extent".digest(state);
+        self.0.digest(state);
+        true
+    }
+
+    fn draw(&self, _: &Bounds, _: PixmapMut) {}
+
+    fn draw_at_scale(&self, _: &Bounds, _: f32, _: PixmapMut) {}
+}
+
+/// Legend naming the tracks of a comparison, with a swatch of their color.
+struct TrackLegend {
+    entries: Vec<(String, Color)>,
+    style: TextStyle,
+    corner: Corner,
+    width: f32,
+}
+
+impl TrackLegend {
+    /// Length of the color swatches, in pixels.
+    const SWATCH: f32 = 20.;
+    /// Space around and between the entries, in pixels.
+    const PADDING: f32 = 6.;
+    /// Distance between the legend and the edges of the map, in pixels.
+    const MARGIN: f32 = 10.;
+}
+
+impl Tool for TrackLegend {
+    fn extent(&self, _: u8, _: f64) -> (f64, f64, f64, f64) {
+        (f64::NAN, f64::NAN, f64::NAN, f64::NAN)
+    }
+
+    fn digest(&self, state: &mut dyn Hasher) -> bool {
+        "track_legend".digest(state);
+        for (name, color) in &self.entries {
+            name.digest(state);
+            color.digest(state);
+        }
+        self.style.digest(state);
+        self.corner.digest(state);
+        self.width.digest(state);
+        true
+    }
+
+    fn draw(&self, bounds: &Bounds, pixmap: PixmapMut) {
+        self.draw_at_scale(bounds, 1., pixmap);
+    }
+
+    fn draw_at_scale(&self, bounds: &Bounds, scale: f32, mut pixmap: PixmapMut) {
+        let transform = Transform::from_scale(scale, scale);
+        let sizes: Vec<(u32, u32)> = self
+            .entries
+            .iter()
+            .map(|(name, _)| self.style.measure(name))
+            .collect();
+        let text_width = sizes.iter().map(|x| x.0).max().unwrap_or(0) as f32;
+        let row_height = sizes.iter().map(|x| x.1).max().unwrap_or(0) as f32;
+
+        let width = 3. * Self::PADDING + Self::SWATCH + text_width;
+        let height = Self::PADDING + self.entries.len() as f32 * (row_height + Self::PADDING);
+        let (left, top) = self.corner.anchor(bounds, width, height, Self::MARGIN);
+
+        if let Some(rect) = tiny_skia::Rect::from_xywh(left, top, width, height) {
+            let background = Color::new(true, 255, 255, 255, 220);
+            pixmap.fill_rect(rect, background.paint(), transform, None);
+        }
+
+        let stroke = Stroke {
+            width: self.width,
+            ..Default::default()
+        };
+        for (row, ((name, color), (name_width, _))) in self.entries.iter().zip(&sizes).enumerate() {
+            let y =
+                top + Self::PADDING + row as f32 * (row_height + Self::PADDING) + row_height / 2.;
+            let x = left + Self::PADDING;
+
+            let mut swatch = PathBuilder::new();
+            swatch.move_to(x, y);
+            swatch.line_to(x + Self::SWATCH, y);
+            if let Some(swatch) = swatch.finish() {
+                pixmap.stroke_path(&swatch, color.paint(), &stroke, transform, None);
+            }
+
+            let x = x + Self::SWATCH + Self::PADDING;
+            self.style
+                .draw_centered(&mut pixmap, name, x + *name_width as f32 / 2., y, scale);
+        }
+    }
+}
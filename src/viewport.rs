@@ -0,0 +1,108 @@
+use crate::{
+    coord::BoundingBox, geodesy::destination, lat_to_y, lon_to_x_continuous, x_to_lon, y_to_lat,
+};
+
+/// What part of the world a map shows: its size, zoom and center.
+///
+/// Get the viewport of a map with [StaticMap::viewport][crate::StaticMap::viewport],
+/// adjust it, and render it with
+/// [StaticMap::render_with_viewport][crate::StaticMap::render_with_viewport],
+/// e.g. for a sequence of frames panning or zooming over one configured map.
+///
+/// Pixels depend on the tile size and grid of the map, so panning by pixels is done
+/// on the map with [StaticMap::pan_pixels][crate::StaticMap::pan_pixels].
+///
+/// ## Example
+/// ```rust
+/// use staticmap::{testing::MockTileFetcher, StaticMapBuilder, Viewport};
+///
+/// let mut map = StaticMapBuilder::new()
+///     .tile_fetcher(MockTileFetcher::new())
+///     .build()
+///     .unwrap();
+///
+/// let mut viewport = Viewport::new(200, 150, 10, (13.2, 52.5));
+/// let mut frames = Vec::new();
+/// for _ in 0..3 {
+///     frames.push(map.render_with_viewport(&viewport).unwrap());
+///     viewport.pan(0.1, 0.);
+/// }
+///
+/// viewport.zoom_by(-2);
+/// assert_eq!(viewport.zoom, 8);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Width of the map in pixels.
+    pub width: u32,
+
+    /// Height of the map in pixels.
+    pub height: u32,
+
+    /// Zoom of the map, not including any zoom offset.
+    pub zoom: u8,
+
+    /// Geographic center of the map, as (longitude, latitude).
+    pub center: (f64, f64),
+}
+
+impl Viewport {
+    /// Creates a new [Viewport][Viewport], with the center as (longitude, latitude).
+    pub fn new(width: u32, height: u32, zoom: u8, center: (f64, f64)) -> Self {
+        Viewport {
+            width,
+            height,
+            zoom,
+            center,
+        }
+    }
+
+    /// Move the center by the given longitude and latitude, in degrees.
+    pub fn pan(&mut self, lon: f64, lat: f64) {
+        self.center = (self.center.0 + lon, self.center.1 + lat);
+    }
+
+    /// Move the center by the given distance in meters, in the direction of the bearing
+    /// in degrees clockwise from north, e.g. 0.0 for north and 90.0 for east.
+    pub fn pan_meters(&mut self, distance: f64, bearing: f64) {
+        let (lon, lat) = self.center;
+        let (lat, lon) = destination(lat, lon, distance, bearing);
+        self.center = (lon, lat);
+    }
+
+    /// Change the zoom by the given number of levels, keeping the center.
+    /// Negative levels zoom out. The zoom does not go below 0 or above 255.
+    pub fn zoom_by(&mut self, levels: i8) {
+        self.zoom = self.zoom.saturating_add_signed(levels);
+    }
+
+    /// Zoom in by the given number of levels, keeping the center.
+    pub fn zoom_in(&mut self, levels: u8) {
+        self.zoom = self.zoom.saturating_add(levels);
+    }
+
+    /// Zoom out by the given number of levels, keeping the center.
+    pub fn zoom_out(&mut self, levels: u8) {
+        self.zoom = self.zoom.saturating_sub(levels);
+    }
+
+    /// Geographic box visible on a Web Mercator map with tiles of `tile_size` pixels,
+    /// e.g. 256. Maps with a [zoom offset][crate::StaticMapBuilder::zoom_offset] or a
+    /// custom tile grid are better served by [Bounds::bbox][crate::Bounds::bbox].
+    pub fn bbox(&self, tile_size: u32) -> BoundingBox {
+        let (lon, lat) = self.center;
+        let (x, y) = (
+            lon_to_x_continuous(lon, self.zoom),
+            lat_to_y(lat, self.zoom),
+        );
+        let half_width = f64::from(self.width) / 2. / f64::from(tile_size);
+        let half_height = f64::from(self.height) / 2. / f64::from(tile_size);
+
+        BoundingBox {
+            south: y_to_lat(y + half_height, self.zoom),
+            west: x_to_lon(x - half_width, self.zoom),
+            north: y_to_lat(y - half_height, self.zoom),
+            east: x_to_lon(x + half_width, self.zoom),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    layer::{features_from_geojson, Feature, FeatureSource},
+    service::{fetch_json, service_error},
+    BoundingBox, Error, Result,
+};
+use std::time::Duration;
+
+/// Source of features from a WFS service, e.g. GeoServer, for a [FeatureLayer][crate::FeatureLayer].
+/// Features are requested as GeoJSON for the area visible on the map each time it is rendered,
+/// so the data always matches the extent of the map.
+/// Use [WfsSourceBuilder][WfsSourceBuilder] as an entrypoint.
+///
+/// Requests use WFS 2.0.0, with the box in WGS 84 longitude, latitude order (`EPSG:4326`
+/// as interpreted by GeoServer), and ask for `application/json` output.
+///
+/// Requires the `wfs` feature.
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{tools::Color, FeatureLayer, FeatureStyle, StaticMapBuilder, WfsSourceBuilder};
+///
+/// let source = WfsSourceBuilder::new()
+///     .endpoint("https://geoserver.example.com/geoserver/wfs")
+///     .type_name("topp:states")
+///     .build()
+///     .unwrap();
+///
+/// let layer = FeatureLayer::new(source).style(|feature| {
+///     let fill = match feature.property("STATE_NAME") {
+///         Some("Colorado") => Color::new(true, 230, 120, 40, 160),
+///         _ => Color::new(true, 80, 120, 200, 100),
+///     };
+///     Some(FeatureStyle::new().fill(fill).width(1.))
+/// });
+///
+/// let mut map = StaticMapBuilder::new()
+///     .width(800)
+///     .height(500)
+///     .zoom(4)
+///     .lat_center(39.)
+///     .lon_center(-105.)
+///     .build()
+///     .unwrap();
+/// map.add_layer(layer);
+/// map.save_png("states.png").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WfsSource {
+    endpoint: String,
+    type_name: String,
+    max_features: Option<u32>,
+    params: Vec<(String, String)>,
+    timeout: Duration,
+}
+
+/// Builder for [WfsSource][WfsSource].
+#[derive(Debug, Clone)]
+pub struct WfsSourceBuilder {
+    endpoint: Option<String>,
+    type_name: Option<String>,
+    max_features: Option<u32>,
+    params: Vec<(String, String)>,
+    timeout: Duration,
+}
+
+impl Default for WfsSourceBuilder {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            type_name: None,
+            max_features: None,
+            params: Vec::new(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl WfsSourceBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// URL of the WFS service, e.g. `https://example.com/geoserver/wfs`.
+    pub fn endpoint<I: Into<String>>(mut self, endpoint: I) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// **Required**.
+    /// Name of the feature type to request, e.g. `topp:states`.
+    pub fn type_name<I: Into<String>>(mut self, type_name: I) -> Self {
+        self.type_name = Some(type_name.into());
+        self
+    }
+
+    /// Greatest number of features returned by a request.
+    /// Default is the limit of the service.
+    pub fn max_features(mut self, count: u32) -> Self {
+        self.max_features = Some(count);
+        self
+    }
+
+    /// Add a parameter to the requests, e.g. a vendor parameter like
+    /// `("propertyName", "the_geom,name")` or an API key.
+    pub fn param<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Time a request may take before it fails.
+    /// Default is 30 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Build the source, consuming the builder.
+    /// Returns an error if the endpoint or type name are not supplied.
+    pub fn build(self) -> Result<WfsSource> {
+        Ok(WfsSource {
+            endpoint: self
+                .endpoint
+                .ok_or(Error::BuildError("Endpoint not supplied."))?,
+            type_name: self
+                .type_name
+                .ok_or(Error::BuildError("Type name not supplied."))?,
+            max_features: self.max_features,
+            params: self.params,
+            timeout: self.timeout,
+        })
+    }
+}
+
+impl WfsSource {
+    /// Fetch the features in a box, e.g. the [box visible on a map][crate::Bounds::bbox].
+    pub fn fetch(&self, bbox: &BoundingBox) -> Result<Vec<Feature>> {
+        let mut request = attohttpc::get(&self.endpoint)
+            .param("service", "WFS")
+            .param("version", "2.0.0")
+            .param("request", "GetFeature")
+            .param("typeNames", &self.type_name)
+            .param("outputFormat", "application/json")
+            .param("srsName", "EPSG:4326")
+            .param(
+                "bbox",
+                format!(
+                    "{},{},{},{},EPSG:4326",
+                    bbox.west, bbox.south, bbox.east, bbox.north
+                ),
+            )
+            .timeout(self.timeout);
+        if let Some(count) = self.max_features {
+            request = request.param("count", count.to_string());
+        }
+        for (key, value) in self.params.iter() {
+            request = request.param(key, value);
+        }
+
+        let json = fetch_json(request, &self.endpoint)?;
+        features_from_geojson(&json).map_err(|reason| service_error(&self.endpoint, reason))
+    }
+}
+
+impl FeatureSource for WfsSource {
+    fn features(&self, bbox: &BoundingBox) -> Result<Vec<Feature>> {
+        self.fetch(bbox)
+    }
+}
@@ -0,0 +1,379 @@
+use crate::{
+    grid::{TileGrid, TileGridBuilder},
+    Error, Result,
+};
+use roxmltree::{Document, Node};
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+/// Size of a pixel in meters, as defined by the WMTS standard.
+const PIXEL_SIZE: f64 = 0.000_28;
+
+/// Radius used by Web Mercator, in meters.
+const WEB_MERCATOR_RADIUS: f64 = 6_378_137.;
+
+/// Tile source configured from a WMTS GetCapabilities document.
+/// Use [WmtsSourceBuilder][WmtsSourceBuilder] as an entrypoint,
+/// and [StaticMapBuilder::wmts_source][crate::StaticMapBuilder::wmts_source] to use it.
+///
+/// Requires the `wmts` feature.
+#[derive(Debug, Clone)]
+pub struct WmtsSource {
+    url_template: String,
+    grid: TileGrid,
+    tile_size: u32,
+}
+
+impl WmtsSource {
+    /// URL template of the tiles, with `{z}`, `{x}` and `{y}` placeholders.
+    pub fn url_template(&self) -> &str {
+        &self.url_template
+    }
+
+    /// Tile grid of the tile matrix set.
+    pub fn tile_grid(&self) -> &TileGrid {
+        &self.grid
+    }
+
+    /// Tile size, in pixels.
+    pub fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+}
+
+type Projection = (
+    Box<dyn Fn(f64, f64) -> (f64, f64) + Send + Sync>,
+    Box<dyn Fn(f64, f64) -> (f64, f64) + Send + Sync>,
+);
+
+/// Builder for [WmtsSource][WmtsSource].
+///
+/// Web Mercator (EPSG:3857), WGS 84 (EPSG:4326, CRS:84) and Swiss LV95 (EPSG:2056)
+/// tile matrix sets are supported out of the box,
+/// other coordinate systems require a [projection][WmtsSourceBuilder::projection].
+///
+/// ## Example
+/// ```rust,no_run
+/// use staticmap::{StaticMapBuilder, WmtsSourceBuilder};
+///
+/// let capabilities = std::fs::read_to_string("WMTSCapabilities.xml").unwrap();
+///
+/// let source = WmtsSourceBuilder::new()
+///     .capabilities(capabilities)
+///     .layer("ch.swisstopo.pixelkarte-farbe")
+///     .build()
+///     .unwrap();
+///
+/// let map = StaticMapBuilder::new()
+///     .wmts_source(source)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct WmtsSourceBuilder {
+    capabilities: Option<String>,
+    layer: Option<String>,
+    tile_matrix_set: Option<String>,
+    style: Option<String>,
+    format: Option<String>,
+    projection: Option<Projection>,
+}
+
+impl WmtsSourceBuilder {
+    /// Create a new builder with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// **Required**.
+    /// The GetCapabilities XML document.
+    pub fn capabilities<I: Into<String>>(mut self, capabilities: I) -> Self {
+        self.capabilities = Some(capabilities.into());
+        self
+    }
+
+    /// **Required**.
+    /// Identifier of the layer.
+    pub fn layer<I: Into<String>>(mut self, layer: I) -> Self {
+        self.layer = Some(layer.into());
+        self
+    }
+
+    /// Identifier of the tile matrix set.
+    /// Default is the first tile matrix set linked to the layer.
+    pub fn tile_matrix_set<I: Into<String>>(mut self, tile_matrix_set: I) -> Self {
+        self.tile_matrix_set = Some(tile_matrix_set.into());
+        self
+    }
+
+    /// Identifier of the style.
+    /// Default is the default style of the layer.
+    pub fn style<I: Into<String>>(mut self, style: I) -> Self {
+        self.style = Some(style.into());
+        self
+    }
+
+    /// Tile format, e.g. "image/png".
+    /// Default is the first format of the layer.
+    pub fn format<I: Into<String>>(mut self, format: I) -> Self {
+        self.format = Some(format.into());
+        self
+    }
+
+    /// Transformations from (longitude, latitude) to the coordinate system
+    /// of the tile matrix set, and back.
+    /// Required for coordinate systems not supported out of the box.
+    pub fn projection<F, I>(mut self, forward: F, inverse: I) -> Self
+    where
+        F: Fn(f64, f64) -> (f64, f64) + Send + Sync + 'static,
+        I: Fn(f64, f64) -> (f64, f64) + Send + Sync + 'static,
+    {
+        self.projection = Some((Box::new(forward), Box::new(inverse)));
+        self
+    }
+
+    /// Build the source, consuming the builder.
+    /// Returns an error if the builder is missing required fields,
+    /// or if the capabilities document does not describe the layer.
+    pub fn build(self) -> Result<WmtsSource> {
+        let capabilities = self
+            .capabilities
+            .ok_or(Error::BuildError("Capabilities document not supplied."))?;
+        let layer_id = self.layer.ok_or(Error::BuildError("Layer not supplied."))?;
+
+        let document = Document::parse(&capabilities)
+            .map_err(|_| Error::BuildError("Invalid WMTS capabilities document."))?;
+
+        let contents = find_child(document.root_element(), "Contents")
+            .ok_or(Error::BuildError("Capabilities document has no contents."))?;
+
+        let layer = children(contents, "Layer")
+            .find(|x| identifier(*x) == Some(layer_id.as_str()))
+            .ok_or(Error::BuildError(
+                "Layer not found in capabilities document.",
+            ))?;
+
+        let style = match self.style {
+            Some(style) => style,
+            None => children(layer, "Style")
+                .find(|x| x.attribute("isDefault") == Some("true"))
+                .or_else(|| find_child(layer, "Style"))
+                .and_then(identifier)
+                .unwrap_or("default")
+                .to_string(),
+        };
+
+        let format = match self.format {
+            Some(format) => Some(format),
+            None => find_child(layer, "Format").and_then(|x| text(x).map(str::to_string)),
+        };
+
+        let tile_matrix_set_id = match self.tile_matrix_set {
+            Some(tile_matrix_set) => tile_matrix_set,
+            None => children(layer, "TileMatrixSetLink")
+                .filter_map(|x| find_child(x, "TileMatrixSet"))
+                .find_map(text)
+                .ok_or(Error::BuildError("Layer has no tile matrix set."))?
+                .to_string(),
+        };
+
+        let tile_matrix_set = children(contents, "TileMatrixSet")
+            .find(|x| identifier(*x) == Some(tile_matrix_set_id.as_str()))
+            .ok_or(Error::BuildError(
+                "Tile matrix set not found in capabilities document.",
+            ))?;
+
+        let crs = find_child(tile_matrix_set, "SupportedCRS")
+            .and_then(text)
+            .unwrap_or_default()
+            .to_string();
+
+        let mut tile_matrices: Vec<(String, f64, (f64, f64), u32)> =
+            children(tile_matrix_set, "TileMatrix")
+                .map(|x| {
+                    let scale = find_child(x, "ScaleDenominator")
+                        .and_then(text)
+                        .and_then(|x| x.parse::<f64>().ok());
+                    let corner = find_child(x, "TopLeftCorner").and_then(text).and_then(|x| {
+                        let mut values = x.split_whitespace().map(str::parse::<f64>);
+                        match (values.next(), values.next()) {
+                            (Some(Ok(a)), Some(Ok(b))) => Some((a, b)),
+                            _ => None,
+                        }
+                    });
+                    let tile_size = find_child(x, "TileWidth")
+                        .and_then(text)
+                        .and_then(|x| x.parse::<u32>().ok())
+                        .unwrap_or(256);
+
+                    match (identifier(x), scale, corner) {
+                        (Some(id), Some(scale), Some(corner)) => {
+                            Ok((id.to_string(), scale, corner, tile_size))
+                        }
+                        _ => Err(Error::BuildError(
+                            "Invalid tile matrix in capabilities document.",
+                        )),
+                    }
+                })
+                .collect::<Result<_>>()?;
+
+        // Zoom levels go from the coarsest to the finest tile matrix.
+        tile_matrices.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let (_, _, corner, tile_size) = tile_matrices
+            .first()
+            .cloned()
+            .ok_or(Error::BuildError("Tile matrix set has no tile matrices."))?;
+
+        let (projection, meters_per_unit, swap_axes) = match self.projection {
+            Some(projection) => (projection, 1., false),
+            None => builtin_projection(&crs)
+                .ok_or(Error::BuildError("Unsupported CRS, supply a projection."))?,
+        };
+        let origin = if swap_axes {
+            (corner.1, corner.0)
+        } else {
+            corner
+        };
+
+        let (forward, inverse) = projection;
+        let grid = TileGridBuilder::new()
+            .projection(forward, inverse)
+            .origin(origin.0, origin.1)
+            .resolutions(
+                tile_matrices
+                    .iter()
+                    .map(|x| x.1 * PIXEL_SIZE / meters_per_unit)
+                    .collect(),
+            )
+            .zoom_identifiers(tile_matrices.iter().map(|x| x.0.clone()).collect())
+            .build()?;
+
+        let template = children(layer, "ResourceURL")
+            .filter(|x| x.attribute("resourceType") == Some("tile"))
+            .find(|x| format.is_none() || x.attribute("format") == format.as_deref())
+            .and_then(|x| x.attribute("template"))
+            .map(str::to_string);
+
+        let mut url_template = match template {
+            Some(template) => template,
+            None => {
+                let endpoint = get_tile_endpoint(document.root_element())
+                    .ok_or(Error::BuildError("Layer has no tile URL."))?;
+                let separator = if endpoint.contains('?') { "&" } else { "?" };
+                format!(
+                    "{}{}SERVICE=WMTS&REQUEST=GetTile&VERSION=1.0.0&LAYER={}&STYLE={}&FORMAT={}\
+                     &TILEMATRIXSET={{TileMatrixSet}}&TILEMATRIX={{TileMatrix}}\
+                     &TILEROW={{TileRow}}&TILECOL={{TileCol}}",
+                    endpoint,
+                    separator,
+                    layer_id,
+                    style,
+                    format.as_deref().unwrap_or("image/png")
+                )
+            }
+        };
+
+        for dimension in children(layer, "Dimension") {
+            if let (Some(id), Some(default)) = (
+                identifier(dimension),
+                find_child(dimension, "Default").and_then(text),
+            ) {
+                url_template = url_template.replace(&format!("{{{}}}", id), default);
+            }
+        }
+
+        let url_template = url_template
+            .replace("{TileMatrixSet}", &tile_matrix_set_id)
+            .replace("{Style}", &style)
+            .replace("{TileMatrix}", "{z}")
+            .replace("{TileRow}", "{y}")
+            .replace("{TileCol}", "{x}");
+
+        Ok(WmtsSource {
+            url_template,
+            grid,
+            tile_size,
+        })
+    }
+}
+
+/// Projection, meters per CRS unit and whether the axes are (north, east),
+/// for the coordinate systems supported out of the box.
+fn builtin_projection(crs: &str) -> Option<(Projection, f64, bool)> {
+    let code = crs.rsplit([':', '/']).next().unwrap_or(crs);
+
+    match code {
+        "3857" | "900913" => Some((
+            (
+                Box::new(|lon, lat| {
+                    (
+                        lon.to_radians() * WEB_MERCATOR_RADIUS,
+                        (FRAC_PI_4 + lat.to_radians() / 2.).tan().ln() * WEB_MERCATOR_RADIUS,
+                    )
+                }),
+                Box::new(|x, y| {
+                    (
+                        (x / WEB_MERCATOR_RADIUS).to_degrees(),
+                        (2. * (y / WEB_MERCATOR_RADIUS).exp().atan() - FRAC_PI_2).to_degrees(),
+                    )
+                }),
+            ),
+            1.,
+            false,
+        )),
+        "4326" | "CRS84" => Some((
+            (Box::new(|lon, lat| (lon, lat)), Box::new(|x, y| (x, y))),
+            2. * PI * WEB_MERCATOR_RADIUS / 360.,
+            code == "4326",
+        )),
+        "2056" => {
+            let grid = TileGrid::swiss_lv95();
+            let inverse = grid.clone();
+            Some((
+                (
+                    Box::new(move |lon, lat| grid.project(lon, lat)),
+                    Box::new(move |x, y| inverse.unproject(x, y)),
+                ),
+                1.,
+                false,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// URL of the KVP GetTile operation, for servers without RESTful resource URLs.
+fn get_tile_endpoint<'a>(root: Node<'a, 'a>) -> Option<&'a str> {
+    root.descendants()
+        .filter(|x| x.tag_name().name() == "Operation" && x.attribute("name") == Some("GetTile"))
+        .flat_map(|x| x.descendants())
+        .find(|x| x.tag_name().name() == "Get")
+        .and_then(|x| {
+            x.attributes()
+                .find(|x| x.name() == "href")
+                .map(|x| x.value())
+        })
+}
+
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |x| x.is_element() && x.tag_name().name() == name)
+}
+
+fn find_child<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+fn text<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.text().map(str::trim)
+}
+
+fn identifier<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    find_child(node, "Identifier").and_then(text)
+}
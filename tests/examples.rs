@@ -0,0 +1,258 @@
+//! The examples rendered with generated tiles and compared against the golden images
+//! in `tests/golden`. Set `STATICMAP_UPDATE_SNAPSHOTS` to update the golden images
+//! after intended changes to drawing.
+
+use staticmap::{
+    testing::Harness,
+    tools::{
+        Alignment, CircleBuilder, Color, Corner, Font, IconBuilder, LineBuilder, RectBuilder,
+        StampBuilder, TextBuilder, TextStyle,
+    },
+};
+use std::path::Path;
+
+fn harness() -> Harness {
+    Harness::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")).tolerance(2)
+}
+
+fn asset(path: &str) -> String {
+    format!("{}/{}", env!("CARGO_MANIFEST_DIR"), path)
+}
+
+#[test]
+fn circle() {
+    let harness = harness();
+    let mut map = harness
+        .builder()
+        .width(200)
+        .height(200)
+        .zoom(5)
+        .build()
+        .unwrap();
+
+    let circle = CircleBuilder::new().lon_coordinate(10.).lat_coordinate(47.);
+    map.add_tool(
+        circle
+            .clone()
+            .color(Color::new(true, 255, 255, 255, 255))
+            .radius(9.)
+            .build()
+            .unwrap(),
+    );
+    map.add_tool(
+        circle
+            .color(Color::new(true, 0, 0, 255, 255))
+            .radius(6.)
+            .build()
+            .unwrap(),
+    );
+
+    harness.assert_render("circle", &mut map);
+}
+
+#[test]
+fn empty_map() {
+    let harness = harness();
+    let mut map = harness
+        .builder()
+        .width(300)
+        .height(300)
+        .zoom(4)
+        .lon_center(4.)
+        .lat_center(54.)
+        .build()
+        .unwrap();
+
+    harness.assert_render("empty_map", &mut map);
+}
+
+#[test]
+fn icon() {
+    let harness = harness();
+    let mut map = harness
+        .builder()
+        .width(200)
+        .height(200)
+        .padding((80, 0))
+        .zoom(12)
+        .build()
+        .unwrap();
+
+    map.add_tool(
+        IconBuilder::new()
+            .lon_coordinate(6.63204)
+            .lat_coordinate(45.85378)
+            .x_offset(12.)
+            .y_offset(32.)
+            .path(asset("examples/icons/icon-flag.png"))
+            .unwrap()
+            .build()
+            .unwrap(),
+    );
+    map.add_tool(
+        IconBuilder::new()
+            .lon_coordinate(6.6015)
+            .lat_coordinate(45.8485)
+            .x_offset(18.)
+            .y_offset(18.)
+            .path(asset("examples/icons/icon-factory.png"))
+            .unwrap()
+            .build()
+            .unwrap(),
+    );
+
+    harness.assert_render("icon", &mut map);
+}
+
+#[test]
+fn line() {
+    let harness = harness();
+    let mut map = harness
+        .builder()
+        .width(300)
+        .height(400)
+        .padding((10, 0))
+        .build()
+        .unwrap();
+
+    let line = LineBuilder::new()
+        .lat_coordinates(vec![52.5, 48.9])
+        .lon_coordinates(vec![13.4, 2.3])
+        .simplify(true);
+    map.add_tool(
+        line.clone()
+            .width(5.)
+            .color(Color::new(true, 255, 255, 255, 255))
+            .build()
+            .unwrap(),
+    );
+    map.add_tool(
+        line.width(3.)
+            .color(Color::new(true, 255, 0, 0, 255))
+            .build()
+            .unwrap(),
+    );
+
+    harness.assert_render("line", &mut map);
+}
+
+#[test]
+fn open_circle() {
+    let harness = harness();
+    let mut map = harness.builder().width(200).height(200).build().unwrap();
+
+    let circle = CircleBuilder::new()
+        .lon_coordinate(-3.17)
+        .lat_coordinate(55.98)
+        .color(Color::new(true, 255, 0, 0, 255))
+        .stroke_width(2.);
+    map.add_tool(
+        circle
+            .clone()
+            .radius_in_meters(500.0 * 1609.34)
+            .build()
+            .unwrap(),
+    );
+    map.add_tool(circle.radius_in_meters(1000.0 * 1609.34).build().unwrap());
+
+    harness.assert_render("open_circle", &mut map);
+}
+
+#[test]
+fn rect() {
+    let harness = harness();
+    let mut map = harness.builder().width(200).height(200).build().unwrap();
+
+    map.add_tool(
+        RectBuilder::new()
+            .north_lat_coordinate(43.12398687511079)
+            .south_lat_coordinate(43.107942538441854)
+            .east_lon_coordinate(141.39078581150866)
+            .west_lon_coordinate(141.37070467336105)
+            .color(Color::new(true, 255, 0, 0, 255))
+            .build()
+            .unwrap(),
+    );
+    map.add_tool(
+        RectBuilder::new()
+            .north_lat_coordinate(42.81587629948163)
+            .south_lat_coordinate(42.76094473505349)
+            .east_lon_coordinate(141.698469171195)
+            .west_lon_coordinate(141.65625418708007)
+            .color(Color::new(true, 0, 0, 255, 255))
+            .stroke_width(2.0)
+            .build()
+            .unwrap(),
+    );
+
+    harness.assert_render("rect", &mut map);
+}
+
+#[test]
+fn stamp() {
+    let harness = harness();
+    let mut map = harness
+        .builder()
+        .width(300)
+        .height(200)
+        .zoom(6)
+        .lon_center(10.)
+        .lat_center(60.)
+        .build()
+        .unwrap();
+
+    let font = Font::from_path(asset("tests/fonts/DejaVuSans.ttf")).unwrap();
+    map.add_tool(
+        StampBuilder::new()
+            .text("Golden image\nDejaVu Sans")
+            .style(TextStyle::new(font))
+            .corner(Corner::TopRight)
+            .background(Color::new(true, 255, 255, 255, 200))
+            .build()
+            .unwrap(),
+    );
+
+    harness.assert_render("stamp", &mut map);
+}
+
+#[test]
+fn text() {
+    let harness = harness();
+    let mut map = harness
+        .builder()
+        .width(300)
+        .height(200)
+        .zoom(6)
+        .lon_center(10.)
+        .lat_center(60.)
+        .build()
+        .unwrap();
+
+    let font = Font::from_path(asset("tests/fonts/DejaVuSans.ttf")).unwrap();
+    map.add_tool(
+        CircleBuilder::new()
+            .lat_coordinate(59.91)
+            .lon_coordinate(10.75)
+            .color(Color::new(true, 200, 0, 0, 255))
+            .radius(5.)
+            .build()
+            .unwrap(),
+    );
+    map.add_tool(
+        TextBuilder::new()
+            .lat_coordinate(59.91)
+            .lon_coordinate(10.75)
+            .text("Oslo")
+            .style(
+                TextStyle::new(font)
+                    .size(16.)
+                    .halo(Color::new(true, 255, 255, 255, 255), 2.),
+            )
+            .anchor(Alignment::Bottom)
+            .offset(0., -6.)
+            .build()
+            .unwrap(),
+    );
+
+    harness.assert_render("text", &mut map);
+}